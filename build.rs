@@ -0,0 +1,11 @@
+// 由 proto/tunnel_manager.proto 生成 gRPC 控制接口的代码；使用随依赖提供的 protoc，构建时无需另行安装
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    if std::env::var_os("PROTOC").is_none() {
+        // SAFETY: 构建脚本是单线程的
+        unsafe { std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?) };
+    }
+    tonic_prost_build::configure()
+        .build_client(false)
+        .compile_protos(&["proto/tunnel_manager.proto"], &["proto"])?;
+    Ok(())
+}
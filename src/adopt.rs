@@ -0,0 +1,133 @@
+use anyhow::Result;
+
+use crate::config::Config;
+use crate::i18n::t;
+use crate::platform::{self, ProcessInfo};
+use crate::state::RuntimeState;
+use crate::tunnel::Tunnel;
+
+/// 在本地监听端口的 `cloudflared access` 子命令
+const LISTENING_SUBCOMMANDS: &[&str] = &["tcp", "ssh", "rdp", "smb"];
+
+/// 正在运行的 `cloudflared access` 进程
+#[derive(Debug)]
+pub struct AccessProcess {
+    pub pid: u32,
+    pub hostname: String,
+    pub port: u16,
+}
+
+impl AccessProcess {
+    /// 从命令行中解析 `access tcp --hostname <主机名> --url <地址>`；不是 access 转发进程时返回 None
+    fn parse(process: &ProcessInfo) -> Option<Self> {
+        let args = &process.args;
+        let access = args.iter().position(|arg| arg == "access")?;
+        if !LISTENING_SUBCOMMANDS.contains(&args.get(access + 1)?.as_str()) {
+            return None;
+        }
+        let hostname = flag_value(args, "--hostname")?;
+        let url = flag_value(args, "--url")?;
+        let port = url.trim_end_matches('/').rsplit(':').next()?.parse().ok()?;
+        Some(Self { pid: process.pid, hostname, port })
+    }
+
+    fn serves(&self, tunnel: &Tunnel) -> bool {
+        tunnel.provider.is_default() && normalize_host(&tunnel.source) == normalize_host(&self.hostname)
+    }
+}
+
+/// 与配置匹配的结果
+#[derive(Debug)]
+pub enum Outcome {
+    /// 与该连接的主机名和端口都一致，可以接管
+    Adoptable(String),
+    /// 已由本工具管理
+    Managed(String),
+    /// 主机名一致但无法接管，附带原因
+    Skipped(String, String),
+    /// 没有对应的连接
+    Unmatched,
+}
+
+/// 扫描手动启动的 cloudflared access 进程，并按主机名和本地端口与已配置的连接匹配
+pub fn scan(config: &Config) -> Result<Vec<(AccessProcess, Outcome)>> {
+    let tunnels = config.list_tunnels()?;
+    let mut results = Vec::new();
+    for process in platform::processes("cloudflared").iter().filter_map(AccessProcess::parse) {
+        let managed = tunnels.iter()
+            .find(|t| RuntimeState::load(&t.alias).is_some_and(|state| state.pid == process.pid));
+        let outcome = if let Some(tunnel) = managed {
+            Outcome::Managed(tunnel.alias.clone())
+        } else if let Some(tunnel) = tunnels.iter().find(|t| process.serves(t) && t.port == process.port) {
+            match RuntimeState::load(&tunnel.alias).filter(RuntimeState::is_alive) {
+                Some(state) => Outcome::Skipped(
+                    tunnel.alias.clone(),
+                    t!("已有由本工具启动的进程 (PID {})", state.pid),
+                ),
+                None => Outcome::Adoptable(tunnel.alias.clone()),
+            }
+        } else if let Some(tunnel) = tunnels.iter().find(|t| process.serves(t)) {
+            Outcome::Skipped(
+                tunnel.alias.clone(),
+                t!("主机名一致，但本地端口为 {}，配置中为 {}", process.port, tunnel.port),
+            )
+        } else {
+            Outcome::Unmatched
+        };
+        results.push((process, outcome));
+    }
+    results.sort_by_key(|(process, _)| process.pid);
+    Ok(results)
+}
+
+/// 扫描没有任何已配置连接与之对应的 cloudflared access 进程，用于导入为新连接
+pub fn discover(config: &Config) -> Result<Vec<AccessProcess>> {
+    Ok(scan(config)?
+        .into_iter()
+        .filter(|(_, outcome)| matches!(outcome, Outcome::Unmatched))
+        .map(|(process, _)| process)
+        .collect())
+}
+
+/// 为导入的进程建议别名：取主机名的第一段，与已有别名重复时追加序号
+pub fn suggest_alias(config: &Config, hostname: &str) -> String {
+    let host = normalize_host(hostname);
+    let base = host.split('.').next().filter(|label| !label.is_empty()).unwrap_or("tunnel");
+    let taken = |alias: &str| config.get_tunnel(alias).is_ok();
+    if !taken(base) {
+        return base.to_string();
+    }
+    (2..).map(|n| format!("{}-{}", base, n)).find(|alias| !taken(alias)).unwrap_or_default()
+}
+
+/// 把进程登记为由本工具启动，之后可以像普通连接一样停止和查看运行时间
+pub fn adopt(tunnel: &Tunnel, process: &AccessProcess) -> Result<()> {
+    RuntimeState {
+        pid: process.pid,
+        started_at: platform::process_start_time(process.pid).unwrap_or_else(platform::unix_now),
+        port: process.port,
+        project: tunnel.project.clone(),
+        expires_at: None,
+        elevated: false,
+        container: None,
+    }
+    .save(&tunnel.alias)
+}
+
+/// 取 `--name value` 或 `--name=value` 形式的参数值
+fn flag_value(args: &[String], name: &str) -> Option<String> {
+    args.iter().enumerate().find_map(|(index, arg)| {
+        if arg == name {
+            args.get(index + 1).cloned()
+        } else {
+            arg.strip_prefix(name)?.strip_prefix('=').map(str::to_string)
+        }
+    })
+}
+
+fn normalize_host(host: &str) -> String {
+    host.trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_end_matches('/')
+        .to_lowercase()
+}
@@ -0,0 +1,337 @@
+//! 告警规则：连接持续不可用、短时间内反复重启时，发送到配置的通知渠道。
+//!
+//! 规则在配置文件的 `alerts` 中定义，由 `tfa alert run` 在前台根据事件总线判断。
+//! 同一规则对同一连接，一次故障只通知一次，恢复时再通知一次；通知之后的冷却时间内不再通知，
+//! 被抑制的次数附在冷却结束后的下一条通知中，反复断开的连接不会刷屏。
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+use crate::config::{self, Config};
+use crate::error::TunnelError;
+use crate::events::{self, Monitor, TunnelEvent};
+use crate::i18n::t;
+use crate::notify::{AlertState, Channel, Notification};
+use crate::platform;
+use crate::tunnel::TunnelStatus;
+use tracing::{info, warn};
+
+/// 检查持续时间类规则的间隔
+const EVALUATE_INTERVAL: Duration = Duration::from_secs(5);
+
+fn default_cooldown() -> u64 {
+    30
+}
+
+/// 一条告警规则
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertRule {
+    pub name: String,
+    /// 别名或通配模式，为空时适用于全部连接
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tunnels: Vec<String>,
+    #[serde(flatten)]
+    pub condition: Condition,
+    /// 通知渠道名称，为空时发送到全部渠道
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notify: Vec<String>,
+    /// 通知之后多少分钟内不再通知
+    #[serde(default = "default_cooldown")]
+    pub cooldown: u64,
+}
+
+impl AlertRule {
+    fn applies_to(&self, alias: &str) -> bool {
+        self.tunnels.is_empty() || self.tunnels.iter().any(|pattern| config::matches_alias(pattern, alias))
+    }
+}
+
+/// 触发条件，配置中以 `when` 区分
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "when", rename_all = "snake_case")]
+pub enum Condition {
+    /// 连接不可用（意外退出、降级或熔断）持续 `minutes` 分钟；经 tfa stop 停止的不算
+    Down { minutes: u64 },
+    /// `minutes` 分钟内崩溃或意外退出达到 `count` 次
+    Restarts { count: usize, minutes: u64 },
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Condition::Down { minutes } => f.write_str(&t!("不可用超过 {} 分钟", minutes)),
+            Condition::Restarts { count, minutes } => f.write_str(&t!("{} 分钟内重启 {} 次", minutes, count)),
+        }
+    }
+}
+
+/// 规则对某个连接的通知状态
+#[derive(Debug, Default)]
+struct RuleState {
+    /// 条件这次成立时已经通知，成立期间不重复通知，持续时间类的恢复时再通知一次
+    notified: bool,
+    /// 条件这次成立时正在冷却，冷却结束后仍成立则通知
+    pending: bool,
+    last_sent: Option<u64>,
+    /// 冷却期间开始并结束、没有通知的次数
+    suppressed: usize,
+}
+
+impl RuleState {
+    /// 条件不再成立；返回这次成立时是否通知过
+    fn clear(&mut self) -> bool {
+        if std::mem::take(&mut self.pending) {
+            self.suppressed += 1;
+        }
+        std::mem::take(&mut self.notified)
+    }
+}
+
+/// 要发送的通知及其渠道
+pub struct Alert {
+    pub notification: Notification,
+    pub channels: Vec<String>,
+}
+
+/// 根据事件判断规则是否成立，并做去重与冷却
+pub struct Engine {
+    rules: Vec<AlertRule>,
+    /// 每条规则使用的渠道
+    channels: Vec<Vec<String>>,
+    /// 不可用的连接及开始时间
+    down: HashMap<String, u64>,
+    /// 经 tfa stop 或暂停而停止的连接，之后检测到的 stopped 状态不算故障
+    stopped: HashSet<String>,
+    /// 每个连接最近的崩溃时间
+    crashes: HashMap<String, VecDeque<u64>>,
+    states: HashMap<(usize, String), RuleState>,
+}
+
+impl Engine {
+    /// 检查规则引用的渠道是否存在
+    pub fn new(rules: &[AlertRule], channels: &BTreeMap<String, Channel>) -> Result<Self> {
+        if rules.is_empty() {
+            return Err(TunnelError::config(t!("没有配置告警规则，请在配置文件的 alerts 中添加")));
+        }
+        if channels.is_empty() {
+            return Err(TunnelError::config(t!("没有配置通知渠道，请在配置文件的 notify 中添加")));
+        }
+        let mut names = HashSet::new();
+        let mut rule_channels = Vec::new();
+        for rule in rules {
+            if !names.insert(rule.name.as_str()) {
+                return Err(TunnelError::config(t!("告警规则 {} 重复", rule.name)));
+            }
+            if let Some(unknown) = rule.notify.iter().find(|name| !channels.contains_key(*name)) {
+                return Err(TunnelError::config(t!("告警规则 {} 使用的通知渠道 {} 不存在", rule.name, unknown)));
+            }
+            rule_channels.push(if rule.notify.is_empty() { channels.keys().cloned().collect() } else { rule.notify.clone() });
+        }
+        Ok(Self {
+            rules: rules.to_vec(),
+            channels: rule_channels,
+            down: HashMap::new(),
+            stopped: HashSet::new(),
+            crashes: HashMap::new(),
+            states: HashMap::new(),
+        })
+    }
+
+    /// 连接当前的状态，启动时调用一次；已经不可用的从现在开始计时
+    pub fn initial(&mut self, alias: &str, status: TunnelStatus, now: u64) {
+        if matches!(status, TunnelStatus::Degraded | TunnelStatus::Failed) {
+            self.down.insert(alias.to_string(), now);
+        }
+    }
+
+    /// 处理一个事件，返回因此恢复的告警
+    pub fn observe(&mut self, event: &TunnelEvent, now: u64) -> Vec<Alert> {
+        match event {
+            TunnelEvent::Started { alias } => {
+                self.stopped.remove(alias);
+                self.recover(alias, now, t!("{} 已恢复", alias))
+            }
+            TunnelEvent::Stopped { alias } => {
+                self.stopped.insert(alias.clone());
+                self.recover(alias, now, t!("{} 已停止", alias))
+            }
+            TunnelEvent::Crashed { alias, .. } => {
+                self.crashes.entry(alias.clone()).or_default().push_back(now);
+                self.down.entry(alias.clone()).or_insert(now);
+                Vec::new()
+            }
+            TunnelEvent::HealthChanged { alias, to, .. } => match to {
+                TunnelStatus::Running => self.recover(alias, now, t!("{} 已恢复", alias)),
+                TunnelStatus::Paused => {
+                    self.stopped.insert(alias.clone());
+                    self.recover(alias, now, t!("{} 已暂停", alias))
+                }
+                TunnelStatus::Stopped if self.stopped.contains(alias) => Vec::new(),
+                TunnelStatus::Degraded | TunnelStatus::Failed | TunnelStatus::Stopped => {
+                    self.down.entry(alias.clone()).or_insert(now);
+                    Vec::new()
+                }
+            },
+            TunnelEvent::LogLine { .. } => Vec::new(),
+        }
+    }
+
+    /// 检查各规则是否成立，返回需要发送的告警
+    pub fn evaluate(&mut self, now: u64) -> Vec<Alert> {
+        let longest = self.rules.iter()
+            .filter_map(|rule| match rule.condition {
+                Condition::Restarts { minutes, .. } => Some(minutes * 60),
+                Condition::Down { .. } => None,
+            })
+            .max()
+            .unwrap_or(0);
+        for times in self.crashes.values_mut() {
+            while times.front().is_some_and(|time| *time + longest < now) {
+                times.pop_front();
+            }
+        }
+        self.crashes.retain(|_, times| !times.is_empty());
+
+        let mut alerts = Vec::new();
+        for index in 0..self.rules.len() {
+            let rule = &self.rules[index];
+            let mut holding: Vec<(String, String)> = Vec::new();
+            match rule.condition {
+                Condition::Down { minutes } => {
+                    for (alias, since) in &self.down {
+                        let elapsed = now.saturating_sub(*since);
+                        if rule.applies_to(alias) && elapsed >= minutes * 60 {
+                            holding.push((alias.clone(), t!("{} 已不可用 {} 分钟", alias, elapsed / 60)));
+                        }
+                    }
+                }
+                Condition::Restarts { count, minutes } => {
+                    for (alias, times) in &self.crashes {
+                        let recent = times.iter().filter(|time| **time + minutes * 60 >= now).count();
+                        if rule.applies_to(alias) && recent >= count {
+                            holding.push((alias.clone(), t!("{} 在 {} 分钟内重启了 {} 次", alias, minutes, recent)));
+                        }
+                    }
+                }
+            }
+            // 重启次数回落到阈值以下后，下次达到阈值时重新通知
+            if let Condition::Restarts { .. } = rule.condition {
+                for ((rule_index, alias), state) in self.states.iter_mut() {
+                    if *rule_index == index && !holding.iter().any(|(holding, _)| holding == alias) {
+                        state.clear();
+                    }
+                }
+            }
+            for (alias, message) in holding {
+                alerts.extend(self.fire(index, alias, message, now));
+            }
+        }
+        alerts
+    }
+
+    fn fire(&mut self, index: usize, alias: String, message: String, now: u64) -> Option<Alert> {
+        let rule = &self.rules[index];
+        let state = self.states.entry((index, alias.clone())).or_default();
+        if state.notified {
+            return None;
+        }
+        if state.last_sent.is_some_and(|sent| now < sent + rule.cooldown * 60) {
+            if !state.pending {
+                state.pending = true;
+                info!(rule = %rule.name, alias = %alias, "冷却中，不发送通知");
+            }
+            return None;
+        }
+        let message = match std::mem::take(&mut state.suppressed) {
+            0 => message,
+            suppressed => format!("{} {}", message, t!("（冷却期间另有 {} 次未通知）", suppressed)),
+        };
+        state.pending = false;
+        state.notified = true;
+        state.last_sent = Some(now);
+        Some(self.alert(index, alias, AlertState::Firing, message, now))
+    }
+
+    /// 连接恢复或被停止：结束它的故障，已通知的持续时间类告警发送恢复通知
+    fn recover(&mut self, alias: &str, now: u64, message: String) -> Vec<Alert> {
+        self.down.remove(alias);
+        let mut resolved = Vec::new();
+        for index in 0..self.rules.len() {
+            if !matches!(self.rules[index].condition, Condition::Down { .. }) {
+                continue;
+            }
+            if let Some(state) = self.states.get_mut(&(index, alias.to_string()))
+                && state.clear()
+            {
+                resolved.push(index);
+            }
+        }
+        resolved.into_iter()
+            .map(|index| self.alert(index, alias.to_string(), AlertState::Resolved, message.clone(), now))
+            .collect()
+    }
+
+    fn alert(&self, index: usize, alias: String, state: AlertState, message: String, now: u64) -> Alert {
+        Alert {
+            notification: Notification { rule: self.rules[index].name.clone(), alias, state, message, time: now },
+            channels: self.channels[index].clone(),
+        }
+    }
+}
+
+/// 在前台监视连接并按规则发送通知，直到按下 Ctrl-C。规则与渠道在启动时读取，修改后需要重新运行
+pub async fn run(config: &Config, interval: Duration) -> Result<()> {
+    let channels = config.notify_channels().clone();
+    let mut engine = Engine::new(config.alert_rules(), &channels)?;
+    let mut events = events::subscribe();
+    let now = platform::unix_now();
+    for tunnel in config.list_tunnels()? {
+        if tunnel.enabled {
+            engine.initial(&tunnel.alias, tunnel.status(), now);
+        }
+    }
+    Monitor::new().health(interval).spawn()?;
+    eprintln!("{}", t!("正在按 {} 条告警规则监视连接，按 Ctrl-C 停止", config.alert_rules().len()));
+
+    let mut ticker = tokio::time::interval(EVALUATE_INTERVAL);
+    loop {
+        let alerts = tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) => engine.observe(&event, platform::unix_now()),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("{}", t!("处理不及，跳过了 {} 个事件", skipped));
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            _ = ticker.tick() => engine.evaluate(platform::unix_now()),
+            _ = tokio::signal::ctrl_c() => break,
+        };
+        if alerts.is_empty() {
+            continue;
+        }
+        let channels = channels.clone();
+        // 发送经 curl 或外部命令完成，会阻塞
+        let _ = tokio::task::spawn_blocking(move || dispatch(&alerts, &channels)).await;
+    }
+    Ok(())
+}
+
+/// 发送到各渠道；某个渠道失败只记录下来，不影响其他渠道
+pub fn dispatch(alerts: &[Alert], channels: &BTreeMap<String, Channel>) {
+    for alert in alerts {
+        eprintln!("{}", alert.notification.text());
+        for name in &alert.channels {
+            let Some(channel) = channels.get(name) else {
+                continue;
+            };
+            if let Err(e) = channel.send(name, &alert.notification) {
+                warn!("{}", t!("通过 {} 发送通知失败: {}", name, e));
+            }
+        }
+    }
+}
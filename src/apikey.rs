@@ -0,0 +1,113 @@
+//! 控制接口（`tfa grpc`、`tfa web`）的 API 密钥：按权限范围区分只读、操作和管理，
+//! 例如监控程序只能查看状态，不能停止连接。
+//!
+//! 配置中只保存密钥的 SHA-256 摘要，密钥本身只在创建时显示一次。每个请求都重新读取配置，撤销立即生效。
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chacha20poly1305::aead::OsRng;
+use chacha20poly1305::aead::rand_core::RngCore;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::i18n::t;
+use crate::platform;
+
+/// 密钥的前缀，便于在日志和密钥扫描中识别
+const PREFIX: &str = "tfa_";
+
+/// 列表中显示的密钥开头的字符数（含前缀）
+const HINT_LEN: usize = 10;
+
+/// 密钥的权限范围，后者包含前者的全部权限
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    /// 查看连接、日志和事件
+    Read,
+    /// 启动、停止连接
+    Operate,
+    /// 强制停止不是由本工具启动的进程
+    Admin,
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Scope::Read => "read",
+            Scope::Operate => "operate",
+            Scope::Admin => "admin",
+        })
+    }
+}
+
+/// 配置中保存的一个密钥，以名称为键
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKey {
+    pub scope: Scope,
+    /// 密钥的 SHA-256 摘要（十六进制）
+    pub hash: String,
+    /// 密钥开头的几个字符，用于在列表中辨认
+    pub hint: String,
+    pub created_at: u64,
+}
+
+impl ApiKey {
+    /// 生成新密钥，返回要保存的记录和只显示一次的密钥
+    pub fn generate(scope: Scope) -> (Self, String) {
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes);
+        let secret = format!("{}{}", PREFIX, URL_SAFE_NO_PAD.encode(bytes));
+        let key = Self {
+            scope,
+            hash: digest(&secret),
+            hint: secret.chars().take(HINT_LEN).collect(),
+            created_at: platform::unix_now(),
+        };
+        (key, secret)
+    }
+}
+
+fn digest(secret: &str) -> String {
+    Sha256::digest(secret.as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// 请求被拒绝的原因
+#[derive(Debug)]
+pub enum Denied {
+    /// 没有提供密钥，或密钥不存在、已撤销
+    Unknown,
+    /// 密钥的权限范围不足
+    Insufficient { name: String, scope: Scope, required: Scope },
+}
+
+impl fmt::Display for Denied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Denied::Unknown => f.write_str(&t!("缺少 API 密钥或密钥无效")),
+            Denied::Insufficient { name, scope, required } => {
+                f.write_str(&t!("API 密钥 {} 的权限为 {}，该操作需要 {}", name, scope, required))
+            }
+        }
+    }
+}
+
+/// 检查请求中的密钥是否存在且权限范围不低于 `required`
+pub fn authorize(keys: &BTreeMap<String, ApiKey>, secret: Option<&str>, required: Scope) -> Result<(), Denied> {
+    let hash = digest(secret.ok_or(Denied::Unknown)?.trim());
+    let (name, key) = keys.iter()
+        .find(|(_, key)| constant_time_eq(key.hash.as_bytes(), hash.as_bytes()))
+        .ok_or(Denied::Unknown)?;
+    if key.scope < required {
+        return Err(Denied::Insufficient { name: name.clone(), scope: key.scope, required });
+    }
+    Ok(())
+}
+
+/// 比较时间与内容无关，避免通过响应时间逐字节猜出密钥
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
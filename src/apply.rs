@@ -0,0 +1,148 @@
+//! `tfa apply`：按清单声明式地调整全局配置中的连接。
+//!
+//! 清单中有而配置中没有的连接被添加，字段不同的被更新；加上 `--prune` 时，配置中有而清单中没有的连接被移除。
+//! 钩子、服务令牌等本机设置在更新时保留。由团队目录导入的受管连接和锁定的连接不会被修改，在计划中标为跳过。
+//! `tfa diff` 只输出同一计划，不做修改。
+
+use anyhow::Result;
+use std::collections::{BTreeSet, HashMap};
+
+use crate::config::Config;
+use crate::error::TunnelError;
+use crate::i18n::t;
+use crate::tunnel::Tunnel;
+
+/// 计划中单个连接的变化
+#[derive(Debug, PartialEq, Eq)]
+pub enum Change {
+    Added,
+    /// 附带有变化的字段
+    Updated(Vec<String>),
+    Unchanged,
+    Removed,
+    /// 受管或锁定的连接，附带原因
+    Skipped(String),
+}
+
+impl Change {
+    /// 是否会修改配置
+    pub fn is_pending(&self) -> bool {
+        matches!(self, Change::Added | Change::Updated(_) | Change::Removed)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Change::Added => "add",
+            Change::Updated(_) => "update",
+            Change::Unchanged => "unchanged",
+            Change::Removed => "remove",
+            Change::Skipped(_) => "skip",
+        }
+    }
+}
+
+/// 应用清单的计划
+pub struct Plan {
+    /// 按别名排序的各连接的变化
+    pub changes: Vec<(String, Change)>,
+    /// 应用后的全局连接
+    pub tunnels: Vec<Tunnel>,
+}
+
+impl Plan {
+    pub fn count(&self, wanted: fn(&Change) -> bool) -> usize {
+        self.changes.iter().filter(|(_, change)| wanted(change)).count()
+    }
+
+    /// 应用后该别名的连接，被移除时为 None
+    pub fn tunnel(&self, alias: &str) -> Option<&Tunnel> {
+        self.tunnels.iter().find(|t| t.alias == alias)
+    }
+}
+
+/// 比较清单与当前配置，得到计划；不修改配置
+pub fn plan(config: &Config, manifest: Vec<Tunnel>, prune: bool) -> Result<Plan> {
+    let mut current = config.global_tunnels();
+    let mut seen = BTreeSet::new();
+    let mut changes = Vec::new();
+    let mut incoming: HashMap<String, Tunnel> = HashMap::new();
+    for mut tunnel in manifest {
+        let alias = tunnel.alias.clone();
+        if !seen.insert(alias.clone()) {
+            return Err(TunnelError::config(t!("别名 {} 在清单中重复", alias)));
+        }
+        tunnel.managed_by = None;
+        let change = match current.get(&alias) {
+            None => Change::Added,
+            Some(existing) => {
+                // 本机设置不写在清单中，更新时保留
+                tunnel.hooks = existing.hooks.clone();
+                tunnel.service_token = existing.service_token;
+                tunnel.client_password = existing.client_password;
+                tunnel.outbound_proxy = existing.outbound_proxy.clone();
+                tunnel.log_sinks = existing.log_sinks.clone();
+                tunnel.ping_url = existing.ping_url.clone();
+                tunnel.ssh_host = existing.ssh_host;
+                tunnel.privileged = existing.privileged;
+                tunnel.enabled = existing.enabled;
+                tunnel.autostart = existing.autostart;
+                tunnel.on_change = existing.on_change;
+                let fields = changed_fields(existing, &tunnel);
+                match skip_reason(existing) {
+                    _ if fields.is_empty() => Change::Unchanged,
+                    Some(reason) => Change::Skipped(reason),
+                    None => Change::Updated(fields),
+                }
+            }
+        };
+        if change.is_pending() {
+            incoming.insert(alias.clone(), tunnel);
+        }
+        changes.push((alias, change));
+    }
+
+    if prune {
+        let mut extraneous: Vec<&Tunnel> = current.values().filter(|t| !seen.contains(&t.alias)).collect();
+        extraneous.sort_by(|a, b| a.alias.cmp(&b.alias));
+        let mut removed = Vec::new();
+        for tunnel in extraneous {
+            match skip_reason(tunnel) {
+                Some(reason) => changes.push((tunnel.alias.clone(), Change::Skipped(reason))),
+                None => {
+                    removed.push(tunnel.alias.clone());
+                    changes.push((tunnel.alias.clone(), Change::Removed));
+                }
+            }
+        }
+        for alias in removed {
+            current.remove(&alias);
+        }
+    }
+    current.extend(incoming);
+    changes.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(Plan { changes, tunnels: current.into_values().collect() })
+}
+
+fn skip_reason(tunnel: &Tunnel) -> Option<String> {
+    if let Some(url) = &tunnel.managed_by {
+        Some(t!("已由目录 {} 管理", url))
+    } else if tunnel.locked {
+        Some(t!("已锁定"))
+    } else {
+        None
+    }
+}
+
+/// 两个连接之间取值不同的字段
+fn changed_fields(before: &Tunnel, after: &Tunnel) -> Vec<String> {
+    let as_map = |tunnel: &Tunnel| match serde_json::to_value(tunnel) {
+        Ok(serde_json::Value::Object(map)) => map,
+        _ => Default::default(),
+    };
+    let (before, after) = (as_map(before), as_map(after));
+    let keys: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    keys.into_iter()
+        .filter(|key| before.get(*key) != after.get(*key))
+        .cloned()
+        .collect()
+}
@@ -0,0 +1,75 @@
+//! 经由连接的访问记录。
+//!
+//! cloudflared access tcp 在默认日志级别下只记录监听与出错，不记录客户端连接的建立与断开，
+//! 因此访问记录由统计代理写入：每个客户端连接断开时追加一条，包含建立时间、时长与流量。
+//! 只有启用了统计代理（`--proxy true`）的连接才会留下记录。
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::proxy::ConnectionInfo;
+use tracing::debug;
+
+/// 访问记录文件中的一行
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Access {
+    pub alias: String,
+    pub peer: String,
+    pub opened_at: u64,
+    pub closed_at: u64,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+}
+
+impl Access {
+    pub fn duration(&self) -> u64 {
+        self.closed_at.saturating_sub(self.opened_at)
+    }
+}
+
+/// 记录一次已结束的客户端连接。写入失败只影响审计，不影响转发
+pub fn record(alias: &str, conn: &ConnectionInfo, closed_at: u64) {
+    let access = Access {
+        alias: alias.to_string(),
+        peer: conn.peer.clone(),
+        opened_at: conn.opened_at,
+        closed_at,
+        bytes_in: conn.bytes_in,
+        bytes_out: conn.bytes_out,
+    };
+    if let Err(e) = append(&access) {
+        debug!(error = %e, "写入访问记录失败");
+    }
+}
+
+fn append(access: &Access) -> Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path()?)?;
+    writeln!(file, "{}", serde_json::to_string(access)?)?;
+    Ok(())
+}
+
+/// 该连接最近的访问记录，按建立时间先后排列；`limit` 为 0 时不限条数
+pub fn load(alias: &str, limit: usize) -> Result<Vec<Access>> {
+    let path = path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let mut entries: Vec<Access> = fs::read_to_string(path)?
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .filter(|access: &Access| access.alias == alias)
+        .collect();
+    entries.sort_by_key(|access| access.opened_at);
+    if limit > 0 && entries.len() > limit {
+        entries.drain(..entries.len() - limit);
+    }
+    Ok(entries)
+}
+
+fn path() -> Result<PathBuf> {
+    Ok(Config::config_dir()?.join("audit.jsonl"))
+}
@@ -0,0 +1,120 @@
+//! 配置备份：每次写入配置文件前把原来的内容保存到 `<档案目录>/backups/`，只保留最近的若干份，
+//! 误删连接或导入了错误的目录后可以用 `tfa config restore` 恢复。
+//!
+//! 备份文件名为 `<配置文件名>-<YYYYMMDD-HHMMSS>.json`（UTC），同一秒内多次写入只保留第一次之前的内容，
+//! 即命令执行前的状态。
+
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use crate::error::TunnelError;
+use crate::history;
+use crate::i18n::t;
+use crate::platform;
+use tracing::debug;
+
+/// 未设置时保留的备份份数
+pub const DEFAULT_RETENTION: usize = 20;
+
+/// 一份配置备份
+#[derive(Debug, Clone)]
+pub struct Backup {
+    /// 时间戳，如 20241016-045511，也用作 `--at` 的参数
+    pub stamp: String,
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+impl Backup {
+    /// 备份时间，`YYYY-MM-DD HH:MM:SS`（UTC）
+    pub fn time(&self) -> String {
+        let d = &self.stamp;
+        format!("{}-{}-{} {}:{}:{}", &d[0..4], &d[4..6], &d[6..8], &d[9..11], &d[11..13], &d[13..15])
+    }
+
+    /// 备份中的连接数，无法解析（如加密的字段损坏）时为 None
+    pub fn tunnel_count(&self) -> Option<usize> {
+        let content = fs::read_to_string(&self.path).ok()?;
+        let value: serde_json::Value = serde_json::from_str(&content).ok()?;
+        value.get("tunnels")?.as_object().map(|tunnels| tunnels.len())
+    }
+}
+
+/// 备份目录
+fn dir() -> Result<PathBuf> {
+    Ok(Config::config_dir()?.join("backups"))
+}
+
+/// 备份文件名的前缀，区分档案的 config.json 与 --config 指定的文件
+fn prefix(config_path: &Path) -> String {
+    let stem = config_path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    format!("{}-", stem)
+}
+
+/// 由 Unix 时间生成时间戳
+fn stamp(secs: u64) -> String {
+    let time = history::format_time(secs);
+    let digits: String = time.chars().filter(char::is_ascii_digit).collect();
+    format!("{}-{}", &digits[..8], &digits[8..])
+}
+
+/// 备份当前的配置文件，并删除超出保留份数的旧备份。配置文件尚不存在时什么也不做
+pub fn snapshot(config_path: &Path, retention: usize) -> Result<Option<PathBuf>> {
+    if retention == 0 || !config_path.is_file() {
+        return Ok(None);
+    }
+    let dir = dir()?;
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("{}{}.json", prefix(config_path), stamp(platform::unix_now())));
+    if !path.exists() {
+        fs::copy(config_path, &path)?;
+        debug!(path = %path.display(), "已备份配置");
+    }
+    for old in list(config_path)?.into_iter().skip(retention) {
+        let _ = fs::remove_file(old.path);
+    }
+    Ok(Some(path))
+}
+
+/// 配置文件的全部备份，最近的在前
+pub fn list(config_path: &Path) -> Result<Vec<Backup>> {
+    let dir = dir()?;
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let prefix = prefix(config_path);
+    let mut backups: Vec<Backup> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let stamp = name.strip_prefix(&prefix)?.strip_suffix(".json")?.to_string();
+            let valid = stamp.len() == 15
+                && stamp.as_bytes()[8] == b'-'
+                && stamp.bytes().enumerate().all(|(i, b)| i == 8 || b.is_ascii_digit());
+            valid.then(|| Backup { stamp, size: entry.metadata().map(|m| m.len()).unwrap_or(0), path: entry.path() })
+        })
+        .collect();
+    backups.sort_by(|a, b| b.stamp.cmp(&a.stamp));
+    Ok(backups)
+}
+
+/// 按时间戳查找备份，接受 `20241016-045511` 与 `2024-10-16 04:55:11` 两种写法
+pub fn find(config_path: &Path, at: &str) -> Result<Backup> {
+    let digits: String = at.chars().filter(char::is_ascii_digit).collect();
+    list(config_path)?
+        .into_iter()
+        .find(|backup| backup.stamp.replace('-', "") == digits)
+        .ok_or_else(|| TunnelError::config(t!("没有 {} 的配置备份，运行 'tfa config backup --list' 查看全部备份", at)))
+}
+
+/// 用备份替换配置文件；替换前先备份当前的配置，恢复操作本身也可以撤销
+pub fn restore(config_path: &Path, backup: &Backup, retention: usize) -> Result<()> {
+    let content = fs::read_to_string(&backup.path)?;
+    serde_json::from_str::<Config>(&content)
+        .map_err(|e| TunnelError::config(t!("备份 {} 无法解析: {}", backup.path.display(), e)))?;
+    snapshot(config_path, retention)?;
+    fs::write(config_path, content)?;
+    Ok(())
+}
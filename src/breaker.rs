@@ -0,0 +1,122 @@
+//! 崩溃循环熔断：连接在短时间内反复失败时停止重试，而不是不停地重连 Cloudflare。
+//!
+//! 启动尝试崩溃或超时，以及上次启动的进程没有经过 `tfa stop` 就退出了，都记为一次失败，
+//! 保存在运行目录的 `<alias>.failures.json` 中。最近 `crash_window` 分钟内的失败达到
+//! `crash_limit` 次时熔断：不再重试、拒绝启动，状态显示为 failed，并执行 on_failure 钩子。
+//! 失败记录超出时间窗口后自动恢复，也可以用 `tfa reset` 立即清除。
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::error::TunnelError;
+use crate::events::{self, TunnelEvent};
+use crate::hooks::{self, HookKind};
+use crate::ping;
+use crate::platform;
+use crate::state::StateStore;
+use crate::tunnel::Tunnel;
+use tracing::{debug, info};
+
+/// 错误摘要保留的最大字符数
+const EXCERPT_CHARS: usize = 200;
+
+/// 连接最近的失败记录
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Failures {
+    /// 失败时间（Unix 秒），只保留时间窗口内的
+    times: Vec<u64>,
+    /// 最后一次失败的错误摘要
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+impl Failures {
+    fn path(alias: &str) -> Result<PathBuf> {
+        Ok(Config::runtime_dir()?.join(format!("{}.failures.json", alias)))
+    }
+
+    pub fn load(alias: &str) -> Self {
+        Self::path(alias).ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, alias: &str) -> Result<()> {
+        StateStore::write(&Self::path(alias)?, serde_json::to_string_pretty(self)?.as_bytes())?;
+        Ok(())
+    }
+
+    /// 清除失败记录，解除熔断
+    pub fn clear(alias: &str) -> Result<bool> {
+        let path = Self::path(alias)?;
+        if !path.exists() {
+            return Ok(false);
+        }
+        fs::remove_file(path)?;
+        Ok(true)
+    }
+
+    /// 时间窗口内的失败次数
+    pub fn recent(&self, tunnel: &Tunnel) -> usize {
+        let since = platform::unix_now().saturating_sub(tunnel.crash_window * 60);
+        self.times.iter().filter(|time| **time >= since).count()
+    }
+
+    /// 是否已熔断；`crash_limit` 为 0 时不熔断
+    pub fn tripped(&self, tunnel: &Tunnel) -> bool {
+        tunnel.crash_limit > 0 && self.recent(tunnel) >= tunnel.crash_limit as usize
+    }
+
+    fn error(&self, tunnel: &Tunnel) -> TunnelError {
+        TunnelError::CrashLoop {
+            alias: tunnel.alias.clone(),
+            failures: self.recent(tunnel),
+            minutes: tunnel.crash_window,
+            last_error: self.last_error.clone().unwrap_or_default(),
+        }
+    }
+}
+
+/// 已熔断时拒绝启动
+pub fn check(tunnel: &Tunnel) -> Result<(), TunnelError> {
+    let failures = Failures::load(&tunnel.alias);
+    if failures.tripped(tunnel) {
+        return Err(failures.error(tunnel));
+    }
+    Ok(())
+}
+
+/// 记录一次失败并推送到 ping_url；这次失败导致熔断时执行 on_failure 钩子，并返回熔断错误
+pub fn record(tunnel: &Tunnel, error: &str) -> Option<TunnelError> {
+    let mut failures = Failures::load(&tunnel.alias);
+    let since = platform::unix_now().saturating_sub(tunnel.crash_window * 60);
+    failures.times.retain(|time| *time >= since);
+    failures.times.push(platform::unix_now());
+    failures.last_error = Some(excerpt(error));
+    events::publish(TunnelEvent::Crashed { alias: tunnel.alias.clone(), error: excerpt(error) });
+    ping::down(tunnel, &excerpt(error));
+    if let Err(e) = failures.save(&tunnel.alias) {
+        debug!(alias = %tunnel.alias, error = %e, "保存失败记录失败");
+    }
+    debug!(alias = %tunnel.alias, failures = failures.times.len(), limit = tunnel.crash_limit, "记录启动失败");
+    if !failures.tripped(tunnel) {
+        return None;
+    }
+    let error = failures.error(tunnel);
+    info!(alias = %tunnel.alias, "{}", error);
+    let _ = hooks::run_with_env(tunnel, HookKind::OnFailure, &[("TUNNEL_ERROR", failures.last_error.as_deref().unwrap_or_default())]);
+    Some(error)
+}
+
+/// 错误信息的最后一个非空行，过长时截断
+fn excerpt(error: &str) -> String {
+    let line = error.lines().rev().find(|line| !line.trim().is_empty()).unwrap_or_default().trim();
+    match line.char_indices().nth(EXCERPT_CHARS) {
+        Some((index, _)) => format!("{}…", &line[..index]),
+        None => line.to_string(),
+    }
+}
@@ -0,0 +1,143 @@
+//! 连接链：连接的后端经另一个连接访问目标，例如先连上跳板机，再访问只有跳板机能到达的服务。
+//!
+//! - socks5、http：上游连接的本地端口是跳板机上的代理，本连接的后端通过 HTTPS_PROXY 经它访问目标，
+//!   启动本连接前会先启动上游连接；
+//! - bastion：上游连接是 cloudflared 跳板模式的 Access 应用，本连接改用它的主机名，
+//!   并以 `--destination` 指定自己的 source；上游连接本身不需要运行。
+
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::SocketAddr;
+
+use crate::config::Config;
+use crate::error::TunnelError;
+use crate::i18n::t;
+use crate::outbound::{self, OutboundProxy};
+use crate::provider::Provider;
+use crate::tunnel::Tunnel;
+
+/// 连接经过的上游连接
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Via {
+    pub alias: String,
+    #[serde(default, skip_serializing_if = "ViaMode::is_default")]
+    pub mode: ViaMode,
+}
+
+/// 经过上游连接的方式：socks5、http 为上游连接本地端口上代理的协议，
+/// bastion 表示上游连接是 cloudflared 跳板模式的 Access 应用
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ViaMode {
+    #[default]
+    Socks5,
+    Http,
+    Bastion,
+}
+
+impl ViaMode {
+    pub fn is_default(&self) -> bool {
+        *self == ViaMode::Socks5
+    }
+}
+
+impl fmt::Display for ViaMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ViaMode::Socks5 => "socks5",
+            ViaMode::Http => "http",
+            ViaMode::Bastion => "bastion",
+        })
+    }
+}
+
+impl Via {
+    /// 需要上游连接在本地监听，即启动本连接前要先启动上游连接
+    pub fn needs_upstream(&self) -> bool {
+        self.mode != ViaMode::Bastion
+    }
+}
+
+impl fmt::Display for Via {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.alias, self.mode)
+    }
+}
+
+/// 后端实际访问目标的方式
+#[derive(Debug, Clone)]
+pub struct Route {
+    /// cloudflared 的 --hostname
+    pub hostname: String,
+    /// cloudflared 的 --destination，只在经跳板访问时设置
+    pub destination: Option<String>,
+    /// 后端进程使用的出站代理，None 表示沿用本进程的环境变量
+    pub proxy: Option<OutboundProxy>,
+    /// 依次经过的上游连接
+    pub hops: Vec<String>,
+}
+
+/// 解析连接的完整链路；没有经过其他连接时即为直接访问
+pub fn resolve(config: &Config, tunnel: &Tunnel) -> Result<Route> {
+    resolve_from(config, tunnel, &mut vec![tunnel.alias.clone()])
+}
+
+/// 不经过其他连接、直接访问 source 的链路
+pub fn direct(tunnel: &Tunnel) -> Route {
+    Route {
+        hostname: tunnel.source.clone(),
+        destination: None,
+        proxy: outbound::effective(tunnel.outbound_proxy.as_ref()),
+        hops: Vec::new(),
+    }
+}
+
+fn resolve_from(config: &Config, tunnel: &Tunnel, visited: &mut Vec<String>) -> Result<Route> {
+    let Some(via) = &tunnel.via else {
+        return Ok(direct(tunnel));
+    };
+    if visited.contains(&via.alias) {
+        visited.push(via.alias.clone());
+        return Err(TunnelError::config(t!("连接之间存在循环依赖: {}", visited.join(" -> "))));
+    }
+    let upstream = config.get_tunnel(&via.alias)
+        .map_err(|_| TunnelError::config(t!("连接 {} 经过的 {} 不存在", tunnel.alias, via.alias)))?;
+    visited.push(via.alias.clone());
+    let upstream_route = resolve_from(config, upstream, visited)?;
+    let mut hops = vec![via.alias.clone()];
+    hops.extend(upstream_route.hops.iter().cloned());
+
+    match via.mode {
+        ViaMode::Socks5 | ViaMode::Http => {
+            // socks5h 由跳板机解析目标主机名，内网域名在本机通常无法解析
+            let scheme = if via.mode == ViaMode::Http { "http" } else { "socks5h" };
+            let addr = SocketAddr::new(upstream.local_ip(), upstream.port);
+            Ok(Route {
+                hostname: tunnel.source.clone(),
+                destination: None,
+                proxy: Some(OutboundProxy::new(&format!("{}://{}", scheme, addr), Vec::new())?),
+                hops,
+            })
+        }
+        ViaMode::Bastion => {
+            if tunnel.provider != Provider::Cloudflared || upstream.provider != Provider::Cloudflared {
+                return Err(TunnelError::config(t!(
+                    "bastion 方式只适用于 cloudflared 连接：{} 与 {} 都必须使用 cloudflared 后端",
+                    tunnel.alias,
+                    via.alias
+                )));
+            }
+            if upstream_route.destination.is_some() {
+                return Err(TunnelError::config(t!("连接 {} 本身经跳板访问，不能再作为 {} 的跳板", via.alias, tunnel.alias)));
+            }
+            Ok(Route {
+                hostname: upstream_route.hostname,
+                destination: Some(tunnel.source.clone()),
+                proxy: upstream_route.proxy,
+                hops,
+            })
+        }
+    }
+}
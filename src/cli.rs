@@ -1,20 +1,243 @@
 use anyhow::Result;
-use clap::Subcommand;
-use dialoguer::{theme::ColorfulTheme, Select};
-use indicatif::ProgressBar;
+use clap::{Subcommand, ValueEnum};
+use comfy_table::{presets, Attribute, Cell, Color, ContentArrangement, Table};
+use dialoguer::console::Term;
+use dialoguer::{theme::ColorfulTheme, Confirm, FuzzySelect, Input, MultiSelect, Password, Select};
+use indicatif::{MultiProgress, ProgressBar};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
 
-use crate::config::Config;
+use crate::adopt::{self, Outcome};
+use crate::alert::{self, AlertRule};
+use crate::apikey::{ApiKey, Scope};
+use crate::apply::{self, Change as ApplyChange};
+use crate::audit;
+use crate::backup;
+use crate::breaker::Failures;
+use crate::chain::{Via, ViaMode};
+use crate::client;
+use crate::clipboard;
+use crate::cloudflared::{self, Pin};
+use crate::compose;
+use crate::config::{self, Config, PortRange, TunnelUpdate};
+use crate::container::Runtime;
+use crate::error::{exit_code, TunnelError};
+use crate::events::{self, Monitor, TunnelEvent};
+use crate::grpc;
+use crate::history::{self, HistoryAction};
+use crate::httpproxy;
+use crate::hooks::Hooks;
+use crate::i18n::{self, t, Lang};
+use crate::import::{self, Change as ImportChange};
+use crate::ipc;
+use crate::jwt;
+use crate::logsink::{self, LogSink};
+use crate::manifest;
+use crate::notify::{AlertState, Channel, Notification};
+use crate::otel::{self, Operation, OtelSettings};
+use crate::outbound::{self, OutboundProxy};
+use crate::ping;
+use crate::platform;
+use crate::privileged::{self, Privileged};
+use crate::probe::Probe;
+use crate::search;
+use crate::template::Template;
+#[cfg(feature = "tray")]
+use crate::tray;
+use crate::profile;
+use crate::provider::{self, LogLevel, Provider, ProviderKind};
+use crate::proxy::{self, ProxyStats};
+use crate::prune;
+use crate::redact::Redactor;
+use crate::reload::{self, OnChange};
+use crate::replay;
+use crate::secrets::{self, ClientPassword, ServiceToken, SmtpPassword};
+use crate::share;
+use crate::sshconfig;
+use crate::state::{LatencyHistory, LatencySample, PauseMark, RuntimeState, Watcher, WatcherMark};
+use crate::sync::{self, Change, Resolve, SyncSettings};
+use crate::system;
+use crate::tunnel::{PortMapping, Recovery, Tunnel, TunnelStatus};
+use crate::validate::{self, Severity};
+use crate::web;
+use tracing::{debug, info, warn};
+
+/// 批量启动/停止时默认同时进行的连接数
+const DEFAULT_JOBS: usize = 4;
+
+/// 等待有效期到达时，重新读取有效期的最长间隔（秒）
+const TTL_POLL_SECS: u64 = 30;
+
+/// 列表中延迟走势显示的最近探测次数
+const LATENCY_SPARKLINE_WIDTH: usize = 20;
+
+/// `tfa status` 供状态栏显示，延迟走势只取最近的几次
+const STATUS_SPARKLINE_WIDTH: usize = 8;
+
+/// 交互选择时超过该数量的连接先按关键字筛选
+const NARROW_THRESHOLD: usize = 10;
+
+/// 后台定期探测的超时上限（秒），探测间隔更短时以间隔为准
+const PROBE_TIMEOUT_SECS: u64 = 10;
+
+/// 只设置了 ping_url 时后台探测的间隔（秒）
+const DEFAULT_PING_INTERVAL: u64 = 60;
 
 #[derive(Subcommand)]
 pub enum Commands {
     /// 列出所有连接
-    List,
+    List {
+        /// 排序方式
+        #[arg(long, value_enum, default_value_t = SortKey::Alias)]
+        sort: SortKey,
+        /// 只显示指定状态的连接
+        #[arg(long, value_enum)]
+        filter: Option<StatusFilter>,
+        /// 按间隔（秒）持续刷新，高亮状态发生变化的连接
+        #[arg(long, value_name = "SECONDS", num_args = 0..=1, default_missing_value = "2")]
+        watch: Option<u64>,
+        /// 只显示系统配置中定义、由系统服务管理的连接
+        #[arg(long)]
+        system: bool,
+    },
+    /// 按别名、源地址与标签模糊查找连接，按匹配程度排序
+    Find {
+        /// 查找的关键字，字符按顺序出现即匹配，如 pdb 可以匹配 prod-db
+        pattern: String,
+    },
+    /// 快速输出连接状态，只读取运行时文件、不启动外部进程，适合状态栏每隔几秒轮询
+    Status {
+        /// 同时列出已停止的连接
+        #[arg(long)]
+        all: bool,
+        /// 输出紧凑的 JSON 数组：别名、状态、端口与运行时长
+        #[arg(long)]
+        json: bool,
+    },
     /// 运行选定的连接
-    Run,
+    Run {
+        /// 要启动的连接别名，支持 `prod-*` 这样的通配符（不再交互选择）
+        #[arg(conflicts_with_all = ["all", "tag", "project"])]
+        aliases: Vec<String>,
+        /// 启动全部连接（不再交互选择）
+        #[arg(long, conflicts_with_all = ["tag", "project"])]
+        all: bool,
+        /// 启动带有该标签的全部连接（不再交互选择）
+        #[arg(long, conflicts_with = "project")]
+        tag: Option<String>,
+        /// 只启动当前项目 .cf-manager.toml 中声明的连接（不再交互选择）
+        #[arg(long)]
+        project: bool,
+        /// 同时启动的连接数上限
+        #[arg(short, long, default_value_t = DEFAULT_JOBS)]
+        jobs: usize,
+        /// 只按启动顺序输出将要执行的命令，不启动任何连接
+        #[arg(long)]
+        dry_run: bool,
+        /// 有效期，如 30m、2h；到期后自动停止所选连接
+        #[arg(long, value_parser = parse_duration, value_name = "DURATION", conflicts_with = "dry_run")]
+        ttl: Option<Duration>,
+        /// 等到所选连接都通过健康检查才返回，适合在 CI 中下一步使用连接之前调用
+        #[arg(long, conflicts_with = "dry_run")]
+        wait: bool,
+        /// --wait 的最长等待时间，如 30、30s、2m
+        #[arg(long, value_parser = parse_duration, default_value = "30s", requires = "wait")]
+        timeout: Duration,
+        /// 启动后把连接的使用方法（未设置时为本地地址）复制到剪贴板；默认值见 tfa config copy
+        #[arg(long, conflicts_with_all = ["dry_run", "no_copy"])]
+        copy: bool,
+        /// 不复制到剪贴板，即使配置中默认复制
+        #[arg(long)]
+        no_copy: bool,
+    },
+    /// 等待正在启动的连接通过健康检查，超时以退出码 6 失败
+    Wait {
+        /// 连接别名
+        #[arg(required = true)]
+        aliases: Vec<String>,
+        /// 最长等待时间，如 30、30s、2m
+        #[arg(long, value_parser = parse_duration, default_value = "30s")]
+        timeout: Duration,
+    },
+    /// 查看单个连接：合并默认值与项目配置后的完整配置、运行状态、启动命令与最近的日志
+    Show {
+        /// 连接别名
+        alias: String,
+        /// 以 JSON 输出
+        #[arg(long)]
+        json: bool,
+        /// 显示的日志行数
+        #[arg(short = 'n', long, default_value_t = 20)]
+        lines: usize,
+    },
+    /// 输出启动连接时将要执行的完整命令、环境变量和工作目录
+    ShowCmd {
+        /// 连接别名
+        alias: String,
+    },
     /// 停止选定的连接
-    Stop,
+    Stop {
+        /// 要停止的连接别名，支持 `prod-*` 这样的通配符（不再交互选择）
+        aliases: Vec<String>,
+        /// 同时结束端口上不是由本工具启动的 cloudflared / kubectl 进程
+        #[arg(long)]
+        force: bool,
+        /// 反过来停止给定连接以外的全部连接，给定连接依赖的连接同样保留；不带别名时交互选择要保留的连接
+        #[arg(long)]
+        others: bool,
+    },
+    /// 启用连接，恢复参与批量启动与状态监视
+    Enable {
+        /// 连接别名
+        #[arg(required = true)]
+        aliases: Vec<String>,
+    },
+    /// 停用连接：仍显示在列表中，但 run --all、--tag、--project 与状态监视都会跳过，运行中的连接不受影响
+    Disable {
+        /// 连接别名
+        #[arg(required = true)]
+        aliases: Vec<String>,
+    },
+    /// 暂停连接：停止运行中的进程，并在 run --all、--tag、--project 等批量启动时跳过，直到恢复
+    Pause {
+        /// 连接别名
+        #[arg(required = true)]
+        aliases: Vec<String>,
+        /// 同时结束端口上不是由本工具启动的 cloudflared / kubectl 进程
+        #[arg(long)]
+        force: bool,
+    },
+    /// 恢复暂停的连接并启动它们
+    Resume {
+        /// 连接别名
+        #[arg(required = true)]
+        aliases: Vec<String>,
+        /// 同时启动的连接数上限
+        #[arg(short, long, default_value_t = DEFAULT_JOBS)]
+        jobs: usize,
+    },
+    /// 清除连接的失败记录，解除因反复失败而停止重试（failed）的状态
+    Reset {
+        /// 连接别名
+        #[arg(required = true)]
+        aliases: Vec<String>,
+    },
     /// 查看连接日志
-    Log,
+    Log {
+        /// 要查看的连接别名，支持通配符（不再交互选择）
+        aliases: Vec<String>,
+        /// 显示已有的日志后继续显示新写入的行，按 Ctrl-C 退出
+        #[arg(short, long)]
+        follow: bool,
+    },
     /// 设置连接参数
     Set {
         /// 连接别名
@@ -22,154 +245,4934 @@ pub enum Commands {
         /// 源地址
         #[arg(long)]
         source: Option<String>,
-        /// 本地端口
+        /// 本地端口，auto 表示从保留端口范围中分配
+        #[arg(long, visible_alias = "local-port", value_parser = parse_port, value_name = "PORT")]
+        port: Option<PortArg>,
+        /// Access 应用背后的服务，如 tcp://localhost:5432，用于显示目标端口和判断客户端类型；传入空字符串删除
+        #[arg(long, value_name = "URL")]
+        service: Option<String>,
+        /// 改为使用的后端；改为 kubectl 时远端端口默认与本地端口相同
+        #[arg(long, value_enum)]
+        provider: Option<ProviderKind>,
+        /// kubectl 后端的远端端口
         #[arg(long)]
-        port: Option<u16>,
+        remote_port: Option<u16>,
+        /// 在 Docker 或 Podman 容器中运行 cloudflared，主机上不需要安装
+        #[arg(long, value_enum)]
+        runtime: Option<Runtime>,
+        /// kubectl 后端使用的 kubeconfig context；传入空字符串删除
+        #[arg(long)]
+        context: Option<String>,
+        /// kubectl 后端的命名空间；传入空字符串删除
+        #[arg(long)]
+        namespace: Option<String>,
+        /// 是否启用统计代理
+        #[arg(long)]
+        proxy: Option<bool>,
+        /// 上行限速（字节/秒，可带 K、M、G 后缀），需要统计代理；运行中的连接立即生效，0 表示不限速
+        #[arg(long, value_parser = parse_rate, value_name = "RATE")]
+        max_up: Option<u64>,
+        /// 下行限速（字节/秒，可带 K、M、G 后缀），需要统计代理；运行中的连接立即生效，0 表示不限速
+        #[arg(long, value_parser = parse_rate, value_name = "RATE")]
+        max_down: Option<u64>,
+        /// 等待连接就绪的超时秒数
+        #[arg(long)]
+        start_timeout: Option<u64>,
+        /// 启动因崩溃或超时失败后的重试次数
+        #[arg(long)]
+        start_retries: Option<u32>,
+        /// 首次重试前等待的秒数，之后每次翻倍
+        #[arg(long)]
+        retry_backoff: Option<u64>,
+        /// 在 --crash-window 分钟内失败达到该次数时停止重试，0 表示不限制
+        #[arg(long)]
+        crash_limit: Option<u32>,
+        /// 统计失败次数的时间窗口（分钟）
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+        crash_window: Option<u64>,
+        /// 标签，多个用逗号分隔；传入空字符串清空
+        #[arg(long, value_delimiter = ',')]
+        tags: Option<Vec<String>>,
+        /// 依赖的连接，多个用逗号分隔；传入空字符串清空
+        #[arg(long, value_delimiter = ',')]
+        depends_on: Option<Vec<String>>,
+        /// 后端经过的上游连接，如跳板机上的代理；传入空字符串改为直接访问
+        #[arg(long, value_name = "ALIAS")]
+        via: Option<String>,
+        /// 经过上游连接的方式，默认 socks5
+        #[arg(long, value_enum, value_name = "MODE")]
+        via_mode: Option<ViaMode>,
+        /// 同时转发的其他端口，写作 本地端口:目标端口，可重复指定；传入空字符串删除全部额外端口
+        #[arg(long = "extra-port", value_name = "LOCAL:REMOTE")]
+        extra_ports: Option<Vec<String>>,
+        /// 启动前执行的命令，失败时放弃启动；传入空字符串删除
+        #[arg(long)]
+        pre_start: Option<String>,
+        /// 启动成功后执行的命令；传入空字符串删除
+        #[arg(long)]
+        post_start: Option<String>,
+        /// 停止前执行的命令；传入空字符串删除
+        #[arg(long)]
+        pre_stop: Option<String>,
+        /// 停止后执行的命令；传入空字符串删除
+        #[arg(long)]
+        post_stop: Option<String>,
+        /// 连接反复失败而停止重试时执行的命令，可通过 TUNNEL_ERROR 获取最后的错误；传入空字符串删除
+        #[arg(long)]
+        on_failure: Option<String>,
+        /// 本地端口绑定的地址，如 0.0.0.0 或 ::1；传入空字符串恢复为只监听 127.0.0.1
+        #[arg(long, value_name = "IP")]
+        listen_addr: Option<String>,
+        /// 运行期间在系统 hosts 文件中指向本地地址的主机名，如 prod-db.local；传入空字符串删除
+        #[arg(long, value_name = "HOST")]
+        local_hostname: Option<String>,
+        /// 是否在 ~/.ssh/config 中生成 `Host <别名>` 条目，使用该连接专用的 known_hosts
+        #[arg(long)]
+        ssh_host: Option<bool>,
+        /// `tfa test` 使用的探测方式：tcp、ssh、http[:/路径]、postgres；传入空字符串恢复为 tcp
+        #[arg(long)]
+        probe: Option<String>,
+        /// 运行期间每隔多少秒在后台探测一次并记录延迟，下次启动时生效；0 表示不记录
+        #[arg(long, value_name = "SECONDS")]
+        probe_interval: Option<u64>,
+        /// 探测成功后请求的推送地址（healthchecks.io、Uptime Kuma），失败时发送失败信号，下次启动时生效；传入空字符串删除
+        #[arg(long, value_name = "URL")]
+        ping_url: Option<String>,
+        /// `tfa exec <alias>` 不带命令时执行的客户端命令，可使用 TUNNEL_HOST、TUNNEL_PORT 环境变量；传入空字符串删除
+        #[arg(long, value_name = "COMMAND")]
+        client: Option<String>,
+        /// `tfa connect` 使用的连接串模板，如 postgresql://app@{host}:{port}/orders；传入空字符串删除
+        #[arg(long, value_name = "URL")]
+        connect_url: Option<String>,
+        /// 连接的使用说明，由 tfa show 显示，启动后也会显示；传入空字符串删除
+        #[arg(long, value_name = "TEXT")]
+        notes: Option<String>,
+        /// 启动后显示的使用方法模板，如 ssh -p {port} user@{host}；传入空字符串删除
+        #[arg(long, value_name = "TEMPLATE")]
+        connect_hint: Option<String>,
+        /// 后端使用的出站代理（http://、socks5:// 等），direct 表示不使用全局代理；传入空字符串删除
+        #[arg(long, value_name = "URL")]
+        outbound_proxy: Option<String>,
+        /// cloudflared 的日志级别
+        #[arg(long, value_enum)]
+        log_level: Option<LogLevel>,
+        /// 写入日志前替换为 ****** 的正则表达式，可重复指定；传入空字符串删除全部规则
+        #[arg(long, value_name = "REGEX")]
+        redact: Option<Vec<String>>,
+        /// 日志转发目标（syslog、journald、eventlog、tcp://、http(s)://、loki+http(s)://），可重复指定；传入空字符串改为使用全局设置
+        #[arg(long = "log-sink", value_name = "SINK")]
+        log_sinks: Option<Vec<String>>,
+        /// 是否锁定连接，锁定后修改或删除需要 --unlock
+        #[arg(long)]
+        locked: Option<bool>,
+        /// 是否启用连接，停用的连接不随批量操作启动
+        #[arg(long)]
+        enabled: Option<bool>,
+        /// 是否在 tfa grpc、tfa web 运行期间自动启动该连接
+        #[arg(long)]
+        autostart: Option<bool>,
+        /// 连接运行期间配置被修改时，tfa grpc、tfa web 的处理方式
+        #[arg(long, value_enum)]
+        on_change: Option<OnChange>,
+        /// 删除该字段或恢复为默认值，可重复指定
+        #[arg(long, value_enum, value_name = "KEY")]
+        unset: Vec<UnsetKey>,
+        /// 允许修改锁定的连接
+        #[arg(long)]
+        unlock: bool,
+        /// 连接正在运行时立即重启它，使修改（如新的本地端口）生效；只重启这一个连接
+        #[arg(long)]
+        apply: bool,
     },
     /// 移除连接
     Remove {
         /// 连接别名
         alias: String,
+        /// 同时结束端口上不是由本工具启动的 cloudflared / kubectl 进程
+        #[arg(long)]
+        force: bool,
+        /// 允许删除锁定的连接
+        #[arg(long)]
+        unlock: bool,
     },
     /// 添加新连接
     Add {
         /// 连接别名
+        #[arg(required_unless_present = "from_file")]
+        alias: Option<String>,
+        /// 源地址；kubectl 后端为目标资源，如 svc/foo
+        #[arg(long, required_unless_present = "from_file")]
+        source: Option<String>,
+        /// 本地端口，auto 表示从保留端口范围中分配；使用模板时默认为模板中的端口
+        #[arg(long, visible_alias = "local-port", value_parser = parse_port, value_name = "PORT", required_unless_present_any = ["from_file", "template"])]
+        port: Option<PortArg>,
+        /// Access 应用背后的服务，如 tcp://localhost:5432，用于显示目标端口和判断客户端类型
+        #[arg(long, value_name = "URL", conflicts_with = "from_file")]
+        service: Option<String>,
+        /// 按模板预设本地端口、探测方式与客户端命令：内置 ssh、rdp、postgres、mysql、redis、mongo、k8s-api，也可以在配置中定义
+        #[arg(long, value_name = "NAME", conflicts_with = "from_file")]
+        template: Option<String>,
+        /// 从清单文件批量添加：CSV（alias,source,port[,tags]）或 YAML
+        #[arg(long, value_name = "FILE", conflicts_with_all = ["alias", "source", "port"])]
+        from_file: Option<PathBuf>,
+        /// 只预览清单中将要添加的连接，不修改配置
+        #[arg(long, requires = "from_file")]
+        dry_run: bool,
+        /// 提供连接的后端
+        #[arg(long, value_enum, default_value_t = ProviderKind::Cloudflared)]
+        provider: ProviderKind,
+        /// kubectl 后端的远端端口，默认与本地端口相同
+        #[arg(long)]
+        remote_port: Option<u16>,
+        /// 在 Docker 或 Podman 容器中运行 cloudflared，主机上不需要安装；只适用于 cloudflared 后端
+        #[arg(long, value_enum)]
+        runtime: Option<Runtime>,
+        /// kubectl 后端使用的 kubeconfig context
+        #[arg(long)]
+        context: Option<String>,
+        /// kubectl 后端的命名空间
+        #[arg(long)]
+        namespace: Option<String>,
+        /// 启用统计代理，记录连接数与流量
+        #[arg(long)]
+        proxy: bool,
+        /// 上行限速（字节/秒，可带 K、M、G 后缀），需要 --proxy
+        #[arg(long, value_parser = parse_rate, value_name = "RATE", requires = "proxy")]
+        max_up: Option<u64>,
+        /// 下行限速（字节/秒，可带 K、M、G 后缀），需要 --proxy
+        #[arg(long, value_parser = parse_rate, value_name = "RATE", requires = "proxy")]
+        max_down: Option<u64>,
+        /// 标签，多个用逗号分隔
+        #[arg(long, value_delimiter = ',')]
+        tags: Vec<String>,
+        /// 依赖的连接，启动前会先启动它们，多个用逗号分隔
+        #[arg(long, value_delimiter = ',')]
+        depends_on: Vec<String>,
+        /// 后端经过的上游连接，如跳板机上的代理；socks5、http 方式下启动前会先启动它
+        #[arg(long, value_name = "ALIAS")]
+        via: Option<String>,
+        /// 经过上游连接的方式：socks5、http 把上游连接的本地端口作为代理，bastion 经 cloudflared 跳板以 --destination 访问 source
+        #[arg(long, value_enum, value_name = "MODE", requires = "via")]
+        via_mode: Option<ViaMode>,
+        /// 同时转发的其他端口，写作 本地端口:目标端口，可重复指定；只适用于 kubectl 连接和经跳板（bastion）访问的 cloudflared 连接
+        #[arg(long = "extra-port", value_name = "LOCAL:REMOTE")]
+        extra_ports: Vec<String>,
+        /// 本地端口绑定的地址，如 0.0.0.0、::1 或某个网卡的 IP，默认只监听 127.0.0.1
+        #[arg(long, value_name = "IP")]
+        listen_addr: Option<IpAddr>,
+        /// 运行期间在系统 hosts 文件中指向本地地址的主机名，如 prod-db.local
+        #[arg(long, value_name = "HOST")]
+        local_hostname: Option<String>,
+        /// 在 ~/.ssh/config 中生成 `Host <别名>` 条目，之后可以直接 ssh <别名>，使用该连接专用的 known_hosts
+        #[arg(long)]
+        ssh_host: bool,
+        /// `tfa test` 使用的探测方式：tcp、ssh、http[:/路径]、postgres，默认 tcp
+        #[arg(long)]
+        probe: Option<Probe>,
+        /// 运行期间每隔多少秒在后台探测一次并记录延迟，显示在 list 中
+        #[arg(long, value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..))]
+        probe_interval: Option<u64>,
+        /// 探测成功后请求的推送地址（healthchecks.io、Uptime Kuma），失败时发送失败信号；未设置 --probe-interval 时每 60 秒探测一次
+        #[arg(long, value_name = "URL")]
+        ping_url: Option<String>,
+        /// `tfa exec <alias>` 不带命令时执行的客户端命令，可使用 TUNNEL_HOST、TUNNEL_PORT 环境变量
+        #[arg(long, value_name = "COMMAND")]
+        client: Option<String>,
+        /// `tfa connect` 使用的连接串模板，可写用户名与库名，{host}、{port} 替换为本地地址，如 postgresql://app@{host}:{port}/orders
+        #[arg(long, value_name = "URL")]
+        connect_url: Option<String>,
+        /// 连接的使用说明，由 tfa show 显示，启动后也会显示
+        #[arg(long, value_name = "TEXT")]
+        notes: Option<String>,
+        /// 启动后显示的使用方法模板，{alias}、{host}、{port} 替换为连接的别名与本地地址，如 ssh -p {port} user@{host}
+        #[arg(long, value_name = "TEMPLATE")]
+        connect_hint: Option<String>,
+        /// 后端使用的出站代理（http://、socks5:// 等），direct 表示不使用全局代理
+        #[arg(long, value_name = "URL")]
+        outbound_proxy: Option<String>,
+        /// cloudflared 的日志级别
+        #[arg(long, value_enum)]
+        log_level: Option<LogLevel>,
+        /// 写入日志前替换为 ****** 的正则表达式，可重复指定
+        #[arg(long, value_name = "REGEX")]
+        redact: Vec<String>,
+        /// 日志转发目标（syslog、journald、eventlog、tcp://、http(s)://、loki+http(s)://），可重复指定，默认使用全局设置
+        #[arg(long = "log-sink", value_name = "SINK")]
+        log_sinks: Vec<String>,
+        /// 锁定连接，之后修改或删除需要 --unlock
+        #[arg(long)]
+        locked: bool,
+        /// tfa grpc、tfa web 运行期间自动启动该连接
+        #[arg(long)]
+        autostart: bool,
+        /// 连接运行期间配置被修改时，tfa grpc、tfa web 的处理方式
+        #[arg(long, value_enum)]
+        on_change: Option<OnChange>,
+    },
+    /// 复制已有连接的设置到新的别名
+    Copy {
+        /// 要复制的连接别名
         alias: String,
-        /// 源地址
+        /// 新连接的别名
+        new_alias: String,
+        /// 新连接的源地址，默认与原连接相同
+        #[arg(long)]
+        source: Option<String>,
+        /// 新连接的本地端口，auto 表示从保留端口范围中分配；默认与原连接相同
+        #[arg(long, value_parser = parse_port, value_name = "PORT")]
+        port: Option<PortArg>,
+    },
+    /// 输出正在运行的连接的本地地址，供 shell、direnv 或 docker compose 使用
+    Env {
+        /// 连接别名，或标签（输出带有该标签的全部连接）
+        target: String,
+        /// 输出格式
+        #[arg(long, value_enum, default_value_t = EnvFormat::Dotenv)]
+        format: EnvFormat,
+    },
+    /// 启动 compose 文件 x-cf-tunnels 中声明的连接，并把本地地址作为环境变量传给 docker compose
+    Compose {
+        #[command(subcommand)]
+        action: ComposeAction,
+    },
+    /// 查看连接的客户端与流量统计（需启用代理）
+    Connections {
+        /// 连接别名
+        alias: String,
+    },
+    /// 查看经由连接的访问记录：客户端、建立时间、时长与流量（需启用代理）
+    Audit {
+        /// 连接别名
+        alias: String,
+        /// 最多显示的条数，0 表示全部
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// 在连接可用期间执行命令，结束后自动停止由本次调用启动的连接
+    Exec {
+        /// 连接别名
+        alias: String,
+        /// 等待连接就绪的超时秒数
+        #[arg(long, default_value_t = 15)]
+        timeout: u64,
+        /// 要执行的命令，写在 `--` 之后；省略时执行连接的客户端命令
+        #[arg(last = true)]
+        command: Vec<String>,
+    },
+    /// 按数据库类型启动 psql、mysql、redis-cli 或 mongosh 并连接到连接的本地地址，结束后自动停止由本次调用启动的连接
+    Connect {
+        /// 连接别名
+        alias: String,
+        /// 数据库类型，默认按连接串、探测方式、客户端命令与端口判断
+        #[arg(long, value_enum)]
+        kind: Option<client::Kind>,
+        /// 等待连接就绪的超时秒数
+        #[arg(long, default_value_t = 15)]
+        timeout: u64,
+        /// 输入数据库密码并保存到系统钥匙串，之后连接时自动使用
+        #[arg(long, conflicts_with = "forget_password")]
+        save_password: bool,
+        /// 删除系统钥匙串中保存的数据库密码
+        #[arg(long)]
+        forget_password: bool,
+        /// 传给客户端的其他参数，写在 `--` 之后
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+    /// 经 `cloudflared access curl` 请求连接主机上受 Access 保护的 HTTP 接口，不需要先启动连接
+    Curl {
+        /// 连接别名
+        alias: String,
+        /// 请求的路径，如 /api/health
+        #[arg(default_value = "/")]
+        path: String,
+        /// 传给 curl 的其他参数，写在 `--` 之后
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+    /// 取得连接主机当前的 Access 令牌并显示其中的身份、受众与过期时间，即将过期时给出提醒
+    Token {
+        /// 连接别名
+        alias: String,
+        /// 输出令牌中的全部声明（JSON）
+        #[arg(long, conflicts_with = "raw")]
+        json: bool,
+        /// 只输出令牌本身，便于在脚本中使用
+        #[arg(long)]
+        raw: bool,
+    },
+    /// 按设置了 ssh_host 的连接重新生成 ~/.ssh/config 中由本工具管理的条目
+    SshConfig {
+        /// 只输出将要写入的条目，不修改文件
+        #[arg(long, conflicts_with = "forget_key")]
+        print: bool,
+        /// 删除该连接专用的 known_hosts，远端主机更换密钥后使用
+        #[arg(long, value_name = "ALIAS")]
+        forget_key: Option<String>,
+    },
+    /// 设置本地端口低于 1024 时的处理方式：redirect 改为监听非特权端口并设置端口转发规则，elevate 经 sudo 启动后端进程，off 删除转发规则并恢复为直接监听
+    PrivilegedPort {
+        /// 连接别名
+        alias: String,
+        #[arg(value_enum)]
+        mode: privileged::Mode,
+        /// 端口转发时实际监听的非特权端口，默认为本地端口加 8000
+        #[arg(long)]
+        port: Option<u16>,
+        /// 只输出端口转发规则的命令，由你以管理员身份自行执行
+        #[arg(long)]
+        print: bool,
+    },
+    /// 测试能否经连接访问目标服务：连接未运行时临时启动，按 probe 设置探测一次并报告往返耗时
+    Test {
+        /// 连接别名
+        alias: String,
+        /// 本次使用的探测方式，覆盖连接的 probe 设置
+        #[arg(long)]
+        probe: Option<Probe>,
+        /// 等待连接就绪与探测应答的超时秒数
+        #[arg(long, default_value_t = 15)]
+        timeout: u64,
+    },
+    /// 临时连接到一个主机名而不写入配置：在前台运行并输出日志，按 Ctrl-C 结束
+    Tcp {
+        /// Cloudflare Access 主机名
+        hostname: String,
+        /// 本地监听端口，默认自动选取一个空闲端口
+        #[arg(short, long)]
+        port: Option<u16>,
+    },
+    /// 以 quick tunnel 把本机服务临时公开到 trycloudflare.com，不需要 Cloudflare 账号：在前台运行，显示地址与二维码，按 Ctrl-C 结束
+    Share {
+        /// 要公开的本机端口，或完整的地址，如 http://localhost:3000
+        target: String,
+        /// 不显示二维码
+        #[arg(long)]
+        no_qr: bool,
+    },
+    /// 接管手动启动的 cloudflared access 进程：按主机名和本地端口匹配已配置的连接，之后可以正常停止和管理
+    Adopt {
+        /// 只列出匹配结果，不接管
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// 立即应用配置的修改：启动新加入的 autostart 连接，停止已删除的连接，按 on_change 重启有修改的运行中连接
+    Reload,
+    /// 发现与任何连接都不对应的 cloudflared access 进程，逐个确认后导入为新连接并接管
+    Discover,
+    /// 重新接管仍在运行的连接：核对记录的后端进程，补上意外退出的统计代理、有效期监视与延迟记录进程
+    Recover {
+        /// 连接别名，支持通配符；省略时处理全部连接
+        aliases: Vec<String>,
+    },
+    /// 查看启动、停止、添加、移除、修改连接的操作历史
+    History {
+        /// 只看该连接的记录
+        #[arg(long)]
+        alias: Option<String>,
+        /// 最多显示的条数，0 表示全部
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// 按原顺序重新执行最近一次交互式 run / stop 会话中选择的连接，不再询问
+    Replay {
+        /// 要重放的会话，1 为最近一次
+        #[arg(default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+        session: u64,
+        /// 列出记录的会话
+        #[arg(long, conflicts_with = "dry_run")]
+        list: bool,
+        /// 只显示将要执行的操作
+        #[arg(long)]
+        dry_run: bool,
+        /// 同时启动的连接数上限
+        #[arg(short, long, default_value_t = DEFAULT_JOBS)]
+        jobs: usize,
+    },
+    /// 持续输出连接事件：启动、停止、失败、状态变化，以及可选的日志行，按 Ctrl-C 退出
+    Events {
+        /// 只看这些连接的事件，默认全部
+        aliases: Vec<String>,
+        /// 同时输出连接日志中新写入的行
+        #[arg(long)]
+        logs: bool,
+        /// 每行输出一个 JSON 对象，便于脚本处理
+        #[arg(long)]
+        json: bool,
+        /// 检测状态变化的间隔秒数
+        #[arg(long, default_value_t = 2)]
+        interval: u64,
+    },
+    /// 以 gRPC 提供控制接口（proto/tunnel_manager.proto），供其他程序列出、启动、停止连接并订阅日志和事件，按 Ctrl-C 退出
+    Grpc {
+        /// 监听地址
+        #[arg(long, default_value = grpc::DEFAULT_LISTEN)]
+        listen: SocketAddr,
+    },
+    /// 运行本机 Web 服务：网页控制台，以及以 WebSocket 推送连接事件的 /events，按 Ctrl-C 退出
+    Web {
+        /// 监听地址
+        #[arg(long, default_value = web::DEFAULT_LISTEN)]
+        listen: SocketAddr,
+        /// 生成新的访问令牌，旧令牌随即失效
+        #[arg(long)]
+        new_token: bool,
+    },
+    /// 在系统托盘显示连接菜单：勾选启动连接，取消勾选停止连接；需要 tfa web 或 tfa grpc 在运行，按 Ctrl-C 退出
+    #[cfg(feature = "tray")]
+    Tray,
+    /// 管理 tfa grpc 与 tfa web 的 API 密钥：按权限范围区分只读、操作和管理
+    Apikey {
+        #[command(subcommand)]
+        action: ApikeyAction,
+    },
+    /// 告警：连接持续不可用或反复重启时，按配置文件中 alerts 定义的规则发送到 notify 中的通知渠道
+    Alert {
+        #[command(subcommand)]
+        action: AlertAction,
+    },
+    /// 实时显示各连接进程的 CPU 与内存占用及合计，便于发现失控的进程，按 Ctrl-C 退出
+    Top {
+        /// 刷新间隔秒数
+        #[arg(long, default_value_t = 2)]
+        interval: u64,
+        /// 采样一次后输出并退出，不清屏
+        #[arg(long)]
+        once: bool,
+    },
+    /// 校验配置文件：未知字段、端口、主机名与依赖，有错误时以非零状态退出，适合在 CI 中使用
+    Validate {
+        /// 要校验的文件，`.toml` 按项目配置校验；默认校验全局配置与当前项目配置
+        file: Option<PathBuf>,
+        /// 把警告也视为错误
+        #[arg(long)]
+        strict: bool,
+    },
+    /// 清理已不在配置中的连接留下的进程与运行时文件，以及过期的状态记录
+    Prune,
+    /// 检查 cloudflared 版本是否与档案固定的版本一致，以及能否经连接使用的出站代理访问 Cloudflare Access 主机
+    Doctor {
+        /// 只检查该连接，默认检查全部 cloudflared 连接
+        alias: Option<String>,
+    },
+    /// 安装档案固定的 cloudflared 版本；未固定版本时通过 winget 或 brew 升级
+    UpgradeCloudflared,
+    /// 设置或清除连接的 Cloudflare Access 服务令牌（保存在系统钥匙串中）
+    ServiceToken {
+        /// 连接别名
+        alias: String,
+        /// CF-Access-Client-Id，省略时交互输入
+        #[arg(long)]
+        client_id: Option<String>,
+        /// CF-Access-Client-Secret，省略时交互输入
+        #[arg(long)]
+        client_secret: Option<String>,
+        /// 清除已保存的服务令牌
+        #[arg(long, conflicts_with_all = ["client_id", "client_secret"])]
+        clear: bool,
+    },
+    /// 管理配置文件
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+    /// 按清单声明式地调整连接：添加缺少的、更新有变化的，加上 --prune 时移除清单之外的；先输出计划，确认后执行
+    Apply {
+        /// 清单文件：YAML 连接列表或 CSV，格式与 add --from-file 相同
+        file: PathBuf,
+        /// 同时移除配置中有而清单中没有的连接
+        #[arg(long)]
+        prune: bool,
+        /// 只输出计划，不修改配置
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// 显示应用清单时将添加、更新、移除的连接，以及需要重启或停止的运行中连接，不做任何修改
+    Diff {
+        /// 清单文件：YAML 连接列表或 CSV，格式与 add --from-file 相同
+        file: PathBuf,
+        /// 同时列出配置中有而清单中没有、apply --prune 会移除的连接
+        #[arg(long)]
+        prune: bool,
+        /// 输出 JSON，便于在 CI 中处理
+        #[arg(long)]
+        json: bool,
+        /// 有待应用的修改时以退出码 9 结束
+        #[arg(long)]
+        exit_code: bool,
+    },
+    /// 从 HTTP(S) 地址导入团队维护的连接目录，导入的连接只能通过重新导入修改
+    Import {
+        /// 目录地址，内容格式与配置文件相同
+        #[arg(long)]
+        url: String,
+        /// Bearer 令牌，也可以通过 CF_MANAGER_IMPORT_TOKEN 环境变量提供
+        #[arg(long)]
+        token: Option<String>,
+        /// 目录内容应有的 SHA-256，不一致时拒绝导入
+        #[arg(long, value_name = "HEX")]
+        sha256: Option<String>,
+        /// 只显示导入结果，不修改配置
+        #[arg(long)]
+        dry_run: bool,
+        /// 移除由该目录导入的全部连接
+        #[arg(long, conflicts_with_all = ["token", "sha256"])]
+        forget: bool,
+    },
+    /// 与 git 仓库同步连接目录：拉取他人的修改并推送本地的修改，双方修改同一连接时报告冲突
+    Sync {
+        /// 只显示将要拉取和推送的连接，不做修改
+        #[arg(long)]
+        dry_run: bool,
+        /// 冲突时采用本地版本
+        #[arg(long, conflicts_with = "theirs")]
+        ours: bool,
+        /// 冲突时采用远端版本
+        #[arg(long)]
+        theirs: bool,
+    },
+    /// 管理配置档案：每个档案有独立的连接、运行状态与操作历史
+    Profile {
+        #[command(subcommand)]
+        action: ProfileAction,
+    },
+    /// 在有效期到达后停止连接（内部使用）
+    #[command(hide = true)]
+    TtlWatch {
+        /// 连接别名
+        alias: String,
+    },
+    /// 按 probe_interval 定期探测，记录延迟并推送到 ping_url（内部使用）
+    #[command(hide = true)]
+    LatencyWatch {
+        /// 连接别名
+        alias: String,
+    },
+    /// 脱敏并转发后端输出，写入日志文件（内部使用）
+    #[command(hide = true)]
+    LogFilter {
+        /// 连接别名
+        alias: String,
+        /// 日志文件路径
+        log_path: PathBuf,
+    },
+    /// 运行 http 后端的本地反向代理（内部使用）
+    #[command(hide = true)]
+    HttpServe {
+        /// Access 保护的 Web 应用主机名
+        host: String,
+        /// 监听端口
+        #[arg(long)]
+        listen: u16,
+        /// 监听地址
+        #[arg(long, default_value = "127.0.0.1")]
+        bind: IpAddr,
+    },
+    /// 运行统计代理（内部使用）
+    #[command(hide = true)]
+    ProxyServe {
+        /// 连接别名
+        alias: String,
+        /// 监听端口
         #[arg(long)]
-        source: String,
-        /// 本地端口
+        listen: u16,
+        /// 监听地址
+        #[arg(long, default_value = "127.0.0.1")]
+        bind: IpAddr,
+        /// cloudflared 所在的上游端口
+        #[arg(long)]
+        upstream: u16,
+    },
+    /// 持有标准输入中的内容作为剪贴板内容，直到其他程序写入（内部使用）
+    #[command(hide = true)]
+    ClipboardServe,
+}
+
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// 加密存储配置中的敏感字段（如源地址）
+    Encrypt,
+    /// 以明文存储配置
+    Decrypt,
+    /// 查看或设置界面语言
+    Lang {
+        /// 要使用的语言，省略时显示当前设置
+        #[arg(value_enum, value_name = "LANG")]
+        language: Option<Lang>,
+        /// 清除设置，改为按 LANG 等环境变量选择
+        #[arg(long, conflicts_with = "language")]
+        reset: bool,
+    },
+    /// 查看或设置 sync 使用的 git 仓库
+    Sync {
+        /// 仓库地址，省略时显示当前设置
+        remote: Option<String>,
+        /// 同步的分支
+        #[arg(long, requires = "remote")]
+        branch: Option<String>,
+        /// 连接目录在仓库中的路径
+        #[arg(long, requires = "remote")]
+        file: Option<String>,
+        /// 清除同步设置
+        #[arg(long, conflicts_with = "remote")]
+        reset: bool,
+    },
+    /// 查看或设置全局出站代理，未单独设置代理的连接经由它访问外网
+    Proxy {
+        /// 代理地址，如 http://proxy.corp:3128 或 socks5://127.0.0.1:1080；省略时显示当前设置
+        url: Option<String>,
+        /// 不经过代理的主机，多个用逗号分隔
+        #[arg(long, value_delimiter = ',', requires = "url")]
+        no_proxy: Vec<String>,
+        /// 清除全局代理，改为沿用 HTTPS_PROXY 等环境变量
+        #[arg(long, conflicts_with = "url")]
+        reset: bool,
+    },
+    /// 列出可用于 `add --template` 的模板，包括内置模板与配置中定义的模板
+    Templates,
+    /// 查看或设置全局日志转发目标，未单独设置转发目标的连接使用它们
+    LogSinks {
+        /// 转发目标：syslog、journald、eventlog、tcp://主机:端口、http(s)://（按行 JSON）或 loki+http(s)://（Loki 推送接口）；省略时显示当前设置
+        #[arg(value_name = "SINK")]
+        sinks: Vec<String>,
+        /// 清除全局转发目标
+        #[arg(long, conflicts_with = "sinks")]
+        reset: bool,
+    },
+    /// 查看或设置 OpenTelemetry 导出：启动、停止、重启与健康探测以 OTLP/HTTP 发送 span 和耗时指标
+    Otel {
+        /// OTLP/HTTP 地址，如 http://otel-collector:4318；省略时显示当前设置
+        #[arg(value_name = "ENDPOINT")]
+        endpoint: Option<String>,
+        /// 附加的请求头，写作 名称=值，可重复使用
+        #[arg(long = "header", value_name = "NAME=VALUE", requires = "endpoint")]
+        headers: Vec<String>,
+        /// 资源属性 service.name，默认为 tfa
+        #[arg(long, value_name = "NAME", requires = "endpoint")]
+        service_name: Option<String>,
+        /// 关闭导出
+        #[arg(long, conflicts_with = "endpoint")]
+        reset: bool,
+    },
+    /// 查看或设置本档案固定使用的 cloudflared 版本，由 `tfa upgrade-cloudflared` 从 GitHub Releases 安装
+    CloudflaredVersion {
+        /// 版本号，如 2024.8.2；省略时显示当前设置
+        version: Option<String>,
+        /// 本平台发布文件的 SHA-256，设置后安装前校验
+        #[arg(long, requires = "version")]
+        sha256: Option<String>,
+        /// 取消固定，改为使用 PATH 中的 cloudflared
+        #[arg(long, conflicts_with = "version")]
+        reset: bool,
+    },
+    /// 查看或设置 tfa run 启动后是否默认把连接的使用方法复制到剪贴板
+    Copy {
+        /// 是否默认复制（true 或 false），省略时显示当前设置
+        #[arg(value_name = "BOOL")]
+        enabled: Option<bool>,
+    },
+    /// 查看或设置本档案保留的本地端口范围，`--port auto` 从中分配端口
+    PortRange {
+        /// 端口范围，如 42000-42999；省略时显示当前设置
+        #[arg(value_name = "RANGE")]
+        range: Option<PortRange>,
+        /// 清除保留端口范围
+        #[arg(long, conflicts_with = "range")]
+        reset: bool,
+    },
+    /// 立即备份配置文件；修改配置的命令也会在写入前自动备份，只保留最近的若干份
+    Backup {
+        /// 列出全部备份
+        #[arg(long, conflicts_with = "keep")]
+        list: bool,
+        /// 设置保留的备份份数，0 表示不再自动备份
+        #[arg(long, value_name = "N")]
+        keep: Option<usize>,
+    },
+    /// 用备份替换配置文件，替换前会先备份当前的配置
+    Restore {
+        /// 要恢复的备份时间，如 20241016-045511（见 `tfa config backup --list`）；省略时交互选择
+        #[arg(long, value_name = "TIMESTAMP")]
+        at: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ProfileAction {
+    /// 列出全部档案，* 标记当前使用的档案
+    List,
+    /// 创建空档案
+    Create {
+        /// 档案名
+        name: String,
+    },
+    /// 设置之后默认使用的档案
+    Switch {
+        /// 档案名，default 为默认档案
+        name: String,
+    },
+    /// 把档案的连接配置复制为新档案（服务令牌需重新设置）
+    Copy {
+        /// 要复制的档案
+        from: String,
+        /// 新档案名
+        to: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ApikeyAction {
+    /// 创建 API 密钥；密钥只在创建时显示一次，配置中只保存摘要
+    Create {
+        /// 密钥名称，如使用它的程序名
+        name: String,
+        /// 权限范围：read 只能查看，operate 还可以启动、停止，admin 还可以强制停止
+        #[arg(long, value_enum, default_value_t = Scope::Read)]
+        scope: Scope,
+    },
+    /// 列出 API 密钥
+    List,
+    /// 撤销 API 密钥，使用它的请求立即被拒绝
+    Revoke {
+        /// 密钥名称
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum AlertAction {
+    /// 在前台监视连接并按规则发送通知，按 Ctrl-C 退出
+    Run {
+        /// 检测状态变化的间隔秒数
+        #[arg(long, default_value_t = 5)]
+        interval: u64,
+    },
+    /// 列出告警规则与通知渠道
+    List,
+    /// 向通知渠道发送一条测试通知，省略名称时发送到全部渠道
+    Test {
+        /// 通知渠道名称
+        channels: Vec<String>,
+    },
+    /// 设置电子邮件渠道的 SMTP 密码，保存在系统钥匙串中
+    Password {
+        /// 通知渠道名称
+        channel: String,
+        /// 删除保存的密码
         #[arg(long)]
-        port: u16,
+        delete: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ComposeAction {
+    /// 启动声明的连接后执行 docker compose up
+    Up {
+        /// compose 文件，默认在当前目录查找
+        #[arg(short, long)]
+        file: Option<PathBuf>,
+        /// 传给 docker compose up 的其他参数，如 -d
+        #[arg(last = true)]
+        args: Vec<String>,
     },
+    /// 执行 docker compose down 后停止声明的连接及其依赖
+    Down {
+        /// compose 文件，默认在当前目录查找
+        #[arg(short, long)]
+        file: Option<PathBuf>,
+        /// 传给 docker compose down 的其他参数
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum SortKey {
+    Alias,
+    Port,
+    Status,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum EnvFormat {
+    /// NAME=value
+    Dotenv,
+    /// export NAME=value
+    Export,
+    /// JSON 对象
+    Json,
+}
+
+/// `tfa set --unset` 可以删除或恢复为默认值的字段
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum UnsetKey {
+    MaxUp,
+    MaxDown,
+    StartTimeout,
+    StartRetries,
+    RetryBackoff,
+    CrashLimit,
+    CrashWindow,
+    Tags,
+    DependsOn,
+    Service,
+    Via,
+    ExtraPorts,
+    PreStart,
+    PostStart,
+    PreStop,
+    PostStop,
+    OnFailure,
+    Provider,
+    Runtime,
+    Context,
+    Namespace,
+    ListenAddr,
+    LocalHostname,
+    Probe,
+    ProbeInterval,
+    PingUrl,
+    Client,
+    ConnectUrl,
+    Notes,
+    ConnectHint,
+    OutboundProxy,
+    LogLevel,
+    Redact,
+    LogSinks,
+}
+
+impl UnsetKey {
+    fn name(self) -> String {
+        self.to_possible_value().map(|value| value.get_name().to_string()).unwrap_or_default()
+    }
+
+    /// 把删除写入更新；同一字段同时被设置时报错
+    fn apply(self, update: &mut TunnelUpdate, defaults: &Tunnel) -> Result<()> {
+        fn clear<T>(field: &mut Option<T>, value: T, key: UnsetKey) -> Result<()> {
+            if field.is_some() {
+                return Err(TunnelError::config(t!("不能同时设置并删除 {}", key.name())));
+            }
+            *field = Some(value);
+            Ok(())
+        }
+        match self {
+            UnsetKey::MaxUp => clear(&mut update.max_up, None, self),
+            UnsetKey::MaxDown => clear(&mut update.max_down, None, self),
+            UnsetKey::StartTimeout => clear(&mut update.start_timeout, defaults.start_timeout, self),
+            UnsetKey::StartRetries => clear(&mut update.start_retries, defaults.start_retries, self),
+            UnsetKey::RetryBackoff => clear(&mut update.retry_backoff, defaults.retry_backoff, self),
+            UnsetKey::CrashLimit => clear(&mut update.crash_limit, defaults.crash_limit, self),
+            UnsetKey::CrashWindow => clear(&mut update.crash_window, defaults.crash_window, self),
+            UnsetKey::Tags => clear(&mut update.tags, Vec::new(), self),
+            UnsetKey::DependsOn => clear(&mut update.depends_on, Vec::new(), self),
+            UnsetKey::Service => clear(&mut update.service, None, self),
+            UnsetKey::Via => clear(&mut update.via, None, self),
+            UnsetKey::ExtraPorts => clear(&mut update.extra_ports, Vec::new(), self),
+            // 钩子合并时空字符串表示删除
+            UnsetKey::PreStart => clear(&mut update.hooks.pre_start, String::new(), self),
+            UnsetKey::PostStart => clear(&mut update.hooks.post_start, String::new(), self),
+            UnsetKey::PreStop => clear(&mut update.hooks.pre_stop, String::new(), self),
+            UnsetKey::PostStop => clear(&mut update.hooks.post_stop, String::new(), self),
+            UnsetKey::OnFailure => clear(&mut update.hooks.on_failure, String::new(), self),
+            UnsetKey::Provider => clear(&mut update.provider, Provider::Cloudflared, self),
+            UnsetKey::Runtime => clear(&mut update.runtime, None, self),
+            // context 与 namespace 在确定后端之后处理
+            UnsetKey::Context | UnsetKey::Namespace => Ok(()),
+            UnsetKey::ListenAddr => clear(&mut update.listen_addr, None, self),
+            UnsetKey::LocalHostname => clear(&mut update.local_hostname, None, self),
+            UnsetKey::Probe => clear(&mut update.probe, None, self),
+            UnsetKey::ProbeInterval => clear(&mut update.probe_interval, None, self),
+            UnsetKey::PingUrl => clear(&mut update.ping_url, None, self),
+            UnsetKey::Client => clear(&mut update.client, None, self),
+            UnsetKey::ConnectUrl => clear(&mut update.connect_url, None, self),
+            UnsetKey::Notes => clear(&mut update.notes, None, self),
+            UnsetKey::ConnectHint => clear(&mut update.connect_hint, None, self),
+            UnsetKey::OutboundProxy => clear(&mut update.outbound_proxy, None, self),
+            UnsetKey::LogLevel => clear(&mut update.log_level, None, self),
+            UnsetKey::Redact => clear(&mut update.redact, Vec::new(), self),
+            UnsetKey::LogSinks => clear(&mut update.log_sinks, Vec::new(), self),
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum StatusFilter {
+    Running,
+    Stopped,
+    Degraded,
+    Failed,
+    Paused,
+}
+
+impl StatusFilter {
+    fn matches(self, status: TunnelStatus) -> bool {
+        matches!(
+            (self, status),
+            (StatusFilter::Running, TunnelStatus::Running)
+                | (StatusFilter::Stopped, TunnelStatus::Stopped)
+                | (StatusFilter::Degraded, TunnelStatus::Degraded)
+                | (StatusFilter::Failed, TunnelStatus::Failed)
+                | (StatusFilter::Paused, TunnelStatus::Paused)
+        )
+    }
 }
 
 impl Commands {
-    pub async fn execute(&self) -> Result<()> {
+    /// 该命令是否需要本机已安装 cloudflared
+    pub fn requires_cloudflared(&self) -> bool {
+        #[cfg(feature = "tray")]
+        if let Commands::Tray = self {
+            return false;
+        }
+        !matches!(
+            self,
+            Commands::ProxyServe { .. }
+                | Commands::ClipboardServe
+                | Commands::HttpServe { .. }
+                | Commands::LogFilter { .. }
+                | Commands::Env { .. }
+                | Commands::Prune
+                | Commands::Doctor { .. }
+                | Commands::UpgradeCloudflared
+                | Commands::Wait { .. }
+                | Commands::Reset { .. }
+                | Commands::Enable { .. }
+                | Commands::Disable { .. }
+                | Commands::Events { .. }
+                | Commands::Grpc { .. }
+                | Commands::Web { .. }
+                | Commands::Apikey { .. }
+                | Commands::Alert { .. }
+                | Commands::Top { .. }
+                | Commands::Status { .. }
+                | Commands::Find { .. }
+                | Commands::Show { .. }
+                | Commands::TtlWatch { .. }
+                | Commands::LatencyWatch { .. }
+                | Commands::Recover { .. }
+                | Commands::Validate { .. }
+                | Commands::History { .. }
+                | Commands::Replay { list: true, .. }
+                | Commands::Apply { dry_run: true, .. }
+                | Commands::Diff { .. }
+                | Commands::SshConfig { .. }
+                | Commands::PrivilegedPort { .. }
+                | Commands::Add { runtime: Some(_), .. }
+                | Commands::Audit { .. }
+                | Commands::Profile { .. }
+                | Commands::Sync { .. }
+                | Commands::Import { .. }
+                | Commands::Config { .. }
+        )
+    }
+
+    /// `yes` 为真时跳过删除、清理等操作前的确认
+    pub async fn execute(&self, yes: bool) -> Result<()> {
+        if let Commands::ProxyServe { alias, listen, bind, upstream } = self {
+            return proxy::serve(alias, SocketAddr::new(*bind, *listen), *upstream).await;
+        }
+        if let Commands::HttpServe { host, listen, bind } = self {
+            return httpproxy::serve(host, SocketAddr::new(*bind, *listen)).await;
+        }
+        if let Commands::ClipboardServe = self {
+            return clipboard::serve();
+        }
+        // 每个请求各自读取配置；服务重新启动时先接管仍在运行的连接
+        if let Commands::Grpc { listen } = self {
+            recover_all(&Config::load()?);
+            return grpc::serve(*listen).await;
+        }
+        if let Commands::Web { listen, new_token } = self {
+            recover_all(&Config::load()?);
+            return web::serve(*listen, *new_token).await;
+        }
+        // 托盘经控制协议操作连接，不读取配置
+        #[cfg(feature = "tray")]
+        if let Commands::Tray = self {
+            return tray::run().await;
+        }
+        // 档案管理不读取当前档案的配置
+        if let Commands::Profile { action } = self {
+            return profile_command(action);
+        }
+        // 校验直接读取原始文件，配置无法加载时同样可用
+        if let Commands::Validate { file, strict } = self {
+            return validate_config(file.as_deref(), *strict);
+        }
+
+
         let mut config = Config::load()?;
         
         match self {
-            Commands::List => {
-                let tunnels = config.list_tunnels()?;
-                println!("{:<15} {:<30} {:<20} {:<10}", "alias", "source", "target", "status");
-                for tunnel in tunnels {
-                    println!("{:<15} {:<30} {:<20} {:<10}", 
-                        tunnel.alias,
-                        tunnel.source,
-                        format!("tcp://localhost:{}", tunnel.port),
-                        tunnel.status()
-                    );
-                }
-            }
-            Commands::Run => {
-                let tunnels = config.list_tunnels()?;
-                if tunnels.is_empty() {
-                    println!("没有配置任何连接，请先使用 'cfa add' 添加连接");
+            Commands::List { sort, filter, watch: None, system } => {
+                let rows = list_rows(&config, *sort, *filter, *system)?;
+                println!("{}", tunnel_table(&rows, &HashSet::new()));
+                print_failed(&rows);
+            }
+            Commands::Find { pattern } => {
+                let matches = search::tunnels(&config.list_tunnels()?, pattern);
+                if matches.is_empty() {
+                    println!("{}", t!("没有与 {} 匹配的连接", pattern));
                     return Ok(());
                 }
-
-                let items: Vec<String> = tunnels.iter()
-                    .map(|t| format!("{} ({})", t.alias, t.source))
+                let rows: Vec<(&Tunnel, TunnelStatus)> = matches.into_iter().map(|t| (t, t.status())).collect();
+                println!("{}", tunnel_table(&rows, &HashSet::new()));
+            }
+            Commands::Status { all, json } => {
+                let rows: Vec<StatusRow> = config.list_tunnels()?
+                    .into_iter()
+                    .map(|t| {
+                        let latency = LatencyHistory::load(&t.alias);
+                        StatusRow {
+                            alias: &t.alias,
+                            status: t.recorded_status(),
+                            port: t.port,
+                            uptime: t.uptime(),
+                            latency_ms: latency.last().and_then(|sample| sample.ms),
+                            trend: latency_summary(&latency.samples, STATUS_SPARKLINE_WIDTH),
+                        }
+                    })
+                    .filter(|row| *all || row.status != TunnelStatus::Stopped)
                     .collect();
-                
-                let selection = Select::with_theme(&ColorfulTheme::default())
-                    .with_prompt("选择要运行的连接")
-                    .items(&items)
-                    .default(0)
-                    .interact()?;
-                
-                let tunnel = &tunnels[selection];
-                let pb = ProgressBar::new_spinner();
-                pb.set_message(format!("正在启动 {}...", tunnel.alias));
-                
-                match tunnel.start() {
-                    Ok(_) => {
-                        pb.finish_with_message(format!("{} 已启动", tunnel.alias));
-                    }
-                    Err(e) => {
-                        pb.finish_with_message(format!("启动失败: {}", e));
-                        return Err(e);
+                if *json {
+                    println!("{}", serde_json::to_string(&rows)?);
+                } else {
+                    let line: Vec<String> = rows.iter()
+                        .map(|row| if row.trend.is_empty() {
+                            format!("{}:{}", row.alias, row.status)
+                        } else {
+                            format!("{}:{} {}", row.alias, row.status, row.trend)
+                        })
+                        .collect();
+                    println!("{}", line.join(" "));
+                }
+            }
+            Commands::List { sort, filter, watch: Some(interval), system } => {
+                let term = Term::stdout();
+                let mut previous: HashMap<String, TunnelStatus> = HashMap::new();
+                // 其他终端启动或停止连接时立即刷新，不必等到下一轮
+                let mut events = events::subscribe();
+                Monitor::new().spawn()?;
+                loop {
+                    // 每轮重新读取配置，以便看到其他终端中的增删改
+                    let config = Config::load()?;
+                    let rows = list_rows(&config, *sort, *filter, *system)?;
+                    let changed: HashSet<String> = rows.iter()
+                        .filter(|(t, status)| previous.get(&t.alias).is_some_and(|old| old != status))
+                        .map(|(t, _)| t.alias.clone())
+                        .collect();
+                    previous = rows.iter().map(|(t, status)| (t.alias.clone(), *status)).collect();
+
+                    term.clear_screen()?;
+                    println!("{}", t!("每 {} 秒刷新，按 Ctrl-C 退出", interval));
+                    println!("{}", tunnel_table(&rows, &changed));
+                    print_failed(&rows);
+
+                    tokio::select! {
+                        _ = tokio::time::sleep(Duration::from_secs((*interval).max(1))) => {}
+                        _ = events.recv() => {}
+                        _ = tokio::signal::ctrl_c() => break,
                     }
                 }
             }
-            Commands::Stop => {
-                let tunnels = config.list_running_tunnels()?;
+            Commands::Run { aliases, jobs, dry_run, ttl, wait, timeout, copy, no_copy, .. } if !aliases.is_empty() => {
+                let aliases = &config.expand_aliases(aliases)?;
+                for alias in aliases {
+                    ensure_enabled(config.get_tunnel(alias)?)?;
+                }
+                run_selected(&mut config, aliases, *jobs, *dry_run, *ttl, wait.then_some(*timeout), (*copy || *no_copy).then_some(*copy))?;
+            }
+            Commands::Run { all: true, jobs, dry_run, ttl, wait, timeout, copy, no_copy, .. } => {
+                let mut selected: Vec<String> = config.list_tunnels()?.iter().map(|t| t.alias.clone()).collect();
+                if selected.is_empty() {
+                    println!("{}", t!("没有配置任何连接，请先使用 'cfa add' 添加连接"));
+                    return Ok(());
+                }
+                selected.sort();
+                let selected = bulk_selection(&config, selected);
+                run_selected(&mut config, &selected, *jobs, *dry_run, *ttl, wait.then_some(*timeout), (*copy || *no_copy).then_some(*copy))?;
+            }
+            Commands::Run { tag: Some(tag), jobs, dry_run, ttl, wait, timeout, copy, no_copy, .. } => {
+                let mut selected: Vec<String> = config.list_tunnels()?.iter()
+                    .filter(|t| t.tags.contains(tag))
+                    .map(|t| t.alias.clone())
+                    .collect();
+                if selected.is_empty() {
+                    return Err(TunnelError::config(t!("没有带标签 {} 的连接", tag)));
+                }
+                selected.sort();
+                let selected = bulk_selection(&config, selected);
+                run_selected(&mut config, &selected, *jobs, *dry_run, *ttl, wait.then_some(*timeout), (*copy || *no_copy).then_some(*copy))?;
+            }
+            Commands::Run { project: true, jobs, dry_run, ttl, wait, timeout, copy, no_copy, .. } => {
+                let selected = bulk_selection(&config, config.project_tunnels()?.to_vec());
+                if let Some(path) = config.project_path() {
+                    println!("{}", t!("项目配置: {}", path.display()));
+                }
+                run_selected(&mut config, &selected, *jobs, *dry_run, *ttl, wait.then_some(*timeout), (*copy || *no_copy).then_some(*copy))?;
+            }
+            Commands::Run { jobs, dry_run, ttl, wait, timeout, copy, no_copy, .. } => {
+                let tunnels: Vec<&Tunnel> = config.list_tunnels()?.into_iter().filter(|t| t.enabled).collect();
                 if tunnels.is_empty() {
-                    println!("没有正在运行的连接");
+                    println!("{}", t!("没有配置任何连接，请先使用 'cfa add' 添加连接"));
                     return Ok(());
                 }
-                
+                require_interactive(t!("非交互环境中无法选择连接，请指定别名（tfa run <alias>）或使用 --all、--tag、--project"))?;
+                let tunnels = narrow(tunnels)?;
+
                 let items: Vec<String> = tunnels.iter()
                     .map(|t| format!("{} ({})", t.alias, t.source))
                     .collect();
                 
-                let selection = Select::with_theme(&ColorfulTheme::default())
-                    .with_prompt("选择要停止的连接")
+                let selections = MultiSelect::with_theme(&ColorfulTheme::default())
+                    .with_prompt(t!("选择要运行的连接（空格选择，回车确认）"))
                     .items(&items)
-                    .default(0)
                     .interact()?;
+                if selections.is_empty() {
+                    println!("{}", t!("未选择任何连接"));
+                    return Ok(());
+                }
+
+                let selected: Vec<String> = selections.iter().map(|&i| tunnels[i].alias.clone()).collect();
+                if !*dry_run {
+                    replay::record(HistoryAction::Start, &selected);
+                }
+                run_selected(&mut config, &selected, *jobs, *dry_run, *ttl, wait.then_some(*timeout), (*copy || *no_copy).then_some(*copy))?;
+            }
+            Commands::TtlWatch { alias } => {
+                ttl_watch(&config, alias)?;
+            }
+            Commands::LatencyWatch { alias } => {
+                latency_watch(alias)?;
+            }
+            Commands::LogFilter { alias, log_path } => {
+                logsink::pipe(config.get_tunnel(alias)?, log_path)?;
+            }
+            Commands::Wait { aliases, timeout } => {
+                let aliases = &config.expand_aliases(aliases)?;
+                for alias in aliases {
+                    config.get_tunnel(alias)?;
+                }
+                wait_healthy(&config, aliases, *timeout)?;
+            }
+            Commands::Show { alias, json, lines } => {
+                let alias = &config.resolve_alias(alias)?;
+                let tunnel = config.get_tunnel(alias)?;
+                let status = tunnel.status();
+                let state = RuntimeState::load(alias).filter(RuntimeState::is_alive);
+                let logs = tunnel.get_logs();
+                let logs = &logs[logs.len().saturating_sub(*lines)..];
+                if *json {
+                    let command = tunnel.preview_command().ok().map(|command| provider::redacted_args(&command));
+                    let detail = serde_json::json!({
+                        "alias": alias,
+                        "profile": profile::current(),
+                        "project": tunnel.project,
+                        "system": tunnel.system,
+                        "config": tunnel_fields(tunnel),
+                        "status": status,
+                        "runtime": state,
+                        "latency_ms": LatencyHistory::load(alias).last().and_then(|sample| sample.ms),
+                        "connect_hint": tunnel.connect_hint(),
+                        "command": command,
+                        "logs": logs,
+                    });
+                    println!("{}", serde_json::to_string_pretty(&detail)?);
+                    return Ok(());
+                }
+
+                println!("# {}", t!("配置"));
+                let mut table = Table::new();
+                table.load_preset(presets::UTF8_FULL_CONDENSED).set_header(["field", "value"]);
+                table.add_row(vec!["profile".to_string(), profile::current().to_string()]);
+                if let Some(project) = &tunnel.project {
+                    table.add_row(vec!["project".to_string(), project.display().to_string()]);
+                }
+                if tunnel.system {
+                    table.add_row(vec!["system".to_string(), system::config_path().display().to_string()]);
+                }
+                for (key, value) in tunnel_fields(tunnel) {
+                    let value = match value {
+                        serde_json::Value::String(value) => value,
+                        value => value.to_string(),
+                    };
+                    table.add_row(vec![key, value]);
+                }
+                println!("{}", table);
+
+                let usage = usage_lines(tunnel);
+                if !usage.is_empty() {
+                    println!("\n# {}", t!("使用方法"));
+                    for line in usage {
+                        println!("{}", line);
+                    }
+                }
+
+                println!("\n# {}", t!("运行状态"));
+                println!("{}", t!("状态: {}", status));
+                if let Some(state) = &state {
+                    println!("PID: {}", state.pid);
+                    println!("{}", t!("启动时间: {}（已运行 {}）", history::format_time(state.started_at), format_duration(platform::unix_now().saturating_sub(state.started_at))));
+                    if state.port != tunnel.port {
+                        println!("{}", t!("内部端口: {}", state.port));
+                    }
+                    if let Some(expires_at) = state.expires_at {
+                        println!("{}", t!("到期时间: {}", history::format_time(expires_at)));
+                    }
+                }
+                let latency = LatencyHistory::load(alias);
+                if !latency.samples.is_empty() {
+                    println!("{}", t!("延迟: {}", latency_summary(&latency.samples, LATENCY_SPARKLINE_WIDTH)));
+                }
+
+                println!("\n# {}", t!("启动命令"));
+                match describe_command(tunnel) {
+                    Ok(text) => print!("{}", text),
+                    Err(e) => println!("{}", e),
+                }
+
+                println!("\n# {}", t!("最近日志"));
+                if logs.is_empty() {
+                    println!("{}", t!("暂无日志"));
+                }
+                for line in logs {
+                    println!("{}", line);
+                }
+            }
+            Commands::ShowCmd { alias } => {
+                let alias = &config.resolve_alias(alias)?;
+                print!("{}", describe_command(config.get_tunnel(alias)?)?);
+            }
+            Commands::Top { interval, once } => {
+                top(*interval, *once).await?;
+            }
+            Commands::Events { aliases, logs, json, interval } => {
+                let aliases = &config.expand_aliases(aliases)?;
+                for alias in aliases {
+                    config.get_tunnel(alias)?;
+                }
+                let mut events = events::subscribe();
+                Monitor::new().health(Duration::from_secs(*interval)).logs(*logs).spawn()?;
+                loop {
+                    let event = tokio::select! {
+                        event = events.recv() => event,
+                        _ = tokio::signal::ctrl_c() => break,
+                    };
+                    let event = match event {
+                        Ok(event) => event,
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            warn!("{}", t!("处理不及，跳过了 {} 个事件", skipped));
+                            continue;
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    };
+                    if !aliases.is_empty() && !aliases.iter().any(|alias| alias == event.alias()) {
+                        continue;
+                    }
+                    println!("{}", describe_event(&event, platform::unix_now(), *json)?);
+                }
+            }
+            Commands::Reset { aliases } => {
+                let aliases = &config.expand_aliases(aliases)?;
+                for alias in aliases {
+                    config.get_tunnel(alias)?;
+                }
+                for alias in aliases {
+                    if Failures::clear(alias)? {
+                        println!("{}", t!("已清除连接 {} 的失败记录", alias));
+                    } else {
+                        println!("{}", t!("连接 {} 没有失败记录", alias));
+                    }
+                }
+            }
+            Commands::Stop { aliases: patterns, force, others: true } => {
+                let running = config.list_running_tunnels()?;
+                if running.is_empty() {
+                    println!("{}", t!("没有正在运行的连接"));
+                    return Ok(());
+                }
+                let kept = if patterns.is_empty() {
+                    require_interactive(t!("非交互环境中无法选择连接，请指定要保留的别名（tfa stop --others <alias>）"))?;
+                    let tunnels = narrow(running.clone())?;
+                    let items: Vec<String> = tunnels.iter()
+                        .map(|t| format!("{} ({})", t.alias, t.source))
+                        .collect();
+                    let selections = MultiSelect::with_theme(&ColorfulTheme::default())
+                        .with_prompt(t!("选择要保留的连接，其余的全部停止（空格选择，回车确认）"))
+                        .items(&items)
+                        .interact()?;
+                    selections.iter().map(|&i| tunnels[i].alias.clone()).collect()
+                } else {
+                    config.expand_aliases(patterns)?
+                };
+                // 保留的连接依赖的连接也要保留，否则它们会随之失效
+                let mut keep: Vec<String> = config.start_order(&kept)?.into_iter().flatten().collect();
+                keep.sort();
+                let needed: Vec<String> = keep.iter().filter(|alias| !kept.contains(alias)).cloned().collect();
+                if !needed.is_empty() {
+                    println!("{}", t!("同时保留被依赖的连接: {}", needed.join(", ")));
+                }
+                let aliases: Vec<String> = running.iter()
+                    .map(|t| t.alias.clone())
+                    .filter(|alias| !keep.contains(alias))
+                    .collect();
+                if aliases.is_empty() {
+                    println!("{}", t!("除保留的连接外没有正在运行的连接"));
+                    return Ok(());
+                }
+                if !confirm(yes, t!("{} 以下 {} 个连接: {}？", t!("停止"), aliases.len(), aliases.join(", ")))? {
+                    println!("{}", t!("已取消"));
+                    return Ok(());
+                }
+                if patterns.is_empty() {
+                    replay::record(HistoryAction::Stop, &aliases);
+                }
+                stop_selected(&config, &aliases, *force)?;
+                let still: Vec<String> = keep.into_iter()
+                    .filter(|alias| config.get_tunnel(alias).is_ok_and(Tunnel::is_live))
+                    .collect();
+                println!("{}", t!("已停止 {} 个连接: {}", aliases.len(), aliases.join(", ")));
+                if still.is_empty() {
+                    println!("{}", t!("现在没有正在运行的连接"));
+                } else {
+                    println!("{}", t!("仍在运行: {}", still.join(", ")));
+                }
+            }
+            Commands::Stop { aliases: patterns, force, .. } if !patterns.is_empty() => {
+                let mut aliases = Vec::new();
+                for alias in config.expand_aliases(patterns)? {
+                    if config.get_tunnel(&alias)?.is_live() {
+                        aliases.push(alias);
+                    }
+                }
+                if aliases.is_empty() {
+                    println!("{}", t!("没有正在运行的连接"));
+                    return Ok(());
+                }
+                if !confirm_matches(yes, patterns, &aliases, t!("停止"))? {
+                    println!("{}", t!("已取消"));
+                    return Ok(());
+                }
+                stop_selected(&config, &aliases, *force)?;
+            }
+            Commands::Stop { force, .. } => {
+                let tunnels = config.list_running_tunnels()?;
+                if tunnels.is_empty() {
+                    println!("{}", t!("没有正在运行的连接"));
+                    return Ok(());
+                }
+                require_interactive(t!("非交互环境中无法选择连接，请指定别名（tfa stop <alias>，可以使用 '*' 停止全部）"))?;
+                let tunnels = narrow(tunnels)?;
                 
-                let tunnel = &tunnels[selection];
-                let pb = ProgressBar::new_spinner();
-                pb.set_message(format!("正在停止 {}...", tunnel.alias));
+                let items: Vec<String> = tunnels.iter()
+                    .map(|t| format!("{} ({})", t.alias, t.source))
+                    .collect();
                 
-                match tunnel.stop() {
-                    Ok(_) => {
-                        pb.finish_with_message(format!("{} 已停止", tunnel.alias));
+                let selections = MultiSelect::with_theme(&ColorfulTheme::default())
+                    .with_prompt(t!("选择要停止的连接（空格选择，回车确认）"))
+                    .items(&items)
+                    .interact()?;
+                if selections.is_empty() {
+                    println!("{}", t!("未选择任何连接"));
+                    return Ok(());
+                }
+
+                let selected: Vec<String> = selections.iter().map(|&i| tunnels[i].alias.clone()).collect();
+                replay::record(HistoryAction::Stop, &selected);
+                stop_selected(&config, &selected, *force)?;
+            }
+            Commands::Enable { aliases: patterns } | Commands::Disable { aliases: patterns } => {
+                let enabled = matches!(self, Commands::Enable { .. });
+                let aliases = &config.expand_aliases(patterns)?;
+                if !enabled && !confirm_matches(yes, patterns, aliases, t!("停用"))? {
+                    println!("{}", t!("已取消"));
+                    return Ok(());
+                }
+                for alias in aliases {
+                    config.get_tunnel(alias)?;
+                }
+                for alias in aliases {
+                    let result = config.update_tunnel(alias, TunnelUpdate { enabled: Some(enabled), ..Default::default() });
+                    history::record(HistoryAction::Set, alias, Some("enabled".to_string()), &result);
+                    result?;
+                }
+                if enabled {
+                    println!("{}", t!("已启用 {}", aliases.join(", ")));
+                } else {
+                    println!("{}", t!("已停用 {}，批量启动时将跳过，使用 tfa enable 重新启用", aliases.join(", ")));
+                }
+            }
+            Commands::Pause { aliases: patterns, force } => {
+                let aliases = &config.expand_aliases(patterns)?;
+                if !confirm_matches(yes, patterns, aliases, t!("暂停"))? {
+                    println!("{}", t!("已取消"));
+                    return Ok(());
+                }
+                for alias in aliases {
+                    config.get_tunnel(alias)?;
+                }
+                for alias in aliases {
+                    PauseMark::set(alias)?;
+                }
+                let running: Vec<String> = aliases.iter()
+                    .filter(|alias| config.get_tunnel(alias).is_ok_and(Tunnel::is_live))
+                    .cloned()
+                    .collect();
+                stop_selected(&config, &running, *force)?;
+                println!("{}", t!("已暂停 {}，批量启动时将跳过，使用 tfa resume 恢复", aliases.join(", ")));
+            }
+            Commands::Resume { aliases, jobs } => {
+                let aliases = &config.expand_aliases(aliases)?;
+                for alias in aliases {
+                    ensure_enabled(config.get_tunnel(alias)?)?;
+                }
+                for alias in aliases {
+                    if !PauseMark::clear(alias)? {
+                        println!("{}", t!("连接 {} 没有暂停", alias));
+                    }
+                }
+                start_in_order(&mut config, aliases, *jobs)?;
+            }
+            Commands::Log { aliases, follow } if !aliases.is_empty() => {
+                let aliases = config.expand_aliases(aliases)?;
+                if *follow {
+                    return follow_logs(&config, &aliases).await;
+                }
+                for (index, alias) in aliases.iter().enumerate() {
+                    let tunnel = config.get_tunnel(alias)?;
+                    if aliases.len() > 1 {
+                        if index > 0 {
+                            println!();
+                        }
+                        println!("# {}", alias);
+                    }
+                    let logs = tunnel.get_logs();
+                    if logs.is_empty() {
+                        println!("{}", t!("暂无日志"));
                     }
-                    Err(e) => {
-                        pb.finish_with_message(format!("停止失败: {}", e));
-                        return Err(e);
+                    for line in logs {
+                        println!("{}", line);
                     }
                 }
             }
-            Commands::Log => {
+            Commands::Log { follow, .. } => {
                 let tunnels = config.list_running_tunnels()?;
                 if tunnels.is_empty() {
-                    println!("没有正在运行的连接");
+                    println!("{}", t!("没有正在运行的连接"));
                     return Ok(());
                 }
+                require_interactive(t!("非交互环境中无法选择连接，请指定别名（tfa log <alias>）"))?;
                 
                 let items: Vec<String> = tunnels.iter()
                     .map(|t| format!("{} ({})", t.alias, t.source))
                     .collect();
                 
-                let selection = Select::with_theme(&ColorfulTheme::default())
-                    .with_prompt("选择要查看日志的连接")
+                let selection = FuzzySelect::with_theme(&ColorfulTheme::default())
+                    .with_prompt(t!("选择要查看日志的连接（输入字符模糊筛选）"))
                     .items(&items)
                     .default(0)
                     .interact()?;
                 
                 let tunnel = &tunnels[selection];
+                if *follow {
+                    return follow_logs(&config, std::slice::from_ref(&tunnel.alias)).await;
+                }
                 let logs = tunnel.get_logs();
                 
                 if logs.is_empty() {
-                    println!("暂无日志");
+                    println!("{}", t!("暂无日志"));
                 } else {
                     for line in logs {
                         println!("{}", line);
                     }
                 }
             }
-            Commands::Set { alias, source, port } => {
-                config.update_tunnel(alias, source.as_deref(), *port)?;
-                println!("已更新连接 {}", alias);
+            Commands::Set { alias, source, port, service, provider, remote_port, runtime, context, namespace, proxy, max_up, max_down, start_timeout, start_retries, retry_backoff, crash_limit, crash_window, tags, depends_on, via, via_mode, extra_ports, pre_start, post_start, pre_stop, post_stop, on_failure, listen_addr, local_hostname, ssh_host, probe, probe_interval, ping_url, client, connect_url, notes, connect_hint, outbound_proxy, log_level, redact, log_sinks, locked, enabled, autostart, on_change, unset, unlock, apply } => {
+                let alias = &config.resolve_alias(alias)?;
+                if *unlock {
+                    config.unlock();
+                }
+                let mut changed: Vec<String> = [
+                    ("source", source.is_some()),
+                    ("port", port.is_some()),
+                    ("service", service.is_some()),
+                    ("provider", provider.is_some()),
+                    ("remote_port", remote_port.is_some()),
+                    ("runtime", runtime.is_some()),
+                    ("context", context.is_some()),
+                    ("namespace", namespace.is_some()),
+                    ("proxy", proxy.is_some()),
+                    ("max_up", max_up.is_some()),
+                    ("max_down", max_down.is_some()),
+                    ("start_timeout", start_timeout.is_some()),
+                    ("start_retries", start_retries.is_some()),
+                    ("retry_backoff", retry_backoff.is_some()),
+                    ("crash_limit", crash_limit.is_some()),
+                    ("crash_window", crash_window.is_some()),
+                    ("tags", tags.is_some()),
+                    ("depends_on", depends_on.is_some()),
+                    ("via", via.is_some() || via_mode.is_some()),
+                    ("extra_ports", extra_ports.is_some()),
+                    ("pre_start", pre_start.is_some()),
+                    ("post_start", post_start.is_some()),
+                    ("pre_stop", pre_stop.is_some()),
+                    ("post_stop", post_stop.is_some()),
+                    ("on_failure", on_failure.is_some()),
+                    ("listen_addr", listen_addr.is_some()),
+                    ("local_hostname", local_hostname.is_some()),
+                    ("ssh_host", ssh_host.is_some()),
+                    ("probe", probe.is_some()),
+                    ("probe_interval", probe_interval.is_some()),
+                    ("ping_url", ping_url.is_some()),
+                    ("client", client.is_some()),
+                    ("connect_url", connect_url.is_some()),
+                    ("notes", notes.is_some()),
+                    ("connect_hint", connect_hint.is_some()),
+                    ("outbound_proxy", outbound_proxy.is_some()),
+                    ("log_level", log_level.is_some()),
+                    ("redact", redact.is_some()),
+                    ("log_sinks", log_sinks.is_some()),
+                    ("locked", locked.is_some()),
+                    ("enabled", enabled.is_some()),
+                    ("autostart", autostart.is_some()),
+                    ("on_change", on_change.is_some()),
+                ].into_iter().filter(|(_, set)| *set).map(|(name, _)| name.to_string()).collect();
+                changed.extend(unset.iter().map(|key| format!("-{}", key.name())));
+                if changed.is_empty() {
+                    return Err(TunnelError::config(t!("没有指定要修改的字段")));
+                }
+                let before = config.get_tunnel(alias)?.clone();
+                let provider = provider_update(&before, *provider, *remote_port, context.as_deref(), namespace.as_deref(), unset)?;
+                let port = port.map(|port| port.resolve(&config)).transpose()?;
+                let via = match (via.as_deref(), via_mode) {
+                    (Some(""), _) => Some(None),
+                    (Some(upstream), mode) => Some(Some(Via {
+                        alias: upstream.to_string(),
+                        mode: mode.or(before.via.as_ref().map(|via| via.mode)).unwrap_or_default(),
+                    })),
+                    (None, Some(mode)) => match &before.via {
+                        Some(via) => Some(Some(Via { alias: via.alias.clone(), mode: *mode })),
+                        None => return Err(TunnelError::config(t!("连接 {} 没有经过其他连接，请同时指定 --via", alias))),
+                    },
+                    (None, None) => None,
+                };
+                let listen_addr = match listen_addr.as_deref() {
+                    Some("") => Some(None),
+                    Some(ip) => Some(Some(parse_listen_addr(ip)?)),
+                    None => None,
+                };
+                let local_hostname = match local_hostname.as_deref() {
+                    Some("") => Some(None),
+                    Some(host) => Some(Some(parse_local_hostname(host)?)),
+                    None => None,
+                };
+                let probe = match probe.as_deref() {
+                    Some("") => Some(None),
+                    Some(probe) => Some(Some(probe.parse::<Probe>().map_err(TunnelError::config)?)),
+                    None => None,
+                };
+                let ping_url = match ping_url.as_deref() {
+                    Some("") => Some(None),
+                    Some(url) => {
+                        ping::check(url)?;
+                        Some(Some(url.to_string()))
+                    }
+                    None => None,
+                };
+                let connect_url = match connect_url.as_deref() {
+                    Some("") => Some(None),
+                    Some(url) => {
+                        client::parse_url(url)?;
+                        Some(Some(url.to_string()))
+                    }
+                    None => None,
+                };
+                let outbound_proxy = match outbound_proxy.as_deref() {
+                    Some("") => Some(None),
+                    Some(url) => Some(Some(OutboundProxy::new(url, Vec::new())?)),
+                    None => None,
+                };
+                let redact = match redact {
+                    Some(patterns) => Some(redact_patterns(patterns)?),
+                    None => None,
+                };
+                let log_sinks = match log_sinks {
+                    Some(specs) => Some(parse_log_sinks(specs)?),
+                    None => None,
+                };
+                let extra_ports = match extra_ports {
+                    Some(specs) => Some(parse_extra_ports(specs)?),
+                    None => None,
+                };
+                let mut update = TunnelUpdate {
+                    source: source.clone(),
+                    port,
+                    service: service.as_ref().map(|service| (!service.trim().is_empty()).then(|| service.trim().to_string())),
+                    provider,
+                    runtime: runtime.map(Some),
+                    proxy: *proxy,
+                    max_up: max_up.map(|rate| (rate > 0).then_some(rate)),
+                    max_down: max_down.map(|rate| (rate > 0).then_some(rate)),
+                    start_timeout: *start_timeout,
+                    start_retries: *start_retries,
+                    retry_backoff: *retry_backoff,
+                    crash_limit: *crash_limit,
+                    crash_window: *crash_window,
+                    tags: tags.as_ref().map(|tags| normalize_list(tags)),
+                    depends_on: depends_on.as_ref().map(|aliases| normalize_list(aliases)),
+                    via,
+                    extra_ports,
+                    hooks: Hooks {
+                        pre_start: pre_start.clone(),
+                        post_start: post_start.clone(),
+                        pre_stop: pre_stop.clone(),
+                        post_stop: post_stop.clone(),
+                        on_failure: on_failure.clone(),
+                    },
+                    locked: *locked,
+                    enabled: *enabled,
+                    autostart: *autostart,
+                    on_change: *on_change,
+                    listen_addr,
+                    local_hostname,
+                    ssh_host: *ssh_host,
+                    probe,
+                    probe_interval: probe_interval.map(|seconds| (seconds > 0).then_some(seconds)),
+                    ping_url,
+                    client: client.as_ref().map(|command| (!command.trim().is_empty()).then(|| command.clone())),
+                    connect_url,
+                    notes: notes.as_ref().map(|notes| (!notes.trim().is_empty()).then(|| notes.trim().to_string())),
+                    connect_hint: connect_hint.as_ref().map(|hint| (!hint.trim().is_empty()).then(|| hint.trim().to_string())),
+                    outbound_proxy,
+                    log_level: log_level.map(Some),
+                    redact,
+                    log_sinks,
+                    ..Default::default()
+                };
+                let defaults = Tunnel::new(alias, &before.source, before.port);
+                for key in unset {
+                    key.apply(&mut update, &defaults)?;
+                }
+                let running = before.is_running();
+                // 新端口被占用时在修改配置之前报错，原来的进程与配置都保持不变
+                if *apply
+                    && running
+                    && let Some(port) = update.port.filter(|port| *port != before.port)
+                    && let Some(occupant) = platform::port_listeners(port).into_iter().next()
+                {
+                    return Err(TunnelError::PortInUse { port, pid: Some(occupant.pid), process: Some(occupant.process) }.into());
+                }
+                let rates_changed = update.max_up.is_some() || update.max_down.is_some();
+                let hostname_changed = update.local_hostname.is_some();
+                let listen_addr = update.listen_addr;
+                let result = config.update_tunnel(alias, update);
+                history::record(HistoryAction::Set, alias, Some(changed.join(", ")), &result);
+                result?;
+                if let Some(Some(ip)) = listen_addr {
+                    warn_exposed(alias, ip);
+                }
+                let diff = tunnel_diff(&before, config.get_tunnel(alias)?);
+                let modified = !diff.is_empty();
+                if diff.is_empty() {
+                    println!("{}", t!("连接 {} 没有变化", alias));
+                } else {
+                    println!("{}", t!("已更新连接 {}", alias));
+                    for line in diff {
+                        println!("  {}", line);
+                    }
+                }
+                if modified {
+                    sync_ssh_config(&config);
+                }
+                let after = config.get_tunnel(alias)?;
+                if *apply && running && modified {
+                    restart_tunnel(&config, &before, after)?;
+                    return Ok(());
+                }
+                if *apply && !running && modified {
+                    println!("{}", t!("连接 {} 未运行，修改将在下次启动时生效", alias));
+                } else if running && after.port != before.port {
+                    println!("{}", t!("连接 {} 仍在端口 {} 上运行，新端口在重启后生效；使用 --apply 立即重启", alias, before.port));
+                }
+                if rates_changed && after.apply_rate_limits()? {
+                    println!("{}", t!("新的限速已应用到运行中的连接"));
+                }
+                if hostname_changed && after.apply_local_hostname(before.local_hostname.as_deref())? {
+                    println!("{}", t!("已更新运行中连接的 hosts 记录"));
+                }
+            }
+            Commands::Remove { alias, force, unlock } => {
+                let alias = &config.resolve_alias(alias)?;
+                if *unlock {
+                    config.unlock();
+                }
+                config.ensure_removable(alias)?;
+                let tunnel = config.get_tunnel(alias)?;
+                println!("{}", tunnel_table(&[(tunnel, tunnel.status())], &HashSet::new()));
+                if !confirm(yes, t!("删除连接 {}？", alias))? {
+                    println!("{}", t!("已取消"));
+                    return Ok(());
+                }
+                if let Some(tunnel) = config.list_tunnels()?.iter().find(|t| t.alias == *alias)
+                    && tunnel.is_running()
+                {
+                    let result = tunnel.stop(*force);
+                    history::record(HistoryAction::Stop, alias, None, &result);
+                    result?;
+                }
+                if config.get_tunnel(alias).is_ok_and(|t| t.service_token) {
+                    ServiceToken::delete(alias)?;
+                }
+                if config.get_tunnel(alias).is_ok_and(|t| t.client_password) {
+                    ClientPassword::delete(alias)?;
+                }
+                let ssh_host = config.get_tunnel(alias).is_ok_and(|t| t.ssh_host);
+                let result = config.remove_tunnel(alias);
+                history::record(HistoryAction::Remove, alias, None, &result);
+                result?;
+                println!("{}", t!("已移除连接 {}", alias));
+                if ssh_host {
+                    sync_ssh_config(&config);
+                }
+                let _ = sshconfig::forget_key(alias);
             }
-            Commands::Remove { alias } => {
-                if let Some(tunnel) = config.list_tunnels()?.iter().find(|t| t.alias == *alias) {
-                    if tunnel.is_running() {
-                        tunnel.stop()?;
+            Commands::Add { from_file: Some(path), dry_run, .. } => {
+                let tunnels = manifest::load(path)?;
+                if tunnels.is_empty() {
+                    println!("{}", t!("清单中没有连接"));
+                    return Ok(());
+                }
+                let (conflicts, warnings) = manifest_conflicts(&config, &tunnels);
+                for warning in &warnings {
+                    warn!("{}", warning);
+                }
+                if !conflicts.is_empty() {
+                    return Err(TunnelError::config(t!("清单与现有配置冲突，未添加任何连接:\n{}", conflicts.join("\n"))));
+                }
+                config.check_new_tunnels(&tunnels)?;
+
+                let rows: Vec<(&Tunnel, TunnelStatus)> = tunnels.iter().map(|t| (t, TunnelStatus::Stopped)).collect();
+                println!("{}", tunnel_table(&rows, &HashSet::new()));
+                if *dry_run {
+                    println!("{}", t!("预览：将添加 {} 个连接，未修改配置", tunnels.len()));
+                } else {
+                    let count = tunnels.len();
+                    let aliases: Vec<String> = tunnels.iter().map(|t| t.alias.clone()).collect();
+                    let result = config.add_tunnels(tunnels);
+                    for alias in &aliases {
+                        history::record(HistoryAction::Add, alias, Some(path.display().to_string()), &result);
                     }
+                    result?;
+                    println!("{}", t!("已添加 {} 个连接", count));
                 }
-                config.remove_tunnel(alias)?;
-                println!("已移除连接 {}", alias);
             }
-            Commands::Add { alias, source, port } => {
-                config.add_tunnel(alias, source, *port)?;
-                println!("已添加连接 {}", alias);
+            Commands::Add { alias: Some(alias), source: Some(source), port, service, template, provider, remote_port, runtime, context, namespace, proxy, max_up, max_down, tags, depends_on, via, via_mode, extra_ports, listen_addr, local_hostname, ssh_host, probe, probe_interval, ping_url, client, connect_url, notes, connect_hint, outbound_proxy, log_level, redact, log_sinks, locked, autostart, on_change, .. } => {
+                let template = match template {
+                    Some(name) => config.template(name)?,
+                    None => Template::default(),
+                };
+                let port = &port.map(|port| port.resolve(&config)).transpose()?.or(template.port)
+                    .ok_or_else(|| TunnelError::config(t!("模板中没有端口，请使用 --port 指定")))?;
+                let mut tunnel = Tunnel::new(alias, source, *port);
+                tunnel.service = service.as_ref().map(|service| service.trim().to_string()).filter(|service| !service.is_empty());
+                tunnel.provider = match provider {
+                    ProviderKind::Cloudflared | ProviderKind::Http if remote_port.is_some() || context.is_some() || namespace.is_some() => {
+                        return Err(anyhow::anyhow!(t!("--remote-port、--context、--namespace 只适用于 kubectl 后端")));
+                    }
+                    ProviderKind::Cloudflared => Provider::Cloudflared,
+                    ProviderKind::Http => Provider::Http,
+                    ProviderKind::Kubectl => Provider::Kubectl {
+                        remote_port: remote_port.unwrap_or(*port),
+                        context: context.clone(),
+                        namespace: namespace.clone(),
+                    },
+                };
+                if runtime.is_some() && tunnel.provider != Provider::Cloudflared {
+                    return Err(TunnelError::config(t!("容器运行时只适用于 cloudflared 后端")));
+                }
+                tunnel.runtime = *runtime;
+                tunnel.proxy = *proxy;
+                tunnel.max_up = max_up.filter(|rate| *rate > 0);
+                tunnel.max_down = max_down.filter(|rate| *rate > 0);
+                tunnel.tags = normalize_list(tags);
+                tunnel.depends_on = normalize_list(depends_on);
+                tunnel.via = via.as_ref().map(|upstream| Via { alias: upstream.clone(), mode: via_mode.unwrap_or_default() });
+                tunnel.extra_ports = parse_extra_ports(extra_ports)?;
+                tunnel.check_extra_ports()?;
+                tunnel.locked = *locked;
+                tunnel.listen_addr = *listen_addr;
+                if let Some(ip) = listen_addr {
+                    warn_exposed(alias, *ip);
+                }
+                tunnel.local_hostname = local_hostname.as_deref().map(parse_local_hostname).transpose()?;
+                tunnel.ssh_host = *ssh_host;
+                tunnel.probe = probe.clone().or(template.probe);
+                tunnel.client = client.clone().or(template.client);
+                if let Some(url) = connect_url {
+                    client::parse_url(url)?;
+                    tunnel.connect_url = Some(url.clone());
+                }
+                tunnel.notes = notes.as_ref().map(|notes| notes.trim().to_string()).filter(|notes| !notes.is_empty());
+                tunnel.connect_hint = connect_hint.as_ref().map(|hint| hint.trim().to_string()).filter(|hint| !hint.is_empty());
+                tunnel.probe_interval = *probe_interval;
+                if let Some(url) = ping_url {
+                    ping::check(url)?;
+                    tunnel.ping_url = Some(url.clone());
+                }
+                tunnel.outbound_proxy = outbound_proxy.as_deref()
+                    .map(|url| OutboundProxy::new(url, Vec::new()))
+                    .transpose()?;
+                tunnel.log_level = *log_level;
+                tunnel.redact = redact_patterns(redact)?;
+                tunnel.log_sinks = parse_log_sinks(log_sinks)?;
+                tunnel.autostart = *autostart;
+                tunnel.on_change = on_change.unwrap_or_default();
+                let result = config.add_tunnel(tunnel);
+                history::record(HistoryAction::Add, alias, None, &result);
+                result?;
+                println!("{}", t!("已添加连接 {}", alias));
+                if *ssh_host {
+                    sync_ssh_config(&config);
+                }
             }
-        }
-        
-        Ok(())
-    }
-} 
\ No newline at end of file
+            Commands::Copy { alias, new_alias, source, port } => {
+                let alias = &config.resolve_alias(alias)?;
+                if config.get_tunnel(new_alias).is_ok() {
+                    return Err(TunnelError::config(t!("连接 {} 已存在", new_alias)));
+                }
+                let mut tunnel = config.get_tunnel(alias)?.clone();
+                tunnel.alias = new_alias.clone();
+                // 副本是本地自建的连接，可以自由修改
+                tunnel.managed_by = None;
+                if let Some(source) = source {
+                    tunnel.source = source.clone();
+                }
+                if let Some(port) = port {
+                    tunnel.port = port.resolve(&config)?;
+                }
+                // 服务令牌和数据库密码保存在钥匙串中按别名区分，一并复制
+                if tunnel.service_token {
+                    match ServiceToken::load(alias)? {
+                        Some(token) => token.save(new_alias)?,
+                        None => tunnel.service_token = false,
+                    }
+                }
+                if tunnel.client_password {
+                    match ClientPassword::load(alias)? {
+                        Some(password) => ClientPassword::save(new_alias, &password)?,
+                        None => tunnel.client_password = false,
+                    }
+                }
+                let same_port = port.is_none();
+                let result = config.add_tunnel(tunnel);
+                history::record(HistoryAction::Add, new_alias, Some(format!("copy {}", alias)), &result);
+                result?;
+                println!("{}", t!("已将连接 {} 复制为 {}", alias, new_alias));
+                if config.get_tunnel(new_alias)?.ssh_host {
+                    sync_ssh_config(&config);
+                }
+                if same_port {
+                    println!("{}", t!("提示: 新连接与 {} 使用相同的本地端口，两者不能同时运行，可使用 --port 指定其他端口", alias));
+                }
+            }
+            Commands::Env { target, format } => {
+                let tunnels: Vec<&Tunnel> = match config.get_tunnel(target) {
+                    Ok(tunnel) => {
+                        if !tunnel.is_running() {
+                            return Err(anyhow::anyhow!(t!("连接 {} 未运行", target)));
+                        }
+                        vec![tunnel]
+                    }
+                    Err(_) => {
+                        let mut tunnels: Vec<&Tunnel> = config.list_tunnels()?
+                            .into_iter()
+                            .filter(|t| t.tags.contains(target))
+                            .collect();
+                        if tunnels.is_empty() {
+                            return Err(TunnelError::config(t!("未找到别名或标签为 {} 的连接", target)));
+                        }
+                        tunnels.retain(|t| t.is_running());
+                        if tunnels.is_empty() {
+                            return Err(anyhow::anyhow!(t!("标签 {} 下没有正在运行的连接", target)));
+                        }
+                        tunnels.sort_by(|a, b| a.alias.cmp(&b.alias));
+                        tunnels
+                    }
+                };
+                print!("{}", format_env(&tunnels, *format)?);
+            }
+            Commands::Alert { action: AlertAction::Run { interval } } => {
+                recover_all(&config);
+                alert::run(&config, Duration::from_secs(*interval)).await?;
+            }
+            Commands::Alert { action: AlertAction::List } => {
+                if config.alert_rules().is_empty() && config.notify_channels().is_empty() {
+                    println!("{}", t!("没有配置告警规则和通知渠道，请在配置文件的 alerts 与 notify 中添加"));
+                } else {
+                    println!("{}", alert_table(config.alert_rules()));
+                    println!("{}", channel_table(config.notify_channels()));
+                }
+            }
+            Commands::Alert { action: AlertAction::Test { channels } } => {
+                let all = config.notify_channels();
+                if all.is_empty() {
+                    return Err(TunnelError::config(t!("没有配置通知渠道，请在配置文件的 notify 中添加")));
+                }
+                let names: Vec<&String> = if channels.is_empty() { all.keys().collect() } else { channels.iter().collect() };
+                let notification = Notification {
+                    rule: "test".to_string(),
+                    alias: String::new(),
+                    state: AlertState::Firing,
+                    message: t!("这是一条测试通知"),
+                    time: platform::unix_now(),
+                };
+                let mut failed = 0;
+                for name in names {
+                    let channel = all.get(name).ok_or_else(|| TunnelError::config(t!("通知渠道 {} 不存在", name)))?;
+                    match channel.send(name, &notification) {
+                        Ok(()) => println!("{}", t!("已发送到 {}", name)),
+                        Err(e) => {
+                            failed += 1;
+                            eprintln!("{}", t!("通过 {} 发送通知失败: {}", name, e));
+                        }
+                    }
+                }
+                if failed > 0 {
+                    return Err(anyhow::anyhow!(t!("{} 个通知渠道发送失败", failed)));
+                }
+            }
+            Commands::Alert { action: AlertAction::Password { channel, delete } } => {
+                match config.notify_channels().get(channel) {
+                    Some(Channel::Email(_)) => {}
+                    Some(_) => return Err(TunnelError::config(t!("通知渠道 {} 不是电子邮件渠道", channel))),
+                    None => return Err(TunnelError::config(t!("通知渠道 {} 不存在", channel))),
+                }
+                if *delete {
+                    SmtpPassword::delete(channel)?;
+                    println!("{}", t!("已删除通知渠道 {} 的 SMTP 密码", channel));
+                } else {
+                    let password = Password::with_theme(&ColorfulTheme::default())
+                        .with_prompt(t!("SMTP 密码"))
+                        .interact()?;
+                    SmtpPassword::save(channel, &password)?;
+                    println!("{}", t!("已保存通知渠道 {} 的 SMTP 密码", channel));
+                }
+            }
+            Commands::Apikey { action: ApikeyAction::Create { name, scope } } => {
+                let (key, secret) = ApiKey::generate(*scope);
+                config.add_api_key(name, key)?;
+                println!("{}", t!("已创建 API 密钥 {}（{}），请立即保存，之后无法再次查看:", name, scope));
+                println!("{}", secret);
+            }
+            Commands::Apikey { action: ApikeyAction::List } => {
+                if config.api_keys().is_empty() {
+                    println!("{}", t!("没有 API 密钥，使用 tfa apikey create <名称> 创建"));
+                } else {
+                    println!("{}", apikey_table(config.api_keys()));
+                }
+            }
+            Commands::Apikey { action: ApikeyAction::Revoke { name } } => {
+                if !config.api_keys().contains_key(name) {
+                    return Err(TunnelError::config(t!("未找到 API 密钥 {}", name)));
+                }
+                if !confirm(yes, t!("撤销 API 密钥 {}？", name))? {
+                    return Ok(());
+                }
+                config.remove_api_key(name)?;
+                println!("{}", t!("已撤销 API 密钥 {}", name));
+            }
+            Commands::Compose { action: ComposeAction::Up { file, args } } => {
+                let file = compose::find_file(file.as_deref())?;
+                let declared = compose::declared_tunnels(&file)?;
+                start_in_order(&mut config, &declared, DEFAULT_JOBS)?;
+
+                // 依赖的连接同样可能被 compose 中的服务使用，一并注入
+                let mut env = Vec::new();
+                for alias in config.start_order(&declared)?.into_iter().flatten() {
+                    env.extend(env_vars(config.get_tunnel(&alias)?));
+                }
+                let status = run_docker_compose(&file, "up", args, &env).await?;
+                if !status.success() {
+                    std::process::exit(status.code().unwrap_or(1));
+                }
+            }
+            Commands::Compose { action: ComposeAction::Down { file, args } } => {
+                let file = compose::find_file(file.as_deref())?;
+                let declared = compose::declared_tunnels(&file)?;
+                let status = run_docker_compose(&file, "down", args, &[]).await?;
+
+                // 与 up 对称：按启动顺序的逆序停止声明的连接及其依赖
+                let mut waves = config.start_order(&declared)?;
+                waves.reverse();
+                let mut failures = Vec::new();
+                for wave in waves {
+                    let mut batch = Vec::new();
+                    for alias in &wave {
+                        let tunnel = config.get_tunnel(alias)?;
+                        if tunnel.is_running() {
+                            batch.push(tunnel);
+                        }
+                    }
+                    failures.extend(recorded(Action::Stop, run_parallel(&batch, Action::Stop, DEFAULT_JOBS, |t, _| t.stop(false)))
+                        .into_iter()
+                        .filter_map(|(alias, result)| result.err().map(|e| (alias, e))));
+                }
+                report_failures(Action::Stop, failures)?;
+                if !status.success() {
+                    std::process::exit(status.code().unwrap_or(1));
+                }
+            }
+            Commands::Connections { alias } => {
+                let alias = &config.resolve_alias(alias)?;
+                let tunnel = config.get_tunnel(alias)?;
+                if !tunnel.proxy {
+                    return Err(anyhow::anyhow!(t!(
+                        "连接 {} 未启用统计代理，请先运行 'tfa set {} --proxy true' 并重新启动",
+                        alias, alias
+                    )));
+                }
+                let Some(stats) = ProxyStats::load(alias) else {
+                    println!("{}", t!("连接 {} 未在运行", alias));
+                    return Ok(());
+                };
+
+                let now = platform::unix_now();
+                println!("{}", t!("活动连接: {}  累计连接: {}  接收: {}  发送: {}  空闲: {}s",
+                    stats.active.len(),
+                    stats.total_connections,
+                    format_bytes(stats.bytes_in),
+                    format_bytes(stats.bytes_out),
+                    now.saturating_sub(stats.last_activity)
+                ));
+                if !stats.active.is_empty() {
+                    println!();
+                    println!("{:<6} {:<25} {:<10} {:<12} {:<12}", "id", "client", "duration", "in", "out");
+                    for (id, conn) in &stats.active {
+                        println!("{:<6} {:<25} {:<10} {:<12} {:<12}",
+                            id,
+                            conn.peer,
+                            format!("{}s", now.saturating_sub(conn.opened_at)),
+                            format_bytes(conn.bytes_in),
+                            format_bytes(conn.bytes_out)
+                        );
+                    }
+                }
+            }
+            Commands::Exec { alias, timeout, command } => {
+                let alias = &config.resolve_alias(alias)?;
+                let tunnel = config.get_tunnel(alias)?;
+                // 没有给出命令时经系统 shell 执行连接的客户端命令
+                let command = &match (command.is_empty(), &tunnel.client) {
+                    (false, _) => command.clone(),
+                    (true, Some(client)) if cfg!(windows) => vec!["cmd".to_string(), "/C".to_string(), client.clone()],
+                    (true, Some(client)) => vec!["sh".to_string(), "-c".to_string(), client.clone()],
+                    (true, None) => return Err(TunnelError::config(t!(
+                        "连接 {} 没有设置客户端命令，请在 `--` 之后给出要执行的命令，或用 --client 设置",
+                        alias
+                    ))),
+                };
+                let started = !tunnel.is_running();
+                if started {
+                    eprintln!("{}", t!("正在启动 {}...", tunnel.alias));
+                    let result = tunnel.start_with_progress(|message| eprintln!("{}", message));
+                    history::record(HistoryAction::Start, &tunnel.alias, None, &result);
+                    result?;
+                }
+
+                let status = match tunnel.wait_ready(Duration::from_secs(*timeout)) {
+                    Ok(()) => run_with_tunnel_env(tunnel, command).await,
+                    Err(e) => Err(e),
+                };
+
+                if started {
+                    eprintln!("{}", t!("正在停止 {}...", tunnel.alias));
+                    let result = tunnel.stop(false);
+                    history::record(HistoryAction::Stop, &tunnel.alias, None, &result);
+                    result?;
+                }
+
+                let status = status?;
+                if !status.success() {
+                    std::process::exit(status.code().unwrap_or(1));
+                }
+            }
+            Commands::Connect { alias, forget_password: true, .. } => {
+                let alias = &config.resolve_alias(alias)?;
+                config.get_tunnel(alias)?;
+                ClientPassword::delete(alias)?;
+                let result = config.update_tunnel(alias, TunnelUpdate {
+                    client_password: Some(false),
+                    ..Default::default()
+                });
+                history::record(HistoryAction::Set, alias, Some("client_password".to_string()), &result);
+                result?;
+                println!("{}", t!("已删除连接 {} 的数据库密码", alias));
+            }
+            Commands::Connect { alias, kind, timeout, save_password, forget_password: false, args } => {
+                let alias = &config.resolve_alias(alias)?;
+                if *save_password {
+                    require_interactive(t!("非交互环境中请通过 {} 环境变量提供数据库密码", client::PASSWORD_ENV))?;
+                    config.get_tunnel(alias)?;
+                    let password = Password::with_theme(&ColorfulTheme::default())
+                        .with_prompt(t!("数据库密码"))
+                        .interact()?;
+                    ClientPassword::save(alias, &password)?;
+                    let result = config.update_tunnel(alias, TunnelUpdate {
+                        client_password: Some(true),
+                        ..Default::default()
+                    });
+                    history::record(HistoryAction::Set, alias, Some("client_password".to_string()), &result);
+                    result?;
+                    eprintln!("{}", t!("已保存连接 {} 的数据库密码", alias));
+                }
+                let tunnel = config.get_tunnel(alias)?;
+                let kind = kind.or_else(|| client::detect(tunnel)).ok_or_else(|| TunnelError::config(t!(
+                    "无法判断连接 {} 的数据库类型，请用 --kind 指定，或用 --connect-url 设置连接串",
+                    alias
+                )))?;
+                let launch = client::launch(tunnel, kind, client::password(tunnel)?, args)?;
+                let started = !tunnel.is_running();
+                if started {
+                    eprintln!("{}", t!("正在启动 {}...", tunnel.alias));
+                    let result = tunnel.start_with_progress(|message| eprintln!("{}", message));
+                    history::record(HistoryAction::Start, &tunnel.alias, None, &result);
+                    result?;
+                }
+
+                let status = match tunnel.wait_ready(Duration::from_secs(*timeout)) {
+                    Ok(()) => {
+                        let mut cmd = tokio::process::Command::new(&launch.program);
+                        cmd.args(&launch.args).envs(launch.env.iter().map(|(k, v)| (k, v)));
+                        run_foreground(cmd, &launch.program).await
+                    }
+                    Err(e) => Err(e),
+                };
+
+                if started {
+                    eprintln!("{}", t!("正在停止 {}...", tunnel.alias));
+                    let result = tunnel.stop(false);
+                    history::record(HistoryAction::Stop, &tunnel.alias, None, &result);
+                    result?;
+                }
+
+                let status = status?;
+                if !status.success() {
+                    std::process::exit(status.code().unwrap_or(1));
+                }
+            }
+            Commands::Curl { alias, path, args } => {
+                let alias = &config.resolve_alias(alias)?;
+                let command = Provider::curl_command(config.get_tunnel(alias)?, path, args)?;
+                let status = run_foreground(command.into(), "cloudflared").await?;
+                if !status.success() {
+                    std::process::exit(status.code().unwrap_or(1));
+                }
+            }
+            Commands::Token { alias, json, raw } => {
+                let alias = &config.resolve_alias(alias)?;
+                let tunnel = config.get_tunnel(alias)?;
+                let route = tunnel.route()?;
+                let output = Provider::token_command(tunnel, &route)?.output()
+                    .map_err(|e| anyhow::anyhow!(t!("无法执行 cloudflared: {}", e)))?;
+                let url = format!("https://{}", route.hostname);
+                let token = String::from_utf8_lossy(&output.stdout).trim().to_string();
+                if !output.status.success() || token.is_empty() {
+                    return Err(anyhow::anyhow!(t!(
+                        "没有 {} 的 Access 令牌，请先运行 cloudflared access login {}",
+                        route.hostname,
+                        url
+                    )));
+                }
+                if *raw {
+                    println!("{}", token);
+                    return Ok(());
+                }
+                let (claims, all) = jwt::decode(&token)?;
+                if *json {
+                    println!("{}", serde_json::to_string_pretty(&all)?);
+                } else {
+                    print!("{}", describe_token(&route.hostname, &claims, platform::unix_now()));
+                }
+                match claims.remaining(platform::unix_now()) {
+                    Some(remaining) if remaining <= 0 => {
+                        return Err(anyhow::anyhow!(t!(
+                            "令牌已过期，请运行 cloudflared access login {} 重新登录",
+                            url
+                        )));
+                    }
+                    Some(remaining) if remaining as u64 <= jwt::EXPIRY_WARNING => {
+                        eprintln!("{}", t!(
+                            "令牌将在 {} 后过期，可以运行 cloudflared access login {} 重新登录",
+                            format_duration(remaining as u64),
+                            url
+                        ));
+                    }
+                    _ => {}
+                }
+            }
+            Commands::SshConfig { forget_key: Some(alias), .. } => {
+                let alias = &config.resolve_alias(alias)?;
+                if sshconfig::forget_key(alias)? {
+                    println!("{}", t!("已删除连接 {} 的 known_hosts，下次 ssh {} 时需要重新确认主机密钥", alias, alias));
+                } else {
+                    println!("{}", t!("连接 {} 还没有记录主机密钥", alias));
+                }
+            }
+            Commands::PrivilegedPort { alias, mode, port, print } => {
+                let alias = &config.resolve_alias(alias)?;
+                set_privileged(&mut config, alias, *mode, *port, *print)?;
+                let tunnel = config.get_tunnel(alias)?;
+                match tunnel.privileged {
+                    Some(Privileged::Redirect { port: to }) => println!("{}", t!("连接 {} 将监听端口 {}，端口 {} 经转发规则到达该端口", alias, to, tunnel.port)),
+                    Some(Privileged::Elevate) => println!("{}", t!("连接 {} 将经 sudo 启动后端进程", alias)),
+                    None => println!("{}", t!("连接 {} 恢复为直接监听端口 {}", alias, tunnel.port)),
+                }
+                if *print && matches!(tunnel.privileged, Some(Privileged::Redirect { .. })) {
+                    println!("{}", t!("请以管理员身份执行以上命令"));
+                }
+                if tunnel.is_running() {
+                    println!("{}", t!("连接 {} 正在运行，修改将在下次启动时生效", alias));
+                }
+            }
+            Commands::SshConfig { print: true, .. } => {
+                print!("{}", sshconfig::render(&config)?.1);
+            }
+            Commands::SshConfig { .. } => {
+                let hosts = sshconfig::sync(&config)?;
+                if hosts.is_empty() {
+                    println!("{}", t!("没有设置 ssh_host 的连接，已删除 ~/.ssh/config 中由本工具管理的条目"));
+                } else {
+                    println!("{}", t!("已更新 ~/.ssh/config 中的 {} 个主机: {}", hosts.len(), hosts.join(", ")));
+                }
+            }
+            Commands::Test { alias, probe, timeout } => {
+                let alias = &config.resolve_alias(alias)?;
+                let tunnel = config.get_tunnel(alias)?;
+                let probe = probe.clone().or_else(|| tunnel.probe.clone()).unwrap_or_default();
+                let started = !tunnel.is_running();
+                if started {
+                    eprintln!("{}", t!("正在启动 {}...", tunnel.alias));
+                    let result = tunnel.start_with_progress(|message| eprintln!("{}", message));
+                    history::record(HistoryAction::Start, &tunnel.alias, None, &result);
+                    result?;
+                }
+
+                let timeout = Duration::from_secs(*timeout);
+                let result = tunnel.wait_ready(timeout).and_then(|()| tunnel.probe(&probe, timeout));
+                if let Err(e) = LatencyHistory::record(alias, result.as_ref().ok().map(duration_ms)) {
+                    debug!(alias, "无法记录探测延迟: {}", e);
+                }
+
+                if started {
+                    eprintln!("{}", t!("正在停止 {}...", tunnel.alias));
+                    let result = tunnel.stop(false);
+                    history::record(HistoryAction::Stop, &tunnel.alias, None, &result);
+                    result?;
+                }
+
+                match result {
+                    Ok(elapsed) => println!("{}", t!("{} 的 {} 探测成功，往返 {} ms", alias, probe, format!("{:.1}", duration_ms(&elapsed)))),
+                    Err(e) => return Err(anyhow::anyhow!(t!("{} 的 {} 探测失败: {}", alias, probe, e))),
+                }
+            }
+            Commands::Tcp { hostname, port } => {
+                let port = match port {
+                    Some(port) => *port,
+                    None => platform::free_port()?,
+                };
+                let tunnel = Tunnel::new("tcp", hostname, port);
+                if let Some(occupant) = tunnel.port_occupant() {
+                    return Err(TunnelError::PortInUse {
+                        port,
+                        pid: Some(occupant.pid),
+                        process: Some(occupant.process),
+                    }
+                    .into());
+                }
+                eprintln!("{}", t!("{} 已映射到 {}，按 Ctrl-C 结束", hostname, tunnel.local_url()));
+                let status = run_foreground(tunnel.preview_command()?.into(), "cloudflared").await?;
+                // 被 Ctrl-C 结束时没有退出码，视为正常结束
+                if let Some(code) = status.code() && code != 0 {
+                    std::process::exit(code);
+                }
+            }
+            Commands::Share { target, no_qr } => {
+                let status = share::run(target, !*no_qr).await?;
+                // 被 Ctrl-C 结束时没有退出码，视为正常结束
+                if let Some(code) = status.code() && code != 0 {
+                    std::process::exit(code);
+                }
+            }
+            Commands::Adopt { dry_run } => {
+                let results = adopt::scan(&config)?;
+                if results.is_empty() {
+                    println!("{}", t!("没有发现正在运行的 cloudflared access 进程"));
+                    return Ok(());
+                }
+                let mut adopted = 0;
+                if !*dry_run {
+                    for (process, outcome) in &results {
+                        if let Outcome::Adoptable(alias) = outcome {
+                            adopt::adopt(config.get_tunnel(alias)?, process)?;
+                            adopted += 1;
+                        }
+                    }
+                }
+                println!("{}", adopt_table(&results, *dry_run));
+                if *dry_run {
+                    println!("{}", t!("预览：未接管任何进程"));
+                } else {
+                    println!("{}", t!("已接管 {} 个进程", adopted));
+                }
+            }
+            Commands::Recover { aliases } => {
+                let aliases = if aliases.is_empty() {
+                    config.list_tunnels()?.into_iter().map(|t| t.alias.clone()).collect()
+                } else {
+                    config.expand_aliases(aliases)?
+                };
+                let mut recorded = 0;
+                for alias in &aliases {
+                    match config.get_tunnel(alias)?.recover()? {
+                        Recovery::NotRunning => continue,
+                        Recovery::Exited { pid } => println!("{}", t!("{}: 记录的进程 (PID {}) 已退出，下次启动时计入失败次数", alias, pid)),
+                        Recovery::Attached { pid, respawned, warnings } => {
+                            if respawned.is_empty() {
+                                println!("{}", t!("{}: 进程 (PID {}) 仍在运行，后台进程正常", alias, pid));
+                            } else {
+                                println!("{}", t!("{}: 进程 (PID {}) 仍在运行，已重新启动 {}", alias, pid, respawned.join(", ")));
+                            }
+                            for warning in warnings {
+                                println!("{}: {}", alias, warning);
+                            }
+                        }
+                    }
+                    recorded += 1;
+                }
+                if recorded == 0 {
+                    println!("{}", t!("没有由本工具启动的连接"));
+                }
+            }
+            Commands::Reload => {
+                let outcome = reload::reload()?;
+                if outcome.is_empty() {
+                    println!("{}", t!("配置没有需要应用的变化"));
+                }
+                for line in outcome.lines() {
+                    println!("{}", line);
+                }
+                if !outcome.failed.is_empty() {
+                    return Err(anyhow::anyhow!(t!("{} 个连接未能应用新配置", outcome.failed.len())));
+                }
+            }
+            Commands::Discover => {
+                let processes = adopt::discover(&config)?;
+                if processes.is_empty() {
+                    println!("{}", t!("没有发现未配置的 cloudflared access 进程"));
+                    return Ok(());
+                }
+                println!("{}", discover_table(&processes));
+                if !platform::is_interactive() {
+                    return Err(anyhow::anyhow!(t!("非交互环境中无法导入，请在终端中运行 discover")));
+                }
+                let mut imported = 0;
+                for process in &processes {
+                    let confirmed = Confirm::with_theme(&ColorfulTheme::default())
+                        .with_prompt(t!("导入 {}（PID {}，本地端口 {}）？", process.hostname, process.pid, process.port))
+                        .default(true)
+                        .interact()?;
+                    if !confirmed {
+                        continue;
+                    }
+                    let alias: String = Input::with_theme(&ColorfulTheme::default())
+                        .with_prompt(t!("连接别名"))
+                        .default(adopt::suggest_alias(&config, &process.hostname))
+                        .validate_with(|input: &String| -> Result<(), String> {
+                            if input.trim().is_empty() {
+                                Err(t!("别名不能为空"))
+                            } else if config.get_tunnel(input.trim()).is_ok() {
+                                Err(t!("连接 {} 已存在", input.trim()))
+                            } else {
+                                Ok(())
+                            }
+                        })
+                        .interact_text()?;
+                    let alias = alias.trim();
+                    let result = config.add_tunnel(Tunnel::new(alias, &process.hostname, process.port));
+                    history::record(HistoryAction::Add, alias, Some("discover".to_string()), &result);
+                    result?;
+                    adopt::adopt(config.get_tunnel(alias)?, process)?;
+                    println!("{}", t!("已添加连接 {} 并接管进程 {}", alias, process.pid));
+                    imported += 1;
+                }
+                println!("{}", t!("已导入 {} 个连接", imported));
+            }
+            Commands::Replay { list: true, .. } => {
+                let sessions = replay::sessions()?;
+                if sessions.is_empty() {
+                    println!("{}", t!("暂无交互选择记录"));
+                } else {
+                    println!("{}", replay_table(&sessions));
+                }
+            }
+            Commands::Replay { session, dry_run, jobs, .. } => {
+                let sessions = replay::sessions()?;
+                let steps = sessions.get(*session as usize - 1).ok_or_else(|| match sessions.len() {
+                    0 => TunnelError::config(t!("暂无交互选择记录，请先运行 tfa run 或 tfa stop 并在菜单中选择连接")),
+                    count => TunnelError::config(t!("只记录了 {} 次会话", count)),
+                })?;
+                for step in steps {
+                    // 记录之后被删除的连接跳过
+                    let (aliases, missing): (Vec<String>, Vec<String>) = step.aliases.iter().cloned()
+                        .partition(|alias| config.get_tunnel(alias).is_ok());
+                    if !missing.is_empty() {
+                        println!("{}", t!("跳过已不存在的连接: {}", missing.join(", ")));
+                    }
+                    if aliases.is_empty() {
+                        continue;
+                    }
+                    match step.action {
+                        HistoryAction::Stop => println!("{}", t!("停止: {}", aliases.join(", "))),
+                        _ => println!("{}", t!("启动: {}", aliases.join(", "))),
+                    }
+                    match step.action {
+                        HistoryAction::Stop if *dry_run => {}
+                        HistoryAction::Stop => {
+                            let running: Vec<String> = aliases.into_iter()
+                                .filter(|alias| config.get_tunnel(alias).is_ok_and(Tunnel::is_live))
+                                .collect();
+                            if running.is_empty() {
+                                println!("{}", t!("没有正在运行的连接"));
+                            } else {
+                                stop_selected(&config, &running, false)?;
+                            }
+                        }
+                        _ => run_selected(&mut config, &aliases, *jobs, *dry_run, None, None, Some(false))?,
+                    }
+                }
+            }
+            Commands::History { alias, limit } => {
+                let entries = history::load(alias.as_deref(), *limit)?;
+                if entries.is_empty() {
+                    println!("{}", t!("暂无操作记录"));
+                } else {
+                    println!("{}", history_table(&entries));
+                }
+            }
+            Commands::Audit { alias, limit } => {
+                let alias = &config.resolve_alias(alias)?;
+                let tunnel = config.get_tunnel(alias)?;
+                let entries = audit::load(alias, *limit)?;
+                // 统计代理已退出时，快照中的活动连接不再可信
+                let active = ProxyStats::load(alias)
+                    .filter(|_| tunnel.is_running())
+                    .map(|stats| stats.active)
+                    .unwrap_or_default();
+                if entries.is_empty() && active.is_empty() {
+                    println!("{}", t!("连接 {} 暂无访问记录", alias));
+                } else {
+                    println!("{}", audit_table(&entries, active.values()));
+                    if let Some(last) = active.values().map(|conn| conn.opened_at).chain(entries.iter().map(|e| e.opened_at)).max() {
+                        println!("{}", t!("最近一次访问: {} (UTC)", history::format_time(last)));
+                    }
+                }
+                if !tunnel.proxy {
+                    println!("{}", t!("连接 {} 未启用统计代理，不会记录访问；可使用 'tfa set {} --proxy true' 启用", alias, alias));
+                }
+            }
+            Commands::Prune => {
+                let orphans = prune::scan(&config)?;
+                if orphans.is_empty() {
+                    println!("{}", t!("没有需要清理的进程或文件"));
+                    return Ok(());
+                }
+                println!("{}", prune_table(&orphans));
+                if !confirm(yes, t!("结束上述进程并删除上述文件？"))? {
+                    println!("{}", t!("已取消"));
+                    return Ok(());
+                }
+                // 先结束进程，再删除它们的状态文件
+                for orphan in &orphans {
+                    orphan.clean();
+                }
+                let processes = orphans.iter().filter(|o| o.is_process()).count();
+                println!("{}", t!("已结束 {} 个进程，删除 {} 个文件", processes, orphans.len() - processes));
+            }
+            Commands::ServiceToken { alias, clear: true, .. } => {
+                let alias = &config.resolve_alias(alias)?;
+                config.get_tunnel(alias)?;
+                ServiceToken::delete(alias)?;
+                let result = config.update_tunnel(alias, TunnelUpdate {
+                    service_token: Some(false),
+                    ..Default::default()
+                });
+                history::record(HistoryAction::Set, alias, Some("service_token".to_string()), &result);
+                result?;
+                println!("{}", t!("已清除连接 {} 的服务令牌", alias));
+            }
+            Commands::ServiceToken { alias, client_id, client_secret, clear: false } => {
+                let alias = &config.resolve_alias(alias)?;
+                if !config.get_tunnel(alias)?.provider.is_default() {
+                    return Err(anyhow::anyhow!(t!("服务令牌只适用于 cloudflared 后端的连接")));
+                }
+                if client_id.is_none() || client_secret.is_none() {
+                    require_interactive(t!("非交互环境中请通过 --client-id 与 --client-secret 传入服务令牌"))?;
+                }
+                let client_id = match client_id {
+                    Some(id) => id.clone(),
+                    None => Input::with_theme(&ColorfulTheme::default())
+                        .with_prompt("CF-Access-Client-Id")
+                        .interact_text()?,
+                };
+                let client_secret = match client_secret {
+                    Some(secret) => secret.clone(),
+                    None => Password::with_theme(&ColorfulTheme::default())
+                        .with_prompt("CF-Access-Client-Secret")
+                        .interact()?,
+                };
+                ServiceToken { client_id, client_secret }.save(alias)?;
+                let result = config.update_tunnel(alias, TunnelUpdate {
+                    service_token: Some(true),
+                    ..Default::default()
+                });
+                history::record(HistoryAction::Set, alias, Some("service_token".to_string()), &result);
+                result?;
+                println!("{}", t!("已保存连接 {} 的服务令牌，下次启动时生效", alias));
+            }
+            Commands::Config { action: ConfigAction::Encrypt } => {
+                if config.is_encrypted() {
+                    println!("{}", t!("配置已处于加密状态"));
+                } else {
+                    config.set_encrypted(true)?;
+                    println!("{}", t!("已加密配置中的敏感字段，密钥保存在系统钥匙串（或 {} 环境变量）中", secrets::CONFIG_KEY_ENV));
+                }
+            }
+            Commands::Config { action: ConfigAction::Decrypt } => {
+                if config.is_encrypted() {
+                    config.set_encrypted(false)?;
+                    println!("{}", t!("配置已改为明文存储"));
+                } else {
+                    println!("{}", t!("配置未加密"));
+                }
+            }
+            Commands::Config { action: ConfigAction::Lang { language: None, reset: true } } => {
+                config.set_lang(None)?;
+                println!("{}", t!("已清除语言设置，将按 LANG 等环境变量选择"));
+            }
+            Commands::Config { action: ConfigAction::Lang { language: Some(lang), .. } } => {
+                config.set_lang(Some(*lang))?;
+                i18n::set(*lang);
+                println!("{}", t!("界面语言已设置为 {}", lang));
+            }
+            Commands::Config { action: ConfigAction::Lang { language: None, reset: false } } => {
+                match config.lang() {
+                    Some(lang) => println!("{}", t!("当前语言: {}（配置文件）", lang)),
+                    None => println!("{}", t!("当前语言: {}（未设置，按环境变量选择）", i18n::current())),
+                }
+            }
+            Commands::Config { action: ConfigAction::Copy { enabled: Some(enabled) } } => {
+                config.set_copy_on_run(*enabled)?;
+                if *enabled {
+                    println!("{}", t!("tfa run 启动后将默认复制使用方法，--no-copy 可跳过"));
+                } else {
+                    println!("{}", t!("tfa run 启动后不再默认复制，--copy 可复制"));
+                }
+            }
+            Commands::Config { action: ConfigAction::Copy { enabled: None } } => {
+                println!("{}", t!("启动后默认复制到剪贴板: {}", config.copy_on_run()));
+            }
+            Commands::Config { action: ConfigAction::Sync { reset: true, .. } } => {
+                config.set_sync_settings(None)?;
+                println!("{}", t!("已清除同步设置"));
+            }
+            Commands::Config { action: ConfigAction::Sync { remote: Some(remote), branch, file, .. } } => {
+                let settings = SyncSettings::new(remote, branch.as_deref(), file.as_deref());
+                println!("{}", t!("同步仓库已设置为 {}（分支 {}，文件 {}）", settings.remote, settings.branch, settings.file));
+                config.set_sync_settings(Some(settings))?;
+            }
+            Commands::Config { action: ConfigAction::Sync { remote: None, .. } } => {
+                match config.sync_settings() {
+                    Some(settings) => println!("{}", t!("同步仓库: {}（分支 {}，文件 {}）", settings.remote, settings.branch, settings.file)),
+                    None => println!("{}", t!("尚未设置同步仓库")),
+                }
+            }
+            Commands::Config { action: ConfigAction::Proxy { reset: true, .. } } => {
+                config.set_outbound_proxy(None)?;
+                println!("{}", t!("已清除全局代理，将沿用 HTTPS_PROXY 等环境变量"));
+            }
+            Commands::Config { action: ConfigAction::Proxy { url: Some(url), no_proxy, .. } } => {
+                let proxy = OutboundProxy::new(url, normalize_list(no_proxy))?;
+                println!("{}", t!("全局代理已设置为 {}", proxy));
+                config.set_outbound_proxy(Some(proxy))?;
+            }
+            Commands::Config { action: ConfigAction::Proxy { url: None, .. } } => {
+                match config.outbound_proxy() {
+                    Some(proxy) if proxy.no_proxy.is_empty() => println!("{}", t!("全局代理: {}", proxy)),
+                    Some(proxy) => println!("{}", t!("全局代理: {}（不经代理: {}）", proxy, proxy.no_proxy.join(", "))),
+                    None => println!("{}", t!("未设置全局代理，沿用 HTTPS_PROXY 等环境变量")),
+                }
+            }
+            Commands::Config { action: ConfigAction::LogSinks { reset: true, .. } } => {
+                config.set_log_sinks(Vec::new())?;
+                println!("{}", t!("已清除全局日志转发目标"));
+            }
+            Commands::Config { action: ConfigAction::LogSinks { sinks, .. } } if !sinks.is_empty() => {
+                let sinks = parse_log_sinks(sinks)?;
+                let names: Vec<String> = sinks.iter().map(ToString::to_string).collect();
+                config.set_log_sinks(sinks)?;
+                println!("{}", t!("全局日志转发目标已设置为 {}，对之后启动的连接生效", names.join(", ")));
+            }
+            Commands::Config { action: ConfigAction::Otel { reset: true, .. } } => {
+                config.set_otel(None)?;
+                println!("{}", t!("已关闭 OpenTelemetry 导出"));
+            }
+            Commands::Config { action: ConfigAction::Otel { endpoint: Some(endpoint), headers, service_name, .. } } => {
+                let settings = OtelSettings::new(endpoint, headers, service_name.as_deref())?;
+                println!("{}", t!("OpenTelemetry 导出已设置为 {}（service.name={}）", settings.endpoint, settings.service_name()));
+                config.set_otel(Some(settings))?;
+            }
+            Commands::Config { action: ConfigAction::Otel { endpoint: None, .. } } => {
+                match config.otel() {
+                    Some(settings) => {
+                        println!("{}", t!("OpenTelemetry 导出: {}（service.name={}）", settings.endpoint, settings.service_name()));
+                        if !settings.headers.is_empty() {
+                            println!("{}", t!("请求头: {}", settings.header_names().join(", ")));
+                        }
+                    }
+                    None => println!("{}", t!("未设置 OpenTelemetry 导出")),
+                }
+            }
+            Commands::Config { action: ConfigAction::CloudflaredVersion { reset: true, .. } } => {
+                config.set_cloudflared_pin(None)?;
+                println!("{}", t!("已取消固定 cloudflared 版本，将使用 PATH 中的 cloudflared"));
+            }
+            Commands::Config { action: ConfigAction::CloudflaredVersion { version: Some(version), sha256, .. } } => {
+                let pin = Pin::new(version, sha256.clone())?;
+                println!("{}", t!("已将 cloudflared 版本固定为 {}，运行 'tfa upgrade-cloudflared' 安装", pin.version));
+                config.set_cloudflared_pin(Some(pin))?;
+            }
+            Commands::Config { action: ConfigAction::CloudflaredVersion { version: None, .. } } => {
+                match config.cloudflared_pin() {
+                    Some(pin) => {
+                        println!("{}", t!("固定的 cloudflared 版本: {}", pin.version));
+                        if let Some(sha256) = &pin.sha256 {
+                            println!("SHA-256: {}", sha256);
+                        }
+                    }
+                    None => println!("{}", t!("未固定 cloudflared 版本，使用 PATH 中的 cloudflared")),
+                }
+            }
+            Commands::UpgradeCloudflared => {
+                match cloudflared::pinned() {
+                    Some(pin) => println!("{}", t!("正在安装 cloudflared {}...", pin.version)),
+                    None => println!("{}", t!("正在升级 cloudflared...")),
+                }
+                Tunnel::upgrade_cloudflared()?;
+                let (program, version) = cloudflared::current();
+                println!("{}", t!("cloudflared {}（{}）", version.unwrap_or_else(|| "?".to_string()), program.display()));
+            }
+            Commands::Config { action: ConfigAction::PortRange { reset: true, .. } } => {
+                config.set_port_range(None)?;
+                println!("{}", t!("已清除保留端口范围"));
+            }
+            Commands::Config { action: ConfigAction::PortRange { range: Some(range), .. } } => {
+                config.set_port_range(Some(*range))?;
+                println!("{}", t!("保留端口范围已设置为 {}", range));
+                let outside = ports_outside(&config, *range)?;
+                if !outside.is_empty() {
+                    println!("{}", t!("提示: 以下连接的端口不在该范围内: {}", outside.join(", ")));
+                }
+            }
+            Commands::Config { action: ConfigAction::PortRange { range: None, .. } } => {
+                match config.port_range() {
+                    Some(range) => {
+                        println!("{}", t!("保留端口范围: {}", range));
+                        let outside = ports_outside(&config, range)?;
+                        if !outside.is_empty() {
+                            println!("{}", t!("不在范围内的连接: {}", outside.join(", ")));
+                        }
+                    }
+                    None => println!("{}", t!("未设置保留端口范围")),
+                }
+            }
+            Commands::Config { action: ConfigAction::Backup { keep: Some(keep), .. } } => {
+                config.set_backup_retention(Some(*keep))?;
+                if *keep == 0 {
+                    println!("{}", t!("已关闭配置的自动备份"));
+                } else {
+                    println!("{}", t!("将保留最近 {} 份配置备份", keep));
+                }
+            }
+            Commands::Config { action: ConfigAction::Backup { list: true, .. } } => {
+                let backups = backup::list(&Config::config_path()?)?;
+                if backups.is_empty() {
+                    println!("{}", t!("暂无配置备份"));
+                } else {
+                    println!("{}", backup_table(&backups));
+                }
+                println!("{}", t!("保留最近 {} 份备份", config.backup_retention()));
+            }
+            Commands::Config { action: ConfigAction::Backup { .. } } => {
+                match backup::snapshot(&Config::config_path()?, config.backup_retention().max(1))? {
+                    Some(path) => println!("{}", t!("已备份配置到 {}", path.display())),
+                    None => println!("{}", t!("配置文件尚不存在，无需备份")),
+                }
+            }
+            Commands::Config { action: ConfigAction::Restore { at } } => {
+                let config_path = Config::config_path()?;
+                let chosen = match at {
+                    Some(at) => backup::find(&config_path, at)?,
+                    None => {
+                        let backups = backup::list(&config_path)?;
+                        if backups.is_empty() {
+                            return Err(TunnelError::config(t!("暂无配置备份")));
+                        }
+                        require_interactive(t!("非交互环境中无法选择备份，请使用 --at 指定备份时间"))?;
+                        let items: Vec<String> = backups.iter()
+                            .map(|backup| match backup.tunnel_count() {
+                                Some(count) => t!("{} UTC（{} 个连接）", backup.time(), count),
+                                None => format!("{} UTC", backup.time()),
+                            })
+                            .collect();
+                        let selection = Select::with_theme(&ColorfulTheme::default())
+                            .with_prompt(t!("选择要恢复的配置备份"))
+                            .items(&items)
+                            .default(0)
+                            .interact()?;
+                        backups[selection].clone()
+                    }
+                };
+                if !confirm(yes, t!("用 {} UTC 的备份替换当前配置？", chosen.time()))? {
+                    println!("{}", t!("已取消"));
+                    return Ok(());
+                }
+                backup::restore(&config_path, &chosen, config.backup_retention())?;
+                println!("{}", t!("已恢复 {} UTC 的配置备份", chosen.time()));
+            }
+            Commands::Config { action: ConfigAction::Templates } => {
+                println!("{}", template_table(&config.templates()));
+            }
+            Commands::Config { action: ConfigAction::LogSinks { .. } } => {
+                let sinks: Vec<String> = config.log_sinks().iter().map(ToString::to_string).collect();
+                if sinks.is_empty() {
+                    println!("{}", t!("未设置全局日志转发目标"));
+                } else {
+                    println!("{}", t!("全局日志转发目标: {}", sinks.join(", ")));
+                }
+            }
+            Commands::Doctor { alias } => {
+                let drifted = check_cloudflared_version();
+                let tunnels: Vec<&Tunnel> = match alias {
+                    Some(alias) => config.expand_aliases(std::slice::from_ref(alias))?
+                        .iter()
+                        .map(|alias| config.get_tunnel(alias))
+                        .collect::<Result<_>>()?,
+                    None => config.list_tunnels()?
+                        .into_iter()
+                        .filter(|t| matches!(t.provider, Provider::Cloudflared | Provider::Http))
+                        .collect(),
+                };
+                if tunnels.is_empty() {
+                    println!("{}", t!("没有需要检查的连接"));
+                    return drifted;
+                }
+                let results: Vec<(&Tunnel, Option<OutboundProxy>, Result<u16>)> = tunnels.into_iter()
+                    .map(|tunnel| {
+                        let proxy = outbound::effective(tunnel.outbound_proxy.as_ref());
+                        let result = outbound::check(proxy.as_ref(), &tunnel.source);
+                        (tunnel, proxy, result)
+                    })
+                    .collect();
+                println!("{}", doctor_table(&results));
+                let failed = results.iter().filter(|(_, _, result)| result.is_err()).count();
+                if failed > 0 {
+                    return Err(anyhow::anyhow!(t!("{} 个连接无法访问，请检查代理设置", failed)));
+                }
+                drifted?;
+            }
+            Commands::Apply { file, prune, dry_run } => {
+                let plan = apply::plan(&config, manifest::load(file)?, *prune)?;
+                println!("{}", apply_table(&plan.changes));
+                let pending = plan.count(ApplyChange::is_pending);
+                if pending == 0 {
+                    println!("{}", t!("配置已与清单一致"));
+                    return Ok(());
+                }
+                if *dry_run {
+                    println!("{}", t!("预览：未修改配置"));
+                    return Ok(());
+                }
+                if !confirm(yes, t!("应用以上 {} 项修改？", pending))? {
+                    println!("{}", t!("已取消"));
+                    return Ok(());
+                }
+                // 应用后用原来的设置停止被移除的连接、重启被更新的连接
+                let before: HashMap<String, Tunnel> = plan.changes.iter()
+                    .filter(|(_, change)| matches!(change, ApplyChange::Updated(_) | ApplyChange::Removed))
+                    .filter_map(|(alias, _)| config.get_tunnel(alias).ok().map(|t| (alias.clone(), t.clone())))
+                    .collect();
+                let summary = t!(
+                    "应用完成：新增 {}，更新 {}，移除 {}，跳过 {}",
+                    plan.count(|c| *c == ApplyChange::Added),
+                    plan.count(|c| matches!(c, ApplyChange::Updated(_))),
+                    plan.count(|c| *c == ApplyChange::Removed),
+                    plan.count(|c| matches!(c, ApplyChange::Skipped(_)))
+                );
+                let source = Some(file.display().to_string());
+                let result = config.set_global_tunnels(plan.tunnels);
+                for (alias, change) in &plan.changes {
+                    let action = match change {
+                        ApplyChange::Added => HistoryAction::Add,
+                        ApplyChange::Updated(_) => HistoryAction::Set,
+                        ApplyChange::Removed => HistoryAction::Remove,
+                        ApplyChange::Unchanged | ApplyChange::Skipped(_) => continue,
+                    };
+                    history::record(action, alias, source.clone(), &result);
+                }
+                result?;
+                for (alias, change) in &plan.changes {
+                    let Some(previous) = before.get(alias).filter(|t| t.is_running()) else {
+                        continue;
+                    };
+                    if *change == ApplyChange::Removed {
+                        let result = previous.stop(false);
+                        history::record(HistoryAction::Stop, alias, None, &result);
+                        result?;
+                        println!("{}", t!("已停止被移除的连接 {}", alias));
+                    } else {
+                        restart_tunnel(&config, previous, config.get_tunnel(alias)?)?;
+                    }
+                }
+                for (alias, change) in &plan.changes {
+                    if *change == ApplyChange::Removed {
+                        let _ = sshconfig::forget_key(alias);
+                    }
+                }
+                sync_ssh_config(&config);
+                println!("{}", summary);
+            }
+            Commands::Diff { file, prune, json, exit_code } => {
+                let plan = apply::plan(&config, manifest::load(file)?, *prune)?;
+                let rows: Vec<DiffRow> = plan.changes.iter()
+                    .filter(|(_, change)| *change != ApplyChange::Unchanged)
+                    .map(|(alias, change)| {
+                        let current = config.get_tunnel(alias).ok();
+                        let running = current.is_some_and(|t| t.is_running());
+                        DiffRow {
+                            alias,
+                            action: change.label(),
+                            fields: match (current, plan.tunnel(alias)) {
+                                (Some(before), Some(after)) if matches!(change, ApplyChange::Updated(_)) => field_changes(before, after),
+                                _ => BTreeMap::new(),
+                            },
+                            reason: match change {
+                                ApplyChange::Skipped(reason) => Some(reason.clone()),
+                                _ => None,
+                            },
+                            process: match change {
+                                ApplyChange::Updated(_) if running => Some("restart"),
+                                ApplyChange::Removed if running => Some("stop"),
+                                _ => None,
+                            },
+                        }
+                    })
+                    .collect();
+                let pending = plan.count(ApplyChange::is_pending);
+                if *json {
+                    println!("{}", serde_json::to_string_pretty(&serde_json::json!({ "pending": pending, "changes": rows }))?);
+                } else if pending == 0 && rows.is_empty() {
+                    println!("{}", t!("配置已与清单一致"));
+                } else {
+                    for row in &rows {
+                        print!("{}", describe_diff_row(row));
+                    }
+                    let processes = |wanted: &str| rows.iter().filter(|row| row.process == Some(wanted)).count();
+                    println!("{}", t!(
+                        "计划：新增 {}，更新 {}，移除 {}，跳过 {}；重启 {} 个、停止 {} 个运行中的连接",
+                        plan.count(|c| *c == ApplyChange::Added),
+                        plan.count(|c| matches!(c, ApplyChange::Updated(_))),
+                        plan.count(|c| *c == ApplyChange::Removed),
+                        plan.count(|c| matches!(c, ApplyChange::Skipped(_))),
+                        processes("restart"),
+                        processes("stop")
+                    ));
+                }
+                if *exit_code && pending > 0 {
+                    std::process::exit(exit_code::DRIFT);
+                }
+            }
+            Commands::Import { url, forget: true, dry_run, .. } => {
+                let (removed, tunnels) = import::forget(&config, url);
+                if removed.is_empty() {
+                    println!("{}", t!("没有由 {} 导入的连接", url));
+                    return Ok(());
+                }
+                if *dry_run {
+                    println!("{}", t!("预览：将移除 {}", removed.join(", ")));
+                    return Ok(());
+                }
+                let result = config.set_global_tunnels(tunnels);
+                for alias in &removed {
+                    history::record(HistoryAction::Remove, alias, Some(url.clone()), &result);
+                }
+                result?;
+                println!("{}", t!("已移除由 {} 导入的 {} 个连接", url, removed.len()));
+            }
+            Commands::Import { url, token, sha256, dry_run, forget: false } => {
+                let token = token.clone().or_else(|| std::env::var(import::TOKEN_ENV).ok().filter(|t| !t.is_empty()));
+                let imported = import::fetch(url, token.as_deref(), sha256.as_deref())?;
+                let (changes, tunnels) = import::merge(&config, url, imported);
+                println!("{}", import_table(&changes));
+                if *dry_run {
+                    println!("{}", t!("预览：未修改配置"));
+                    return Ok(());
+                }
+                let result = config.set_global_tunnels(tunnels);
+                for (alias, change) in &changes {
+                    let action = match change {
+                        ImportChange::Added => HistoryAction::Add,
+                        ImportChange::Updated => HistoryAction::Set,
+                        ImportChange::Removed => HistoryAction::Remove,
+                        ImportChange::Unchanged | ImportChange::Skipped(_) => continue,
+                    };
+                    history::record(action, alias, Some(url.clone()), &result);
+                }
+                result?;
+                let count = |wanted: fn(&ImportChange) -> bool| changes.iter().filter(|(_, c)| wanted(c)).count();
+                println!("{}", t!(
+                    "导入完成：新增 {}，更新 {}，移除 {}，跳过 {}",
+                    count(|c| *c == ImportChange::Added),
+                    count(|c| *c == ImportChange::Updated),
+                    count(|c| *c == ImportChange::Removed),
+                    count(|c| matches!(c, ImportChange::Skipped(_)))
+                ));
+            }
+            Commands::Sync { dry_run, ours, theirs } => {
+                let resolve = match (ours, theirs) {
+                    (true, _) => Resolve::Ours,
+                    (_, true) => Resolve::Theirs,
+                    _ => Resolve::Abort,
+                };
+                let plan = sync::sync(&mut config, resolve, *dry_run)?;
+                if plan.changes.is_empty() {
+                    println!("{}", t!("本地与远端一致"));
+                    return Ok(());
+                }
+                println!("{}", sync_table(&plan.changes));
+                let conflicts = plan.conflicts();
+                if !conflicts.is_empty() {
+                    return Err(TunnelError::config(t!(
+                        "{} 个连接在本地和远端都被修改: {}；请修改后重试，或使用 --ours / --theirs 选择保留的版本",
+                        conflicts.len(),
+                        conflicts.join(", ")
+                    )));
+                }
+                if *dry_run {
+                    println!("{}", t!("预览：未修改配置，也未推送"));
+                } else {
+                    println!("{}", t!("同步完成"));
+                }
+            }
+            Commands::Add { .. } | Commands::ProxyServe { .. } | Commands::ClipboardServe | Commands::HttpServe { .. } | Commands::Grpc { .. } | Commands::Web { .. } | Commands::Validate { .. } | Commands::Profile { .. } => unreachable!(),
+            #[cfg(feature = "tray")]
+            Commands::Tray => unreachable!(),
+        }
+        
+        Ok(())
+    }
+}
+
+/// 批量执行的操作，决定进度条和汇总中的文字
+#[derive(Clone, Copy)]
+enum Action {
+    Start,
+    Stop,
+}
+
+impl Action {
+    fn waiting(self, alias: &str) -> String {
+        match self {
+            Action::Start => t!("等待启动 {}...", alias),
+            Action::Stop => t!("等待停止 {}...", alias),
+        }
+    }
+
+    fn running(self, alias: &str) -> String {
+        match self {
+            Action::Start => t!("正在启动 {}...", alias),
+            Action::Stop => t!("正在停止 {}...", alias),
+        }
+    }
+
+    fn done(self, alias: &str) -> String {
+        match self {
+            Action::Start => t!("{} 已启动", alias),
+            Action::Stop => t!("{} 已停止", alias),
+        }
+    }
+
+    fn failed(self, alias: &str, error: &TunnelError) -> String {
+        match self {
+            Action::Start => t!("{} 启动失败: {}", alias, error),
+            Action::Stop => t!("{} 停止失败: {}", alias, error),
+        }
+    }
+
+    fn failures(self, count: usize) -> String {
+        match self {
+            Action::Start => t!("{} 个连接启动失败", count),
+            Action::Stop => t!("{} 个连接停止失败", count),
+        }
+    }
+}
+
+/// 单个连接的操作结果
+type OpResult = (String, Result<(), TunnelError>);
+
+/// 并行对多个连接执行操作，同时进行的数量不超过 `jobs`，每个连接显示一个进度条；
+/// 非交互环境中不显示进度条，改为逐行输出
+fn run_parallel<F>(tunnels: &[&Tunnel], action: Action, jobs: usize, op: F) -> Vec<OpResult>
+where
+    F: Fn(&Tunnel, &ProgressBar) -> Result<(), TunnelError> + Sync,
+{
+    let multi = MultiProgress::new();
+    let bars: Vec<ProgressBar> = tunnels.iter()
+        .map(|tunnel| {
+            if !platform::is_interactive() {
+                return ProgressBar::hidden();
+            }
+            let pb = multi.add(ProgressBar::new_spinner());
+            pb.set_message(action.waiting(&tunnel.alias));
+            pb
+        })
+        .collect();
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<OpResult>>> = Mutex::new(tunnels.iter().map(|_| None).collect());
+
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, tunnels.len().max(1)) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                let (Some(&tunnel), Some(pb)) = (tunnels.get(index), bars.get(index)) else {
+                    break;
+                };
+                pb.enable_steady_tick(Duration::from_millis(100));
+                progress(pb, action.running(&tunnel.alias));
+                let result = op(tunnel, pb);
+                let message = match &result {
+                    Ok(()) => action.done(&tunnel.alias),
+                    Err(e) => action.failed(&tunnel.alias, e),
+                };
+                if pb.is_hidden() {
+                    println!("{}", message);
+                }
+                pb.finish_with_message(message);
+                results.lock().unwrap()[index] = Some((tunnel.alias.clone(), result));
+            });
+        }
+    });
+    results.into_inner().unwrap().into_iter().flatten().collect()
+}
+
+/// 把批量操作中每个连接的结果记入操作历史
+fn recorded(action: Action, results: Vec<OpResult>) -> Vec<OpResult> {
+    let kind = match action {
+        Action::Start => HistoryAction::Start,
+        Action::Stop => HistoryAction::Stop,
+    };
+    for (alias, result) in &results {
+        history::record(kind, alias, None, result);
+    }
+    results
+}
+
+/// 启动所选连接；`dry_run` 时只按启动顺序输出将要执行的命令
+/// `ttl` 为 Some 时所选连接到期后自动停止；`wait` 为 Some 时，启动后继续等待所选连接通过健康检查；
+/// `copy` 为 None 时按配置决定是否把使用方法复制到剪贴板
+fn run_selected(
+    config: &mut Config,
+    selected: &[String],
+    jobs: usize,
+    dry_run: bool,
+    ttl: Option<Duration>,
+    wait: Option<Duration>,
+    copy: Option<bool>,
+) -> Result<()> {
+    if !dry_run {
+        start_in_order(config, selected, jobs)?;
+        if let Some(ttl) = ttl {
+            for alias in selected {
+                config.get_tunnel(alias)?.set_ttl(ttl)?;
+            }
+            println!("{}", t!("所选连接将在 {} 后自动停止", format_duration(ttl.as_secs())));
+        }
+        if let Some(timeout) = wait {
+            wait_healthy(config, selected, timeout)?;
+        }
+        // 命令行未指定 --copy 或 --no-copy 时按配置决定
+        if copy.unwrap_or(config.copy_on_run()) {
+            copy_usage(config, selected)?;
+        }
+        return Ok(());
+    }
+    for (index, alias) in config.start_order(selected)?.into_iter().flatten().enumerate() {
+        if index > 0 {
+            println!();
+        }
+        println!("# {}", alias);
+        print!("{}", describe_command(config.get_tunnel(&alias)?)?);
+    }
+    Ok(())
+}
+
+/// 把所选连接的使用方法复制到剪贴板，每个连接一行；没有使用方法模板的连接复制本地地址。
+/// 连接已经启动，无法复制只给出警告
+fn copy_usage(config: &Config, selected: &[String]) -> Result<()> {
+    let mut lines = Vec::new();
+    for alias in selected {
+        let tunnel = config.get_tunnel(alias)?;
+        lines.push(tunnel.connect_hint().unwrap_or_else(|| format!("{}:{}", tunnel.local_host(), tunnel.port)));
+    }
+    match clipboard::copy(&lines.join("\n")) {
+        Ok(()) => println!("{}", t!("已复制到剪贴板: {}", lines.join(", "))),
+        Err(e) => warn!("{}", e),
+    }
+    Ok(())
+}
+
+/// 等到连接的有效期到达后停止它；连接已停止、被重新启动或有效期被取消时直接退出
+fn ttl_watch(config: &Config, alias: &str) -> Result<()> {
+    let _claim = WatcherMark::claim(alias, Watcher::Ttl)?;
+    loop {
+        // 有效期被再次设置时由新启动的进程接手
+        if !WatcherMark::is_mine(alias, Watcher::Ttl) {
+            return Ok(());
+        }
+        let Some(expires_at) = RuntimeState::load(alias)
+            .filter(RuntimeState::is_alive)
+            .and_then(|state| state.expires_at)
+        else {
+            return Ok(());
+        };
+        let now = platform::unix_now();
+        if now >= expires_at {
+            info!(alias, "有效期已到，停止连接");
+            let result = config.get_tunnel(alias)?.stop(false);
+            history::record(HistoryAction::Stop, alias, Some("ttl".to_string()), &result);
+            return Ok(result?);
+        }
+        // 有效期可能被再次设置，定期重新读取
+        thread::sleep(Duration::from_secs((expires_at - now).min(TTL_POLL_SECS)));
+    }
+}
+
+/// 以服务方式运行的命令启动时重新接管仍在运行的连接，结果只写入内部日志
+fn recover_all(config: &Config) {
+    let Ok(tunnels) = config.list_tunnels() else {
+        return;
+    };
+    for tunnel in tunnels {
+        match tunnel.recover() {
+            Ok(Recovery::Attached { warnings, .. }) => {
+                for warning in warnings {
+                    warn!(alias = %tunnel.alias, "{}", warning);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => warn!(alias = %tunnel.alias, "{}", t!("无法接管连接: {}", e)),
+        }
+    }
+}
+
+/// 定期探测连接，记录延迟并推送到 ping_url；连接停止、被重新启动或不再设置 probe_interval 与 ping_url 时退出。
+/// 每轮重新读取配置，以便修改探测方式和间隔后立即生效
+fn latency_watch(alias: &str) -> Result<()> {
+    let Some(pid) = RuntimeState::load(alias).map(|state| state.pid) else {
+        return Ok(());
+    };
+    let _claim = WatcherMark::claim(alias, Watcher::Latency)?;
+    loop {
+        if !WatcherMark::is_mine(alias, Watcher::Latency) {
+            return Ok(());
+        }
+        match RuntimeState::load(alias) {
+            Some(state) if state.pid == pid && state.is_alive() => {}
+            Some(state) if state.pid == pid => {
+                // tfa stop 先结束进程再删除运行状态，稍等后状态仍在才是意外退出
+                thread::sleep(Duration::from_secs(1));
+                if RuntimeState::load(alias).is_some_and(|state| state.pid == pid) {
+                    ping::down(Config::load()?.get_tunnel(alias)?, &t!("连接进程意外退出"));
+                }
+                return Ok(());
+            }
+            _ => return Ok(()),
+        }
+        let config = Config::load()?;
+        let tunnel = config.get_tunnel(alias)?;
+        let Some(interval) = tunnel.probe_interval.or(tunnel.ping_url.as_ref().map(|_| DEFAULT_PING_INTERVAL)) else {
+            return Ok(());
+        };
+        let probe = tunnel.probe.clone().unwrap_or_default();
+        let timeout = Duration::from_secs(interval.clamp(1, PROBE_TIMEOUT_SECS));
+        let result = tunnel.probe(&probe, timeout);
+        match &result {
+            Ok(elapsed) => ping::up(tunnel, elapsed.as_millis() as u64),
+            Err(e) => {
+                debug!(alias, "探测失败: {}", e);
+                ping::down(tunnel, &e.to_string());
+            }
+        }
+        LatencyHistory::record(alias, result.as_ref().ok().map(duration_ms))?;
+        thread::sleep(Duration::from_secs(interval.max(1)));
+    }
+}
+
+/// 依次等待连接通过健康检查，所有连接共用同一个截止时间
+fn wait_healthy(config: &Config, aliases: &[String], timeout: Duration) -> Result<()> {
+    let deadline = Instant::now() + timeout;
+    for alias in aliases {
+        let tunnel = config.get_tunnel(alias)?;
+        tunnel.wait_healthy(deadline.saturating_duration_since(Instant::now()))
+            .map_err(|e| match e {
+                // 报告总的等待时间，而不是该连接剩余的时间
+                TunnelError::Unhealthy { alias, status, .. } => TunnelError::Unhealthy { alias, seconds: timeout.as_secs(), status },
+                e => e,
+            })?;
+        println!("{}", t!("连接 {} 已通过健康检查", alias));
+    }
+    Ok(())
+}
+
+/// 解析时长：纯数字为秒，也可以带 s、m、h 后缀
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => value.split_at(index),
+        None => (value, "s"),
+    };
+    let number: u64 = number.parse().map_err(|_| t!("无效的时长 {}，应为 30、30s、2m 这样的格式", value))?;
+    let multiplier: u64 = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        _ => return Err(t!("无效的时长 {}，应为 30、30s、2m 这样的格式", value)),
+    };
+    number.checked_mul(multiplier)
+        .map(Duration::from_secs)
+        .ok_or_else(|| t!("无效的时长 {}，应为 30、30s、2m 这样的格式", value))
+}
+
+/// `--port` 的取值：端口号，或 auto 表示从保留端口范围中分配
+#[derive(Clone, Copy)]
+pub enum PortArg {
+    Auto,
+    Fixed(u16),
+}
+
+impl PortArg {
+    fn resolve(self, config: &Config) -> Result<u16> {
+        match self {
+            PortArg::Fixed(port) => Ok(port),
+            PortArg::Auto => config.allocate_port(&[]),
+        }
+    }
+}
+
+fn parse_port(value: &str) -> Result<PortArg, String> {
+    if value.eq_ignore_ascii_case("auto") {
+        return Ok(PortArg::Auto);
+    }
+    match value.parse::<u16>() {
+        Ok(port) if port > 0 => Ok(PortArg::Fixed(port)),
+        _ => Err(t!("无效的端口 {}，应为 1-65535 或 auto", value)),
+    }
+}
+
+/// 端口不在保留范围内的连接，格式为 `别名(端口)`，按别名排序
+fn ports_outside(config: &Config, range: PortRange) -> Result<Vec<String>> {
+    let mut outside: Vec<String> = config.list_tunnels()?
+        .into_iter()
+        .filter(|t| !range.contains(t.port))
+        .map(|t| format!("{}({})", t.alias, t.port))
+        .collect();
+    outside.sort();
+    Ok(outside)
+}
+
+/// 解析限速：纯数字为字节/秒，也可以带 K、M、G 后缀（按 1024 进位）
+fn parse_rate(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => value.split_at(index),
+        None => (value, ""),
+    };
+    let number: u64 = number.parse().map_err(|_| t!("无效的速率 {}，应为 500000、512K、2M 这样的格式", value))?;
+    let multiplier: u64 = match unit.to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1024,
+        "M" | "MB" | "MIB" => 1024 * 1024,
+        "G" | "GB" | "GIB" => 1024 * 1024 * 1024,
+        _ => return Err(t!("无效的速率 {}，应为 500000、512K、2M 这样的格式", value)),
+    };
+    number.checked_mul(multiplier).ok_or_else(|| t!("无效的速率 {}，应为 500000、512K、2M 这样的格式", value))
+}
+
+fn format_rate(rate: Option<u64>) -> String {
+    match rate {
+        Some(rate) => format!("{}/s", format_bytes(rate)),
+        None => t!("不限"),
+    }
+}
+
+/// 描述启动连接时将要执行的命令，服务令牌等敏感参数会被隐藏
+fn describe_command(tunnel: &Tunnel) -> Result<String> {
+    let command = tunnel.preview_command()?;
+    let args: Vec<String> = provider::redacted_args(&command)
+        .iter()
+        .map(|arg| if arg == provider::REDACTED { arg.clone() } else { shell_quote(arg) })
+        .collect();
+
+    let cwd = match command.get_current_dir() {
+        Some(dir) => dir.to_path_buf(),
+        None => std::env::current_dir()?,
+    };
+    let env: Vec<String> = command.get_envs()
+        .map(|(key, value)| match value {
+            Some(value) => format!("{}={}", key.to_string_lossy(), shell_quote(&outbound::redact(&value.to_string_lossy()))),
+            None => format!("unset {}", key.to_string_lossy()),
+        })
+        .collect();
+
+    let mut text = t!("命令: {}\n", args.join(" "));
+    if let Some(via) = &tunnel.via {
+        let mut hops = vec![tunnel.alias.clone()];
+        hops.extend(tunnel.route()?.hops);
+        text.push_str(&t!("链路: {}（{}）\n", hops.join(" -> "), via.mode));
+    }
+    text.push_str(&t!("工作目录: {}\n", cwd.display()));
+    if env.is_empty() {
+        text.push_str(&t!("环境变量: 继承当前环境\n"));
+    } else {
+        text.push_str(&t!("环境变量: 继承当前环境，并设置 {}\n", env.join(" ")));
+    }
+    text.push_str(&t!("日志: {}\n", tunnel.log_path()?.display()));
+    let sinks = logsink::effective(&tunnel.log_sinks);
+    if !sinks.is_empty() {
+        let sinks: Vec<String> = sinks.iter().map(ToString::to_string).collect();
+        text.push_str(&t!("日志转发: {}\n", sinks.join(", ")));
+    }
+    if tunnel.proxy {
+        text.push_str(&t!("统计代理: 监听 {}，转发到上面命令中的内部端口（启动时重新分配）\n", tunnel.port));
+    }
+    if tunnel.max_up.is_some() || tunnel.max_down.is_some() {
+        text.push_str(&t!("限速: 上行 {}，下行 {}\n", format_rate(tunnel.max_up), format_rate(tunnel.max_down)));
+    }
+    if let Some(pre_start) = &tunnel.hooks.pre_start {
+        text.push_str(&t!("pre_start 钩子: {}\n", pre_start));
+    }
+    Ok(text)
+}
+
+/// 按 POSIX shell 规则在需要时给参数加单引号
+fn shell_quote(arg: &str) -> String {
+    let safe = !arg.is_empty()
+        && arg.chars().all(|c| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c));
+    if safe {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+/// 启动连接，重试信息显示在进度条上
+fn start_with_spinner(tunnel: &Tunnel, pb: &ProgressBar) -> Result<(), TunnelError> {
+    tunnel.start_with_progress(|message| progress(pb, t!("正在启动 {}（{}）...", tunnel.alias, message)))
+}
+
+/// 更新进度条的消息；进度条不显示时（非交互环境）逐行输出
+fn progress(pb: &ProgressBar, message: String) {
+    if pb.is_hidden() {
+        println!("{}", message);
+    }
+    pb.set_message(message);
+}
+
+/// 批量启动中单个连接的结果
+enum StartOutcome {
+    Started,
+    Skipped(String),
+    Failed(TunnelError),
+}
+
+/// 停止所选连接；先停止依赖它们的连接，再停止被依赖的连接
+fn stop_selected(config: &Config, selected: &[String], force: bool) -> Result<()> {
+    // 普通用户跳过由系统服务管理的连接；只选择了这类连接时报错
+    let (system, selected): (Vec<String>, Vec<String>) = selected.iter()
+        .cloned()
+        .partition(|alias| config.get_tunnel(alias).is_ok_and(Tunnel::requires_admin));
+    if let Some(alias) = system.first() {
+        if selected.is_empty() {
+            return Err(TunnelError::SystemTunnel { alias: alias.clone() }.into());
+        }
+        println!("{}", t!("跳过由系统服务管理的连接: {}（需要管理员权限）", system.join(", ")));
+    }
+    let mut failures = Vec::new();
+    for wave in config.stop_order(&selected)? {
+        let mut batch = Vec::new();
+        for alias in &wave {
+            let tunnel = config.get_tunnel(alias)?;
+            if (selected.contains(alias) || tunnel.is_live()) && !tunnel.requires_admin() {
+                batch.push(tunnel);
+            }
+        }
+        failures.extend(recorded(Action::Stop, run_parallel(&batch, Action::Stop, DEFAULT_JOBS, |t, _| t.stop(force)))
+            .into_iter()
+            .filter_map(|(alias, result)| result.err().map(|e| (alias, e))));
+    }
+    report_failures(Action::Stop, failures)
+}
+
+/// 用修改后的配置重启运行中的连接，其他连接不受影响；剩余的临时时长在重启后保留
+fn restart_tunnel(config: &Config, before: &Tunnel, after: &Tunnel) -> Result<()> {
+    let ttl = before.remaining_ttl();
+    println!("{}", t!("正在重启 {}...", after.alias));
+    otel::traced(Operation::Restart, after, || {
+        let result = before.stop(false);
+        history::record(HistoryAction::Stop, &after.alias, None, &result);
+        result?;
+        let result = after.start_with_progress(|message| println!("{}", message));
+        history::record(HistoryAction::Start, &after.alias, None, &result);
+        result.map_err(anyhow::Error::from)
+    })?;
+    if let Some(ttl) = ttl {
+        after.set_ttl(Duration::from_secs(ttl))?;
+    }
+    println!("{}", t!("{} 已重启，现在监听 {}", after.alias, after.local_url()));
+    if after.port != before.port || after.listen_addr != before.listen_addr {
+        // 经它代理的连接在启动时记下了代理地址，需要各自重启
+        let downstream: Vec<String> = config.list_tunnels()?
+            .into_iter()
+            .filter(|t| t.via.as_ref().is_some_and(|via| via.alias == after.alias && via.needs_upstream()) && t.is_running())
+            .map(|t| t.alias.clone())
+            .collect();
+        if !downstream.is_empty() {
+            println!("{}", t!("提示: {} 经过该连接访问目标，仍在使用原来的地址，需要重启后才会使用新地址", downstream.join(", ")));
+        }
+    }
+    Ok(())
+}
+
+/// 连接变化后同步 ~/.ssh/config 中由本工具管理的条目，失败时只给出警告
+fn sync_ssh_config(config: &Config) {
+    if let Err(e) = sshconfig::sync(config) {
+        warn!("{}", t!("无法更新 ~/.ssh/config: {}", e));
+    }
+}
+
+/// 批量选择时去掉已停用和已暂停的连接，并说明跳过了哪些
+fn bulk_selection(config: &Config, selected: Vec<String>) -> Vec<String> {
+    let (disabled, selected): (Vec<String>, Vec<String>) = selected.into_iter()
+        .partition(|alias| config.get_tunnel(alias).is_ok_and(|t| !t.enabled));
+    if !disabled.is_empty() {
+        println!("{}", t!("跳过已停用的连接: {}（tfa enable 启用）", disabled.join(", ")));
+    }
+    let (paused, selected): (Vec<String>, Vec<String>) = selected.into_iter().partition(|alias| PauseMark::is_paused(alias));
+    if !paused.is_empty() {
+        println!("{}", t!("跳过已暂停的连接: {}（tfa resume 恢复）", paused.join(", ")));
+    }
+    let (system, selected): (Vec<String>, Vec<String>) = selected.into_iter()
+        .partition(|alias| config.get_tunnel(alias).is_ok_and(Tunnel::requires_admin));
+    if !system.is_empty() {
+        println!("{}", t!("跳过由系统服务管理的连接: {}（需要管理员权限）", system.join(", ")));
+    }
+    selected
+}
+
+/// 通配符匹配到多个连接时，在停止、暂停等操作前列出并确认
+fn confirm_matches(yes: bool, patterns: &[String], aliases: &[String], action: String) -> Result<bool> {
+    if aliases.len() < 2 || !patterns.iter().any(|pattern| config::is_glob(pattern)) {
+        return Ok(true);
+    }
+    confirm(yes, t!("{} 以下 {} 个连接: {}？", action, aliases.len(), aliases.join(", ")))
+}
+
+/// 按别名启动停用的连接时报错，而不是悄悄跳过
+fn ensure_enabled(tunnel: &Tunnel) -> Result<()> {
+    if !tunnel.enabled {
+        return Err(TunnelError::config(t!("连接 {} 已停用，请先使用 tfa enable {} 启用", tunnel.alias, tunnel.alias)));
+    }
+    Ok(())
+}
+
+/// 按依赖顺序分批启动所选连接及其依赖，同一批内并行启动。
+/// 已在运行的连接会被跳过，依赖启动失败的连接不再尝试启动；多个连接时最后输出汇总表
+fn start_in_order(config: &mut Config, selected: &[String], jobs: usize) -> Result<()> {
+    let mut outcomes: Vec<(String, StartOutcome)> = Vec::new();
+    // 启动失败或因依赖失败被跳过的连接，依赖它们的连接同样跳过
+    let mut unavailable: HashSet<String> = HashSet::new();
+    for wave in config.start_order(selected)? {
+        let mut batch = Vec::new();
+        for alias in wave {
+            let tunnel = config.get_tunnel(&alias)?;
+            if let Some(dependency) = config.prerequisites(tunnel).into_iter().find(|d| unavailable.contains(d)) {
+                let reason = t!("依赖的连接 {} 未能启动", dependency);
+                unavailable.insert(alias.clone());
+                outcomes.push((alias, StartOutcome::Skipped(reason)));
+            } else if tunnel.is_running() {
+                outcomes.push((alias, StartOutcome::Skipped(t!("已在运行"))));
+            } else if !tunnel.enabled {
+                unavailable.insert(alias.clone());
+                outcomes.push((alias, StartOutcome::Skipped(t!("已停用"))));
+            } else if !selected.contains(&alias) && PauseMark::is_paused(&alias) {
+                // 作为依赖被带起的连接不解除暂停
+                unavailable.insert(alias.clone());
+                outcomes.push((alias, StartOutcome::Skipped(t!("已暂停"))));
+            } else {
+                batch.push(tunnel);
+            }
+        }
+        // 在显示进度条之前验证 sudo 凭据，需要时询问密码
+        if batch.iter().any(|t| t.elevated()) {
+            privileged::authorize();
+        }
+        let results = run_parallel(&batch, Action::Start, jobs, start_with_spinner);
+
+        // 端口冲突需要交互处理，在本批进度条结束后逐个询问，处理完再启动下一批
+        for (alias, mut result) in results {
+            loop {
+                let retry = match &result {
+                    Err(TunnelError::PortInUse { port, pid, process }) => resolve_port_conflict(config, &alias, *port, *pid, process.as_deref())?,
+                    Err(TunnelError::PrivilegedPort { port, .. }) => resolve_privileged_port(config, &alias, *port)?,
+                    _ => false,
+                };
+                if !retry {
+                    break;
+                }
+                let tunnel = config.get_tunnel(&alias)?;
+                result = run_parallel(&[tunnel], Action::Start, 1, start_with_spinner).remove(0).1;
+            }
+            history::record(HistoryAction::Start, &alias, None, &result);
+            match result {
+                Ok(()) => {
+                    // 按别名显式启动即视为恢复；连接已经启动，删除标记失败只记录下来
+                    if selected.contains(&alias)
+                        && let Err(e) = PauseMark::clear(&alias)
+                    {
+                        debug!(alias = %alias, error = %e, "删除暂停标记失败");
+                    }
+                    outcomes.push((alias, StartOutcome::Started));
+                }
+                Err(e) => {
+                    unavailable.insert(alias.clone());
+                    outcomes.push((alias, StartOutcome::Failed(e)));
+                }
+            }
+        }
+    }
+
+    if outcomes.len() > 1 {
+        println!("{}", start_summary_table(&outcomes));
+    }
+    for (alias, outcome) in &outcomes {
+        let usage = usage_lines(config.get_tunnel(alias)?);
+        if matches!(outcome, StartOutcome::Started) && !usage.is_empty() {
+            println!("{}", t!("{} 的使用方法:", alias));
+            for line in usage {
+                println!("  {}", line);
+            }
+        }
+    }
+    let failures = outcomes.into_iter()
+        .filter_map(|(alias, outcome)| match outcome {
+            StartOutcome::Failed(e) => Some((alias, e)),
+            _ => None,
+        })
+        .collect();
+    report_failures(Action::Start, failures)
+}
+
+/// 连接的使用方法与说明，每项一行
+fn usage_lines(tunnel: &Tunnel) -> Vec<String> {
+    tunnel.connect_hint()
+        .into_iter()
+        .chain(tunnel.notes.iter().flat_map(|notes| notes.lines().map(str::to_string)))
+        .collect()
+}
+
+fn start_summary_table(outcomes: &[(String, StartOutcome)]) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(presets::UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec!["alias", "result", "reason"]);
+    for (alias, outcome) in outcomes {
+        let (result, color, reason) = match outcome {
+            StartOutcome::Started => ("started", Color::Green, String::new()),
+            StartOutcome::Skipped(reason) => ("skipped", Color::Yellow, reason.clone()),
+            StartOutcome::Failed(e) => ("failed", Color::Red, e.to_string()),
+        };
+        table.add_row(vec![
+            Cell::new(alias),
+            Cell::new(result).fg(color),
+            Cell::new(reason),
+        ]);
+    }
+    table
+}
+
+/// 汇总批量操作中失败的连接及修复建议
+fn report_failures(action: Action, failures: Vec<(String, TunnelError)>) -> Result<()> {
+    if failures.is_empty() {
+        return Ok(());
+    }
+    for (alias, error) in &failures {
+        eprintln!("{}: {}", alias, error);
+        if let Some(hint) = error.hint() {
+            eprintln!("{}", t!("  提示: {}", hint));
+        }
+    }
+    // 所有失败属于同一类时沿用该类的退出码
+    let mut codes = failures.iter().map(|(_, error)| error.exit_code());
+    let first = codes.next().unwrap_or(exit_code::FAILURE);
+    Err(TunnelError::Batch {
+        message: action.failures(failures.len()),
+        exit_code: if codes.all(|code| code == first) { first } else { exit_code::FAILURE },
+    }.into())
+}
+
+/// 端口被占用时可选择的处理方式
+enum PortAction {
+    Release,
+    ChangePort,
+    Cancel,
+}
+
+/// 无权绑定低于 1024 的端口时可选择的处理方式
+enum PrivilegedAction {
+    Mode(privileged::Mode),
+    ChangePort,
+    Cancel,
+}
+
+/// 解析日志转发目标，忽略空字符串；全部为空时表示使用全局设置
+fn parse_log_sinks(specs: &[String]) -> Result<Vec<LogSink>> {
+    specs.iter().filter(|spec| !spec.is_empty()).map(|spec| LogSink::new(spec)).collect()
+}
+
+/// 解析额外端口，忽略空字符串；全部为空时表示删除额外端口
+fn parse_extra_ports(specs: &[String]) -> Result<Vec<PortMapping>> {
+    specs.iter()
+        .filter(|spec| !spec.is_empty())
+        .map(|spec| spec.parse().map_err(TunnelError::config))
+        .collect()
+}
+
+/// 去掉空白的脱敏规则并检查能否编译；全部为空时表示清除规则
+fn redact_patterns(patterns: &[String]) -> Result<Vec<String>> {
+    let patterns: Vec<String> = patterns.iter().filter(|p| !p.is_empty()).cloned().collect();
+    Redactor::new(&patterns)?;
+    Ok(patterns)
+}
+
+fn parse_listen_addr(value: &str) -> Result<IpAddr> {
+    value.trim().trim_matches(['[', ']']).parse()
+        .map_err(|_| TunnelError::config(t!("监听地址 {} 无效，应为 IPv4 或 IPv6 地址", value)))
+}
+
+/// hosts 文件中的主机名只能是裸主机名，不带协议或端口
+fn parse_local_hostname(value: &str) -> Result<String> {
+    let host = value.trim().to_ascii_lowercase();
+    if host.contains("://") || !validate::is_valid_hostname(&host) {
+        return Err(TunnelError::config(t!("主机名 {} 不符合 DNS 语法", value)));
+    }
+    Ok(host)
+}
+
+/// 绑定到回环以外的地址时，局域网中的其他机器也能使用该连接
+fn warn_exposed(alias: &str, ip: IpAddr) {
+    if !ip.is_loopback() {
+        warn!("{}", t!("连接 {} 将监听 {}，其他机器也可以通过它访问目标服务", alias, ip));
+    }
+}
+
+/// `tfa set` 对后端的修改：切换后端，或调整 kubectl 后端的远端端口、context 与命名空间；
+/// 没有涉及后端时返回 None
+fn provider_update(
+    tunnel: &Tunnel,
+    kind: Option<ProviderKind>,
+    remote_port: Option<u16>,
+    context: Option<&str>,
+    namespace: Option<&str>,
+    unset: &[UnsetKey],
+) -> Result<Option<Provider>> {
+    let unset_context = unset.contains(&UnsetKey::Context);
+    let unset_namespace = unset.contains(&UnsetKey::Namespace);
+    let kubectl_only = remote_port.is_some() || context.is_some() || namespace.is_some() || unset_context || unset_namespace;
+    if (context.is_some() && unset_context) || (namespace.is_some() && unset_namespace) {
+        let key = if unset_context { UnsetKey::Context } else { UnsetKey::Namespace };
+        return Err(TunnelError::config(t!("不能同时设置并删除 {}", key.name())));
+    }
+    let current = match (&tunnel.provider, kind) {
+        (_, Some(ProviderKind::Cloudflared | ProviderKind::Http)) if kubectl_only => {
+            return Err(anyhow::anyhow!(t!("--remote-port、--context、--namespace 只适用于 kubectl 后端")));
+        }
+        (_, Some(ProviderKind::Cloudflared)) => return Ok(Some(Provider::Cloudflared)),
+        (_, Some(ProviderKind::Http)) => return Ok(Some(Provider::Http)),
+        (Provider::Kubectl { remote_port, context, namespace }, _) => (*remote_port, context.clone(), namespace.clone()),
+        (_, Some(ProviderKind::Kubectl)) => (tunnel.port, None, None),
+        (_, None) if kubectl_only => {
+            return Err(anyhow::anyhow!(t!("--remote-port、--context、--namespace 只适用于 kubectl 后端")));
+        }
+        (_, None) => return Ok(None),
+    };
+    if kind.is_none() && !kubectl_only {
+        return Ok(None);
+    }
+    // 空字符串与 --unset 都表示删除
+    let pick = |value: Option<&str>, unset: bool, current: Option<String>| match value {
+        Some(value) => Some(value.trim().to_string()).filter(|value| !value.is_empty()),
+        None if unset => None,
+        None => current,
+    };
+    Ok(Some(Provider::Kubectl {
+        remote_port: remote_port.unwrap_or(current.0),
+        context: pick(context, unset_context, current.1),
+        namespace: pick(namespace, unset_namespace, current.2),
+    }))
+}
+
+/// 连接的各个配置字段，嵌套的对象展开为 `hooks.pre_start` 形式；代理地址中的密码不显示
+fn tunnel_fields(tunnel: &Tunnel) -> BTreeMap<String, serde_json::Value> {
+    fn flatten(prefix: &str, value: serde_json::Value, fields: &mut BTreeMap<String, serde_json::Value>) {
+        match value {
+            serde_json::Value::Object(map) => {
+                for (key, value) in map {
+                    let key = if prefix.is_empty() { key } else { format!("{}.{}", prefix, key) };
+                    flatten(&key, value, fields);
+                }
+            }
+            value => {
+                fields.insert(prefix.to_string(), value);
+            }
+        }
+    }
+    let mut tunnel = tunnel.clone();
+    if let Some(proxy) = &mut tunnel.outbound_proxy {
+        proxy.url = outbound::redact(&proxy.url);
+    }
+    let mut fields = BTreeMap::new();
+    flatten("", serde_json::to_value(&tunnel).unwrap_or_default(), &mut fields);
+    // 取默认值时不写入配置的字段也列出
+    fields.entry("provider.kind".to_string()).or_insert_with(|| tunnel.provider.label().into());
+    fields.entry("enabled".to_string()).or_insert_with(|| tunnel.enabled.into());
+    if let Some(via) = &tunnel.via {
+        fields.entry("via.mode".to_string()).or_insert_with(|| via.mode.to_string().into());
+    }
+    fields
+}
+
+/// 修改前后的字段差异
+fn tunnel_diff(before: &Tunnel, after: &Tunnel) -> Vec<String> {
+    let mut lines = Vec::new();
+    for (key, change) in field_changes(before, after) {
+        if let Some(old) = change.before {
+            lines.push(format!("- {}: {}", key, old));
+        }
+        if let Some(new) = change.after {
+            lines.push(format!("+ {}: {}", key, new));
+        }
+    }
+    lines
+}
+
+/// 字段修改前后的值，字段不存在时为 None
+#[derive(Serialize)]
+struct FieldChange {
+    before: Option<serde_json::Value>,
+    after: Option<serde_json::Value>,
+}
+
+/// 取值不同的字段，嵌套字段以 . 连接
+fn field_changes(before: &Tunnel, after: &Tunnel) -> BTreeMap<String, FieldChange> {
+    let (mut before, mut after) = (tunnel_fields(before), tunnel_fields(after));
+    let keys: BTreeSet<String> = before.keys().chain(after.keys()).cloned().collect();
+    keys.into_iter()
+        .filter_map(|key| {
+            let change = FieldChange { before: before.remove(&key), after: after.remove(&key) };
+            (change.before != change.after).then_some((key, change))
+        })
+        .collect()
+}
+
+/// `tfa diff` 输出的一项
+#[derive(Serialize)]
+struct DiffRow<'a> {
+    alias: &'a str,
+    /// add、update、remove 或 skip
+    action: &'static str,
+    /// update 时有变化的字段
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    fields: BTreeMap<String, FieldChange>,
+    /// skip 的原因
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+    /// 对运行中进程的操作：restart 或 stop
+    process: Option<&'static str>,
+}
+
+/// 以 +、~、-、! 标记添加、更新、移除与跳过，更新时逐行列出字段的变化
+fn describe_diff_row(row: &DiffRow) -> String {
+    let mark = match row.action {
+        "add" => '+',
+        "update" => '~',
+        "remove" => '-',
+        _ => '!',
+    };
+    let mut text = format!("{} {}", mark, row.alias);
+    match row.process {
+        Some("restart") => text.push_str(&t!("（运行中，将重启）")),
+        Some(_) => text.push_str(&t!("（运行中，将停止）")),
+        None => {}
+    }
+    if let Some(reason) = &row.reason {
+        text.push_str(&format!(": {}", reason));
+    }
+    text.push('\n');
+    for (key, change) in &row.fields {
+        if let Some(old) = &change.before {
+            text.push_str(&format!("    - {}: {}\n", key, old));
+        }
+        if let Some(new) = &change.after {
+            text.push_str(&format!("    + {}: {}\n", key, new));
+        }
+    }
+    text
+}
+
+/// 连接较多时先输入关键字模糊筛选，再在匹配的连接中选择；留空表示不筛选。
+/// 单选菜单直接使用 FuzzySelect，dialoguer 没有可以模糊筛选的多选菜单，run/stop 以此代替
+fn narrow(tunnels: Vec<&Tunnel>) -> Result<Vec<&Tunnel>> {
+    if tunnels.len() <= NARROW_THRESHOLD {
+        return Ok(tunnels);
+    }
+    loop {
+        let pattern: String = Input::with_theme(&ColorfulTheme::default())
+            .with_prompt(t!("共 {} 个连接，输入关键字筛选（留空显示全部）", tunnels.len()))
+            .allow_empty(true)
+            .interact_text()?;
+        if pattern.trim().is_empty() {
+            return Ok(tunnels);
+        }
+        let matches = search::tunnels(&tunnels, &pattern);
+        if !matches.is_empty() {
+            return Ok(matches);
+        }
+        println!("{}", t!("没有与 {} 匹配的连接", pattern));
+    }
+}
+
+/// 非交互环境中无法弹出菜单或输入框，以提示应当使用的参数的错误代替
+fn require_interactive(hint: String) -> Result<()> {
+    if platform::is_interactive() {
+        Ok(())
+    } else {
+        Err(TunnelError::config(hint))
+    }
+}
+
+/// 破坏性操作前的确认，默认为否；`yes` 时直接通过，非交互环境中要求使用 --yes
+fn confirm(yes: bool, prompt: String) -> Result<bool> {
+    if yes {
+        return Ok(true);
+    }
+    if !platform::is_interactive() {
+        return Err(anyhow::anyhow!(t!("非交互环境中请使用 --yes 确认操作")));
+    }
+    Ok(Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(prompt)
+        .default(false)
+        .interact()?)
+}
+
+/// 展示端口占用者，并让用户选择释放端口或更换端口；返回 false 表示放弃重试
+fn resolve_port_conflict(
+    config: &mut Config,
+    alias: &str,
+    port: u16,
+    pid: Option<u32>,
+    process: Option<&str>,
+) -> Result<bool> {
+    // 非交互环境中无法询问，保留端口占用错误
+    if !platform::is_interactive() {
+        return Ok(false);
+    }
+    // 占用者可能是另一个受管理的连接
+    let managed = config.list_tunnels()?
+        .into_iter()
+        .find(|t| t.alias != alias && t.port == port && t.is_running());
+    let mut choices = Vec::new();
+    if let Some(other) = managed {
+        println!("{}", t!("端口 {} 正由连接 {} 使用", port, other.alias));
+        choices.push((PortAction::Release, t!("停止连接 {} 后重试", other.alias)));
+    } else if let Some(pid) = pid {
+        let name = process.map(str::to_string).unwrap_or_else(|| t!("未知进程"));
+        choices.push((PortAction::Release, t!("结束 {} (PID {}) 后重试", name, pid)));
+    }
+    choices.push((PortAction::ChangePort, t!("更换本地端口后重试")));
+    choices.push((PortAction::Cancel, t!("取消")));
+
+    let items: Vec<&String> = choices.iter().map(|(_, label)| label).collect();
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt(t!("{}: 端口 {} 已被占用，如何处理？", alias, port))
+        .items(&items)
+        .default(0)
+        .interact()?;
+
+    match choices[selection].0 {
+        PortAction::Release => match managed {
+            Some(other) => {
+                let result = other.stop(false);
+                history::record(HistoryAction::Stop, &other.alias, None, &result);
+                result?;
+            }
+            None => {
+                if let Some(pid) = pid {
+                    platform::kill_process(pid);
+                }
+            }
+        },
+        PortAction::ChangePort => change_port(config, alias)?,
+        PortAction::Cancel => return Ok(false),
+    }
+    Ok(true)
+}
+
+/// 说明低于 1024 的端口需要的权限，并让用户选择端口转发、经 sudo 启动或更换端口；选择会保存到配置中，
+/// 之后启动时沿用。返回 false 表示放弃重试
+fn resolve_privileged_port(config: &mut Config, alias: &str, port: u16) -> Result<bool> {
+    if !platform::is_interactive() {
+        return Ok(false);
+    }
+    println!("{}", t!("端口 {} 低于 1024，只有管理员可以直接绑定", port));
+    let mut choices = vec![(
+        PrivilegedAction::Mode(privileged::Mode::Redirect),
+        t!("改为监听端口 {}，并以管理员身份设置从端口 {} 转发过去的规则", privileged::default_redirect_port(port), port),
+    )];
+    if privileged::can_elevate() {
+        choices.push((PrivilegedAction::Mode(privileged::Mode::Elevate), t!("经 sudo 以管理员身份启动后端进程")));
+    }
+    choices.push((PrivilegedAction::ChangePort, t!("更换本地端口后重试")));
+    choices.push((PrivilegedAction::Cancel, t!("取消")));
+
+    let items: Vec<&String> = choices.iter().map(|(_, label)| label).collect();
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt(t!("{}: 如何绑定端口 {}？", alias, port))
+        .items(&items)
+        .default(0)
+        .interact()?;
+
+    match choices[selection].0 {
+        PrivilegedAction::Mode(mode) => {
+            set_privileged(config, alias, mode, None, false)?;
+            if mode == privileged::Mode::Elevate && !privileged::authorize() {
+                return Ok(false);
+            }
+        }
+        PrivilegedAction::ChangePort => change_port(config, alias)?,
+        PrivilegedAction::Cancel => return Ok(false),
+    }
+    Ok(true)
+}
+
+/// 询问新的本地端口并保存，默认建议一个空闲端口
+fn change_port(config: &mut Config, alias: &str) -> Result<()> {
+    let suggested = platform::free_port()?;
+    let new_port: u16 = Input::with_theme(&ColorfulTheme::default())
+        .with_prompt(t!("新的本地端口"))
+        .default(suggested)
+        .interact_text()?;
+    let result = config.update_tunnel(alias, TunnelUpdate {
+        port: Some(new_port),
+        ..Default::default()
+    });
+    history::record(HistoryAction::Set, alias, Some("port".to_string()), &result);
+    result?;
+    Ok(())
+}
+
+/// 修改连接在本地端口低于 1024 时的处理方式：先删除原有的端口转发规则，改为 redirect 时再设置新的规则。
+/// `print` 时只输出规则命令，不执行
+fn set_privileged(config: &mut Config, alias: &str, mode: privileged::Mode, port: Option<u16>, print: bool) -> Result<()> {
+    let tunnel = config.get_tunnel(alias)?.clone();
+    let wanted = match mode {
+        privileged::Mode::Redirect => {
+            let port = port.unwrap_or_else(|| privileged::default_redirect_port(tunnel.port));
+            if port < privileged::PRIVILEGED_PORTS {
+                return Err(TunnelError::config(t!("转发到的端口 {} 同样低于 1024，请用 --port 指定 1024 以上的端口", port)));
+            }
+            Some(Privileged::Redirect { port })
+        }
+        privileged::Mode::Elevate if !privileged::can_elevate() => {
+            return Err(TunnelError::config(t!("当前系统或用户绑定低于 1024 的端口不需要 sudo")));
+        }
+        privileged::Mode::Elevate => Some(Privileged::Elevate),
+        privileged::Mode::Off => None,
+    };
+    if tunnel.privileged != wanted {
+        let removed = match tunnel.privileged {
+            Some(Privileged::Redirect { port }) => privileged::unredirect_commands(&tunnel, port),
+            _ => Vec::new(),
+        };
+        let added = match wanted {
+            Some(Privileged::Redirect { port }) => privileged::redirect_commands(&tunnel, port),
+            _ => Vec::new(),
+        };
+        if print {
+            for line in removed.iter().chain(&added) {
+                println!("{}", line);
+            }
+        } else {
+            // 原有规则可能已被手动删除，删除失败不妨碍设置新的方式
+            if !removed.is_empty()
+                && let Err(e) = privileged::run_as_admin(&removed)
+            {
+                warn!("{}", t!("删除原有的端口转发规则失败: {}", e));
+            }
+            if !added.is_empty() {
+                privileged::run_as_admin(&added)?;
+            }
+        }
+    }
+    let result = config.update_tunnel(alias, TunnelUpdate {
+        privileged: Some(wanted),
+        ..Default::default()
+    });
+    history::record(HistoryAction::Set, alias, Some("privileged".to_string()), &result);
+    result?;
+    Ok(())
+}
+
+/// 执行命令并注入连接信息环境变量；Ctrl-C 交给子进程处理，
+/// 以便命令退出后仍能清理连接
+async fn run_with_tunnel_env(tunnel: &Tunnel, command: &[String]) -> Result<ExitStatus> {
+    let mut cmd = tokio::process::Command::new(&command[0]);
+    cmd.args(&command[1..])
+        .env("TUNNEL_ALIAS", &tunnel.alias)
+        .env("TUNNEL_HOST", tunnel.local_host())
+        .env("TUNNEL_PORT", tunnel.port.to_string());
+    run_foreground(cmd, &command[0]).await
+}
+
+/// 以指定 compose 文件执行 docker compose 子命令，并注入连接地址环境变量
+async fn run_docker_compose(
+    file: &Path,
+    subcommand: &str,
+    args: &[String],
+    env: &[(String, String)],
+) -> Result<ExitStatus> {
+    let mut cmd = tokio::process::Command::new("docker");
+    cmd.arg("compose")
+        .arg("-f")
+        .arg(file)
+        .arg(subcommand)
+        .args(args)
+        .envs(env.iter().map(|(k, v)| (k, v)));
+    run_foreground(cmd, "docker").await
+}
+
+/// 在前台运行子进程直到退出；Ctrl-C 交给子进程处理，本进程不随之退出
+async fn run_foreground(mut cmd: tokio::process::Command, program: &str) -> Result<ExitStatus> {
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| anyhow::anyhow!(t!("无法执行 {}: {}", program, e)))?;
+
+    loop {
+        tokio::select! {
+            status = child.wait() => return Ok(status?),
+            _ = tokio::signal::ctrl_c() => {}
+        }
+    }
+}
+
+/// `tfa log --follow`：显示已有的日志后继续显示新写入的行，直到按下 Ctrl-C。
+/// 有 `tfa grpc` 或 `tfa web` 在运行时经控制协议订阅它的日志流，否则由本进程的监视线程读取日志文件
+async fn follow_logs(config: &Config, aliases: &[String]) -> Result<()> {
+    // 多个连接的日志交错显示，每行注明来自哪个连接
+    let print = |alias: &str, line: &str| {
+        if aliases.len() > 1 {
+            println!("[{}] {}", alias, line);
+        } else {
+            println!("{}", line);
+        }
+    };
+    match ipc::Client::connect().await {
+        Ok(Some(mut client)) => {
+            tokio::select! {
+                result = client.logs(aliases, true, |line| print(&line.alias, &line.line)) => return result,
+                _ = tokio::signal::ctrl_c() => return Ok(()),
+            }
+        }
+        Ok(None) => {}
+        Err(e) => debug!(error = %e, "无法使用控制协议，改为直接读取日志文件"),
+    }
+
+    // 先订阅再读取已有的日志，以免两者之间写入的行丢失
+    let mut events = events::subscribe();
+    Monitor::new().logs(true).spawn()?;
+    for alias in aliases {
+        let prefix = format!("[{}] ", alias);
+        for line in config.get_tunnel(alias)?.get_logs() {
+            print(alias, line.strip_prefix(&prefix).unwrap_or(&line));
+        }
+    }
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(TunnelEvent::LogLine { alias, line }) if aliases.contains(&alias) => print(&alias, &line),
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => return Ok(()),
+            },
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        }
+    }
+}
+
+/// `tfa status --json` 输出的一项
+#[derive(Serialize)]
+struct StatusRow<'a> {
+    alias: &'a str,
+    status: TunnelStatus,
+    port: u16,
+    /// 运行时长（秒），未运行时为 null
+    uptime: Option<u64>,
+    /// 最近一次探测的延迟（毫秒），没有记录或探测失败时为 null
+    latency_ms: Option<f64>,
+    /// 人类可读输出中的延迟走势，JSON 中已有 latency_ms
+    #[serde(skip)]
+    trend: String,
+}
+
+/// 按条件筛选并排序连接，同时计算各自的状态
+fn list_rows(
+    config: &Config,
+    sort: SortKey,
+    filter: Option<StatusFilter>,
+    system: bool,
+) -> Result<Vec<(&Tunnel, TunnelStatus)>> {
+    let mut rows: Vec<(&Tunnel, TunnelStatus)> = config.list_tunnels()?
+        .into_iter()
+        .filter(|t| !system || t.system)
+        .map(|t| (t, t.status()))
+        .filter(|(_, status)| filter.is_none_or(|f| f.matches(*status)))
+        .collect();
+    match sort {
+        SortKey::Alias => rows.sort_by(|a, b| a.0.alias.cmp(&b.0.alias)),
+        SortKey::Port => rows.sort_by_key(|(t, _)| t.port),
+        SortKey::Status => rows.sort_by(|a, b| a.1.cmp(&b.1).then(a.0.alias.cmp(&b.0.alias))),
+    }
+    Ok(rows)
+}
+
+/// 事件的一行输出：JSON 对象，或带时间的说明文字
+fn describe_event(event: &TunnelEvent, time: u64, json: bool) -> Result<String> {
+    if json {
+        return Ok(events::to_json(event, time)?.to_string());
+    }
+    let text = match event {
+        TunnelEvent::Started { alias } => t!("{} 已启动", alias),
+        TunnelEvent::Stopped { alias } => t!("{} 已停止", alias),
+        TunnelEvent::Crashed { alias, error } => t!("{} 失败: {}", alias, error),
+        TunnelEvent::HealthChanged { alias, from, to } => t!("{} 状态 {} → {}", alias, from, to),
+        TunnelEvent::LogLine { alias, line } => format!("[{}] {}", alias, line),
+    };
+    Ok(format!("{} {}", history::format_time(time), text))
+}
+
+/// `tfa top` 中的一个进程：连接的后端进程或统计代理
+struct TopProcess {
+    alias: String,
+    process: &'static str,
+    pid: u32,
+    started_at: u64,
+}
+
+/// 正在运行的连接进程；每轮重新读取配置，以便看到其他终端中的增删改
+fn top_processes() -> Result<Vec<TopProcess>> {
+    let config = Config::load()?;
+    let mut processes = Vec::new();
+    for tunnel in config.list_tunnels()? {
+        if let Some(state) = RuntimeState::load(&tunnel.alias).filter(RuntimeState::is_alive) {
+            processes.push(TopProcess { alias: tunnel.alias.clone(), process: tunnel.provider.name(), pid: state.pid, started_at: state.started_at });
+        }
+        if let Some(stats) = ProxyStats::load(&tunnel.alias)
+            && platform::process_start_time(stats.pid).is_some()
+        {
+            processes.push(TopProcess { alias: tunnel.alias.clone(), process: "proxy", pid: stats.pid, started_at: stats.started_at });
+        }
+    }
+    Ok(processes)
+}
+
+/// CPU 占用率由两次采样间的累计 CPU 时间之差求得，因此第一次采样后等待一秒再输出
+async fn top(interval: u64, once: bool) -> Result<()> {
+    let term = Term::stdout();
+    let mut previous: HashMap<u32, (Instant, f64)> = HashMap::new();
+    let mut first = true;
+    loop {
+        let processes = top_processes()?;
+        let pids: Vec<u32> = processes.iter().map(|p| p.pid).collect();
+        let usage = platform::process_usage(&pids);
+        let now = Instant::now();
+        let mut rows: Vec<(&TopProcess, Option<f64>, u64)> = processes.iter()
+            .filter_map(|process| {
+                let sample = usage.get(&process.pid)?;
+                let cpu = previous.get(&process.pid).map(|(at, cpu_seconds)| {
+                    let elapsed = now.duration_since(*at).as_secs_f64().max(f64::EPSILON);
+                    (sample.cpu_seconds - cpu_seconds).max(0.0) / elapsed * 100.0
+                });
+                Some((process, cpu, sample.memory))
+            })
+            .collect();
+        previous = usage.iter().map(|(pid, sample)| (*pid, (now, sample.cpu_seconds))).collect();
+
+        if !first || rows.is_empty() {
+            rows.sort_by(|a, b| b.1.unwrap_or(0.0).total_cmp(&a.1.unwrap_or(0.0)).then(b.2.cmp(&a.2)));
+            if !once {
+                term.clear_screen()?;
+                println!("{}", t!("每 {} 秒刷新，按 Ctrl-C 退出", interval));
+            }
+            if rows.is_empty() {
+                println!("{}", t!("没有正在运行的连接"));
+            } else {
+                println!("{}", top_table(&rows));
+            }
+            if once {
+                return Ok(());
+            }
+        }
+
+        let wait = if first { Duration::from_secs(1) } else { Duration::from_secs(interval.max(1)) };
+        first = false;
+        tokio::select! {
+            _ = tokio::time::sleep(wait) => {}
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        }
+    }
+}
+
+fn top_table(rows: &[(&TopProcess, Option<f64>, u64)]) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(presets::UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(["alias", "process", "pid", "cpu", "memory", "uptime"]);
+    let now = platform::unix_now();
+    for (process, cpu, memory) in rows {
+        let cpu_cell = match cpu {
+            Some(cpu) => {
+                let color = if *cpu >= 80.0 { Color::Red } else if *cpu >= 30.0 { Color::Yellow } else { Color::Reset };
+                Cell::new(format!("{:.1}%", cpu)).fg(color)
+            }
+            None => Cell::new("-"),
+        };
+        table.add_row([
+            Cell::new(&process.alias),
+            Cell::new(process.process),
+            Cell::new(process.pid),
+            cpu_cell,
+            Cell::new(format_bytes(*memory)),
+            Cell::new(format_duration(now.saturating_sub(process.started_at))),
+        ]);
+    }
+    let total_cpu: f64 = rows.iter().filter_map(|(_, cpu, _)| *cpu).sum();
+    let total_memory: u64 = rows.iter().map(|(_, _, memory)| memory).sum();
+    table.add_row([
+        Cell::new("total").add_attribute(Attribute::Bold),
+        Cell::new(t!("{} 个进程", rows.len())),
+        Cell::new(""),
+        Cell::new(format!("{:.1}%", total_cpu)).add_attribute(Attribute::Bold),
+        Cell::new(format_bytes(total_memory)).add_attribute(Attribute::Bold),
+        Cell::new(""),
+    ]);
+    table
+}
+
+/// 在列表下方说明因反复失败而停止重试的连接及最后的错误
+fn print_failed(rows: &[(&Tunnel, TunnelStatus)]) {
+    for (tunnel, _) in rows.iter().filter(|(_, status)| *status == TunnelStatus::Failed) {
+        let failures = Failures::load(&tunnel.alias);
+        println!("{}", t!(
+            "{}: {} 分钟内失败 {} 次，已停止重试（tfa reset {} 解除）: {}",
+            tunnel.alias,
+            tunnel.crash_window,
+            failures.recent(tunnel),
+            tunnel.alias,
+            failures.last_error.as_deref().unwrap_or_default()
+        ));
+    }
+}
+
+/// 渲染连接列表，列宽随终端宽度自动调整；`changed` 中的连接整行反色高亮
+fn tunnel_table(rows: &[(&Tunnel, TunnelStatus)], changed: &HashSet<String>) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(presets::UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(["alias", "source", "target", "status", "provider", "tags", "uptime", "latency"]);
+    for (tunnel, status) in rows {
+        let color = match status {
+            TunnelStatus::Running => Color::Green,
+            TunnelStatus::Degraded => Color::Yellow,
+            TunnelStatus::Failed => Color::Magenta,
+            TunnelStatus::Paused => Color::DarkGrey,
+            TunnelStatus::Stopped => Color::Red,
+        };
+        let mut cells = vec![
+            Cell::new(&tunnel.alias),
+            Cell::new(&tunnel.source),
+            Cell::new(tunnel.port_mappings().join("\n")),
+            Cell::new(status).fg(color),
+            Cell::new(tunnel.provider.label()),
+            Cell::new(tunnel.tags.join(",")),
+            Cell::new(match (tunnel.uptime(), tunnel.remaining_ttl()) {
+                (Some(uptime), Some(remaining)) => t!("{}（{} 后停止）", format_duration(uptime), format_duration(remaining)),
+                (Some(uptime), None) => format_duration(uptime),
+                (None, _) => PauseMark::load(&tunnel.alias)
+                    .filter(|_| *status == TunnelStatus::Paused)
+                    .map(|mark| t!("已暂停 {}", format_duration(platform::unix_now().saturating_sub(mark.paused_at))))
+                    .unwrap_or_default(),
+            }),
+            Cell::new(latency_summary(&LatencyHistory::load(&tunnel.alias).samples, LATENCY_SPARKLINE_WIDTH)),
+        ];
+        if !tunnel.enabled {
+            cells = cells.into_iter().map(|cell| cell.fg(Color::DarkGrey)).collect();
+        }
+        if changed.contains(&tunnel.alias) {
+            cells = cells.into_iter().map(|cell| cell.add_attribute(Attribute::Reverse)).collect();
+        }
+        table.add_row(cells);
+    }
+    table
+}
+
+/// 最近 `width` 次的延迟走势与最后一次的结果，如 `▁▂▁▇× 12.3ms`；没有记录时为空
+fn latency_summary(samples: &[LatencySample], width: usize) -> String {
+    let recent = &samples[samples.len().saturating_sub(width)..];
+    let Some(last) = recent.last() else {
+        return String::new();
+    };
+    let last = match last.ms {
+        Some(ms) => format!("{:.1}ms", ms),
+        None => t!("失败"),
+    };
+    format!("{} {}", sparkline(recent), last)
+}
+
+/// 按窗口内的最小与最大延迟缩放为八级方块字符，失败的探测显示为 ×
+fn sparkline(samples: &[LatencySample]) -> String {
+    const LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let values: Vec<f64> = samples.iter().filter_map(|sample| sample.ms).collect();
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(0.0, f64::max);
+    samples.iter()
+        .map(|sample| match sample.ms {
+            Some(ms) if max > min => LEVELS[(((ms - min) / (max - min)) * 7.0).round() as usize],
+            Some(_) => LEVELS[0],
+            None => '×',
+        })
+        .collect()
+}
+
+fn duration_ms(duration: &Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn alert_table(rules: &[AlertRule]) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(presets::UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(["rule", "tunnels", "when", "notify", "cooldown"]);
+    for rule in rules {
+        table.add_row(vec![
+            Cell::new(&rule.name),
+            Cell::new(if rule.tunnels.is_empty() { "*".to_string() } else { rule.tunnels.join(", ") }),
+            Cell::new(rule.condition),
+            Cell::new(if rule.notify.is_empty() { "*".to_string() } else { rule.notify.join(", ") }),
+            Cell::new(format!("{}m", rule.cooldown)),
+        ]);
+    }
+    table
+}
+
+fn channel_table(channels: &BTreeMap<String, Channel>) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(presets::UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(["channel", "type", "target"]);
+    for (name, channel) in channels {
+        table.add_row(vec![Cell::new(name), Cell::new(channel.kind()), Cell::new(channel.target())]);
+    }
+    table
+}
+
+fn template_table(templates: &BTreeMap<String, Template>) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(presets::UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(["name", "port", "probe", "client"]);
+    for (name, template) in templates {
+        table.add_row(vec![
+            Cell::new(name),
+            Cell::new(template.port.map(|port| port.to_string()).unwrap_or_default()),
+            Cell::new(template.probe.as_ref().map(ToString::to_string).unwrap_or_default()),
+            Cell::new(template.client.as_deref().unwrap_or_default()),
+        ]);
+    }
+    table
+}
+
+fn adopt_table(results: &[(adopt::AccessProcess, Outcome)], dry_run: bool) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(presets::UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(["pid", "hostname", "port", "alias", "result", "reason"]);
+    for (process, outcome) in results {
+        let (alias, result, color, reason) = match outcome {
+            Outcome::Adoptable(alias) if dry_run => (alias.as_str(), "adoptable", Color::Green, String::new()),
+            Outcome::Adoptable(alias) => (alias.as_str(), "adopted", Color::Green, String::new()),
+            Outcome::Managed(alias) => (alias.as_str(), "managed", Color::Reset, String::new()),
+            Outcome::Skipped(alias, reason) => (alias.as_str(), "skipped", Color::Yellow, reason.clone()),
+            Outcome::Unmatched => ("", "unmatched", Color::Yellow, t!("没有主机名一致的连接")),
+        };
+        table.add_row(vec![
+            Cell::new(process.pid),
+            Cell::new(&process.hostname),
+            Cell::new(process.port),
+            Cell::new(alias),
+            Cell::new(result).fg(color),
+            Cell::new(reason),
+        ]);
+    }
+    table
+}
+
+fn profile_command(action: &ProfileAction) -> Result<()> {
+    match action {
+        ProfileAction::List => {
+            for name in profile::list()? {
+                let marker = if name == profile::current() { "*" } else { " " };
+                println!("{} {}", marker, name);
+            }
+        }
+        ProfileAction::Create { name } => {
+            profile::create(name)?;
+            println!("{}", t!("已创建档案 {}，可使用 'tfa profile switch {}' 切换", name, name));
+        }
+        ProfileAction::Switch { name } => {
+            profile::switch(name)?;
+            println!("{}", t!("已切换到档案 {}", name));
+            if let Ok(value) = std::env::var(profile::PROFILE_ENV)
+                && !value.is_empty()
+                && value != *name
+            {
+                println!("{}", t!("提示: 当前设置了 {}={}，它优先于这里的选择", profile::PROFILE_ENV, value));
+            }
+        }
+        ProfileAction::Copy { from, to } => {
+            profile::copy(from, to)?;
+            println!("{}", t!("已将档案 {} 复制为 {}", from, to));
+        }
+    }
+    Ok(())
+}
+
+fn import_table(changes: &[(String, ImportChange)]) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(presets::UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(["alias", "result", "reason"]);
+    for (alias, change) in changes {
+        let (result, color, reason) = match change {
+            ImportChange::Added => ("added", Color::Green, String::new()),
+            ImportChange::Updated => ("updated", Color::Green, String::new()),
+            ImportChange::Unchanged => ("unchanged", Color::Reset, String::new()),
+            ImportChange::Removed => ("removed", Color::Yellow, String::new()),
+            ImportChange::Skipped(reason) => ("skipped", Color::Yellow, reason.clone()),
+        };
+        table.add_row(vec![Cell::new(alias), Cell::new(result).fg(color), Cell::new(reason)]);
+    }
+    table
+}
+
+/// 输出启动连接时使用的 cloudflared 及其版本；与档案固定的版本不一致时返回错误
+fn check_cloudflared_version() -> Result<()> {
+    let (program, version) = cloudflared::current();
+    let Some(pin) = cloudflared::pinned() else {
+        match version {
+            Some(version) => println!("{}", t!("cloudflared {}（{}）", version, program.display())),
+            None => println!("{}", t!("未找到 cloudflared")),
+        }
+        return Ok(());
+    };
+    match version {
+        Some(version) if version == pin.version => {
+            println!("{}", t!("cloudflared {}（固定版本，{}）", version, program.display()));
+            Ok(())
+        }
+        version => {
+            let version = version.unwrap_or_else(|| t!("未安装"));
+            println!("{}", t!("cloudflared 版本 {} 与固定的版本 {} 不一致", version, pin.version));
+            Err(anyhow::anyhow!(t!("cloudflared 版本不一致，请运行 'tfa upgrade-cloudflared' 安装 {}", pin.version)))
+        }
+    }
+}
+
+/// 连通性检查结果；未设置代理时显示 env，表示沿用环境变量
+fn doctor_table(results: &[(&Tunnel, Option<OutboundProxy>, Result<u16>)]) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(presets::UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(["alias", "host", "proxy", "result", "detail"]);
+    for (tunnel, proxy, result) in results {
+        let proxy = proxy.as_ref().map_or_else(|| "env".to_string(), ToString::to_string);
+        let (status, color, detail) = match result {
+            Ok(code) => ("ok", Color::Green, format!("HTTP {}", code)),
+            Err(e) => ("failed", Color::Red, e.to_string()),
+        };
+        table.add_row(vec![
+            Cell::new(&tunnel.alias),
+            Cell::new(&tunnel.source),
+            Cell::new(proxy),
+            Cell::new(status).fg(color),
+            Cell::new(detail),
+        ]);
+    }
+    table
+}
+
+fn sync_table(changes: &[(String, Change)]) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(presets::UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(["alias", "result"]);
+    for (alias, change) in changes {
+        let (result, color) = match change {
+            Change::Pulled => ("pulled", Color::Green),
+            Change::Pushed => ("pushed", Color::Green),
+            Change::Conflict => ("conflict", Color::Red),
+        };
+        table.add_row(vec![Cell::new(alias), Cell::new(result).fg(color)]);
+    }
+    table
+}
+
+fn replay_table(sessions: &[Vec<replay::Step>]) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(presets::UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(["session", "time (UTC)", "steps"]);
+    for (index, steps) in sessions.iter().enumerate() {
+        let summary: Vec<String> = steps.iter()
+            .map(|step| format!("{} {}", step.action, step.aliases.join(",")))
+            .collect();
+        table.add_row(vec![
+            Cell::new(index + 1),
+            Cell::new(steps.first().map(|step| history::format_time(step.time)).unwrap_or_default()),
+            Cell::new(summary.join(" → ")),
+        ]);
+    }
+    table
+}
+
+fn history_table(entries: &[history::Entry]) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(presets::UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(["time (UTC)", "user", "action", "alias", "result", "detail"]);
+    for entry in entries {
+        let (result, color) = match entry.error {
+            None => ("ok", Color::Green),
+            Some(_) => ("failed", Color::Red),
+        };
+        let detail = match (&entry.detail, &entry.error) {
+            (Some(detail), Some(error)) => format!("{}: {}", detail, error),
+            (detail, error) => error.clone().or_else(|| detail.clone()).unwrap_or_default(),
+        };
+        table.add_row(vec![
+            Cell::new(history::format_time(entry.time)),
+            Cell::new(&entry.user),
+            Cell::new(entry.action),
+            Cell::new(&entry.alias),
+            Cell::new(result).fg(color),
+            Cell::new(detail),
+        ]);
+    }
+    table
+}
+
+/// 访问记录，正在进行的连接排在最后
+fn audit_table<'a>(entries: &[audit::Access], active: impl Iterator<Item = &'a proxy::ConnectionInfo>) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(presets::UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(["opened (UTC)", "client", "duration", "in", "out"]);
+    for entry in entries {
+        table.add_row(vec![
+            Cell::new(history::format_time(entry.opened_at)),
+            Cell::new(&entry.peer),
+            Cell::new(format_duration(entry.duration())),
+            Cell::new(format_bytes(entry.bytes_in)),
+            Cell::new(format_bytes(entry.bytes_out)),
+        ]);
+    }
+    let now = platform::unix_now();
+    for conn in active {
+        table.add_row(vec![
+            Cell::new(history::format_time(conn.opened_at)),
+            Cell::new(&conn.peer),
+            Cell::new(t!("{}（进行中）", format_duration(now.saturating_sub(conn.opened_at)))).fg(Color::Green),
+            Cell::new(format_bytes(conn.bytes_in)),
+            Cell::new(format_bytes(conn.bytes_out)),
+        ]);
+    }
+    table
+}
+
+fn validate_config(file: Option<&Path>, strict: bool) -> Result<()> {
+    let (files, issues) = validate::run(file)?;
+    if files.is_empty() {
+        println!("{}", t!("没有找到配置文件"));
+        return Ok(());
+    }
+    for issue in &issues {
+        println!("{}", issue);
+    }
+    let errors = issues.iter().filter(|issue| issue.severity == Severity::Error).count();
+    let warnings = issues.len() - errors;
+    if errors > 0 || (strict && warnings > 0) {
+        return Err(TunnelError::config(t!("校验未通过：{} 个错误，{} 个警告", errors, warnings)));
+    }
+    for file in &files {
+        println!("{}", t!("{} 有效", file.display()));
+    }
+    if warnings > 0 {
+        println!("{}", t!("{} 个警告", warnings));
+    }
+    Ok(())
+}
+
+fn discover_table(processes: &[adopt::AccessProcess]) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(presets::UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(["pid", "hostname", "port"]);
+    for process in processes {
+        table.add_row(vec![
+            Cell::new(process.pid),
+            Cell::new(&process.hostname),
+            Cell::new(process.port),
+        ]);
+    }
+    table
+}
+
+fn prune_table(orphans: &[prune::Orphan]) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(presets::UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(["alias", "type", "detail", "reason"]);
+    for orphan in orphans {
+        let (kind, detail, reason) = orphan.describe();
+        table.add_row(vec![
+            Cell::new(orphan.alias()),
+            Cell::new(kind).fg(if orphan.is_process() { Color::Red } else { Color::Yellow }),
+            Cell::new(detail),
+            Cell::new(reason),
+        ]);
+    }
+    table
+}
+
+/// 批量添加前检查清单：别名重复或已存在为冲突，本地端口重复只给出警告
+fn apply_table(changes: &[(String, ApplyChange)]) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(presets::UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(["alias", "action", "detail"]);
+    for (alias, change) in changes {
+        let (color, detail) = match change {
+            ApplyChange::Added => (Color::Green, String::new()),
+            ApplyChange::Updated(fields) => (Color::Yellow, fields.join(", ")),
+            ApplyChange::Unchanged => (Color::Reset, String::new()),
+            ApplyChange::Removed => (Color::Red, String::new()),
+            ApplyChange::Skipped(reason) => (Color::Reset, reason.clone()),
+        };
+        table.add_row(vec![Cell::new(alias), Cell::new(change.label()).fg(color), Cell::new(detail)]);
+    }
+    table
+}
+
+fn manifest_conflicts(config: &Config, tunnels: &[Tunnel]) -> (Vec<String>, Vec<String>) {
+    let mut conflicts = Vec::new();
+    let mut warnings = Vec::new();
+    let mut aliases: HashSet<&str> = HashSet::new();
+    let mut ports: HashMap<u16, &str> = config.list_tunnels()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|t| (t.port, t.alias.as_str()))
+        .collect();
+    for tunnel in tunnels {
+        if !aliases.insert(&tunnel.alias) {
+            conflicts.push(t!("别名 {} 在清单中重复", tunnel.alias));
+        } else if config.get_tunnel(&tunnel.alias).is_ok() {
+            conflicts.push(t!("连接 {} 已存在", tunnel.alias));
+        }
+        if let Some(other) = ports.insert(tunnel.port, &tunnel.alias)
+            && other != tunnel.alias
+        {
+            warnings.push(t!("{} 与 {} 使用相同的本地端口 {}，两者不能同时运行", tunnel.alias, other, tunnel.port));
+        }
+    }
+    (conflicts, warnings)
+}
+
+/// 连接的环境变量前缀：别名转为大写，非字母数字字符替换为下划线
+fn env_prefix(alias: &str) -> String {
+    alias.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect()
+}
+
+/// 连接的本地地址，如 MY_DB_HOST=localhost、MY_DB_PORT=5432；绑定了具体地址时 HOST 为该地址
+fn env_vars(tunnel: &Tunnel) -> [(String, String); 2] {
+    let prefix = env_prefix(&tunnel.alias);
+    [
+        (format!("{}_HOST", prefix), tunnel.local_host()),
+        (format!("{}_PORT", prefix), tunnel.port.to_string()),
+    ]
+}
+
+fn format_env(tunnels: &[&Tunnel], format: EnvFormat) -> Result<String> {
+    let vars: Vec<(String, String)> = tunnels.iter()
+        .flat_map(|t| env_vars(t))
+        .collect();
+    Ok(match format {
+        EnvFormat::Dotenv => vars.iter().map(|(k, v)| format!("{}={}\n", k, v)).collect(),
+        EnvFormat::Export => vars.iter().map(|(k, v)| format!("export {}={}\n", k, v)).collect(),
+        EnvFormat::Json => {
+            let map: serde_json::Map<String, serde_json::Value> = vars.into_iter()
+                .map(|(k, v)| (k, serde_json::Value::String(v)))
+                .collect();
+            format!("{}\n", serde_json::to_string_pretty(&map)?)
+        }
+    })
+}
+
+fn normalize_list(tags: &[String]) -> Vec<String> {
+    tags.iter()
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect()
+}
+
+/// `tfa token` 的输出：主机、身份、受众与有效期
+fn describe_token(hostname: &str, claims: &jwt::Claims, now: u64) -> String {
+    let mut text = String::new();
+    text.push_str(&t!("主机: {}\n", hostname));
+    if let Some(identity) = claims.identity() {
+        text.push_str(&t!("身份: {}\n", identity));
+    }
+    if !claims.aud.is_empty() {
+        text.push_str(&t!("受众: {}\n", claims.aud.join(", ")));
+    }
+    if let Some(iss) = &claims.iss {
+        text.push_str(&t!("签发者: {}\n", iss));
+    }
+    if let Some(iat) = claims.iat {
+        text.push_str(&t!("签发时间: {} UTC\n", history::format_time(iat)));
+    }
+    match (claims.exp, claims.remaining(now)) {
+        (Some(exp), Some(remaining)) if remaining > 0 => {
+            text.push_str(&t!("过期时间: {} UTC（剩余 {}）\n", history::format_time(exp), format_duration(remaining as u64)));
+        }
+        (Some(exp), _) => text.push_str(&t!("过期时间: {} UTC（已过期）\n", history::format_time(exp))),
+        _ => text.push_str(&t!("过期时间: 未知\n")),
+    }
+    text
+}
+
+fn apikey_table(keys: &BTreeMap<String, ApiKey>) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(presets::UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(["name", "scope", "key", "created"]);
+    for (name, key) in keys {
+        table.add_row(vec![
+            Cell::new(name),
+            Cell::new(key.scope),
+            Cell::new(format!("{}…", key.hint)),
+            Cell::new(history::format_time(key.created_at)),
+        ]);
+    }
+    table
+}
+
+fn backup_table(backups: &[backup::Backup]) -> Table {
+    let mut table = Table::new();
+    table
+        .load_preset(presets::UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(["timestamp", "time (UTC)", "tunnels", "size"]);
+    for backup in backups {
+        table.add_row(vec![
+            Cell::new(&backup.stamp),
+            Cell::new(backup.time()),
+            Cell::new(backup.tunnel_count().map(|count| count.to_string()).unwrap_or_else(|| "?".to_string())),
+            Cell::new(format_bytes(backup.size)),
+        ]);
+    }
+    table
+}
+
+fn format_duration(secs: u64) -> String {
+    let (days, hours, minutes) = (secs / 86400, secs % 86400 / 3600, secs % 3600 / 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m {}s", minutes, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
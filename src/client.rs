@@ -0,0 +1,195 @@
+//! `tfa connect`：按连接的数据库类型启动对应的客户端（psql、mysql、redis-cli、mongosh），并指向连接的本地地址。
+//!
+//! 连接串模板（`connect_url`）中可以写用户名与库名，`{host}`、`{port}` 替换为连接的本地地址，
+//! 如 `postgresql://app@{host}:{port}/orders`。密码不写在模板中，而是依次取自
+//! [`PASSWORD_ENV`] 环境变量和系统钥匙串，并尽量通过客户端自己的环境变量传入，不出现在命令行参数中。
+
+use anyhow::Result;
+use clap::ValueEnum;
+use std::fmt;
+
+use crate::error::TunnelError;
+use crate::i18n::t;
+use crate::probe::Probe;
+use crate::secrets::ClientPassword;
+use crate::tunnel::Tunnel;
+
+/// 优先于钥匙串使用的数据库密码
+pub const PASSWORD_ENV: &str = "CF_MANAGER_DB_PASSWORD";
+
+/// 数据库类型：postgres、mysql、redis、mongo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Kind {
+    Postgres,
+    Mysql,
+    Redis,
+    Mongo,
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Kind::Postgres => "postgres",
+            Kind::Mysql => "mysql",
+            Kind::Redis => "redis",
+            Kind::Mongo => "mongo",
+        })
+    }
+}
+
+impl Kind {
+    /// 由连接串的 scheme 判断
+    fn from_scheme(scheme: &str) -> Option<Self> {
+        match scheme {
+            "postgres" | "postgresql" => Some(Kind::Postgres),
+            "mysql" | "mariadb" => Some(Kind::Mysql),
+            "redis" | "rediss" => Some(Kind::Redis),
+            "mongodb" => Some(Kind::Mongo),
+            _ => None,
+        }
+    }
+
+    /// 由客户端程序名判断，如连接的 client 命令 `psql -h ...`
+    fn from_program(program: &str) -> Option<Self> {
+        let name = program.rsplit(['/', '\\']).next().unwrap_or(program).trim_end_matches(".exe");
+        match name {
+            "psql" | "pgcli" => Some(Kind::Postgres),
+            "mysql" | "mariadb" | "mycli" => Some(Kind::Mysql),
+            "redis-cli" | "valkey-cli" => Some(Kind::Redis),
+            "mongosh" | "mongo" => Some(Kind::Mongo),
+            _ => None,
+        }
+    }
+
+    /// 由端口判断：数据库的默认端口，以及内置模板使用的本地端口
+    fn from_port(port: u16) -> Option<Self> {
+        match port {
+            5432 | 15432 => Some(Kind::Postgres),
+            3306 | 13306 => Some(Kind::Mysql),
+            6379 | 16379 => Some(Kind::Redis),
+            27017 | 27018 => Some(Kind::Mongo),
+            _ => None,
+        }
+    }
+
+    fn default_url(self) -> &'static str {
+        match self {
+            Kind::Postgres => "postgresql://{host}:{port}",
+            Kind::Mysql => "mysql://{host}:{port}",
+            Kind::Redis => "redis://{host}:{port}",
+            Kind::Mongo => "mongodb://{host}:{port}",
+        }
+    }
+
+    fn program(self) -> &'static str {
+        match self {
+            Kind::Postgres => "psql",
+            Kind::Mysql => "mysql",
+            Kind::Redis => "redis-cli",
+            Kind::Mongo => "mongosh",
+        }
+    }
+}
+
+/// 连接串的 scheme，如 postgresql
+fn scheme(url: &str) -> Option<&str> {
+    url.split_once("://").map(|(scheme, _)| scheme)
+}
+
+/// 校验连接串模板，返回其数据库类型
+pub fn parse_url(url: &str) -> Result<Kind> {
+    scheme(url)
+        .and_then(Kind::from_scheme)
+        .ok_or_else(|| TunnelError::config(t!(
+            "无法识别连接串 {}，应以 postgresql://、mysql://、redis:// 或 mongodb:// 开头",
+            url
+        )))
+}
+
+/// 判断连接的数据库类型：连接串、探测方式、客户端命令，最后是端口
+pub fn detect(tunnel: &Tunnel) -> Option<Kind> {
+    if let Some(kind) = tunnel.connect_url.as_deref().and_then(scheme).and_then(Kind::from_scheme) {
+        return Some(kind);
+    }
+    if tunnel.probe == Some(Probe::Postgres) {
+        return Some(Kind::Postgres);
+    }
+    if let Some(kind) = tunnel.client.as_deref()
+        .and_then(|client| client.split_whitespace().next())
+        .and_then(Kind::from_program)
+    {
+        return Some(kind);
+    }
+    tunnel.remote_port().and_then(Kind::from_port).or_else(|| Kind::from_port(tunnel.port))
+}
+
+/// 要执行的客户端命令：程序、参数与环境变量
+pub struct Launch {
+    pub program: String,
+    pub args: Vec<String>,
+    pub env: Vec<(String, String)>,
+}
+
+/// 组装客户端命令；`extra` 追加在最后
+pub fn launch(tunnel: &Tunnel, kind: Kind, password: Option<String>, extra: &[String]) -> Result<Launch> {
+    let template = match &tunnel.connect_url {
+        Some(url) if parse_url(url)? == kind => url.as_str(),
+        _ => kind.default_url(),
+    };
+    let url = template
+        .replace("{host}", &tunnel.local_host())
+        .replace("{port}", &tunnel.port.to_string());
+    let mut args = Vec::new();
+    let mut env = Vec::new();
+    match kind {
+        Kind::Postgres => {
+            args.push(url);
+            env.extend(password.map(|password| ("PGPASSWORD".to_string(), password)));
+        }
+        Kind::Mysql => {
+            // mysql 客户端不接受连接串，拆成参数；localhost 会被当作本地套接字，需要显式使用 TCP
+            let rest = url.split_once("://").map_or(url.as_str(), |(_, rest)| rest);
+            let (authority, database) = rest.split_once('/').unwrap_or((rest, ""));
+            let (user, address) = match authority.rsplit_once('@') {
+                Some((user, address)) => (Some(user), address),
+                None => (None, authority),
+            };
+            let (host, port) = address.rsplit_once(':').unwrap_or((address, "3306"));
+            args.extend(["--protocol=TCP", "-h", host, "-P", port].map(str::to_string));
+            if let Some(user) = user {
+                args.extend(["-u".to_string(), user.to_string()]);
+            }
+            let database = database.split('?').next().unwrap_or_default();
+            if !database.is_empty() {
+                args.push(database.to_string());
+            }
+            env.extend(password.map(|password| ("MYSQL_PWD".to_string(), password)));
+        }
+        Kind::Redis => {
+            args.extend(["-u".to_string(), url]);
+            env.extend(password.map(|password| ("REDISCLI_AUTH".to_string(), password)));
+        }
+        Kind::Mongo => {
+            args.push(url);
+            // mongosh 没有读取密码的环境变量，只能作为参数传入
+            if let Some(password) = password {
+                args.extend(["--password".to_string(), password]);
+            }
+        }
+    }
+    args.extend(extra.iter().cloned());
+    Ok(Launch { program: kind.program().to_string(), args, env })
+}
+
+/// 数据库密码：环境变量优先，其次是钥匙串中为该连接保存的密码
+pub fn password(tunnel: &Tunnel) -> Result<Option<String>> {
+    if let Ok(password) = std::env::var(PASSWORD_ENV)
+        && !password.is_empty()
+    {
+        return Ok(Some(password));
+    }
+    if !tunnel.client_password {
+        return Ok(None);
+    }
+    ClientPassword::load(&tunnel.alias)
+}
@@ -0,0 +1,57 @@
+//! 复制到系统剪贴板，`tfa run --copy` 启动连接后复制使用方法或本地地址。
+//!
+//! macOS 与 Windows 由系统保存剪贴板的内容，直接写入即可。X11 与 Wayland 上内容由写入它的进程提供，
+//! 进程退出后随之消失，因此交给后台的 `tfa clipboard-serve` 持有，直到其他程序写入新的内容。
+
+use anyhow::Result;
+use arboard::Clipboard;
+
+use crate::i18n::t;
+
+/// 把 `text` 写入系统剪贴板
+pub fn copy(text: &str) -> Result<()> {
+    // 先在本进程中打开剪贴板，没有图形界面时直接报错，而不是让后台进程悄悄失败
+    let clipboard = Clipboard::new().map_err(|e| anyhow::anyhow!(t!("无法访问剪贴板: {}", e)))?;
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        use std::io::Write;
+        use std::process::{Command, Stdio};
+
+        drop(clipboard);
+        let mut child = crate::platform::detach(
+            Command::new(std::env::current_exe()?)
+                .arg("clipboard-serve")
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null()),
+        )
+        .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+    }
+    #[cfg(not(all(unix, not(target_os = "macos"))))]
+    {
+        let mut clipboard = clipboard;
+        clipboard.set_text(text).map_err(|e| anyhow::anyhow!(t!("无法写入剪贴板: {}", e)))?;
+    }
+    Ok(())
+}
+
+/// 从标准输入读取内容写入剪贴板，并持有它直到其他程序写入新的内容
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn serve() -> Result<()> {
+    use arboard::SetExtLinux;
+    use std::io::Read;
+
+    let mut text = String::new();
+    std::io::stdin().read_to_string(&mut text)?;
+    Clipboard::new()?.set().wait().text(text)?;
+    Ok(())
+}
+
+/// 其他平台的剪贴板内容由系统保存，不需要持有
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+pub fn serve() -> Result<()> {
+    Ok(())
+}
@@ -0,0 +1,167 @@
+//! 固定 cloudflared 版本：档案可以指定团队统一使用的 cloudflared 版本，
+//! 由本工具从 GitHub Releases 下载到 `<配置根目录>/tools/cloudflared/<版本>/`，启动连接时优先使用它。
+//!
+//! 下载的文件仍名为 cloudflared，按进程名识别端口上的后端进程时与系统安装的版本一致。
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::RwLock;
+
+use crate::error::TunnelError;
+use crate::i18n::t;
+use crate::outbound;
+use crate::profile;
+use tracing::debug;
+
+const RELEASES_URL: &str = "https://github.com/cloudflare/cloudflared/releases/download";
+
+/// 下载超时秒数
+const DOWNLOAD_TIMEOUT: u64 = 300;
+
+/// 当前档案固定的版本，加载配置时记录，供查找可执行文件时使用
+static PINNED: RwLock<Option<Pin>> = RwLock::new(None);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Pin {
+    /// 发布版本号，如 2024.8.2
+    pub version: String,
+    /// 下载文件的 SHA-256，设置时安装前校验
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
+impl Pin {
+    /// 校验版本号：cloudflared 的版本为 年.月.序号
+    pub fn new(version: &str, sha256: Option<String>) -> Result<Self> {
+        let version = version.trim().trim_start_matches('v');
+        let parts: Vec<&str> = version.split('.').collect();
+        if parts.len() != 3 || parts.iter().any(|part| part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit())) {
+            return Err(TunnelError::config(t!("无效的 cloudflared 版本 {}，应为 2024.8.2 这样的格式", version)));
+        }
+        if let Some(sha256) = &sha256
+            && (sha256.len() != 64 || !sha256.bytes().all(|b| b.is_ascii_hexdigit()))
+        {
+            return Err(TunnelError::config(t!("无效的 SHA-256 {}，应为 64 位十六进制", sha256)));
+        }
+        Ok(Self { version: version.to_string(), sha256: sha256.map(|sha| sha.to_ascii_lowercase()) })
+    }
+}
+
+/// 记录当前档案固定的版本
+pub fn set_pinned(pin: Option<Pin>) {
+    if let Ok(mut pinned) = PINNED.write() {
+        *pinned = pin;
+    }
+}
+
+pub fn pinned() -> Option<Pin> {
+    PINNED.read().ok().and_then(|pinned| pinned.clone())
+}
+
+/// 固定版本的安装位置
+pub fn managed_path(version: &str) -> Result<PathBuf> {
+    let name = if cfg!(windows) { "cloudflared.exe" } else { "cloudflared" };
+    Ok(profile::root_dir()?.join("tools").join("cloudflared").join(version).join(name))
+}
+
+/// 已安装的固定版本；未固定或尚未安装时为 None
+pub fn managed_program() -> Option<PathBuf> {
+    let pin = pinned()?;
+    managed_path(&pin.version).ok().filter(|path| path.is_file())
+}
+
+/// 启动连接时使用的 cloudflared 及其版本；找不到或无法执行时版本为 None
+pub fn current() -> (PathBuf, Option<String>) {
+    let program = managed_program().unwrap_or_else(|| PathBuf::from(if cfg!(windows) { "cloudflared.exe" } else { "cloudflared" }));
+    let version = version_of(&program);
+    (program, version)
+}
+
+/// 可执行文件报告的版本，如 `cloudflared version 2024.8.2 (built ...)` 中的 2024.8.2
+pub fn version_of(program: &Path) -> Option<String> {
+    let output = Command::new(program).arg("--version").output().ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let mut words = text.split_whitespace();
+    words.find(|word| *word == "version")?;
+    words.next().map(str::to_string)
+}
+
+/// 当前平台对应的发布文件名；macOS 只提供 tgz 压缩包
+fn asset_name() -> Result<&'static str> {
+    let asset = match (std::env::consts::OS, std::env::consts::ARCH) {
+        ("linux", "x86_64") => "cloudflared-linux-amd64",
+        ("linux", "aarch64") => "cloudflared-linux-arm64",
+        ("linux", "arm") => "cloudflared-linux-arm",
+        ("linux", "x86") => "cloudflared-linux-386",
+        ("macos", "x86_64") => "cloudflared-darwin-amd64.tgz",
+        ("macos", "aarch64") => "cloudflared-darwin-arm64.tgz",
+        ("windows", "x86_64") => "cloudflared-windows-amd64.exe",
+        ("windows", "x86") => "cloudflared-windows-386.exe",
+        (os, arch) => return Err(anyhow::anyhow!(t!("cloudflared 没有提供 {}/{} 平台的发布文件", os, arch))),
+    };
+    Ok(asset)
+}
+
+/// 从 GitHub Releases 下载并安装固定的版本，已安装时直接返回安装位置。
+/// 下载经全局出站代理；安装后确认可执行文件报告的版本与固定的版本一致
+pub fn install(pin: &Pin) -> Result<PathBuf> {
+    let path = managed_path(&pin.version)?;
+    if path.is_file() && version_of(&path).as_deref() == Some(pin.version.as_str()) {
+        return Ok(path);
+    }
+    let asset = asset_name()?;
+    let url = format!("{}/{}/{}", RELEASES_URL, pin.version, asset);
+    let dir = path.parent().ok_or_else(|| anyhow::anyhow!(t!("无法获取配置目录")))?;
+    fs::create_dir_all(dir)?;
+
+    debug!(url, "下载 cloudflared");
+    let mut command = Command::new("curl");
+    command.args(["-fsSL", "--max-time", &DOWNLOAD_TIMEOUT.to_string()]);
+    if let Some(proxy) = outbound::effective(None) {
+        proxy.apply(&mut command);
+    }
+    let output = command.arg(&url).output()
+        .map_err(|e| anyhow::anyhow!(t!("无法执行 curl: {}", e)))?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(t!("下载 {} 失败: {}", url, String::from_utf8_lossy(&output.stderr).trim())));
+    }
+    if let Some(expected) = &pin.sha256 {
+        let actual: String = Sha256::digest(&output.stdout).iter().map(|byte| format!("{:02x}", byte)).collect();
+        if actual != *expected {
+            return Err(TunnelError::config(t!("{} 的 SHA-256 不匹配：期望 {}，实际 {}", asset, expected, actual)));
+        }
+    }
+
+    if asset.ends_with(".tgz") {
+        let archive = dir.join(asset);
+        fs::write(&archive, &output.stdout)?;
+        let status = Command::new("tar").arg("-xzf").arg(&archive).arg("-C").arg(dir).status();
+        let _ = fs::remove_file(&archive);
+        if !status.is_ok_and(|status| status.success()) {
+            return Err(anyhow::anyhow!(t!("解压 {} 失败", asset)));
+        }
+    } else {
+        fs::write(&path, &output.stdout)?;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+    }
+
+    match version_of(&path) {
+        Some(version) if version == pin.version => Ok(path),
+        reported => {
+            let _ = fs::remove_dir_all(dir);
+            Err(anyhow::anyhow!(t!(
+                "安装的 cloudflared 报告的版本为 {}，与固定的版本 {} 不一致",
+                reported.unwrap_or_else(|| "?".to_string()),
+                pin.version
+            )))
+        }
+    }
+}
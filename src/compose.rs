@@ -0,0 +1,40 @@
+use anyhow::Result;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::TunnelError;
+use crate::i18n::t;
+
+/// docker compose 默认查找的文件名，按优先级排列
+const COMPOSE_FILES: &[&str] = &["compose.yaml", "compose.yml", "docker-compose.yaml", "docker-compose.yml"];
+
+/// compose 文件中只关心 `x-cf-tunnels` 扩展字段：需要的连接别名列表
+#[derive(Deserialize)]
+struct ComposeFile {
+    #[serde(default, rename = "x-cf-tunnels")]
+    tunnels: Vec<String>,
+}
+
+/// 使用指定的 compose 文件，未指定时在当前目录按 docker compose 的规则查找
+pub fn find_file(explicit: Option<&Path>) -> Result<PathBuf> {
+    if let Some(path) = explicit {
+        return Ok(path.to_path_buf());
+    }
+    COMPOSE_FILES.iter()
+        .map(PathBuf::from)
+        .find(|path| path.is_file())
+        .ok_or_else(|| TunnelError::config(t!("当前目录中没有找到 compose 文件（{}）", COMPOSE_FILES.join(&t!("、")))))
+}
+
+/// 读取 compose 文件中 `x-cf-tunnels` 声明的连接别名
+pub fn declared_tunnels(path: &Path) -> Result<Vec<String>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| TunnelError::config(t!("无法读取 {}: {}", path.display(), e)))?;
+    let file: ComposeFile = serde_yaml::from_str(&content)
+        .map_err(|e| TunnelError::config(t!("解析 {} 失败: {}", path.display(), e)))?;
+    if file.tunnels.is_empty() {
+        return Err(TunnelError::config(t!("{} 中没有 x-cf-tunnels 声明", path.display())));
+    }
+    Ok(file.tunnels)
+}
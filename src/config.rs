@@ -1,14 +1,300 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
-use crate::tunnel::Tunnel;
+use crate::alert::AlertRule;
+use crate::apikey::ApiKey;
+use crate::backup;
+use crate::error::TunnelError;
+use crate::chain::{self, Via};
+use crate::cloudflared::{self, Pin};
+use crate::container::Runtime;
+use crate::hooks::Hooks;
+use crate::i18n::{t, Lang};
+use crate::logsink::{self, LogSink};
+use crate::notify::Channel;
+use crate::otel::{self, OtelSettings};
+use crate::outbound::{self, OutboundProxy};
+use crate::platform;
+use crate::privileged::Privileged;
+use crate::probe::Probe;
+use crate::profile;
+use crate::reload::OnChange;
+use crate::provider::{LogLevel, Provider};
+use crate::secrets;
+use crate::sync::SyncSettings;
+use crate::system;
+use crate::template::{self, Template};
+use crate::tunnel::{PortMapping, Tunnel};
+use tracing::{debug, trace, warn};
 
-#[derive(Debug, Serialize, Deserialize)]
+/// 对已有连接的修改，None 表示保持不变
+#[derive(Debug, Default)]
+pub struct TunnelUpdate {
+    pub source: Option<String>,
+    pub port: Option<u16>,
+    /// Some(None) 表示删除服务地址
+    pub service: Option<Option<String>>,
+    pub provider: Option<Provider>,
+    pub proxy: Option<bool>,
+    /// Some(None) 表示取消限速
+    pub max_up: Option<Option<u64>>,
+    pub max_down: Option<Option<u64>>,
+    pub start_timeout: Option<u64>,
+    pub start_retries: Option<u32>,
+    pub retry_backoff: Option<u64>,
+    pub crash_limit: Option<u32>,
+    pub crash_window: Option<u64>,
+    pub tags: Option<Vec<String>>,
+    pub service_token: Option<bool>,
+    pub hooks: Hooks,
+    pub depends_on: Option<Vec<String>>,
+    /// Some(None) 表示不再经过其他连接
+    pub via: Option<Option<Via>>,
+    /// Some(空列表) 表示删除全部额外端口
+    pub extra_ports: Option<Vec<PortMapping>>,
+    pub locked: Option<bool>,
+    pub enabled: Option<bool>,
+    pub autostart: Option<bool>,
+    pub on_change: Option<OnChange>,
+    /// Some(None) 表示恢复为只监听 127.0.0.1
+    pub listen_addr: Option<Option<IpAddr>>,
+    /// Some(None) 表示不再映射主机名
+    pub local_hostname: Option<Option<String>>,
+    pub ssh_host: Option<bool>,
+    /// Some(None) 表示改为使用主机上安装的 cloudflared
+    pub runtime: Option<Option<Runtime>>,
+    /// Some(None) 表示恢复为直接监听本地端口
+    pub privileged: Option<Option<Privileged>>,
+    /// Some(None) 表示恢复为只建立 TCP 连接
+    pub probe: Option<Option<Probe>>,
+    /// Some(None) 表示不再记录延迟
+    pub probe_interval: Option<Option<u64>>,
+    /// Some(None) 表示不再推送可用性
+    pub ping_url: Option<Option<String>>,
+    /// Some(None) 表示删除客户端命令
+    pub client: Option<Option<String>>,
+    /// Some(None) 表示删除连接串模板
+    pub connect_url: Option<Option<String>>,
+    /// Some(None) 表示删除使用说明
+    pub notes: Option<Option<String>>,
+    /// Some(None) 表示删除使用方法模板
+    pub connect_hint: Option<Option<String>>,
+    pub client_password: Option<bool>,
+    /// Some(None) 表示清除连接的代理设置
+    pub outbound_proxy: Option<Option<OutboundProxy>>,
+    /// Some(None) 表示恢复为 cloudflared 的默认级别
+    pub log_level: Option<Option<LogLevel>>,
+    /// Some(空列表) 表示删除全部脱敏规则
+    pub redact: Option<Vec<String>>,
+    /// Some(空列表) 表示改为使用全局转发目标
+    pub log_sinks: Option<Vec<LogSink>>,
+}
+
+impl TunnelUpdate {
+    /// 是否修改了会在团队之间共享的字段；钩子和服务令牌只在本机生效
+    fn touches_shared(&self) -> bool {
+        self.source.is_some()
+            || self.port.is_some()
+            || self.service.is_some()
+            || self.provider.is_some()
+            || self.runtime.is_some()
+            || self.proxy.is_some()
+            || self.max_up.is_some()
+            || self.max_down.is_some()
+            || self.start_timeout.is_some()
+            || self.start_retries.is_some()
+            || self.retry_backoff.is_some()
+            || self.crash_limit.is_some()
+            || self.crash_window.is_some()
+            || self.tags.is_some()
+            || self.depends_on.is_some()
+            || self.via.is_some()
+            || self.extra_ports.is_some()
+            || self.locked.is_some()
+            || self.listen_addr.is_some()
+            || self.local_hostname.is_some()
+            || self.probe.is_some()
+            || self.probe_interval.is_some()
+            || self.client.is_some()
+            || self.connect_url.is_some()
+            || self.notes.is_some()
+            || self.connect_hint.is_some()
+            || self.log_level.is_some()
+            || self.redact.is_some()
+    }
+
+    /// 只是锁定、启用或停用连接，不需要先解锁
+    fn only_locks(&self) -> bool {
+        matches!(
+            self,
+            TunnelUpdate {
+                source: None,
+                port: None,
+                service: None,
+                provider: None,
+                proxy: None,
+                max_up: None,
+                max_down: None,
+                start_timeout: None,
+                start_retries: None,
+                retry_backoff: None,
+                crash_limit: None,
+                crash_window: None,
+                tags: None,
+                service_token: None,
+                hooks,
+                depends_on: None,
+                via: None,
+                extra_ports: None,
+                locked: Some(true) | None,
+                enabled: _,
+                autostart: None,
+                on_change: None,
+                listen_addr: None,
+                local_hostname: None,
+                ssh_host: None,
+                runtime: None,
+                privileged: None,
+                probe: None,
+                probe_interval: None,
+                ping_url: None,
+                client: None,
+                connect_url: None,
+                notes: None,
+                connect_hint: None,
+                client_password: None,
+                outbound_proxy: None,
+                log_level: None,
+                redact: None,
+                log_sinks: None,
+            } if hooks.is_empty()
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    /// 敏感字段是否加密存储
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    encrypted: bool,
+    /// 界面语言，未设置时按 LANG 等环境变量选择
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    lang: Option<Lang>,
+    /// 通过 git 同步连接目录的仓库
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sync: Option<SyncSettings>,
+    /// 未单独设置代理的连接使用的出站代理
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    outbound_proxy: Option<OutboundProxy>,
+    /// 未单独设置转发目标的连接使用的日志转发目标
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    log_sinks: Vec<LogSink>,
+    /// 用户定义的连接模板，与内置模板同名时覆盖内置模板
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    templates: BTreeMap<String, Template>,
+    /// 为本档案保留的本地端口范围，`--port auto` 从中分配
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    port_range: Option<PortRange>,
+    /// 固定使用的 cloudflared 版本
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cloudflared: Option<Pin>,
+    /// 保留的配置备份份数，未设置时为 [`backup::DEFAULT_RETENTION`]，0 表示不备份
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    backup_retention: Option<usize>,
+    /// 控制接口的 API 密钥，以名称为键，只保存摘要
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    api_keys: BTreeMap<String, ApiKey>,
+    /// 告警使用的通知渠道，以名称为键
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    notify: BTreeMap<String, Channel>,
+    /// 告警规则，由 tfa alert run 判断
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    alerts: Vec<AlertRule>,
+    /// 连接操作的 OpenTelemetry 导出设置
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    otel: Option<OtelSettings>,
+    /// tfa run 启动后是否默认把使用方法复制到剪贴板
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    copy_on_run: bool,
     tunnels: HashMap<String, Tunnel>,
+    /// 当前目录所属项目的配置层，不写回全局配置文件
+    #[serde(skip)]
+    project: Option<ProjectLayer>,
+    /// 本次命令是否允许修改或删除锁定的连接（--unlock）
+    #[serde(skip)]
+    unlocked: bool,
+}
+
+/// 保留给连接使用的本地端口范围（含两端），配置中写作 `42000-42999`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct PortRange {
+    pub start: u16,
+    pub end: u16,
+}
+
+impl PortRange {
+    pub fn contains(&self, port: u16) -> bool {
+        (self.start..=self.end).contains(&port)
+    }
+}
+
+impl std::str::FromStr for PortRange {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = || t!("无效的端口范围 {}，应为 42000-42999 这样的格式", value);
+        let (start, end) = value.trim().split_once('-').ok_or_else(invalid)?;
+        let start: u16 = start.trim().parse().map_err(|_| invalid())?;
+        let end: u16 = end.trim().parse().map_err(|_| invalid())?;
+        if start == 0 || start > end {
+            return Err(invalid());
+        }
+        Ok(Self { start, end })
+    }
+}
+
+impl TryFrom<String> for PortRange {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<PortRange> for String {
+    fn from(range: PortRange) -> Self {
+        range.to_string()
+    }
+}
+
+impl std::fmt::Display for PortRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+/// 项目配置文件名，从当前目录开始逐级向上查找
+pub const PROJECT_FILE: &str = ".cf-manager.toml";
+
+/// 合并到全局配置之上的项目配置
+#[derive(Debug, Clone)]
+struct ProjectLayer {
+    path: PathBuf,
+    aliases: Vec<String>,
+    /// 被项目中同名连接覆盖的全局连接，保存时写回
+    shadowed: HashMap<String, Tunnel>,
+}
+
+#[derive(Deserialize)]
+struct ProjectFile {
+    #[serde(default)]
+    tunnels: HashMap<String, toml::Table>,
 }
 
 impl Config {
@@ -16,65 +302,971 @@ impl Config {
         let config_path = Self::config_path()?;
         
         if !config_path.exists() {
-            return Ok(Self {
+            let mut config = Self {
+                encrypted: false,
+                lang: None,
+                sync: None,
+                outbound_proxy: None,
+                log_sinks: Vec::new(),
+                templates: BTreeMap::new(),
+                port_range: None,
+                cloudflared: None,
+                backup_retention: None,
+                api_keys: BTreeMap::new(),
+                notify: BTreeMap::new(),
+                alerts: Vec::new(),
+                otel: None,
+                copy_on_run: false,
                 tunnels: HashMap::new(),
-            });
+                project: None,
+                unlocked: false,
+            };
+            config.load_system()?;
+            config.load_project()?;
+            return Ok(config);
         }
         
-        let content = fs::read_to_string(config_path)?;
-        let config: Config = serde_json::from_str(&content)?;
+        let content = fs::read_to_string(&config_path)?;
+        let mut config: Config = serde_json::from_str(&content)
+            .map_err(|e| TunnelError::config(t!("解析 {} 失败: {}", config_path.display(), e)))?;
+        if config.encrypted {
+            let key = Self::encryption_key()?;
+            for tunnel in config.tunnels.values_mut() {
+                for field in tunnel.sensitive_fields_mut() {
+                    *field = secrets::decrypt(&key, field)?;
+                }
+            }
+        }
+        config.load_system()?;
+        config.load_project()?;
+        outbound::set_global(config.outbound_proxy.clone());
+        logsink::set_global(config.log_sinks.clone());
+        cloudflared::set_pinned(config.cloudflared.clone());
+        otel::set_global(config.otel.clone());
         Ok(config)
     }
     
+    /// 只包含给定连接的配置，用于在不读写文件的情况下检查依赖关系
+    pub fn with_tunnels(tunnels: HashMap<String, Tunnel>) -> Self {
+        Self {
+            encrypted: false,
+            lang: None,
+            sync: None,
+            outbound_proxy: None,
+            log_sinks: Vec::new(),
+            templates: BTreeMap::new(),
+            port_range: None,
+            cloudflared: None,
+            backup_retention: None,
+            api_keys: BTreeMap::new(),
+            notify: BTreeMap::new(),
+            alerts: Vec::new(),
+            otel: None,
+            copy_on_run: false,
+            tunnels,
+            project: None,
+            unlocked: false,
+        }
+    }
+
+    /// 合并系统配置：用户配置中的同名连接优先
+    fn load_system(&mut self) -> Result<()> {
+        for tunnel in system::load()? {
+            if self.tunnels.contains_key(&tunnel.alias) {
+                debug!(alias = %tunnel.alias, "用户配置覆盖系统配置中的同名连接");
+                continue;
+            }
+            self.tunnels.insert(tunnel.alias.clone(), tunnel);
+        }
+        Ok(())
+    }
+
+    /// 查找并合并项目配置：项目中的连接覆盖全局配置中的同名连接
+    fn load_project(&mut self) -> Result<()> {
+        let Some(path) = Self::find_project_file() else {
+            trace!("没有找到项目配置");
+            return Ok(());
+        };
+        debug!(path = %path.display(), "加载项目配置");
+        let content = fs::read_to_string(&path)?;
+        let file: ProjectFile = toml::from_str(&content)
+            .map_err(|e| TunnelError::config(t!("解析 {} 失败: {}", path.display(), e)))?;
+
+        let mut layer = ProjectLayer {
+            path,
+            aliases: Vec::new(),
+            shadowed: HashMap::new(),
+        };
+        for (alias, mut table) in file.tunnels {
+            table.insert("alias".to_string(), toml::Value::String(alias.clone()));
+            let mut tunnel: Tunnel = toml::Value::Table(table).try_into()
+                .map_err(|e| TunnelError::config(t!("{} 中的连接 {} 无效: {}", layer.path.display(), alias, e)))?;
+            tunnel.project = Some(layer.path.clone());
+            if let Some(global) = self.tunnels.insert(alias.clone(), tunnel) {
+                debug!(alias = %alias, "项目配置覆盖全局配置中的同名连接");
+                layer.shadowed.insert(alias.clone(), global);
+            }
+            layer.aliases.push(alias);
+        }
+        layer.aliases.sort();
+        self.project = Some(layer);
+        Ok(())
+    }
+
+    /// 项目配置文件中是否仍定义了该连接；文件不存在或无法解析时为 false
+    pub fn project_defines(path: &Path, alias: &str) -> bool {
+        fs::read_to_string(path).ok()
+            .and_then(|content| toml::from_str::<ProjectFile>(&content).ok())
+            .is_some_and(|file| file.tunnels.contains_key(alias))
+    }
+
+    pub fn find_project_file() -> Option<PathBuf> {
+        let cwd = std::env::current_dir().ok()?;
+        cwd.ancestors()
+            .map(|dir| dir.join(PROJECT_FILE))
+            .find(|path| path.is_file())
+    }
+
+    /// 当前项目配置文件的路径
+    pub fn project_path(&self) -> Option<&Path> {
+        self.project.as_ref().map(|p| p.path.as_path())
+    }
+
+    /// 当前项目声明的连接
+    pub fn project_tunnels(&self) -> Result<&[String]> {
+        self.project.as_ref()
+            .map(|p| p.aliases.as_slice())
+            .ok_or_else(|| TunnelError::config(t!("当前目录及其上级目录中没有找到 {}", PROJECT_FILE)))
+    }
+
+    /// 项目配置中的连接只能通过编辑项目文件修改
+    fn ensure_global(&self, alias: &str) -> Result<()> {
+        match &self.project {
+            Some(project) if project.aliases.iter().any(|a| a == alias) => Err(TunnelError::config(t!(
+                "连接 {} 定义在项目配置 {} 中，请直接编辑该文件",
+                alias,
+                project.path.display()
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// 系统连接只能由管理员在系统配置中删除；修改时在用户配置中保存覆盖它的副本
+    fn ensure_user_defined(&self, alias: &str) -> Result<()> {
+        if self.tunnels.get(alias).is_some_and(|t| t.system) {
+            return Err(TunnelError::config(t!(
+                "连接 {} 定义在系统配置 {} 中，只能由管理员在该文件中删除",
+                alias,
+                system::config_path().display()
+            )));
+        }
+        Ok(())
+    }
+
+    /// 允许本次命令修改或删除锁定的连接
+    pub fn unlock(&mut self) {
+        self.unlocked = true;
+    }
+
+    fn ensure_unlocked(&self, alias: &str) -> Result<()> {
+        if !self.unlocked && self.tunnels.get(alias).is_some_and(|t| t.locked) {
+            return Err(TunnelError::config(t!("连接 {} 已锁定，确需修改或删除时请加上 --unlock", alias)));
+        }
+        Ok(())
+    }
+
+    /// 由团队目录管理的连接只能通过重新导入修改，钩子和服务令牌这类本机设置除外
+    fn ensure_unmanaged(&self, alias: &str) -> Result<()> {
+        match self.tunnels.get(alias).and_then(|t| t.managed_by.as_deref()) {
+            Some(url) => Err(TunnelError::config(t!(
+                "连接 {} 由目录 {} 管理，请修改目录后重新导入，或使用 'tfa import --url {} --forget' 移除",
+                alias, url, url
+            ))),
+            None => Ok(()),
+        }
+    }
+
     pub fn save(&self) -> Result<()> {
         let config_path = Self::config_path()?;
-        let content = serde_json::to_string_pretty(self)?;
+        // 只写回全局连接，被项目覆盖的同名连接恢复为全局版本
+        let mut global = self.clone();
+        if let Some(project) = global.project.take() {
+            for alias in &project.aliases {
+                global.tunnels.remove(alias);
+            }
+            global.tunnels.extend(project.shadowed);
+        }
+        global.tunnels.retain(|_, tunnel| !tunnel.system);
+        if self.encrypted {
+            let key = Self::encryption_key()?;
+            for tunnel in global.tunnels.values_mut() {
+                for field in tunnel.sensitive_fields_mut() {
+                    *field = secrets::encrypt(&key, field)?;
+                }
+            }
+        }
+        let content = serde_json::to_string_pretty(&global)?;
+        if let Some(parent) = config_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // 备份失败不影响保存本身
+        if let Err(e) = backup::snapshot(&config_path, self.backup_retention()) {
+            warn!(error = %e, "{}", t!("备份配置失败"));
+        }
         fs::write(config_path, content)?;
         Ok(())
     }
     
+    /// 配置文件中设置的界面语言。在解析命令行之前读取，不解密、不报错
+    pub fn stored_lang() -> Option<Lang> {
+        let content = fs::read_to_string(Self::config_path().ok()?).ok()?;
+        let value: serde_json::Value = serde_json::from_str(&content).ok()?;
+        serde_json::from_value(value.get("lang")?.clone()).ok()
+    }
+
+    pub fn lang(&self) -> Option<Lang> {
+        self.lang
+    }
+
+    pub fn set_lang(&mut self, lang: Option<Lang>) -> Result<()> {
+        self.lang = lang;
+        self.save()
+    }
+
+    pub fn copy_on_run(&self) -> bool {
+        self.copy_on_run
+    }
+
+    pub fn set_copy_on_run(&mut self, enabled: bool) -> Result<()> {
+        self.copy_on_run = enabled;
+        self.save()
+    }
+
+    pub fn sync_settings(&self) -> Option<&SyncSettings> {
+        self.sync.as_ref()
+    }
+
+    pub fn set_sync_settings(&mut self, sync: Option<SyncSettings>) -> Result<()> {
+        self.sync = sync;
+        self.save()
+    }
+
+    pub fn outbound_proxy(&self) -> Option<&OutboundProxy> {
+        self.outbound_proxy.as_ref()
+    }
+
+    /// 内置模板与用户定义的模板，同名时以用户定义的为准
+    pub fn templates(&self) -> BTreeMap<String, Template> {
+        let mut templates = template::builtin();
+        templates.extend(self.templates.clone());
+        templates
+    }
+
+    pub fn template(&self, name: &str) -> Result<Template> {
+        self.templates().remove(name).ok_or_else(|| {
+            let names: Vec<String> = self.templates().into_keys().collect();
+            TunnelError::config(t!("模板 {} 不存在，可用的模板: {}", name, names.join(", ")))
+        })
+    }
+
+    pub fn port_range(&self) -> Option<PortRange> {
+        self.port_range
+    }
+
+    pub fn set_port_range(&mut self, range: Option<PortRange>) -> Result<()> {
+        self.port_range = range;
+        self.save()
+    }
+
+    /// 从保留范围中分配一个端口：不与已配置的连接或 `taken` 重复，且当前没有程序在监听
+    pub fn allocate_port(&self, taken: &[u16]) -> Result<u16> {
+        let range = self.port_range.ok_or_else(|| TunnelError::config(t!(
+            "没有为档案 {} 设置保留端口范围，请先运行 'tfa config port-range 42000-42999'",
+            profile::current()
+        )))?;
+        let used: HashSet<u16> = self.tunnels.values().map(|t| t.port).chain(taken.iter().copied()).collect();
+        (range.start..=range.end)
+            .find(|port| !used.contains(port) && platform::port_available(*port))
+            .ok_or_else(|| TunnelError::config(t!("保留端口范围 {} 中已没有可用的端口", range)))
+    }
+
+    pub fn backup_retention(&self) -> usize {
+        self.backup_retention.unwrap_or(backup::DEFAULT_RETENTION)
+    }
+
+    pub fn set_backup_retention(&mut self, retention: Option<usize>) -> Result<()> {
+        self.backup_retention = retention;
+        self.save()
+    }
+
+    pub fn api_keys(&self) -> &BTreeMap<String, ApiKey> {
+        &self.api_keys
+    }
+
+    pub fn add_api_key(&mut self, name: &str, key: ApiKey) -> Result<()> {
+        if self.api_keys.contains_key(name) {
+            return Err(TunnelError::config(t!("API 密钥 {} 已存在", name)));
+        }
+        self.api_keys.insert(name.to_string(), key);
+        self.save()
+    }
+
+    pub fn remove_api_key(&mut self, name: &str) -> Result<()> {
+        if self.api_keys.remove(name).is_none() {
+            return Err(TunnelError::config(t!("未找到 API 密钥 {}", name)));
+        }
+        self.save()
+    }
+
+    pub fn notify_channels(&self) -> &BTreeMap<String, Channel> {
+        &self.notify
+    }
+
+    pub fn alert_rules(&self) -> &[AlertRule] {
+        &self.alerts
+    }
+
+    pub fn cloudflared_pin(&self) -> Option<&Pin> {
+        self.cloudflared.as_ref()
+    }
+
+    pub fn set_cloudflared_pin(&mut self, pin: Option<Pin>) -> Result<()> {
+        cloudflared::set_pinned(pin.clone());
+        self.cloudflared = pin;
+        self.save()
+    }
+
+    pub fn log_sinks(&self) -> &[LogSink] {
+        &self.log_sinks
+    }
+
+    pub fn set_log_sinks(&mut self, sinks: Vec<LogSink>) -> Result<()> {
+        logsink::set_global(sinks.clone());
+        self.log_sinks = sinks;
+        self.save()
+    }
+
+    pub fn otel(&self) -> Option<&OtelSettings> {
+        self.otel.as_ref()
+    }
+
+    pub fn set_otel(&mut self, settings: Option<OtelSettings>) -> Result<()> {
+        otel::set_global(settings.clone());
+        self.otel = settings;
+        self.save()
+    }
+
+    pub fn set_outbound_proxy(&mut self, proxy: Option<OutboundProxy>) -> Result<()> {
+        outbound::set_global(proxy.clone());
+        self.outbound_proxy = proxy;
+        self.save()
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.encrypted
+    }
+    
+    /// 开启或关闭敏感字段加密；开启时若还没有密钥会生成并保存到系统钥匙串
+    pub fn set_encrypted(&mut self, encrypted: bool) -> Result<()> {
+        if encrypted {
+            secrets::ensure_config_key()?;
+        }
+        self.encrypted = encrypted;
+        self.save()
+    }
+    
+    fn encryption_key() -> Result<chacha20poly1305::Key> {
+        secrets::config_key()?.ok_or_else(|| TunnelError::config(t!(
+            "配置文件已加密，但未找到密钥：请设置 {} 环境变量，或在保存密钥的机器上解锁系统钥匙串",
+            secrets::CONFIG_KEY_ENV
+        )))
+    }
+    
     pub fn list_tunnels(&self) -> Result<Vec<&Tunnel>> {
         Ok(self.tunnels.values().collect())
     }
     
+    /// 展开别名中的通配符（`*`、`?`、`[...]`），按别名排序；不含通配符的别名原样保留，由调用方检查是否存在
+    pub fn expand_aliases(&self, patterns: &[String]) -> Result<Vec<String>> {
+        let mut aliases: Vec<String> = Vec::new();
+        for pattern in patterns {
+            let matched = if is_glob(pattern) {
+                let mut matched = self.matching_aliases(pattern)?;
+                if matched.is_empty() {
+                    return Err(TunnelError::config(t!("没有与 {} 匹配的连接", pattern)));
+                }
+                matched.sort();
+                matched
+            } else {
+                vec![pattern.clone()]
+            };
+            for alias in matched {
+                if !aliases.contains(&alias) {
+                    aliases.push(alias);
+                }
+            }
+        }
+        Ok(aliases)
+    }
+
+    /// 只接受一个连接的地方使用：通配符必须恰好匹配一个连接
+    pub fn resolve_alias(&self, pattern: &str) -> Result<String> {
+        if !is_glob(pattern) {
+            return Ok(pattern.to_string());
+        }
+        let mut matched = self.matching_aliases(pattern)?;
+        matched.sort();
+        match matched.len() {
+            0 => Err(TunnelError::config(t!("没有与 {} 匹配的连接", pattern))),
+            1 => Ok(matched.remove(0)),
+            _ => Err(TunnelError::config(t!("{} 匹配到多个连接（{}），此处只能指定一个", pattern, matched.join(", ")))),
+        }
+    }
+
+    fn matching_aliases(&self, pattern: &str) -> Result<Vec<String>> {
+        let regex = glob_regex(pattern)?;
+        Ok(self.tunnels.keys().filter(|alias| regex.is_match(alias)).cloned().collect())
+    }
+
     pub fn list_running_tunnels(&self) -> Result<Vec<&Tunnel>> {
         Ok(self.tunnels.values()
-            .filter(|t| t.is_running())
+            .filter(|t| t.is_live())
             .collect())
     }
     
-    pub fn add_tunnel(&mut self, alias: &str, source: &str, port: u16) -> Result<()> {
-        let tunnel = Tunnel::new(alias, source, port);
-        self.tunnels.insert(alias.to_string(), tunnel);
-        self.save()?;
-        Ok(())
+    /// 全局配置中的连接，不含项目配置中的连接；被项目覆盖的同名连接取全局版本
+    pub fn global_tunnels(&self) -> HashMap<String, Tunnel> {
+        let mut tunnels = self.tunnels.clone();
+        if let Some(project) = &self.project {
+            for alias in &project.aliases {
+                tunnels.remove(alias);
+            }
+            tunnels.extend(project.shadowed.clone());
+        }
+        tunnels.retain(|_, tunnel| !tunnel.system);
+        tunnels
+    }
+
+    /// 用给定的连接替换全局配置中的全部连接，项目配置中的连接保持不变
+    pub fn set_global_tunnels(&mut self, tunnels: Vec<Tunnel>) -> Result<()> {
+        let mut next = self.clone();
+        let project_aliases: HashSet<String> = next.project.as_ref()
+            .map(|project| project.aliases.iter().cloned().collect())
+            .unwrap_or_default();
+        next.tunnels.retain(|alias, tunnel| project_aliases.contains(alias) || tunnel.system);
+        if let Some(project) = &mut next.project {
+            project.shadowed.clear();
+        }
+        for tunnel in tunnels {
+            match &mut next.project {
+                Some(project) if project_aliases.contains(&tunnel.alias) => {
+                    project.shadowed.insert(tunnel.alias.clone(), tunnel);
+                }
+                _ => {
+                    next.tunnels.insert(tunnel.alias.clone(), tunnel);
+                }
+            }
+        }
+        next.validate_dependencies()?;
+        *self = next;
+        self.save()
+    }
+
+    pub fn get_tunnel(&self, alias: &str) -> Result<&Tunnel> {
+        self.tunnels.get(alias)
+            .ok_or_else(|| TunnelError::config(t!("未找到连接 {}", alias)))
+    }
+    
+    pub fn add_tunnel(&mut self, tunnel: Tunnel) -> Result<()> {
+        self.add_tunnels(vec![tunnel])
+    }
+
+    /// 一次添加多个连接并只保存一次；任一连接无效时全部不生效
+    pub fn add_tunnels(&mut self, tunnels: Vec<Tunnel>) -> Result<()> {
+        self.check_new_tunnels(&tunnels)?;
+        for tunnel in tunnels {
+            self.tunnels.insert(tunnel.alias.clone(), tunnel);
+        }
+        self.save()
+    }
+
+    /// 检查添加这些连接后配置是否仍然有效（不修改配置）
+    pub fn check_new_tunnels(&self, tunnels: &[Tunnel]) -> Result<()> {
+        let mut next = self.clone();
+        for tunnel in tunnels {
+            self.ensure_global(&tunnel.alias)?;
+            next.tunnels.insert(tunnel.alias.clone(), tunnel.clone());
+        }
+        next.validate_dependencies()
     }
     
-    pub fn update_tunnel(&mut self, alias: &str, source: Option<&str>, port: Option<u16>) -> Result<()> {
+    pub fn update_tunnel(&mut self, alias: &str, update: TunnelUpdate) -> Result<()> {
+        self.get_tunnel(alias)?;
+        self.ensure_global(alias)?;
+        if update.touches_shared() {
+            self.ensure_unmanaged(alias)?;
+        }
+        if !update.only_locks() {
+            self.ensure_unlocked(alias)?;
+        }
+        if let Some(tunnel) = self.tunnels.get(alias) {
+            let proxy = update.proxy.unwrap_or(tunnel.proxy);
+            let limited = update.max_up.unwrap_or(tunnel.max_up).is_some() || update.max_down.unwrap_or(tunnel.max_down).is_some();
+            if limited && !proxy {
+                return Err(TunnelError::config(t!("连接 {} 需要启用统计代理（--proxy true）才能限速", alias)));
+            }
+            let runtime = update.runtime.unwrap_or(tunnel.runtime);
+            if runtime.is_some() && update.provider.as_ref().unwrap_or(&tunnel.provider) != &Provider::Cloudflared {
+                return Err(TunnelError::config(t!("容器运行时只适用于 cloudflared 后端")));
+            }
+            if update.extra_ports.is_some() || !tunnel.extra_ports.is_empty() {
+                let mut updated = tunnel.clone();
+                updated.port = update.port.unwrap_or(tunnel.port);
+                updated.provider = update.provider.clone().unwrap_or_else(|| tunnel.provider.clone());
+                updated.via = update.via.clone().unwrap_or_else(|| tunnel.via.clone());
+                updated.extra_ports = update.extra_ports.clone().unwrap_or_else(|| tunnel.extra_ports.clone());
+                updated.check_extra_ports()?;
+            }
+        }
         if let Some(tunnel) = self.tunnels.get_mut(alias) {
-            if let Some(source) = source {
-                tunnel.source = source.to_string();
+            // 修改后的系统连接作为用户自己的副本保存
+            tunnel.system = false;
+            if let Some(source) = update.source {
+                tunnel.source = source;
             }
-            if let Some(port) = port {
+            if let Some(port) = update.port {
                 tunnel.port = port;
             }
+            if let Some(service) = update.service {
+                tunnel.service = service;
+            }
+            if let Some(provider) = update.provider {
+                tunnel.provider = provider;
+            }
+            if let Some(proxy) = update.proxy {
+                tunnel.proxy = proxy;
+            }
+            // 0 表示不限速
+            if let Some(max_up) = update.max_up {
+                tunnel.max_up = max_up.filter(|rate| *rate > 0);
+            }
+            if let Some(max_down) = update.max_down {
+                tunnel.max_down = max_down.filter(|rate| *rate > 0);
+            }
+            if let Some(start_timeout) = update.start_timeout {
+                tunnel.start_timeout = start_timeout;
+            }
+            if let Some(start_retries) = update.start_retries {
+                tunnel.start_retries = start_retries;
+            }
+            if let Some(retry_backoff) = update.retry_backoff {
+                tunnel.retry_backoff = retry_backoff;
+            }
+            if let Some(crash_limit) = update.crash_limit {
+                tunnel.crash_limit = crash_limit;
+            }
+            if let Some(crash_window) = update.crash_window {
+                tunnel.crash_window = crash_window;
+            }
+            if let Some(tags) = update.tags {
+                tunnel.tags = tags;
+            }
+            if let Some(service_token) = update.service_token {
+                tunnel.service_token = service_token;
+            }
+            if let Some(locked) = update.locked {
+                tunnel.locked = locked;
+            }
+            if let Some(enabled) = update.enabled {
+                tunnel.enabled = enabled;
+            }
+            if let Some(autostart) = update.autostart {
+                tunnel.autostart = autostart;
+            }
+            if let Some(on_change) = update.on_change {
+                tunnel.on_change = on_change;
+            }
+            if let Some(listen_addr) = update.listen_addr {
+                tunnel.listen_addr = listen_addr;
+            }
+            if let Some(local_hostname) = update.local_hostname {
+                tunnel.local_hostname = local_hostname;
+            }
+            if let Some(runtime) = update.runtime {
+                tunnel.runtime = runtime;
+            }
+            if let Some(ssh_host) = update.ssh_host {
+                tunnel.ssh_host = ssh_host;
+            }
+            if let Some(privileged) = update.privileged {
+                tunnel.privileged = privileged;
+            }
+            if let Some(probe) = update.probe {
+                tunnel.probe = probe;
+            }
+            if let Some(probe_interval) = update.probe_interval {
+                tunnel.probe_interval = probe_interval;
+            }
+            if let Some(ping_url) = update.ping_url {
+                tunnel.ping_url = ping_url;
+            }
+            if let Some(client) = update.client {
+                tunnel.client = client;
+            }
+            if let Some(connect_url) = update.connect_url {
+                tunnel.connect_url = connect_url;
+            }
+            if let Some(notes) = update.notes {
+                tunnel.notes = notes;
+            }
+            if let Some(connect_hint) = update.connect_hint {
+                tunnel.connect_hint = connect_hint;
+            }
+            if let Some(client_password) = update.client_password {
+                tunnel.client_password = client_password;
+            }
+            if let Some(outbound_proxy) = update.outbound_proxy {
+                tunnel.outbound_proxy = outbound_proxy;
+            }
+            if let Some(log_level) = update.log_level {
+                tunnel.log_level = log_level;
+            }
+            if let Some(redact) = update.redact {
+                tunnel.redact = redact;
+            }
+            if let Some(log_sinks) = update.log_sinks {
+                tunnel.log_sinks = log_sinks;
+            }
+            if let Some(extra_ports) = update.extra_ports {
+                tunnel.extra_ports = extra_ports;
+            }
+            tunnel.hooks.merge(update.hooks);
+            if update.depends_on.is_some() || update.via.is_some() {
+                let previous = (tunnel.depends_on.clone(), tunnel.via.clone());
+                if let Some(depends_on) = update.depends_on {
+                    tunnel.depends_on = depends_on;
+                }
+                if let Some(via) = update.via {
+                    tunnel.via = via;
+                }
+                if let Err(e) = self.validate_dependencies() {
+                    if let Some(tunnel) = self.tunnels.get_mut(alias) {
+                        (tunnel.depends_on, tunnel.via) = previous;
+                    }
+                    return Err(e);
+                }
+            }
             self.save()?;
         }
         Ok(())
     }
     
     pub fn remove_tunnel(&mut self, alias: &str) -> Result<()> {
+        self.ensure_removable(alias)?;
         self.tunnels.remove(alias);
         self.save()?;
         Ok(())
     }
+
+    /// 仍被其他连接依赖的连接不能删除
+    pub fn ensure_removable(&self, alias: &str) -> Result<()> {
+        self.get_tunnel(alias)?;
+        self.ensure_global(alias)?;
+        self.ensure_user_defined(alias)?;
+        self.ensure_unmanaged(alias)?;
+        self.ensure_unlocked(alias)?;
+        let mut dependents: Vec<&str> = self.tunnels.values()
+            .filter(|t| t.depends_on.iter().any(|d| d == alias))
+            .map(|t| t.alias.as_str())
+            .collect();
+        if !dependents.is_empty() {
+            dependents.sort();
+            return Err(TunnelError::config(t!(
+                "连接 {} 被 {} 依赖，请先修改它们的 depends_on",
+                alias,
+                dependents.join(", ")
+            )));
+        }
+        let mut downstream: Vec<&str> = self.tunnels.values()
+            .filter(|t| t.via.as_ref().is_some_and(|via| via.alias == alias))
+            .map(|t| t.alias.as_str())
+            .collect();
+        if !downstream.is_empty() {
+            downstream.sort();
+            return Err(TunnelError::config(t!(
+                "连接 {} 被 {} 经过，请先修改它们的 via",
+                alias,
+                downstream.join(", ")
+            )));
+        }
+        Ok(())
+    }
+    
+    /// 检查所有依赖都存在、没有循环依赖，且经过其他连接的链路都能解析
+    pub fn validate_dependencies(&self) -> Result<()> {
+        for tunnel in self.tunnels.values().filter(|t| t.via.is_some()) {
+            chain::resolve(self, tunnel)?;
+        }
+        let mut depths = HashMap::new();
+        for alias in self.tunnels.keys() {
+            self.dependency_depth(alias, &mut depths, &mut Vec::new())?;
+        }
+        Ok(())
+    }
+
+    /// 启动前需要先运行的连接：depends_on，以及作为代理经过的上游连接。
+    /// 经跳板访问时跳板连接本身不需要运行，但跳板连接经过的代理需要
+    pub fn prerequisites(&self, tunnel: &Tunnel) -> Vec<String> {
+        let mut aliases = tunnel.depends_on.clone();
+        let via = match &tunnel.via {
+            Some(via) if !via.needs_upstream() => self.tunnels.get(&via.alias).and_then(|upstream| upstream.via.as_ref()),
+            via => via.as_ref(),
+        };
+        if let Some(via) = via.filter(|via| via.needs_upstream())
+            && !aliases.contains(&via.alias)
+        {
+            aliases.push(via.alias.clone());
+        }
+        aliases
+    }
+
+    /// 启动顺序：所选连接及其全部依赖，按批次排列，依赖在前；同一批内互不依赖，可以并行启动
+    pub fn start_order(&self, aliases: &[String]) -> Result<Vec<Vec<String>>> {
+        let mut included: HashSet<String> = HashSet::new();
+        let mut pending: Vec<String> = aliases.to_vec();
+        while let Some(alias) = pending.pop() {
+            if included.insert(alias.clone()) {
+                pending.extend(self.prerequisites(self.get_tunnel(&alias)?));
+            }
+        }
+        self.dependency_waves(included, false)
+    }
+
+    /// 停止顺序：所选连接及依赖它们的全部连接，依赖方在前
+    pub fn stop_order(&self, aliases: &[String]) -> Result<Vec<Vec<String>>> {
+        let mut included: HashSet<String> = HashSet::new();
+        let mut pending: Vec<String> = aliases.to_vec();
+        while let Some(alias) = pending.pop() {
+            if included.insert(alias.clone()) {
+                pending.extend(self.tunnels.values()
+                    .filter(|t| self.prerequisites(t).contains(&alias))
+                    .map(|t| t.alias.clone()));
+            }
+        }
+        self.dependency_waves(included, true)
+    }
+
+    fn dependency_waves(&self, aliases: HashSet<String>, reverse: bool) -> Result<Vec<Vec<String>>> {
+        let mut depths = HashMap::new();
+        let mut waves: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+        for alias in aliases {
+            let depth = self.dependency_depth(&alias, &mut depths, &mut Vec::new())?;
+            waves.entry(depth).or_default().push(alias);
+        }
+        let mut waves: Vec<Vec<String>> = waves.into_values()
+            .map(|mut wave| {
+                wave.sort();
+                wave
+            })
+            .collect();
+        if reverse {
+            waves.reverse();
+        }
+        Ok(waves)
+    }
+
+    /// 连接在依赖图中的层级：没有依赖为 0，否则比最深的依赖大 1
+    fn dependency_depth(
+        &self,
+        alias: &str,
+        depths: &mut HashMap<String, usize>,
+        path: &mut Vec<String>,
+    ) -> Result<usize> {
+        if let Some(&depth) = depths.get(alias) {
+            return Ok(depth);
+        }
+        if let Some(start) = path.iter().position(|a| a == alias) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(alias.to_string());
+            return Err(TunnelError::config(t!("连接之间存在循环依赖: {}", cycle.join(" -> "))));
+        }
+
+        let tunnel = self.get_tunnel(alias)?;
+        path.push(alias.to_string());
+        let mut depth = 0;
+        for dependency in &self.prerequisites(tunnel) {
+            if !self.tunnels.contains_key(dependency) {
+                return Err(TunnelError::config(t!("连接 {} 依赖的 {} 不存在", alias, dependency)));
+            }
+            depth = depth.max(self.dependency_depth(dependency, depths, path)? + 1);
+        }
+        path.pop();
+
+        depths.insert(alias.to_string(), depth);
+        Ok(depth)
+    }
+
+    /// 当前档案的配置目录
+    pub fn config_dir() -> Result<PathBuf> {
+        profile::ensure_exists(profile::current())?;
+        let path = profile::dir(profile::current())?;
+        fs::create_dir_all(&path)?;
+        Ok(path)
+    }
     
-    fn config_path() -> Result<PathBuf> {
-        let mut path = dirs::config_dir()
-            .ok_or_else(|| anyhow::anyhow!("无法获取配置目录"))?;
-        path.push("cf-manager");
+    /// 运行时文件（代理统计等）所在目录
+    pub fn runtime_dir() -> Result<PathBuf> {
+        let mut path = Self::config_dir()?;
+        path.push("run");
         fs::create_dir_all(&path)?;
+        Ok(path)
+    }
+    
+    /// 配置文件的位置：--config、CF_MANAGER_CONFIG 指定时以其为准，否则为档案目录下的 config.json
+    pub fn config_path() -> Result<PathBuf> {
+        if let Some(path) = Self::custom_file() {
+            return Ok(path.clone());
+        }
+        let mut path = Self::config_dir()?;
         path.push("config.json");
         Ok(path)
     }
-} 
\ No newline at end of file
+
+    /// 确定配置文件：--config、CF_MANAGER_CONFIG，都没有时使用档案目录下的 config.json
+    pub fn select_file(arg: Option<&str>) -> Result<()> {
+        let path = match arg {
+            Some(path) => Some(path.to_string()),
+            None => std::env::var(CONFIG_FILE_ENV).ok(),
+        };
+        let path = path.filter(|path| !path.is_empty()).map(std::path::absolute).transpose()?;
+        let _ = CONFIG_FILE.set(path);
+        Ok(())
+    }
+
+    /// 通过 --config 或环境变量指定的配置文件
+    pub fn custom_file() -> Option<&'static PathBuf> {
+        CONFIG_FILE.get().and_then(Option::as_ref)
+    }
+}
+
+/// 指定配置文件的环境变量，优先级低于 --config
+pub const CONFIG_FILE_ENV: &str = "CF_MANAGER_CONFIG";
+
+static CONFIG_FILE: OnceLock<Option<PathBuf>> = OnceLock::new();
+ 
+/// 别名中是否含有通配符
+pub fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?', '['])
+}
+
+/// 别名是否与别名或通配模式相符
+pub fn matches_alias(pattern: &str, alias: &str) -> bool {
+    if is_glob(pattern) {
+        glob_regex(pattern).is_ok_and(|regex| regex.is_match(alias))
+    } else {
+        pattern == alias
+    }
+}
+
+/// 把 shell 风格的通配符转换为匹配整个别名的正则表达式；`[!...]`（或 `[^...]`）表示取反，
+/// 紧跟在 `[` 后的 `]` 是普通字符，方括号中除表示范围的 `-` 外都按原样匹配
+fn glob_regex(pattern: &str) -> Result<regex::Regex> {
+    let invalid = || TunnelError::config(t!("无效的通配符 {}", pattern));
+    let mut regex = String::from("^");
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            '[' => {
+                regex.push('[');
+                if chars.next_if(|c| *c == '!' || *c == '^').is_some() {
+                    regex.push('^');
+                }
+                let mut first = true;
+                loop {
+                    match chars.next() {
+                        None => return Err(invalid()),
+                        Some(']') if !first => break,
+                        Some('-') => regex.push('-'),
+                        Some(c) => regex.push_str(&regex::escape(&c.to_string())),
+                    }
+                    first = false;
+                }
+                regex.push(']');
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    regex::Regex::new(&regex).map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(aliases: &[&str]) -> Config {
+        let mut config: Config = serde_json::from_str(r#"{"tunnels": {}}"#).unwrap();
+        for alias in aliases {
+            config.tunnels.insert(alias.to_string(), Tunnel::new(alias, "tcp.example.com", 0));
+        }
+        config
+    }
+
+    #[test]
+    fn star_and_question_mark() {
+        assert!(matches_alias("db-*", "db-prod"));
+        assert!(matches_alias("db-*", "db-"));
+        assert!(!matches_alias("db-*", "web-db"));
+        assert!(matches_alias("db-?", "db-1"));
+        assert!(!matches_alias("db-?", "db-10"));
+        // 其余正则元字符按原样匹配
+        assert!(matches_alias("a.b*", "a.bc"));
+        assert!(!matches_alias("a.b*", "axbc"));
+    }
+
+    #[test]
+    fn bracket_classes() {
+        assert!(matches_alias("db-[12]", "db-1"));
+        assert!(!matches_alias("db-[12]", "db-3"));
+        assert!(matches_alias("db-[!1]", "db-2"));
+        assert!(!matches_alias("db-[!1]", "db-1"));
+        assert!(matches_alias("db-[^1]", "db-2"));
+        assert!(matches_alias("db-[a-c]", "db-b"));
+        assert!(!matches_alias("db-[a-c]", "db-d"));
+        // 方括号中的正则元字符不再有特殊含义
+        assert!(matches_alias("db[.]", "db."));
+        assert!(!matches_alias("db[.]", "dbx"));
+        assert!(matches_alias("db[]]", "db]"));
+        assert!(matches_alias(r"db[\w]", r"db\"));
+        assert!(!matches_alias(r"db[\w]", "dbx"));
+    }
+
+    #[test]
+    fn unterminated_bracket_is_invalid() {
+        for pattern in ["db-[", "db-[12", "db-[!", "db-[]"] {
+            let error = glob_regex(pattern).unwrap_err();
+            assert!(error.to_string().contains(&t!("无效的通配符 {}", pattern)), "{}", pattern);
+        }
+        assert!(!matches_alias("db-[12", "db-1"));
+    }
+
+    #[test]
+    fn expand_aliases_dedups_in_order() {
+        let config = config(&["db-2", "db-1", "web"]);
+        let patterns = ["web", "db-*", "db-1", "missing"].map(String::from);
+        assert_eq!(config.expand_aliases(&patterns).unwrap(), ["web", "db-1", "db-2", "missing"]);
+        assert!(config.expand_aliases(&["cache-*".to_string()]).is_err());
+    }
+
+    #[test]
+    fn resolve_alias_requires_one_match() {
+        let config = config(&["db-2", "db-1", "web"]);
+        assert_eq!(config.resolve_alias("w*").unwrap(), "web");
+        assert_eq!(config.resolve_alias("db-1").unwrap(), "db-1");
+        let error = config.resolve_alias("db-*").unwrap_err().to_string();
+        assert!(error.contains("db-1, db-2"), "{}", error);
+        assert!(config.resolve_alias("cache-*").is_err());
+    }
+}
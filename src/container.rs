@@ -0,0 +1,144 @@
+//! 在 Docker 或 Podman 容器中运行 cloudflared（连接的 `runtime`）：主机上不需要安装 cloudflared，
+//! 由本工具拉取官方镜像、发布本地端口并管理容器的生命周期。
+//!
+//! 容器以前台方式运行，`docker run` 进程的输出即 cloudflared 的日志，它的 PID 记录在运行状态中；
+//! 停止时先删除容器，该进程随之退出。Linux 上容器使用主机网络，cloudflared 直接监听连接的地址，
+//! 其他平台上 cloudflared 在容器内监听所有地址，再由 `-p` 发布到连接的地址。
+
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use crate::cloudflared;
+use crate::error::TunnelError;
+use crate::i18n::t;
+use crate::profile;
+use tracing::debug;
+
+/// cloudflared 的官方镜像
+pub const IMAGE: &str = "docker.io/cloudflare/cloudflared";
+
+/// 官方镜像中运行 cloudflared 的用户的主目录，Access 登录状态保存在其下的 .cloudflared 中
+const IMAGE_HOME: &str = "/home/nonroot";
+
+/// 运行 cloudflared 的容器运行时
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Runtime {
+    Docker,
+    Podman,
+}
+
+impl Runtime {
+    /// 运行时的命令行程序
+    pub fn program(self) -> &'static str {
+        match self {
+            Runtime::Docker => "docker",
+            Runtime::Podman => "podman",
+        }
+    }
+}
+
+impl fmt::Display for Runtime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.program())
+    }
+}
+
+/// 正在运行的连接所在的容器，记录在运行状态中
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Container {
+    pub runtime: Runtime,
+    pub name: String,
+}
+
+/// 使用的镜像：档案固定了 cloudflared 版本时使用同一版本的镜像，否则为 latest
+pub fn image() -> String {
+    let tag = cloudflared::pinned().map_or_else(|| "latest".to_string(), |pin| pin.version);
+    format!("{}:{}", IMAGE, tag)
+}
+
+/// 连接的容器名，包含档案名以免不同档案中的同名连接互相影响
+pub fn name(alias: &str) -> String {
+    let name = format!("tfa-{}-{}", profile::current(), alias);
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-') { c } else { '-' }.to_ascii_lowercase())
+        .collect()
+}
+
+/// cloudflared 在容器内监听的地址：使用主机网络时即为 `addr`，否则为所有地址上的同一端口
+pub fn inner_addr(addr: SocketAddr) -> SocketAddr {
+    if cfg!(target_os = "linux") {
+        addr
+    } else {
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), addr.port())
+    }
+}
+
+/// 把 cloudflared 命令改为在容器中运行：沿用其参数，环境变量（如出站代理）传入容器，
+/// 并挂载主机上的 ~/.cloudflared 以复用 Access 登录状态
+pub fn wrap(runtime: Runtime, alias: &str, addr: SocketAddr, command: &Command) -> Command {
+    let mut wrapped = Command::new(runtime.program());
+    wrapped.args(["run", "--rm", "--name", &name(alias)]);
+    if cfg!(target_os = "linux") {
+        wrapped.args(["--network", "host"]);
+    } else {
+        let host = match addr.ip() {
+            IpAddr::V6(ip) => format!("[{}]", ip),
+            ip => ip.to_string(),
+        };
+        wrapped.arg("-p").arg(format!("{}:{}:{}", host, addr.port(), addr.port()));
+    }
+    if let Some(dir) = credentials_dir() {
+        wrapped.arg("-v").arg(format!("{}:{}/.cloudflared", dir.display(), IMAGE_HOME));
+    }
+    // 只传变量名，值取自 docker 进程的环境，不出现在命令行中
+    for (name, value) in command.get_envs() {
+        if let Some(value) = value {
+            wrapped.arg("-e").arg(name).env(name, value);
+        }
+    }
+    wrapped.arg(image()).args(command.get_args());
+    wrapped
+}
+
+fn credentials_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".cloudflared")).filter(|dir| dir.is_dir())
+}
+
+/// 本地没有镜像时拉取，拉取前通过 `progress` 报告
+pub fn ensure_image(runtime: Runtime, progress: impl Fn(&str)) -> Result<(), TunnelError> {
+    let image = image();
+    let present = Command::new(runtime.program())
+        .args(["image", "inspect", &image])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+    match present {
+        Ok(status) if status.success() => return Ok(()),
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(TunnelError::ProgramNotFound { program: runtime.program().to_string() });
+        }
+        Err(e) => return Err(e.into()),
+    }
+    progress(&t!("正在拉取镜像 {}...", image));
+    let output = Command::new(runtime.program()).args(["pull", &image]).output()?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(t!("拉取镜像 {} 失败: {}", image, String::from_utf8_lossy(&output.stderr).trim())).into());
+    }
+    Ok(())
+}
+
+/// 删除容器（运行中时强制停止）；容器不存在时同样视为成功
+pub fn remove(container: &Container) -> Result<()> {
+    let output = Command::new(container.runtime.program())
+        .args(["rm", "-f", &container.name])
+        .output()?;
+    debug!(name = %container.name, status = %output.status, "删除容器");
+    Ok(())
+}
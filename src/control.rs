@@ -0,0 +1,64 @@
+//! `tfa grpc` 与 `tfa web` 共用的启动、停止、重启操作：不交互、不显示进度，与命令行一样写入操作历史。
+
+use anyhow::Result;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::error::TunnelError;
+use crate::history::{self, HistoryAction};
+use crate::i18n::t;
+use crate::otel::{self, Operation};
+use crate::state::PauseMark;
+use crate::tunnel::Tunnel;
+use tracing::debug;
+
+/// 按依赖顺序启动连接及其依赖，已在运行的连接跳过；任一连接失败即停止
+pub fn start(config: &Config, alias: &str) -> Result<()> {
+    for wave in config.start_order(&[alias.to_string()])? {
+        for current in wave {
+            let tunnel = config.get_tunnel(&current)?;
+            if tunnel.is_running() {
+                continue;
+            }
+            if !tunnel.enabled {
+                return Err(TunnelError::config(t!("连接 {} 已停用，请先使用 tfa enable {} 启用", current, current)));
+            }
+            // 作为依赖被带起的连接不解除暂停
+            if current != alias && PauseMark::is_paused(&current) {
+                return Err(TunnelError::config(t!("依赖的连接 {} 已暂停", current)));
+            }
+            let result = tunnel.start_with_progress(|message| debug!(alias = %current, message, "启动进度"));
+            history::record(HistoryAction::Start, &current, None, &result);
+            result?;
+        }
+    }
+    // 按别名显式启动即视为恢复；连接已经启动，删除标记失败只记录下来
+    if let Err(e) = PauseMark::clear(alias) {
+        debug!(alias = %alias, error = %e, "删除暂停标记失败");
+    }
+    Ok(())
+}
+
+/// 停止连接，`force` 同 `tfa stop --force`
+pub fn stop(config: &Config, alias: &str, force: bool) -> Result<()> {
+    let result = config.get_tunnel(alias)?.stop(force);
+    history::record(HistoryAction::Stop, alias, None, &result);
+    Ok(result?)
+}
+
+/// 以修改后的配置 `after` 重启运行中的连接，保留剩余的有效期
+pub fn restart(before: &Tunnel, after: &Tunnel) -> Result<()> {
+    let ttl = before.remaining_ttl();
+    otel::traced(Operation::Restart, after, || {
+        let result = before.stop(false);
+        history::record(HistoryAction::Stop, &after.alias, None, &result);
+        result?;
+        let result = after.start_with_progress(|message| debug!(alias = %after.alias, message, "启动进度"));
+        history::record(HistoryAction::Start, &after.alias, None, &result);
+        result.map_err(anyhow::Error::from)
+    })?;
+    if let Some(ttl) = ttl {
+        after.set_ttl(Duration::from_secs(ttl))?;
+    }
+    Ok(())
+}
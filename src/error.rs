@@ -0,0 +1,179 @@
+use thiserror::Error;
+
+use crate::i18n::t;
+
+/// 启动/管理连接过程中可区分的失败类型
+#[derive(Debug, Error)]
+pub enum TunnelError {
+    #[error("{}", t!("未找到 cloudflared"))]
+    CloudflaredNotFound,
+
+    #[error("{}", t!("未找到 {}", program))]
+    ProgramNotFound { program: String },
+
+    #[error("{}", t!("端口 {} 已被占用{}", port, occupant(*pid, process.as_deref())))]
+    PortInUse {
+        port: u16,
+        pid: Option<u32>,
+        process: Option<String>,
+    },
+
+    #[error("{}", t!("连接 {} 的本地端口 {} 低于 1024，当前用户无权绑定", alias, port))]
+    PrivilegedPort { alias: String, port: u16 },
+
+    #[error("{}", t!("访问 {} 需要先完成 Cloudflare Access 认证", hostname))]
+    AuthRequired { hostname: String },
+
+    #[error("{}", t!("无法启动连接进程: {}", .0))]
+    SpawnFailed(#[source] std::io::Error),
+
+    #[error("{}", t!("连接进程启动失败: {}", stderr))]
+    CrashedOnStartup { stderr: String },
+
+    #[error("{}", t!("连接进程在 {} 秒内未就绪：端口 {} 未被监听，日志中也没有监听或连接记录", seconds, port))]
+    Timeout { port: u16, seconds: u64 },
+
+    #[error("{}", t!("连接 {} 在 {} 秒内未通过健康检查（状态: {}）", alias, seconds, status))]
+    Unhealthy { alias: String, seconds: u64, status: String },
+
+    #[error("{}", t!("连接 {} 在 {} 分钟内失败了 {} 次，已停止重试；最后的错误: {}", alias, minutes, failures, last_error))]
+    CrashLoop { alias: String, failures: usize, minutes: u64, last_error: String },
+
+    #[error("{}", t!("端口 {} 上的 {} (PID {}) 不是由本工具启动的", port, process, pid))]
+    NotOwned { port: u16, pid: u32, process: String },
+
+    #[error("{}", t!("连接 {} 由系统服务管理，只有管理员可以启动或停止", alias))]
+    SystemTunnel { alias: String },
+
+    #[error("{}", t!("{} 钩子执行失败: {}", hook, output))]
+    HookFailed { hook: String, output: String },
+
+    /// 配置文件、项目配置或清单等输入有误
+    #[error("{0}")]
+    Config(String),
+
+    /// 批量操作中有连接失败，退出码取决于失败的类型
+    #[error("{message}")]
+    Batch { message: String, exit_code: i32 },
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+/// 进程退出码，供包装脚本按失败类型分支处理
+pub mod exit_code {
+    /// 其他失败
+    pub const FAILURE: i32 = 1;
+    /// 配置文件、项目配置或清单有误，或连接不存在
+    pub const CONFIG: i32 = 2;
+    /// 未找到 cloudflared、kubectl 等后端程序
+    pub const PROGRAM_NOT_FOUND: i32 = 3;
+    /// 本地端口已被占用，或无权绑定低于 1024 的端口
+    pub const PORT_IN_USE: i32 = 4;
+    /// 需要先完成 Cloudflare Access 认证
+    pub const AUTH_REQUIRED: i32 = 5;
+    /// 等待连接就绪超时
+    pub const TIMEOUT: i32 = 6;
+    /// 连接进程无法启动或启动后立即退出，或因反复失败已停止重试
+    pub const START_FAILED: i32 = 7;
+    /// 钩子执行失败
+    pub const HOOK_FAILED: i32 = 8;
+    /// `diff --exit-code` 发现配置与清单不一致
+    pub const DRIFT: i32 = 9;
+    /// 命令行参数有误
+    pub const USAGE: i32 = 64;
+}
+
+impl TunnelError {
+    /// 配置或输入文件有误的错误
+    pub fn config(message: String) -> anyhow::Error {
+        TunnelError::Config(message).into()
+    }
+
+    /// 该错误对应的进程退出码
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            TunnelError::Config(_) => exit_code::CONFIG,
+            TunnelError::CloudflaredNotFound | TunnelError::ProgramNotFound { .. } => exit_code::PROGRAM_NOT_FOUND,
+            TunnelError::PortInUse { .. } | TunnelError::PrivilegedPort { .. } => exit_code::PORT_IN_USE,
+            TunnelError::AuthRequired { .. } => exit_code::AUTH_REQUIRED,
+            TunnelError::Timeout { .. } | TunnelError::Unhealthy { .. } => exit_code::TIMEOUT,
+            TunnelError::SpawnFailed(_) | TunnelError::CrashedOnStartup { .. } | TunnelError::CrashLoop { .. } => exit_code::START_FAILED,
+            TunnelError::HookFailed { .. } => exit_code::HOOK_FAILED,
+            TunnelError::Batch { exit_code, .. } => *exit_code,
+            TunnelError::NotOwned { .. } | TunnelError::SystemTunnel { .. } | TunnelError::Io(_) => exit_code::FAILURE,
+            TunnelError::Other(e) => e.downcast_ref::<TunnelError>().map_or(exit_code::FAILURE, TunnelError::exit_code),
+        }
+    }
+
+    /// 可能由网络或 DNS 抖动引起、值得重试的失败
+    pub fn is_transient(&self) -> bool {
+        matches!(self, TunnelError::CrashedOnStartup { .. } | TunnelError::Timeout { .. })
+    }
+
+    /// 针对该错误的修复建议
+    pub fn hint(&self) -> Option<String> {
+        match self {
+            TunnelError::CloudflaredNotFound => Some(t!(
+                "请安装 cloudflared：Windows 运行 'winget install --id Cloudflare.cloudflared'，macOS 运行 'brew install cloudflared'"
+            )),
+            TunnelError::ProgramNotFound { program } => Some(t!(
+                "请安装 {} 并确认它在 PATH 中",
+                program
+            )),
+            TunnelError::PortInUse { .. } => Some(t!(
+                "请停止占用该端口的程序，或使用 'tfa set <alias> --port <端口>' 更换本地端口"
+            )),
+            TunnelError::PrivilegedPort { alias, .. } => Some(t!(
+                "使用 'tfa privileged-port {} redirect' 改为监听非特权端口并设置端口转发，或 'tfa privileged-port {} elevate' 经 sudo 启动；也可以用 'tfa set {} --port <端口>' 换成 1024 以上的端口",
+                alias,
+                alias,
+                alias
+            )),
+            TunnelError::AuthRequired { hostname } => Some(t!(
+                "请运行 'cloudflared access login https://{}' 完成认证后重试",
+                hostname
+            )),
+            TunnelError::SpawnFailed(_) => Some(t!(
+                "请确认 cloudflared 可以在终端中正常执行"
+            )),
+            TunnelError::CrashedOnStartup { .. } => Some(t!(
+                "请检查源地址是否正确，完整日志位于配置目录下的 run/<alias>.log"
+            )),
+            TunnelError::Timeout { .. } => Some(t!(
+                "网络较慢时可使用 'tfa set <alias> --start-timeout <秒>' 延长等待时间"
+            )),
+            TunnelError::Unhealthy { alias, .. } => Some(t!(
+                "可使用 'tfa log' 查看连接 {} 的日志，或用 --timeout 延长等待时间",
+                alias
+            )),
+            TunnelError::CrashLoop { alias, minutes, .. } => Some(t!(
+                "排除问题后使用 'tfa reset {}' 清除失败记录，或等待 {} 分钟后自动恢复；完整日志位于配置目录下的 run/{}.log",
+                alias,
+                minutes,
+                alias
+            )),
+            TunnelError::NotOwned { .. } => Some(t!(
+                "确认该进程可以结束后，使用 --force 强制停止"
+            )),
+            TunnelError::SystemTunnel { .. } => Some(t!(
+                "请以管理员身份（如 sudo）运行，或在用户配置中添加同名连接覆盖它"
+            )),
+            TunnelError::HookFailed { .. } => Some(t!(
+                "完整输出位于配置目录下的 run/<alias>.hooks.log；可通过 'tfa set' 为对应钩子传入空字符串来删除它"
+            )),
+            TunnelError::Config(_) | TunnelError::Batch { .. } | TunnelError::Io(_) | TunnelError::Other(_) => None,
+        }
+    }
+}
+
+fn occupant(pid: Option<u32>, process: Option<&str>) -> String {
+    match (pid, process) {
+        (Some(pid), Some(process)) => t!("（{} PID {}）", process, pid),
+        (Some(pid), None) => t!("（PID {}）", pid),
+        _ => String::new(),
+    }
+}
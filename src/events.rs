@@ -0,0 +1,252 @@
+//! 连接事件总线：启动、停止、崩溃、状态变化和日志行都以 `TunnelEvent` 广播，
+//! 列表刷新、`tfa events` 以及通知等使用者订阅同一个事件流，而不是各自轮询。
+//!
+//! 每次命令都是独立的进程，因此发布的事件同时追加到运行目录的 `events.jsonl`；
+//! 订阅方进程中的监视线程读取其他进程写入的事件，并按需检测状态变化、读取新的日志行，
+//! 再广播给本进程内的订阅者。
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use std::thread;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+use crate::config::Config;
+use crate::platform;
+use crate::redact::Redactor;
+use crate::tunnel::TunnelStatus;
+use tracing::{debug, trace};
+
+/// 订阅者来不及处理时，广播通道中保留的事件数
+const CHANNEL_CAPACITY: usize = 1024;
+/// 事件文件超过该大小时清空，订阅方会从头读取
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+/// 监视线程读取事件文件和日志的间隔
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(500);
+
+static BUS: LazyLock<broadcast::Sender<TunnelEvent>> = LazyLock::new(|| broadcast::channel(CHANNEL_CAPACITY).0);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TunnelEvent {
+    /// 连接已启动并就绪
+    Started { alias: String },
+    /// 连接已停止
+    Stopped { alias: String },
+    /// 启动失败或运行中的进程意外退出
+    Crashed { alias: String, error: String },
+    /// 监视线程检测到的状态变化
+    HealthChanged { alias: String, from: TunnelStatus, to: TunnelStatus },
+    /// 连接日志中新写入的一行，额外端口的行以 `[alias@port]` 开头
+    LogLine { alias: String, line: String },
+}
+
+impl TunnelEvent {
+    pub fn alias(&self) -> &str {
+        match self {
+            TunnelEvent::Started { alias }
+            | TunnelEvent::Stopped { alias }
+            | TunnelEvent::Crashed { alias, .. }
+            | TunnelEvent::HealthChanged { alias, .. }
+            | TunnelEvent::LogLine { alias, .. } => alias,
+        }
+    }
+}
+
+/// 事件的 JSON 形式，附带时间，供 `tfa events --json` 与 WebSocket 推送使用
+pub fn to_json(event: &TunnelEvent, time: u64) -> Result<serde_json::Value> {
+    let mut value = serde_json::to_value(event)?;
+    if let Some(object) = value.as_object_mut() {
+        object.insert("time".to_string(), time.into());
+    }
+    Ok(value)
+}
+
+/// 事件文件中的一行
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Record {
+    pub time: u64,
+    /// 发布事件的进程，监视线程跳过本进程发布的事件
+    pub pid: u32,
+    #[serde(flatten)]
+    pub event: TunnelEvent,
+}
+
+/// 订阅本进程内广播的事件；需要其他进程的事件时同时启动 [`Monitor`]
+pub fn subscribe() -> broadcast::Receiver<TunnelEvent> {
+    BUS.subscribe()
+}
+
+/// 向本进程的订阅者广播事件，并写入事件文件供其他进程读取。写入失败只影响事件流，不影响操作本身
+pub fn publish(event: TunnelEvent) {
+    if let Err(e) = append(&event) {
+        debug!(error = %e, "写入事件失败");
+    }
+    // 没有订阅者时发送失败，属于正常情况
+    let _ = BUS.send(event);
+}
+
+fn append(event: &TunnelEvent) -> Result<()> {
+    let path = path()?;
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    if file.metadata()?.len() > MAX_FILE_BYTES {
+        file.set_len(0)?;
+    }
+    let record = Record { time: platform::unix_now(), pid: std::process::id(), event: event.clone() };
+    writeln!(file, "{}", serde_json::to_string(&record)?)?;
+    Ok(())
+}
+
+fn path() -> Result<PathBuf> {
+    Ok(Config::runtime_dir()?.join("events.jsonl"))
+}
+
+/// 在后台线程中把其他进程的事件、状态变化和日志行广播到本进程
+#[derive(Debug, Default)]
+pub struct Monitor {
+    /// 检测状态变化的间隔，None 表示不检测
+    health: Option<Duration>,
+    /// 是否读取连接日志中的新行
+    logs: bool,
+}
+
+impl Monitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn health(mut self, interval: Duration) -> Self {
+        self.health = Some(interval.max(FOLLOW_INTERVAL));
+        self
+    }
+
+    pub fn logs(mut self, logs: bool) -> Self {
+        self.logs = logs;
+        self
+    }
+
+    /// 启动监视线程，线程随进程结束
+    pub fn spawn(self) -> Result<()> {
+        let events = Tail::new(path()?);
+        thread::spawn(move || self.run(events));
+        Ok(())
+    }
+
+    fn run(self, mut events: Tail) {
+        let mut statuses: HashMap<String, TunnelStatus> = HashMap::new();
+        let mut logs: HashMap<String, Tail> = HashMap::new();
+        let mut since_health = Duration::MAX;
+        loop {
+            for line in events.read_lines() {
+                match serde_json::from_str::<Record>(&line) {
+                    Ok(record) if record.pid != std::process::id() => {
+                        let _ = BUS.send(record.event);
+                    }
+                    Ok(_) => {}
+                    Err(e) => trace!(error = %e, "忽略无法解析的事件"),
+                }
+            }
+
+            let check_health = self.health.is_some_and(|interval| since_health >= interval);
+            if check_health || self.logs {
+                // 每轮重新读取配置，以便看到其他终端中的增删改
+                match Config::load().and_then(|config| Ok(config.list_tunnels()?.into_iter().cloned().collect::<Vec<_>>())) {
+                    Ok(tunnels) => {
+                        for tunnel in &tunnels {
+                            // 停用的连接不参与状态监视
+                            if check_health && tunnel.enabled {
+                                let status = tunnel.status();
+                                if let Some(previous) = statuses.insert(tunnel.alias.clone(), status)
+                                    && previous != status
+                                {
+                                    let _ = BUS.send(TunnelEvent::HealthChanged { alias: tunnel.alias.clone(), from: previous, to: status });
+                                }
+                            }
+                            if self.logs
+                                && let Ok(log_path) = tunnel.log_path()
+                            {
+                                let tail = logs.entry(tunnel.alias.clone()).or_insert_with(|| Tail::new(log_path));
+                                let redactor = Redactor::new(&tunnel.redact).unwrap_or_default();
+                                for line in tail.read_lines() {
+                                    let line = redactor.apply(&line).into_owned();
+                                    let _ = BUS.send(TunnelEvent::LogLine { alias: tunnel.alias.clone(), line });
+                                }
+                                // 额外端口的行归入所属的连接，与 get_logs 一样以 [alias@port] 开头以便区分
+                                for (member, log_path) in tunnel.member_logs() {
+                                    let tail = logs.entry(member.clone()).or_insert_with(|| Tail::new(log_path));
+                                    for line in tail.read_lines() {
+                                        let line = format!("[{}] {}", member, redactor.apply(&line));
+                                        let _ = BUS.send(TunnelEvent::LogLine { alias: tunnel.alias.clone(), line });
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    Err(e) => debug!(error = %e, "监视线程读取配置失败"),
+                }
+                if check_health {
+                    since_health = Duration::ZERO;
+                }
+            }
+
+            thread::sleep(FOLLOW_INTERVAL);
+            since_health = since_health.saturating_add(FOLLOW_INTERVAL);
+        }
+    }
+}
+
+/// 从上次读到的位置继续读取文件中新写入的完整行。
+/// 只在完整的行之后停下，因此上次位置的前一个字节不是换行时，说明文件被清空后重新写入，从头读取
+struct Tail {
+    path: PathBuf,
+    offset: u64,
+}
+
+impl Tail {
+    /// 只读取创建之后写入的内容
+    fn new(path: PathBuf) -> Self {
+        let mut tail = Self { path, offset: 0 };
+        if let Ok(content) = fs::read(&tail.path) {
+            tail.offset = content.iter().rposition(|b| *b == b'\n').map_or(0, |end| end as u64 + 1);
+        }
+        tail
+    }
+
+    fn read_lines(&mut self) -> Vec<String> {
+        match self.read() {
+            Ok(lines) => lines,
+            Err(e) => {
+                trace!(path = %self.path.display(), error = %e, "读取文件失败");
+                Vec::new()
+            }
+        }
+    }
+
+    fn read(&mut self) -> Result<Vec<String>> {
+        if !self.path.exists() {
+            self.offset = 0;
+            return Ok(Vec::new());
+        }
+        let mut file = fs::File::open(&self.path)?;
+        if self.offset > 0 {
+            let mut previous = [0u8];
+            file.seek(SeekFrom::Start(self.offset - 1))?;
+            if file.read_exact(&mut previous).is_err() || previous[0] != b'\n' {
+                self.offset = 0;
+            }
+        }
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        let Some(end) = bytes.iter().rposition(|b| *b == b'\n') else {
+            return Ok(Vec::new());
+        };
+        self.offset += end as u64 + 1;
+        Ok(String::from_utf8_lossy(&bytes[..end]).lines().map(str::to_string).collect())
+    }
+}
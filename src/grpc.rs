@@ -0,0 +1,281 @@
+//! `tfa grpc`：以 gRPC 提供控制接口（proto/tunnel_manager.proto），其他语言的工具可以直接列出、启动、停止连接
+//! 并订阅日志和事件，而不必调用命令行。
+//!
+//! 每个请求都重新读取配置，因此命令行中的修改立即可见；启动和停止与命令行一样写入操作历史并发布事件。
+//! 日志和事件流来自事件总线，由本进程的监视线程读取其他进程发布的事件和新写入的日志行。
+
+use anyhow::Result;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::time::Duration;
+use tokio_stream::wrappers::{BroadcastStream, TcpListenerStream};
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::{Stream, StreamExt};
+use tonic::transport::Server;
+use tonic::{Code, Request, Response, Status};
+
+use crate::apikey::{self, Denied, Scope};
+use crate::config::Config;
+use crate::control;
+use crate::error::{exit_code, TunnelError};
+use crate::events::{self, Monitor, TunnelEvent};
+use crate::handoff::{self, Shutdown};
+use crate::i18n::t;
+use crate::ipc;
+use crate::platform;
+use crate::reload;
+use crate::tunnel::{Tunnel, TunnelStatus};
+use tracing::warn;
+
+use pb::tunnel_manager_server::{TunnelManager, TunnelManagerServer};
+
+/// 由 proto/tunnel_manager.proto 生成的消息和服务
+pub mod pb {
+    tonic::include_proto!("tunnel_manager.v1");
+}
+
+/// 默认监听地址
+pub const DEFAULT_LISTEN: &str = "127.0.0.1:50051";
+
+/// 事件流中检测状态变化的间隔
+const HEALTH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// 在 `listen` 上提供控制接口，直到按下 Ctrl-C 或由新启动的服务接手
+pub async fn serve(listen: SocketAddr) -> Result<()> {
+    if !listen.ip().is_loopback() && Config::load()?.api_keys().is_empty() {
+        warn!("{}", t!("没有配置 API 密钥，gRPC 控制接口不检查认证，监听 {} 时其他机器也可以启动或停止连接", listen));
+    }
+    Monitor::new().health(HEALTH_INTERVAL).logs(true).spawn()?;
+    let (listener, _registration) = handoff::bind(handoff::Service::Grpc, listen).await?;
+    let _control = ipc::listen()
+        .map_err(|e| warn!("{}", t!("无法打开控制套接字，tfa log --follow 等命令改为自行读取日志文件: {}", e)))
+        .ok();
+    if let Err(e) = reload::watch() {
+        warn!("{}", t!("无法监视配置文件，修改后需要执行 tfa reload: {}", e));
+    }
+    eprintln!("{}", t!("gRPC 控制接口正在监听 {}，按 Ctrl-C 停止", listen));
+    let shutdown = Shutdown::default();
+    let server = Server::builder()
+        .add_service(TunnelManagerServer::new(Service))
+        .serve_with_incoming_shutdown(TcpListenerStream::new(listener), shutdown.clone().requested(handoff::Service::Grpc));
+    tokio::select! {
+        result = server => result?,
+        () = shutdown.drain_expired() => {}
+    }
+    Ok(())
+}
+
+struct Service;
+
+type ResponseStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl TunnelManager for Service {
+    async fn list_tunnels(&self, request: Request<pb::ListTunnelsRequest>) -> Result<Response<pb::ListTunnelsResponse>, Status> {
+        authorize(&request, Scope::Read).await?;
+        let patterns = request.into_inner().aliases;
+        let tunnels = blocking(move || {
+            let config = load()?;
+            if patterns.is_empty() {
+                return Ok(config.list_tunnels().map_err(status)?.into_iter().map(describe).collect());
+            }
+            config.expand_aliases(&patterns)
+                .map_err(status)?
+                .iter()
+                .map(|alias| find(&config, alias).map(describe))
+                .collect()
+        })
+        .await?;
+        Ok(Response::new(pb::ListTunnelsResponse { tunnels }))
+    }
+
+    async fn get_tunnel(&self, request: Request<pb::GetTunnelRequest>) -> Result<Response<pb::Tunnel>, Status> {
+        authorize(&request, Scope::Read).await?;
+        let alias = request.into_inner().alias;
+        blocking(move || find(&load()?, &alias).map(describe)).await.map(Response::new)
+    }
+
+    async fn start_tunnel(&self, request: Request<pb::StartTunnelRequest>) -> Result<Response<pb::Tunnel>, Status> {
+        authorize(&request, Scope::Operate).await?;
+        let request = request.into_inner();
+        blocking(move || {
+            let config = load()?;
+            let tunnel = find(&config, &request.alias)?;
+            control::start(&config, &request.alias).map_err(status)?;
+            if let Some(ttl) = request.ttl_seconds {
+                tunnel.set_ttl(Duration::from_secs(ttl)).map_err(status)?;
+            }
+            Ok(describe(tunnel))
+        })
+        .await
+        .map(Response::new)
+    }
+
+    async fn stop_tunnel(&self, request: Request<pb::StopTunnelRequest>) -> Result<Response<pb::Tunnel>, Status> {
+        // 强制停止可能结束不是由本工具启动的进程，需要 admin 权限
+        authorize(&request, if request.get_ref().force { Scope::Admin } else { Scope::Operate }).await?;
+        let request = request.into_inner();
+        blocking(move || {
+            let config = load()?;
+            let tunnel = find(&config, &request.alias)?;
+            control::stop(&config, &request.alias, request.force).map_err(status)?;
+            Ok(describe(tunnel))
+        })
+        .await
+        .map(Response::new)
+    }
+
+    type StreamLogsStream = ResponseStream<pb::LogLine>;
+
+    async fn stream_logs(&self, request: Request<pb::StreamLogsRequest>) -> Result<Response<Self::StreamLogsStream>, Status> {
+        authorize(&request, Scope::Read).await?;
+        let request = request.into_inner();
+        // 先订阅再读取已有的日志，以免两者之间写入的行丢失；代价是这些行可能重复出现一次
+        let events = BroadcastStream::new(events::subscribe());
+        let alias = request.alias.clone();
+        let lines = blocking(move || Ok(find(&load()?, &alias)?.get_logs())).await?;
+        let prefix = format!("[{}] ", request.alias);
+        let alias = request.alias.clone();
+        let history = tokio_stream::iter(lines).map(move |line| {
+            // 已有的行带有 [alias] 前缀，与实时推送的行保持一致而去掉；钩子日志的行保留前缀以便区分
+            let line = line.strip_prefix(&prefix).map_or(line.clone(), str::to_string);
+            Ok(pb::LogLine { alias: alias.clone(), line })
+        });
+        if !request.follow {
+            return Ok(Response::new(Box::pin(history)));
+        }
+        let alias = request.alias;
+        let follow = events.filter_map(move |event| match received(event)? {
+            TunnelEvent::LogLine { alias: from, line } if from == alias => Some(Ok(pb::LogLine { alias: from, line })),
+            _ => None,
+        });
+        Ok(Response::new(Box::pin(history.chain(follow))))
+    }
+
+    type StreamEventsStream = ResponseStream<pb::Event>;
+
+    async fn stream_events(&self, request: Request<pb::StreamEventsRequest>) -> Result<Response<Self::StreamEventsStream>, Status> {
+        authorize(&request, Scope::Read).await?;
+        let pb::StreamEventsRequest { aliases, logs } = request.into_inner();
+        let events = BroadcastStream::new(events::subscribe());
+        let aliases = blocking(move || {
+            let config = load()?;
+            let aliases = config.expand_aliases(&aliases).map_err(status)?;
+            for alias in &aliases {
+                find(&config, alias)?;
+            }
+            Ok(aliases)
+        })
+        .await?;
+        let stream = events.filter_map(move |event| {
+            let event = received(event)?;
+            let wanted = (aliases.is_empty() || aliases.iter().any(|alias| alias == event.alias()))
+                && (logs || !matches!(event, TunnelEvent::LogLine { .. }));
+            wanted.then(|| Ok(event_message(event)))
+        });
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+/// 配置了 API 密钥时，检查请求元数据 `authorization: Bearer <密钥>` 的权限范围；没有任何密钥时不检查
+async fn authorize<T>(request: &Request<T>, required: Scope) -> Result<(), Status> {
+    let secret = request.metadata()
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::to_string);
+    blocking(move || {
+        let config = load()?;
+        if config.api_keys().is_empty() {
+            return Ok(());
+        }
+        apikey::authorize(config.api_keys(), secret.as_deref(), required).map_err(|denied| match denied {
+            Denied::Unknown => Status::unauthenticated(denied.to_string()),
+            Denied::Insufficient { .. } => Status::permission_denied(denied.to_string()),
+        })
+    })
+    .await
+}
+
+/// 在阻塞线程中处理请求：读取配置、启动和停止连接都会阻塞
+async fn blocking<T: Send + 'static>(f: impl FnOnce() -> Result<T, Status> + Send + 'static) -> Result<T, Status> {
+    tokio::task::spawn_blocking(f).await.map_err(|e| Status::internal(e.to_string()))?
+}
+
+fn load() -> Result<Config, Status> {
+    Config::load().map_err(status)
+}
+
+fn find<'a>(config: &'a Config, alias: &str) -> Result<&'a Tunnel, Status> {
+    config.get_tunnel(alias).map_err(|e| Status::not_found(e.to_string()))
+}
+
+/// 把错误转换为 gRPC 状态：状态码按与退出码相同的分类选择，消息中附带修复建议
+fn status(error: anyhow::Error) -> Status {
+    let Some(tunnel_error) = error.downcast_ref::<TunnelError>() else {
+        return Status::internal(error.to_string());
+    };
+    let code = match tunnel_error.exit_code() {
+        _ if matches!(tunnel_error, TunnelError::SystemTunnel { .. }) => Code::PermissionDenied,
+        exit_code::CONFIG => Code::InvalidArgument,
+        exit_code::PROGRAM_NOT_FOUND | exit_code::PORT_IN_USE | exit_code::AUTH_REQUIRED | exit_code::HOOK_FAILED => Code::FailedPrecondition,
+        exit_code::TIMEOUT => Code::DeadlineExceeded,
+        exit_code::START_FAILED => Code::Unavailable,
+        _ => Code::Internal,
+    };
+    let message = match tunnel_error.hint() {
+        Some(hint) => format!("{}\n{}", tunnel_error, hint),
+        None => tunnel_error.to_string(),
+    };
+    Status::new(code, message)
+}
+
+/// 取出广播的事件；来不及处理而被跳过的事件只记录下来
+fn received(event: Result<TunnelEvent, BroadcastStreamRecvError>) -> Option<TunnelEvent> {
+    match event {
+        Ok(event) => Some(event),
+        Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+            warn!("{}", t!("处理不及，跳过了 {} 个事件", skipped));
+            None
+        }
+    }
+}
+
+fn describe(tunnel: &Tunnel) -> pb::Tunnel {
+    pb::Tunnel {
+        alias: tunnel.alias.clone(),
+        source: tunnel.source.clone(),
+        local_address: SocketAddr::new(tunnel.listen_ip(), tunnel.port).to_string(),
+        provider: tunnel.provider.label().to_string(),
+        status: status_message(tunnel.status()).into(),
+        enabled: tunnel.enabled,
+        tags: tunnel.tags.clone(),
+        uptime_seconds: tunnel.uptime(),
+        system: tunnel.system,
+    }
+}
+
+fn status_message(status: TunnelStatus) -> pb::TunnelStatus {
+    match status {
+        TunnelStatus::Running => pb::TunnelStatus::Running,
+        TunnelStatus::Degraded => pb::TunnelStatus::Degraded,
+        TunnelStatus::Failed => pb::TunnelStatus::Failed,
+        TunnelStatus::Paused => pb::TunnelStatus::Paused,
+        TunnelStatus::Stopped => pb::TunnelStatus::Stopped,
+    }
+}
+
+fn event_message(event: TunnelEvent) -> pb::Event {
+    use pb::event::Kind;
+    let (alias, kind) = match event {
+        TunnelEvent::Started { alias } => (alias, Kind::Started(pb::Started {})),
+        TunnelEvent::Stopped { alias } => (alias, Kind::Stopped(pb::Stopped {})),
+        TunnelEvent::Crashed { alias, error } => (alias, Kind::Crashed(pb::Crashed { error })),
+        TunnelEvent::HealthChanged { alias, from, to } => (
+            alias,
+            Kind::HealthChanged(pb::HealthChanged { from: status_message(from).into(), to: status_message(to).into() }),
+        ),
+        TunnelEvent::LogLine { alias, line } => (alias.clone(), Kind::LogLine(pb::LogLine { alias, line })),
+    };
+    pb::Event { time: platform::unix_now(), alias, kind: Some(kind) }
+}
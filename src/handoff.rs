@@ -0,0 +1,197 @@
+//! `tfa grpc`、`tfa web` 的无中断交接：升级 tfa 后再次启动同一服务时，新进程接手旧进程的监听地址
+//! 和各连接的后台进程，连接本身不会中断，客户端也不会遇到连接被拒绝。
+//!
+//! 服务启动时在状态目录中登记 `<服务>.service.json`（PID、启动时间、监听地址）。Unix 上监听套接字启用
+//! SO_REUSEPORT，新进程可以在旧进程仍在监听时绑定同一地址；新进程绑定后改写登记，旧进程发现登记已不是自己时
+//! 停止接受新请求，等待已建立的请求结束（最多 [`DRAIN_TIMEOUT`]）后退出。Windows 不支持端口复用，
+//! 新进程先改写登记，等旧进程释放地址后再绑定，期间会有短暂的中断。
+//!
+//! 后端进程和日志过滤进程与服务无关，不需要交接；有效期监视与延迟记录进程由新版本重新启动，
+//! 它们在状态目录中登记后，旧版本的进程随之退出（见 [`WatcherMark`](crate::state::WatcherMark)）。
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::ErrorKind;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::{TcpListener, TcpSocket};
+use tokio::sync::Notify;
+
+use crate::config::Config;
+use crate::i18n::t;
+use crate::platform;
+use crate::state::{self, StateStore};
+use tracing::{debug, info, warn};
+
+/// 旧进程检查登记的间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// 不能复用端口时等待旧进程释放地址的最长时间
+const RELEASE_TIMEOUT: Duration = Duration::from_secs(10);
+/// 停止接受新请求后等待已建立的请求（事件流、日志流等）结束的最长时间
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 可以交接的服务
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Service {
+    Grpc,
+    Web,
+}
+
+impl Service {
+    fn name(self) -> &'static str {
+        match self {
+            Service::Grpc => "grpc",
+            Service::Web => "web",
+        }
+    }
+}
+
+/// 状态目录中的服务登记
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ServiceMark {
+    pid: u32,
+    started_at: u64,
+    listen: SocketAddr,
+}
+
+impl ServiceMark {
+    fn path(service: Service) -> Result<PathBuf> {
+        StateStore::path(service.name(), ".service.json")
+    }
+
+    fn load(service: Service) -> Option<Self> {
+        let content = fs::read_to_string(Self::path(service).ok()?).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    fn claim(service: Service, listen: SocketAddr) -> Result<()> {
+        let pid = std::process::id();
+        let mark = Self { pid, started_at: platform::process_start_time(pid).unwrap_or_else(platform::unix_now), listen };
+        StateStore::write(&Self::path(service)?, serde_json::to_string(&mark)?.as_bytes())
+    }
+
+    fn is_mine(service: Service) -> bool {
+        Self::load(service).is_some_and(|mark| mark.pid == std::process::id())
+    }
+
+    fn is_alive(&self) -> bool {
+        state::process_matches(self.pid, self.started_at)
+    }
+}
+
+/// 本进程的登记，释放时删除；已被新进程接手时保留新进程的登记
+pub struct Registration {
+    service: Service,
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        if ServiceMark::is_mine(self.service)
+            && let Ok(path) = ServiceMark::path(self.service)
+        {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// 绑定服务的监听地址并登记本进程；同一服务的旧进程仍在监听该地址时接手它
+pub async fn bind(service: Service, listen: SocketAddr) -> Result<(TcpListener, Registration)> {
+    let previous = ServiceMark::load(service)
+        .filter(|mark| mark.pid != std::process::id() && mark.listen == listen && mark.is_alive());
+    let listener = match (reusable_bind(listen), &previous) {
+        (Ok(listener), _) => listener,
+        // 不支持端口复用，或旧版本没有启用复用：先让旧进程停止监听，再绑定
+        (Err(e), Some(previous)) if e.kind() == ErrorKind::AddrInUse => {
+            debug!(pid = previous.pid, "无法复用端口，等待旧进程释放地址");
+            ServiceMark::claim(service, listen)?;
+            wait_release(listen).await?
+        }
+        (Err(e), _) => return Err(e.into()),
+    };
+    ServiceMark::claim(service, listen)?;
+    if let Some(previous) = previous {
+        eprintln!("{}", t!("已接手 PID {} 的服务，它会在处理完已建立的请求后退出", previous.pid));
+        respawn_watchers();
+    }
+    Ok((listener, Registration { service }))
+}
+
+/// 绑定地址；Unix 上启用端口复用，新旧进程可以同时监听
+fn reusable_bind(listen: SocketAddr) -> std::io::Result<TcpListener> {
+    let socket = if listen.is_ipv4() { TcpSocket::new_v4()? } else { TcpSocket::new_v6()? };
+    // Windows 的 SO_REUSEADDR 允许抢占其他进程正在监听的端口，不能启用
+    #[cfg(unix)]
+    {
+        socket.set_reuseaddr(true)?;
+        socket.set_reuseport(true)?;
+    }
+    socket.bind(listen)?;
+    socket.listen(1024)
+}
+
+async fn wait_release(listen: SocketAddr) -> Result<TcpListener> {
+    let deadline = Instant::now() + RELEASE_TIMEOUT;
+    loop {
+        match reusable_bind(listen) {
+            Ok(listener) => return Ok(listener),
+            Err(e) if e.kind() == ErrorKind::AddrInUse && Instant::now() < deadline => {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// 以新版本重新启动运行中连接的后台进程，旧版本的进程发现登记被取代后退出
+fn respawn_watchers() {
+    let Ok(config) = Config::load() else {
+        return;
+    };
+    for tunnel in config.list_tunnels().unwrap_or_default() {
+        match tunnel.respawn_watchers() {
+            Ok(respawned) if !respawned.is_empty() => {
+                info!(alias = %tunnel.alias, "由新进程接手后台进程 {}", respawned.join(", "));
+            }
+            Ok(_) => {}
+            Err(e) => warn!(alias = %tunnel.alias, "{}", t!("无法接手后台进程: {}", e)),
+        }
+    }
+}
+
+/// 服务的停止信号：按下 Ctrl-C 或被新进程接手
+#[derive(Clone, Default)]
+pub struct Shutdown {
+    stopping: Arc<Notify>,
+}
+
+impl Shutdown {
+    /// 收到停止信号时结束，传给服务的优雅退出
+    pub async fn requested(self, service: Service) {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            () = superseded(service) => {}
+        }
+        self.stopping.notify_one();
+    }
+
+    /// 收到停止信号后再过 [`DRAIN_TIMEOUT`] 结束，届时不再等待未结束的请求
+    pub async fn drain_expired(self) {
+        self.stopping.notified().await;
+        tokio::time::sleep(DRAIN_TIMEOUT).await;
+        warn!("{}", t!("等待 {} 秒后仍有未结束的请求，不再等待", DRAIN_TIMEOUT.as_secs()));
+    }
+}
+
+async fn superseded(service: Service) {
+    loop {
+        tokio::time::sleep(POLL_INTERVAL).await;
+        if !ServiceMark::is_mine(service) {
+            let pid = ServiceMark::load(service).map(|mark| mark.pid).unwrap_or_default();
+            eprintln!("{}", t!("服务已由 PID {} 接手，不再接受新请求", pid));
+            return;
+        }
+    }
+}
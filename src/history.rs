@@ -0,0 +1,118 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::platform;
+use tracing::debug;
+
+/// 记录到历史中的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryAction {
+    Start,
+    Stop,
+    Add,
+    Remove,
+    Set,
+}
+
+impl fmt::Display for HistoryAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HistoryAction::Start => write!(f, "start"),
+            HistoryAction::Stop => write!(f, "stop"),
+            HistoryAction::Add => write!(f, "add"),
+            HistoryAction::Remove => write!(f, "remove"),
+            HistoryAction::Set => write!(f, "set"),
+        }
+    }
+}
+
+/// 历史文件中的一行
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Entry {
+    pub time: u64,
+    pub user: String,
+    pub action: HistoryAction,
+    pub alias: String,
+    /// 修改了哪些字段等补充说明
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// 失败时的错误信息，成功时为 None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 追加一条操作记录。历史只用于事后查看，写入失败不影响操作本身
+pub fn record<T, E: fmt::Display>(action: HistoryAction, alias: &str, detail: Option<String>, result: &Result<T, E>) {
+    let entry = Entry {
+        time: platform::unix_now(),
+        user: current_user(),
+        action,
+        alias: alias.to_string(),
+        detail,
+        error: result.as_ref().err().map(|e| e.to_string()),
+    };
+    if let Err(e) = append(&entry) {
+        debug!(error = %e, "写入操作历史失败");
+    }
+}
+
+fn append(entry: &Entry) -> Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path()?)?;
+    writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    Ok(())
+}
+
+/// 最近的操作记录，按时间先后排列；可只看某个连接，`limit` 为 0 时不限条数
+pub fn load(alias: Option<&str>, limit: usize) -> Result<Vec<Entry>> {
+    let path = path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let mut entries: Vec<Entry> = fs::read_to_string(path)?
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .filter(|entry: &Entry| alias.is_none_or(|alias| entry.alias == alias))
+        .collect();
+    if limit > 0 && entries.len() > limit {
+        entries.drain(..entries.len() - limit);
+    }
+    Ok(entries)
+}
+
+fn path() -> Result<PathBuf> {
+    Ok(Config::config_dir()?.join("history.jsonl"))
+}
+
+/// 执行操作的用户；通过 sudo 执行时记录原用户
+pub fn current_user() -> String {
+    ["SUDO_USER", "USER", "USERNAME"].iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// 把 Unix 时间格式化为 `YYYY-MM-DD HH:MM:SS`（UTC）
+pub fn format_time(secs: u64) -> String {
+    let days = (secs / 86400) as i64;
+    let rem = secs % 86400;
+    // 由天数换算公历日期（Howard Hinnant 的 civil_from_days）
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year, month, day, rem / 3600, rem % 3600 / 60, rem % 60
+    )
+}
@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use crate::config::Config;
+use crate::error::TunnelError;
+use crate::i18n::t;
+use crate::platform;
+use crate::tunnel::Tunnel;
+use tracing::{info, warn};
+
+/// 失败时附带的输出行数
+const FAILURE_OUTPUT_LINES: usize = 10;
+
+/// 连接生命周期中执行的脚本命令，通过系统 shell 执行
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Hooks {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_start: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_start: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_stop: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_stop: Option<String>,
+    /// 连接反复失败而熔断时执行，用于发送通知
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_failure: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum HookKind {
+    PreStart,
+    PostStart,
+    PreStop,
+    PostStop,
+    OnFailure,
+}
+
+impl fmt::Display for HookKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HookKind::PreStart => write!(f, "pre_start"),
+            HookKind::PostStart => write!(f, "post_start"),
+            HookKind::PreStop => write!(f, "pre_stop"),
+            HookKind::PostStop => write!(f, "post_stop"),
+            HookKind::OnFailure => write!(f, "on_failure"),
+        }
+    }
+}
+
+impl Hooks {
+    pub fn is_empty(&self) -> bool {
+        *self == Hooks::default()
+    }
+
+    fn get(&self, kind: HookKind) -> Option<&str> {
+        match kind {
+            HookKind::PreStart => self.pre_start.as_deref(),
+            HookKind::PostStart => self.post_start.as_deref(),
+            HookKind::PreStop => self.pre_stop.as_deref(),
+            HookKind::PostStop => self.post_stop.as_deref(),
+            HookKind::OnFailure => self.on_failure.as_deref(),
+        }
+    }
+
+    /// 用 `update` 中给出的命令覆盖当前设置；空字符串表示删除该钩子
+    pub fn merge(&mut self, update: Hooks) {
+        for (current, new) in [
+            (&mut self.pre_start, update.pre_start),
+            (&mut self.post_start, update.post_start),
+            (&mut self.pre_stop, update.pre_stop),
+            (&mut self.post_stop, update.post_stop),
+            (&mut self.on_failure, update.on_failure),
+        ] {
+            if let Some(command) = new {
+                *current = Some(command).filter(|c| !c.trim().is_empty());
+            }
+        }
+    }
+}
+
+/// 钩子输出追加写入的日志文件
+pub fn log_path(alias: &str) -> anyhow::Result<PathBuf> {
+    let mut path = Config::runtime_dir()?;
+    path.push(format!("{}.hooks.log", alias));
+    Ok(path)
+}
+
+/// 执行连接的钩子，输出追加到钩子日志中；未配置时直接返回成功。
+/// 通过环境变量 TUNNEL_ALIAS、TUNNEL_HOST、TUNNEL_PORT、TUNNEL_HOSTNAME、TUNNEL_HOOK 传递上下文。
+pub fn run(tunnel: &Tunnel, kind: HookKind) -> Result<(), TunnelError> {
+    run_with_env(tunnel, kind, &[])
+}
+
+/// 与 `run` 相同，并额外设置 `env` 中的环境变量
+pub fn run_with_env(tunnel: &Tunnel, kind: HookKind, env: &[(&str, &str)]) -> Result<(), TunnelError> {
+    let Some(script) = tunnel.hooks.get(kind) else {
+        return Ok(());
+    };
+
+    info!(alias = %tunnel.alias, hook = %kind, "执行钩子: {}", script);
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.arg("/C").arg(script);
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(script);
+        command
+    };
+    let output = command
+        .env("TUNNEL_ALIAS", &tunnel.alias)
+        .env("TUNNEL_HOST", tunnel.local_host())
+        .env("TUNNEL_PORT", tunnel.port.to_string())
+        .env("TUNNEL_HOSTNAME", &tunnel.source)
+        .env("TUNNEL_HOOK", kind.to_string())
+        .envs(env.iter().copied())
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| TunnelError::HookFailed {
+            hook: kind.to_string(),
+            output: e.to_string(),
+        })?;
+
+    let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+    append_log(&tunnel.alias, kind, &text, output.status.success());
+
+    if output.status.success() {
+        return Ok(());
+    }
+    warn!(alias = %tunnel.alias, hook = %kind, status = %output.status, "{}", t!("钩子执行失败"));
+    let lines: Vec<&str> = text.lines().collect();
+    let tail = lines[lines.len().saturating_sub(FAILURE_OUTPUT_LINES)..].join("\n");
+    Err(TunnelError::HookFailed {
+        hook: kind.to_string(),
+        output: if tail.is_empty() { output.status.to_string() } else { tail },
+    })
+}
+
+fn append_log(alias: &str, kind: HookKind, output: &str, success: bool) {
+    let Ok(path) = log_path(alias) else {
+        return;
+    };
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+        let _ = writeln!(
+            file,
+            "--- {} {} ({}) ---",
+            platform::unix_now(),
+            kind,
+            if success { t!("成功") } else { t!("失败") }
+        );
+        let _ = file.write_all(output.as_bytes());
+    }
+}
@@ -0,0 +1,125 @@
+use anyhow::Result;
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use crate::i18n::t;
+use crate::platform;
+use tracing::debug;
+
+/// hosts 文件中由本工具管理的区块的起止标记，区块之外的内容保持不变
+const BEGIN: &str = "# BEGIN tfa";
+const END: &str = "# END tfa";
+
+fn hosts_path() -> PathBuf {
+    if cfg!(windows) {
+        let root = std::env::var("SystemRoot").unwrap_or_else(|_| r"C:\Windows".to_string());
+        PathBuf::from(root).join(r"System32\drivers\etc\hosts")
+    } else {
+        PathBuf::from("/etc/hosts")
+    }
+}
+
+/// 把主机名指向连接的本地地址；已有相同记录时不改动文件
+pub fn map(hostname: &str, ip: IpAddr) -> Result<()> {
+    update(hostname, Some(ip))
+}
+
+/// 删除主机名的记录；没有记录时不改动文件
+pub fn unmap(hostname: &str) -> Result<()> {
+    update(hostname, None)
+}
+
+fn update(hostname: &str, ip: Option<IpAddr>) -> Result<()> {
+    let path = hosts_path();
+    let content = fs::read_to_string(&path).unwrap_or_default();
+    let (before, mut entries, after) = split(&content);
+    let existing = entries.iter().position(|(_, host)| host == hostname);
+    match (existing, ip) {
+        (Some(index), Some(ip)) if entries[index].0 == ip.to_string() => return Ok(()),
+        (Some(index), Some(ip)) => entries[index].0 = ip.to_string(),
+        (None, Some(ip)) => entries.push((ip.to_string(), hostname.to_string())),
+        (Some(index), None) => {
+            entries.remove(index);
+        }
+        (None, None) => return Ok(()),
+    }
+
+    let mut updated = before;
+    if !entries.is_empty() {
+        if !updated.is_empty() && !updated.ends_with('\n') {
+            updated.push('\n');
+        }
+        updated.push_str(BEGIN);
+        updated.push('\n');
+        for (ip, host) in &entries {
+            updated.push_str(&format!("{}\t{}\n", ip, host));
+        }
+        updated.push_str(END);
+        updated.push('\n');
+    }
+    updated.push_str(&after);
+    debug!(path = %path.display(), hostname, ?ip, "更新 hosts 文件");
+    write(&path, &updated)
+}
+
+/// 拆分为区块之前的内容、区块中的记录（地址、主机名）和区块之后的内容
+fn split(content: &str) -> (String, Vec<(String, String)>, String) {
+    let mut before = String::new();
+    let mut after = String::new();
+    let mut entries = Vec::new();
+    // 0：区块之前，1：区块之中，2：区块之后
+    let mut section = 0;
+    for line in content.lines() {
+        match (section, line.trim()) {
+            (0, BEGIN) => section = 1,
+            (1, END) => section = 2,
+            (1, entry) => {
+                let mut fields = entry.split_whitespace();
+                if let (Some(ip), Some(host)) = (fields.next(), fields.next()) {
+                    entries.push((ip.to_string(), host.to_string()));
+                }
+            }
+            (0, _) => {
+                before.push_str(line);
+                before.push('\n');
+            }
+            _ => {
+                after.push_str(line);
+                after.push('\n');
+            }
+        }
+    }
+    (before, entries, after)
+}
+
+/// 没有写入权限时，在交互终端中通过 sudo 写入（由 sudo 提示输入密码）
+fn write(path: &PathBuf, content: &str) -> Result<()> {
+    match fs::write(path, content) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == ErrorKind::PermissionDenied && !cfg!(windows) && platform::is_interactive() => {
+            eprintln!("{}", t!("需要管理员权限来更新 {}", path.display()));
+            let mut child = Command::new("sudo")
+                .arg("tee")
+                .arg(path)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .spawn()?;
+            if let Some(mut stdin) = child.stdin.take() {
+                stdin.write_all(content.as_bytes())?;
+            }
+            if child.wait()?.success() {
+                Ok(())
+            } else {
+                Err(anyhow::anyhow!(t!("无法通过 sudo 更新 {}", path.display())))
+            }
+        }
+        Err(e) if e.kind() == ErrorKind::PermissionDenied => Err(anyhow::anyhow!(t!(
+            "没有权限更新 {}，请以管理员身份运行",
+            path.display()
+        ))),
+        Err(e) => Err(e.into()),
+    }
+}
@@ -0,0 +1,171 @@
+use anyhow::Result;
+use std::net::SocketAddr;
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::process::Command;
+
+use crate::i18n::t;
+
+/// 请求头的最大长度，超过时拒绝请求
+const MAX_HEAD_SIZE: usize = 64 * 1024;
+
+/// 不转发给上游的请求头：由 curl 按目标地址重新生成，或只对本地这一跳有意义
+const HOP_HEADERS: &[&str] = &[
+    "host", "connection", "keep-alive", "proxy-connection", "proxy-authorization",
+    "te", "trailer", "upgrade", "content-length", "expect", "cf-access-token",
+];
+
+/// 本地 HTTP 反向代理：把收到的请求经 curl 转发到 `https://<host>`，并附带
+/// `cloudflared access token` 取得的 `cf-access-token` 请求头。每个连接只处理一个请求，
+/// 应答以关闭连接结束
+pub async fn serve(host: &str, listen: SocketAddr) -> Result<()> {
+    let listener = TcpListener::bind(listen).await?;
+    // 日志中的这一行同时表示已经就绪
+    eprintln!("http proxy listening on {} for https://{}", listen, host);
+    loop {
+        let (client, _) = listener.accept().await?;
+        let host = host.to_string();
+        tokio::spawn(async move {
+            if let Err(e) = handle(client, &host).await {
+                eprintln!("{}", e);
+            }
+        });
+    }
+}
+
+async fn handle(client: TcpStream, host: &str) -> Result<()> {
+    let (read, mut write) = client.into_split();
+    let mut reader = BufReader::new(read);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return respond(&mut write, 400, "bad request").await;
+    };
+    let (method, target) = (method.to_string(), target.to_string());
+
+    let mut headers: Vec<(String, String)> = Vec::new();
+    let mut head_size = request_line.len();
+    let mut content_length = 0usize;
+    let mut chunked = false;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 {
+            break;
+        }
+        head_size += line.len();
+        if head_size > MAX_HEAD_SIZE {
+            return respond(&mut write, 431, "request header too large").await;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            let (name, value) = (name.trim(), value.trim());
+            match name.to_ascii_lowercase().as_str() {
+                "content-length" => content_length = value.parse().unwrap_or(0),
+                "transfer-encoding" => chunked = value.eq_ignore_ascii_case("chunked"),
+                _ => {}
+            }
+            headers.push((name.to_string(), value.to_string()));
+        }
+    }
+    if chunked {
+        return respond(&mut write, 411, "chunked request bodies are not supported").await;
+    }
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body).await?;
+
+    let url = format!("https://{}", host);
+    let token = match access_token(&url).await {
+        Ok(token) => token,
+        Err(e) => {
+            eprintln!("{} {} -> {}", method, target, e);
+            return respond(&mut write, 502, &e.to_string()).await;
+        }
+    };
+
+    let path = if target.starts_with('/') { target.clone() } else { format!("/{}", target) };
+    let mut curl = Command::new("curl");
+    curl.args(["-sS", "-i", "--http1.1", "-X", &method, "-H", "Expect:"])
+        .arg("-H").arg(format!("cf-access-token: {}", token));
+    for (name, value) in headers.iter().filter(|(name, _)| !HOP_HEADERS.contains(&name.to_ascii_lowercase().as_str())) {
+        curl.arg("-H").arg(format!("{}: {}", name, value));
+    }
+    if !body.is_empty() {
+        curl.args(["--data-binary", "@-"]);
+    }
+    let mut child = curl
+        .arg(format!("{}{}", url, path))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| anyhow::anyhow!(t!("无法执行 curl: {}", e)))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(&body).await?;
+    }
+
+    let Some(stdout) = child.stdout.take() else {
+        return respond(&mut write, 502, "curl produced no output").await;
+    };
+    let mut upstream = BufReader::new(stdout);
+    let mut status_line = String::new();
+    if upstream.read_line(&mut status_line).await? == 0 {
+        let output = child.wait_with_output().await?;
+        let message = String::from_utf8_lossy(&output.stderr).trim().trim_start_matches("curl: ").to_string();
+        eprintln!("{} {} -> {}", method, target, message);
+        return respond(&mut write, 502, &message).await;
+    }
+    eprintln!("{} {} -> {}", method, target, status_line.split_whitespace().nth(1).unwrap_or("?"));
+
+    // curl 已解开分块编码，改为以关闭连接结束应答
+    write.write_all(status_line.as_bytes()).await?;
+    loop {
+        let mut line = String::new();
+        if upstream.read_line(&mut line).await? == 0 || line.trim_end().is_empty() {
+            break;
+        }
+        let name = line.split(':').next().unwrap_or_default().trim().to_ascii_lowercase();
+        if name != "transfer-encoding" && name != "connection" {
+            write.write_all(line.as_bytes()).await?;
+        }
+    }
+    write.write_all(b"Connection: close\r\n\r\n").await?;
+    tokio::io::copy(&mut upstream, &mut write).await?;
+    write.shutdown().await?;
+    child.wait().await?;
+    Ok(())
+}
+
+/// 取得 Access 令牌；尚未登录时提示先运行 `cloudflared access login`
+async fn access_token(url: &str) -> Result<String> {
+    let output = Command::new("cloudflared")
+        .args(["access", "token", &format!("-app={}", url)])
+        .output()
+        .await
+        .map_err(|e| anyhow::anyhow!(t!("无法执行 cloudflared: {}", e)))?;
+    let token = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !output.status.success() || token.is_empty() {
+        return Err(anyhow::anyhow!(t!("没有 {} 的 Access 令牌，请先运行 cloudflared access login {}", url, url)));
+    }
+    Ok(token)
+}
+
+/// 代理自身产生的错误应答
+async fn respond(write: &mut (impl AsyncWriteExt + Unpin), status: u16, message: &str) -> Result<()> {
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}\n",
+        status,
+        if status == 502 { "Bad Gateway" } else { "Error" },
+        message.len() + 1,
+        message
+    );
+    write.write_all(response.as_bytes()).await?;
+    write.shutdown().await?;
+    Ok(())
+}
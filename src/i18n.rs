@@ -0,0 +1,140 @@
+//! 界面文字的本地化。源代码中的中文文本即消息 ID，其他语言的目录把它映射为译文，
+//! 缺少译文时原样输出中文。
+
+mod en_us;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// 界面语言
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+pub enum Lang {
+    #[default]
+    #[serde(rename = "zh-CN")]
+    #[value(name = "zh-CN")]
+    ZhCn,
+    #[serde(rename = "en-US")]
+    #[value(name = "en-US")]
+    EnUs,
+}
+
+impl fmt::Display for Lang {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Lang::ZhCn => write!(f, "zh-CN"),
+            Lang::EnUs => write!(f, "en-US"),
+        }
+    }
+}
+
+impl Lang {
+    /// 从 `zh_CN.UTF-8`、`en_US`、`en` 这类 locale 名称中识别语言
+    pub fn from_locale(locale: &str) -> Option<Lang> {
+        let language = locale.split(['_', '-', '.', '@']).next()?.to_ascii_lowercase();
+        match language.as_str() {
+            "zh" => Some(Lang::ZhCn),
+            "en" => Some(Lang::EnUs),
+            _ => None,
+        }
+    }
+
+    /// 按 LC_ALL、LC_MESSAGES、LANG 的顺序从环境变量中识别语言
+    pub fn from_env() -> Option<Lang> {
+        ["LC_ALL", "LC_MESSAGES", "LANG"].iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Lang::from_locale(&value))
+    }
+
+    fn catalog(self) -> Option<&'static HashMap<&'static str, &'static str>> {
+        static EN_US: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
+        match self {
+            Lang::ZhCn => None,
+            Lang::EnUs => Some(EN_US.get_or_init(|| en_us::MESSAGES.iter().copied().collect())),
+        }
+    }
+}
+
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+/// 设置本进程的界面语言
+pub fn set(lang: Lang) {
+    CURRENT.store(lang as u8, Ordering::Relaxed);
+}
+
+pub fn current() -> Lang {
+    match CURRENT.load(Ordering::Relaxed) {
+        1 => Lang::EnUs,
+        _ => Lang::ZhCn,
+    }
+}
+
+/// 查找消息的译文，没有时返回原文
+pub fn translate(msgid: &str) -> &str {
+    current().catalog()
+        .and_then(|catalog| catalog.get(msgid).copied())
+        .unwrap_or(msgid)
+}
+
+/// 用参数替换译文中的占位符：`{}` 依次取下一个参数，`{0}`、`{1}` 按位置引用，便于译文调整语序
+pub fn format(template: &str, args: &[&dyn fmt::Display]) -> String {
+    let mut text = String::with_capacity(template.len());
+    let mut next = 0;
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        text.push_str(&rest[..start]);
+        let Some(len) = rest[start..].find('}') else {
+            rest = &rest[start..];
+            break;
+        };
+        let placeholder = &rest[start + 1..start + len];
+        let index = if placeholder.is_empty() {
+            next += 1;
+            Some(next - 1)
+        } else {
+            placeholder.parse::<usize>().ok()
+        };
+        match index.and_then(|index| args.get(index)) {
+            Some(arg) => text.push_str(&arg.to_string()),
+            None => text.push_str(&rest[start..=start + len]),
+        }
+        rest = &rest[start + len + 1..];
+    }
+    text.push_str(rest);
+    text
+}
+
+/// 本地化命令行帮助：按当前语言翻译各命令与参数的说明
+pub fn localize_command(mut command: clap::Command) -> clap::Command {
+    if current() == Lang::ZhCn {
+        return command;
+    }
+    if let Some(about) = command.get_about().map(|about| about.to_string()) {
+        command = command.about(translate(&about).to_string());
+    }
+    command = command.mut_args(|arg| match arg.get_help().map(|help| help.to_string()) {
+        Some(help) => arg.help(translate(&help).to_string()),
+        None => arg,
+    });
+    let names: Vec<String> = command.get_subcommands().map(|sub| sub.get_name().to_string()).collect();
+    for name in names {
+        command = command.mut_subcommand(name, localize_command);
+    }
+    command
+}
+
+/// 按当前语言输出消息：`t!("未找到连接 {}", alias)`
+macro_rules! t {
+    ($msgid:literal) => {
+        $crate::i18n::translate($msgid).to_string()
+    };
+    ($msgid:literal, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::format($crate::i18n::translate($msgid), &[$(&$arg as &dyn std::fmt::Display),+])
+    };
+}
+
+pub(crate) use t;
@@ -0,0 +1,1059 @@
+//! en-US 目录：中文原文 => 英文译文。新增界面文字时在这里补充译文
+
+pub const MESSAGES: &[(&str, &str)] = &[
+    // 命令行帮助
+    ("Tunnel 连接管理器", "Tunnel connection manager"),
+    ("输出更多内部日志，可重复使用（-v、-vv、-vvv）；设置 RUST_LOG 时以其为准", "Print more internal logs, may be repeated (-v, -vv, -vvv); RUST_LOG takes precedence when set"),
+    ("只输出错误", "Only print errors"),
+    ("内部日志的格式；json 时每条日志输出一行 JSON，便于 journald、Fluent Bit 采集", "Internal log format; json prints one JSON object per line for journald or Fluent Bit ingestion"),
+    ("便于阅读的文本", "Human-readable text"),
+    ("每行一个 JSON 对象", "One JSON object per line"),
+    ("界面语言，优先于配置文件和 LANG 环境变量", "Interface language, overrides the config file and the LANG environment variable"),
+    ("列出所有连接", "List all tunnels"),
+    ("排序方式", "Sort order"),
+    ("只显示指定状态的连接", "Only show tunnels in the given state"),
+    ("按间隔（秒）持续刷新，高亮状态发生变化的连接", "Refresh every SECONDS and highlight tunnels whose state changed"),
+    ("运行选定的连接", "Start the selected tunnels"),
+    ("启动全部连接（不再交互选择）", "Start all tunnels (no interactive selection)"),
+    ("启动带有该标签的全部连接（不再交互选择）", "Start all tunnels with this tag (no interactive selection)"),
+    ("只启动当前项目 .cf-manager.toml 中声明的连接（不再交互选择）", "Only start the tunnels declared in the current project's .cf-manager.toml (no interactive selection)"),
+    ("同时启动的连接数上限", "Maximum number of tunnels started at the same time"),
+    ("只按启动顺序输出将要执行的命令，不启动任何连接", "Print the commands that would run, in start order, without starting anything"),
+    ("输出启动连接时将要执行的完整命令、环境变量和工作目录", "Print the full command, environment and working directory used to start a tunnel"),
+    ("连接别名", "Tunnel alias"),
+    ("停止选定的连接", "Stop the selected tunnels"),
+    ("同时结束端口上不是由本工具启动的 cloudflared / kubectl 进程", "Also kill cloudflared / kubectl processes on the port that were not started by this tool"),
+    ("查看连接日志", "Show tunnel logs"),
+    ("设置连接参数", "Change tunnel settings"),
+    ("源地址", "Source hostname"),
+    ("本地端口", "Local port"),
+    ("是否启用统计代理", "Whether to enable the statistics proxy"),
+    ("等待连接就绪的超时秒数", "Seconds to wait for the tunnel to become ready"),
+    ("启动因崩溃或超时失败后的重试次数", "Number of retries after a start fails because of a crash or timeout"),
+    ("首次重试前等待的秒数，之后每次翻倍", "Seconds to wait before the first retry, doubled after each attempt"),
+    ("标签，多个用逗号分隔；传入空字符串清空", "Tags, comma separated; pass an empty string to clear"),
+    ("依赖的连接，多个用逗号分隔；传入空字符串清空", "Tunnels this one depends on, comma separated; pass an empty string to clear"),
+    ("启动前执行的命令，失败时放弃启动；传入空字符串删除", "Command run before starting, the start is aborted if it fails; pass an empty string to remove"),
+    ("启动成功后执行的命令；传入空字符串删除", "Command run after a successful start; pass an empty string to remove"),
+    ("停止前执行的命令；传入空字符串删除", "Command run before stopping; pass an empty string to remove"),
+    ("停止后执行的命令；传入空字符串删除", "Command run after stopping; pass an empty string to remove"),
+    ("移除连接", "Remove a tunnel"),
+    ("添加新连接", "Add a new tunnel"),
+    ("源地址；kubectl 后端为目标资源，如 svc/foo", "Source hostname; for the kubectl provider the target resource, e.g. svc/foo"),
+    ("从清单文件批量添加：CSV（alias,source,port[,tags]）或 YAML", "Add tunnels in bulk from a manifest: CSV (alias,source,port[,tags]) or YAML"),
+    ("只预览清单中将要添加的连接，不修改配置", "Only preview the tunnels in the manifest without changing the config"),
+    ("提供连接的后端", "Provider that establishes the tunnel"),
+    ("kubectl 后端的远端端口，默认与本地端口相同", "Remote port for the kubectl provider, defaults to the local port"),
+    ("kubectl 后端使用的 kubeconfig context", "kubeconfig context used by the kubectl provider"),
+    ("kubectl 后端的命名空间", "Namespace for the kubectl provider"),
+    ("启用统计代理，记录连接数与流量", "Enable the statistics proxy to record connections and traffic"),
+    ("标签，多个用逗号分隔", "Tags, comma separated"),
+    ("依赖的连接，启动前会先启动它们，多个用逗号分隔", "Tunnels this one depends on, started first, comma separated"),
+    ("复制已有连接的设置到新的别名", "Copy an existing tunnel's settings to a new alias"),
+    ("要复制的连接别名", "Alias of the tunnel to copy"),
+    ("新连接的别名", "Alias of the new tunnel"),
+    ("新连接的源地址，默认与原连接相同", "Source hostname of the new tunnel, defaults to the original"),
+    ("新连接的本地端口，默认与原连接相同", "Local port of the new tunnel, defaults to the original"),
+    ("输出正在运行的连接的本地地址，供 shell、direnv 或 docker compose 使用", "Print the local address of running tunnels for shells, direnv or docker compose"),
+    ("连接别名，或标签（输出带有该标签的全部连接）", "Tunnel alias, or a tag (prints every tunnel with that tag)"),
+    ("输出格式", "Output format"),
+    ("启动 compose 文件 x-cf-tunnels 中声明的连接，并把本地地址作为环境变量传给 docker compose", "Start the tunnels declared in x-cf-tunnels of a compose file and pass their local addresses to docker compose"),
+    ("查看连接的客户端与流量统计（需启用代理）", "Show clients and traffic of a tunnel (requires the proxy)"),
+    ("在连接可用期间执行命令，结束后自动停止由本次调用启动的连接", "Run a command while the tunnel is up, stopping it afterwards if this call started it"),
+    ("设置或清除连接的 Cloudflare Access 服务令牌（保存在系统钥匙串中）", "Set or clear a tunnel's Cloudflare Access service token (stored in the system keychain)"),
+    ("CF-Access-Client-Id，省略时交互输入", "CF-Access-Client-Id, prompted for when omitted"),
+    ("CF-Access-Client-Secret，省略时交互输入", "CF-Access-Client-Secret, prompted for when omitted"),
+    ("清除已保存的服务令牌", "Clear the saved service token"),
+    ("管理配置文件", "Manage the config file"),
+    ("运行统计代理（内部使用）", "Run the statistics proxy (internal)"),
+    ("监听端口", "Listen port"),
+    ("cloudflared 所在的上游端口", "Upstream port of cloudflared"),
+    ("加密存储配置中的敏感字段（如源地址）", "Encrypt sensitive fields in the config (such as source hostnames)"),
+    ("以明文存储配置", "Store the config in plain text"),
+    ("查看或设置界面语言", "Show or set the interface language"),
+    ("要使用的语言，省略时显示当前设置", "Language to use; shows the current setting when omitted"),
+    ("清除设置，改为按 LANG 等环境变量选择", "Clear the setting and choose from LANG and related environment variables"),
+    ("启动声明的连接后执行 docker compose up", "Start the declared tunnels, then run docker compose up"),
+    ("compose 文件，默认在当前目录查找", "Compose file, looked up in the current directory by default"),
+    ("传给 docker compose up 的其他参数，如 -d", "Extra arguments for docker compose up, such as -d"),
+    ("执行 docker compose down 后停止声明的连接及其依赖", "Run docker compose down, then stop the declared tunnels and their dependencies"),
+    ("传给 docker compose down 的其他参数", "Extra arguments for docker compose down"),
+
+    // 命令输出
+    ("每 {} 秒刷新，按 Ctrl-C 退出", "Refreshing every {} seconds, press Ctrl-C to exit"),
+    ("没有配置任何连接，请先使用 'cfa add' 添加连接", "No tunnels configured, add one with 'cfa add' first"),
+    ("没有带标签 {} 的连接", "No tunnels with tag {}"),
+    ("项目配置: {}", "Project config: {}"),
+    ("选择要运行的连接（空格选择，回车确认）", "Select tunnels to start (space to select, enter to confirm)"),
+    ("未选择任何连接", "No tunnels selected"),
+    ("没有正在运行的连接", "No running tunnels"),
+    ("选择要停止的连接（空格选择，回车确认）", "Select tunnels to stop (space to select, enter to confirm)"),
+    ("选择要查看日志的连接（输入字符模糊筛选）", "Select a tunnel to show logs for (type to filter)"),
+    ("暂无日志", "No logs yet"),
+    ("已更新连接 {}", "Updated tunnel {}"),
+    ("已移除连接 {}", "Removed tunnel {}"),
+    ("清单中没有连接", "The manifest contains no tunnels"),
+    ("清单与现有配置冲突，未添加任何连接:\n{}", "The manifest conflicts with the existing config, nothing was added:\n{}"),
+    ("预览：将添加 {} 个连接，未修改配置", "Preview: {} tunnel(s) would be added, config unchanged"),
+    ("已添加 {} 个连接", "Added {} tunnel(s)"),
+    ("--remote-port、--context、--namespace 只适用于 kubectl 后端", "--remote-port, --context and --namespace only apply to the kubectl provider"),
+    ("已添加连接 {}", "Added tunnel {}"),
+    ("连接 {} 已存在", "Tunnel {} already exists"),
+    ("已将连接 {} 复制为 {}", "Copied tunnel {} to {}"),
+    ("提示: 新连接与 {} 使用相同的本地端口，两者不能同时运行，可使用 --port 指定其他端口", "Hint: the new tunnel uses the same local port as {}, so they cannot run at the same time; use --port to pick another port"),
+    ("连接 {} 未运行", "Tunnel {} is not running"),
+    ("未找到别名或标签为 {} 的连接", "No tunnel with alias or tag {}"),
+    ("标签 {} 下没有正在运行的连接", "No running tunnels with tag {}"),
+    ("连接 {} 未启用统计代理，请先运行 'tfa set {} --proxy true' 并重新启动", "Tunnel {} does not have the statistics proxy enabled; run 'tfa set {} --proxy true' and restart it"),
+    ("连接 {} 未在运行", "Tunnel {} is not running"),
+    ("活动连接: {}  累计连接: {}  接收: {}  发送: {}  空闲: {}s", "Active: {}  Total: {}  Received: {}  Sent: {}  Idle: {}s"),
+    ("正在启动 {}...", "Starting {}..."),
+    ("正在停止 {}...", "Stopping {}..."),
+    ("已清除连接 {} 的服务令牌", "Cleared the service token of tunnel {}"),
+    ("服务令牌只适用于 cloudflared 后端的连接", "Service tokens only apply to tunnels using the cloudflared provider"),
+    ("已保存连接 {} 的服务令牌，下次启动时生效", "Saved the service token of tunnel {}, it takes effect on the next start"),
+    ("配置已处于加密状态", "The config is already encrypted"),
+    ("已加密配置中的敏感字段，密钥保存在系统钥匙串（或 {} 环境变量）中", "Encrypted sensitive fields in the config; the key is kept in the system keychain (or the {} environment variable)"),
+    ("配置已改为明文存储", "The config is now stored in plain text"),
+    ("配置未加密", "The config is not encrypted"),
+    ("已清除语言设置，将按 LANG 等环境变量选择", "Cleared the language setting, it will be chosen from LANG and related environment variables"),
+    ("界面语言已设置为 {}", "Interface language set to {}"),
+    ("当前语言: {}（配置文件）", "Current language: {} (config file)"),
+    ("当前语言: {}（未设置，按环境变量选择）", "Current language: {} (not set, chosen from the environment)"),
+    ("等待启动 {}...", "Waiting to start {}..."),
+    ("等待停止 {}...", "Waiting to stop {}..."),
+    ("{} 已启动", "{} started"),
+    ("{} 已停止", "{} stopped"),
+    ("{} 启动失败: {}", "{} failed to start: {}"),
+    ("{} 停止失败: {}", "{} failed to stop: {}"),
+    ("{} 个连接启动失败", "{} tunnel(s) failed to start"),
+    ("{} 个连接停止失败", "{} tunnel(s) failed to stop"),
+    ("命令: {}\n", "Command: {}\n"),
+    ("工作目录: {}\n", "Working directory: {}\n"),
+    ("环境变量: 继承当前环境\n", "Environment: inherited\n"),
+    ("环境变量: 继承当前环境，并设置 {}\n", "Environment: inherited, plus {}\n"),
+    ("日志: {}\n", "Log: {}\n"),
+    ("统计代理: 监听 {}，转发到上面命令中的内部端口（启动时重新分配）\n", "Statistics proxy: listens on {} and forwards to the internal port in the command above (reassigned on start)\n"),
+    ("pre_start 钩子: {}\n", "pre_start hook: {}\n"),
+    ("正在启动 {}（{}）...", "Starting {} ({})..."),
+    ("依赖的连接 {} 未能启动", "dependency {} failed to start"),
+    ("已在运行", "already running"),
+    ("  提示: {}", "  Hint: {}"),
+    ("端口 {} 正由连接 {} 使用", "Port {} is used by tunnel {}"),
+    ("停止连接 {} 后重试", "Stop tunnel {} and retry"),
+    ("未知进程", "unknown process"),
+    ("结束 {} (PID {}) 后重试", "Kill {} (PID {}) and retry"),
+    ("更换本地端口后重试", "Use another local port and retry"),
+    ("取消", "Cancel"),
+    ("{}: 端口 {} 已被占用，如何处理？", "{}: port {} is in use, what should be done?"),
+    ("新的本地端口", "New local port"),
+    ("无法执行 {}: {}", "Cannot run {}: {}"),
+    ("别名 {} 在清单中重复", "Alias {} appears more than once in the manifest"),
+    ("{} 与 {} 使用相同的本地端口 {}，两者不能同时运行", "{} and {} use the same local port {} and cannot run at the same time"),
+    ("未检测到 cloudflared，这是运行本程序必需的。", "cloudflared was not found, it is required to run this program."),
+    ("是否要自动安装 cloudflared？", "Install cloudflared automatically?"),
+    ("正在安装 cloudflared...", "Installing cloudflared..."),
+    ("cloudflared 安装完成！", "cloudflared installed!"),
+    ("错误: {}", "Error: {}"),
+    ("提示: {}", "Hint: {}"),
+
+    // 配置与清单
+    ("当前目录中没有找到 compose 文件（{}）", "No compose file found in the current directory ({})"),
+    ("、", ", "),
+    ("无法读取 {}: {}", "Cannot read {}: {}"),
+    ("解析 {} 失败: {}", "Failed to parse {}: {}"),
+    ("{} 中没有 x-cf-tunnels 声明", "{} has no x-cf-tunnels declaration"),
+    ("{} 中的连接 {} 无效: {}", "Tunnel {1} in {0} is invalid: {2}"),
+    ("当前目录及其上级目录中没有找到 {}", "No {} found in the current directory or its parents"),
+    ("连接 {} 定义在项目配置 {} 中，请直接编辑该文件", "Tunnel {} is defined in the project config {}, edit that file instead"),
+    ("配置文件已加密，但未找到密钥：请设置 {} 环境变量，或在保存密钥的机器上解锁系统钥匙串", "The config file is encrypted but no key was found: set the {} environment variable, or unlock the system keychain on the machine that holds the key"),
+    ("未找到连接 {}", "Tunnel {} not found"),
+    ("连接 {} 被 {} 依赖，请先修改它们的 depends_on", "Tunnel {} is required by {}, change their depends_on first"),
+    ("连接之间存在循环依赖: {}", "Circular dependency between tunnels: {}"),
+    ("连接 {} 依赖的 {} 不存在", "Tunnel {} depends on {}, which does not exist"),
+    ("无法获取配置目录", "Cannot determine the config directory"),
+    ("第 {} 行: {}", "Line {}: {}"),
+    ("清单中有无效的行:\n{}", "The manifest has invalid lines:\n{}"),
+    ("应为 alias,source,port[,tags]，实际有 {} 列", "Expected alias,source,port[,tags], found {} columns"),
+    ("alias 和 source 不能为空", "alias and source must not be empty"),
+    ("端口 {} 无效", "Invalid port {}"),
+
+    // 错误与建议
+    ("未找到 cloudflared", "cloudflared not found"),
+    ("未找到 {}", "{} not found"),
+    ("端口 {} 已被占用{}", "Port {} is already in use{}"),
+    ("（{} PID {}）", " ({} PID {})"),
+    ("（PID {}）", " (PID {})"),
+    ("访问 {} 需要先完成 Cloudflare Access 认证", "Accessing {} requires Cloudflare Access authentication first"),
+    ("无法启动连接进程: {}", "Cannot start the tunnel process: {}"),
+    ("连接进程启动失败: {}", "The tunnel process failed to start: {}"),
+    ("连接进程在 {} 秒内未就绪：端口 {} 未被监听，日志中也没有监听或连接记录", "The tunnel process was not ready within {} seconds: port {} is not listening and the log shows no listener or connection"),
+    ("端口 {} 上的 {} (PID {}) 不是由本工具启动的", "{1} (PID {2}) on port {0} was not started by this tool"),
+    ("{} 钩子执行失败: {}", "{} hook failed: {}"),
+    ("请安装 cloudflared：Windows 运行 'winget install --id Cloudflare.cloudflared'，macOS 运行 'brew install cloudflared'", "Install cloudflared: run 'winget install --id Cloudflare.cloudflared' on Windows or 'brew install cloudflared' on macOS"),
+    ("请安装 {} 并确认它在 PATH 中", "Install {} and make sure it is on PATH"),
+    ("请停止占用该端口的程序，或使用 'tfa set <alias> --port <端口>' 更换本地端口", "Stop the program using the port, or change the local port with 'tfa set <alias> --port <port>'"),
+    ("请运行 'cloudflared access login https://{}' 完成认证后重试", "Run 'cloudflared access login https://{}' to authenticate, then retry"),
+    ("请确认 cloudflared 可以在终端中正常执行", "Make sure cloudflared runs correctly from a terminal"),
+    ("请检查源地址是否正确，完整日志位于配置目录下的 run/<alias>.log", "Check that the source hostname is correct; the full log is at run/<alias>.log in the config directory"),
+    ("网络较慢时可使用 'tfa set <alias> --start-timeout <秒>' 延长等待时间", "On slow networks, extend the wait with 'tfa set <alias> --start-timeout <seconds>'"),
+    ("确认该进程可以结束后，使用 --force 强制停止", "If the process can safely be killed, use --force"),
+    ("完整输出位于配置目录下的 run/<alias>.hooks.log；可通过 'tfa set' 为对应钩子传入空字符串来删除它", "The full output is at run/<alias>.hooks.log in the config directory; remove the hook by passing an empty string to 'tfa set'"),
+    ("钩子执行失败", "hook failed"),
+    ("成功", "succeeded"),
+    ("失败", "failed"),
+    ("连接 {} 已启用服务令牌，但系统钥匙串中没有找到，请重新运行 'tfa service-token {}'", "Tunnel {} has a service token enabled but none was found in the system keychain; run 'tfa service-token {}' again"),
+
+    // 钥匙串与加密
+    ("读取系统钥匙串失败: {}", "Failed to read the system keychain: {}"),
+    ("写入系统钥匙串失败: {}", "Failed to write the system keychain: {}"),
+    ("删除系统钥匙串条目失败: {}", "Failed to delete the system keychain entry: {}"),
+    ("访问系统钥匙串时发生异常", "The system keychain access panicked"),
+    ("{}；也可以通过 {} 环境变量提供 base64 编码的 32 字节密钥（例如 `openssl rand -base64 32` 的输出）", "{}; a base64-encoded 32-byte key can also be provided via the {} environment variable (e.g. the output of `openssl rand -base64 32`)"),
+    ("配置密钥不是有效的 base64", "The config key is not valid base64"),
+    ("配置密钥长度应为 32 字节", "The config key must be 32 bytes long"),
+    ("加密失败", "Encryption failed"),
+    ("加密字段格式错误", "Malformed encrypted field"),
+    ("解密失败，密钥可能不匹配", "Decryption failed, the key may not match"),
+
+    // 连接进程
+    ("安装 cloudflared 失败，请手动运行: winget install --id Cloudflare.cloudflared", "Failed to install cloudflared, run manually: winget install --id Cloudflare.cloudflared"),
+    ("安装 cloudflared 失败，请手动运行: brew install cloudflared", "Failed to install cloudflared, run manually: brew install cloudflared"),
+    ("不支持的操作系统", "Unsupported operating system"),
+    ("统计代理启动失败", "The statistics proxy failed to start"),
+    ("第 {}/{} 次启动失败，{} 秒后重试: {}", "Start attempt {}/{} failed, retrying in {} seconds: {}"),
+    ("退出码: {}", "exit status: {}"),
+    ("等待端口 {} 就绪超时", "Timed out waiting for port {} to become ready"),
+    ("强制结束不是由本工具启动的进程", "Killing a process that was not started by this tool"),
+
+    // 清理
+    ("清理已不在配置中的连接留下的进程与运行时文件，以及过期的状态记录", "Clean up processes and runtime files left by tunnels that are no longer configured, and stale state records"),
+    ("连接已不在配置中", "tunnel is no longer configured"),
+    ("记录的进程已退出", "recorded process has exited"),
+    ("没有需要清理的进程或文件", "Nothing to clean up"),
+    ("结束上述进程并删除上述文件？", "Kill the processes and delete the files listed above?"),
+    ("已取消", "Cancelled"),
+    ("已结束 {} 个进程，删除 {} 个文件", "Killed {} process(es) and deleted {} file(s)"),
+
+    // 接管
+    ("接管手动启动的 cloudflared access 进程：按主机名和本地端口匹配已配置的连接，之后可以正常停止和管理", "Adopt manually started cloudflared access processes: match configured tunnels by hostname and local port so they can be stopped and managed as usual"),
+    ("只列出匹配结果，不接管", "Only list matches without adopting"),
+    ("没有发现正在运行的 cloudflared access 进程", "No running cloudflared access processes found"),
+    ("预览：未接管任何进程", "Dry run: no processes adopted"),
+    ("已接管 {} 个进程", "Adopted {} process(es)"),
+    ("没有主机名一致的连接", "No tunnel with a matching hostname"),
+    ("已有由本工具启动的进程 (PID {})", "Already has a process started by this tool (PID {})"),
+    ("主机名一致，但本地端口为 {}，配置中为 {}", "Hostname matches, but local port is {} while the config has {}"),
+
+    // 发现
+    ("发现与任何连接都不对应的 cloudflared access 进程，逐个确认后导入为新连接并接管", "Discover cloudflared access processes that match no tunnel and, after confirming each, import them as new tunnels and adopt them"),
+    ("没有发现未配置的 cloudflared access 进程", "No unconfigured cloudflared access processes found"),
+    ("非交互环境中无法导入，请在终端中运行 discover", "Cannot import in a non-interactive environment; run discover in a terminal"),
+    ("导入 {}（PID {}，本地端口 {}）？", "Import {} (PID {}, local port {})?"),
+    ("别名不能为空", "Alias cannot be empty"),
+    ("已添加连接 {} 并接管进程 {}", "Added tunnel {} and adopted process {}"),
+    ("已导入 {} 个连接", "Imported {} tunnel(s)"),
+
+    // 校验
+    ("校验配置文件：未知字段、端口、主机名与依赖，有错误时以非零状态退出，适合在 CI 中使用", "Validate config files: unknown fields, ports, hostnames and dependencies; exits non-zero on errors, suitable for CI"),
+    ("要校验的文件，`.toml` 按项目配置校验；默认校验全局配置与当前项目配置", "File to validate, `.toml` is checked as a project config; defaults to the global config and the current project config"),
+    ("把警告也视为错误", "Treat warnings as errors"),
+    ("没有找到配置文件", "No config file found"),
+    ("校验未通过：{} 个错误，{} 个警告", "Validation failed: {} error(s), {} warning(s)"),
+    ("{} 有效", "{} is valid"),
+    ("{} 个警告", "{} warning(s)"),
+    ("错误", "error"),
+    ("警告", "warning"),
+    ("JSON 语法错误: {}", "JSON syntax error: {}"),
+    ("TOML 语法错误: {}", "TOML syntax error: {}"),
+    ("顶层应为 JSON 对象", "Top level must be a JSON object"),
+    ("未知字段 {}", "Unknown field {}"),
+    ("未知字段 hooks.{}", "Unknown field hooks.{}"),
+    ("无效的语言 {}，可选 zh-CN、en-US", "Invalid language {}, expected zh-CN or en-US"),
+    ("encrypted 应为 true 或 false", "encrypted must be true or false"),
+    ("缺少字段 tunnels", "Missing field tunnels"),
+    ("tunnels 应为以别名为键的对象", "tunnels must be an object keyed by alias"),
+    ("tunnels 应为以别名为键的表", "tunnels must be a table keyed by alias"),
+    ("连接应为表", "Tunnel must be a table"),
+    ("配置已加密但未找到密钥，跳过主机名检查", "Config is encrypted but no key was found; skipping hostname checks"),
+    ("alias 字段 {} 与键名不一致", "alias field {} does not match its key"),
+    ("连接无效: {}", "Invalid tunnel: {}"),
+    ("本地端口不能为 0", "Local port cannot be 0"),
+    ("远程端口不能为 0", "Remote port cannot be 0"),
+    ("主机名 {} 不符合 DNS 语法", "Hostname {} is not valid DNS syntax"),
+    ("依赖的 {} 不存在", "Dependency {} does not exist"),
+    ("本地端口 {} 同时被 {} 使用，它们不能同时运行", "Local port {} is used by {}; they cannot run at the same time"),
+
+    // 操作历史
+    ("查看启动、停止、添加、移除、修改连接的操作历史", "Show the history of tunnel starts, stops, additions, removals and changes"),
+    ("只看该连接的记录", "Only show entries for this tunnel"),
+    ("最多显示的条数，0 表示全部", "Maximum number of entries to show, 0 for all"),
+    ("暂无操作记录", "No history yet"),
+
+    // 配置档案
+    ("使用的配置档案，优先于 CF_MANAGER_PROFILE 环境变量和 profile switch 的选择", "Config profile to use, overrides the CF_MANAGER_PROFILE environment variable and the profile switch choice"),
+    ("管理配置档案：每个档案有独立的连接、运行状态与操作历史", "Manage config profiles: each profile has its own tunnels, runtime state and history"),
+    ("列出全部档案，* 标记当前使用的档案", "List all profiles, * marks the active one"),
+    ("创建空档案", "Create an empty profile"),
+    ("档案名", "Profile name"),
+    ("设置之后默认使用的档案", "Set the profile to use from now on"),
+    ("档案名，default 为默认档案", "Profile name, default is the default profile"),
+    ("把档案的连接配置复制为新档案（服务令牌需重新设置）", "Copy a profile's tunnels into a new profile (service tokens must be set again)"),
+    ("要复制的档案", "Profile to copy"),
+    ("新档案名", "New profile name"),
+    ("档案 {} 已存在", "Profile {} already exists"),
+    ("档案 {} 不存在，可使用 'tfa profile create {}' 创建", "Profile {} does not exist; create it with 'tfa profile create {}'"),
+    ("档案名 {} 无效：只能包含字母、数字、- 和 _", "Invalid profile name {}: only letters, digits, - and _ are allowed"),
+    ("已创建档案 {}，可使用 'tfa profile switch {}' 切换", "Created profile {}; switch to it with 'tfa profile switch {}'"),
+    ("已切换到档案 {}", "Switched to profile {}"),
+    ("提示: 当前设置了 {}={}，它优先于这里的选择", "Hint: {}={} is set and takes precedence over this choice"),
+    ("已将档案 {} 复制为 {}", "Copied profile {} to {}"),
+
+    // 同步
+    ("与 git 仓库同步连接目录：拉取他人的修改并推送本地的修改，双方修改同一连接时报告冲突", "Sync the tunnel catalogue with a git repository: pull others' changes and push local ones, reporting conflicts when both sides changed a tunnel"),
+    ("只显示将要拉取和推送的连接，不做修改", "Only show which tunnels would be pulled or pushed, without changing anything"),
+    ("冲突时采用本地版本", "Keep the local version on conflicts"),
+    ("冲突时采用远端版本", "Take the remote version on conflicts"),
+    ("查看或设置 sync 使用的 git 仓库", "Show or set the git repository used by sync"),
+    ("仓库地址，省略时显示当前设置", "Repository URL, shows the current setting when omitted"),
+    ("同步的分支", "Branch to sync"),
+    ("连接目录在仓库中的路径", "Path of the tunnel catalogue within the repository"),
+    ("清除同步设置", "Clear the sync settings"),
+    ("已清除同步设置", "Sync settings cleared"),
+    ("同步仓库已设置为 {}（分支 {}，文件 {}）", "Sync repository set to {} (branch {}, file {})"),
+    ("同步仓库: {}（分支 {}，文件 {}）", "Sync repository: {} (branch {}, file {})"),
+    ("尚未设置同步仓库", "No sync repository set"),
+    ("尚未设置同步仓库，请先运行 'tfa config sync <仓库地址>'", "No sync repository set; run 'tfa config sync <url>' first"),
+    ("本地与远端一致", "Local and remote are in sync"),
+    ("{} 个连接在本地和远端都被修改: {}；请修改后重试，或使用 --ours / --theirs 选择保留的版本", "{} tunnel(s) changed both locally and remotely: {}; edit and retry, or pick a side with --ours / --theirs"),
+    ("预览：未修改配置，也未推送", "Dry run: config unchanged and nothing pushed"),
+    ("同步完成", "Sync complete"),
+    ("解析远端 {} 失败: {}", "Failed to parse remote {}: {}"),
+    ("推送失败，远端可能刚被其他人更新，请重新运行 sync: {}", "Push failed, the remote may have just been updated by someone else; run sync again: {}"),
+    ("无法执行 git: {}", "Cannot run git: {}"),
+    ("克隆 {} 失败: {}", "Failed to clone {}: {}"),
+    ("git {} 失败: {}", "git {} failed: {}"),
+    ("远端的连接 {} 无效: {}", "Remote tunnel {} is invalid: {}"),
+
+    // 导入团队目录
+    ("从 HTTP(S) 地址导入团队维护的连接目录，导入的连接只能通过重新导入修改", "Import a team-maintained tunnel catalogue over HTTP(S); imported tunnels can only be changed by importing again"),
+    ("目录地址，内容格式与配置文件相同", "Catalogue URL, in the same format as the config file"),
+    ("Bearer 令牌，也可以通过 CF_MANAGER_IMPORT_TOKEN 环境变量提供", "Bearer token, can also be provided via the CF_MANAGER_IMPORT_TOKEN environment variable"),
+    ("目录内容应有的 SHA-256，不一致时拒绝导入", "Expected SHA-256 of the catalogue; the import is refused on mismatch"),
+    ("只显示导入结果，不修改配置", "Only show the import result without changing the config"),
+    ("移除由该目录导入的全部连接", "Remove all tunnels imported from this catalogue"),
+    ("没有由 {} 导入的连接", "No tunnels imported from {}"),
+    ("预览：将移除 {}", "Dry run: would remove {}"),
+    ("已移除由 {} 导入的 {} 个连接", "Removed {1} tunnel(s) imported from {0}"),
+    ("预览：未修改配置", "Dry run: config unchanged"),
+    ("导入完成：新增 {}，更新 {}，移除 {}，跳过 {}", "Import complete: {} added, {} updated, {} removed, {} skipped"),
+    ("连接 {} 由目录 {} 管理，请修改目录后重新导入，或使用 'tfa import --url {} --forget' 移除", "Tunnel {} is managed by catalogue {}; change the catalogue and import again, or remove it with 'tfa import --url {} --forget'"),
+    ("无法执行 curl: {}", "Cannot run curl: {}"),
+    ("下载 {} 失败: {}", "Failed to download {}: {}"),
+    ("目录的 SHA-256 不匹配：期望 {}，实际 {}", "Catalogue SHA-256 mismatch: expected {}, got {}"),
+    ("目录中的连接 {} 无效: {}", "Tunnel {} in the catalogue is invalid: {}"),
+    ("已由目录 {} 管理", "Already managed by catalogue {}"),
+    ("与本地连接同名", "Same name as a local tunnel"),
+    ("同步目录中的 {} 与受管连接同名，已跳过", "{} in the sync catalogue has the same name as a managed tunnel; skipped"),
+
+    // 锁定连接
+    ("连接 {} 已锁定，确需修改或删除时请加上 --unlock", "Tunnel {} is locked; pass --unlock to modify or remove it"),
+    ("是否锁定连接，锁定后修改或删除需要 --unlock", "Whether the tunnel is locked; locked tunnels need --unlock to be modified or removed"),
+    ("允许修改锁定的连接", "Allow modifying a locked tunnel"),
+    ("允许删除锁定的连接", "Allow removing a locked tunnel"),
+    ("锁定连接，之后修改或删除需要 --unlock", "Lock the tunnel so that modifying or removing it requires --unlock"),
+
+    // 删除确认
+    ("删除连接 {}？", "Remove tunnel {}?"),
+    ("非交互环境中请使用 --yes 确认操作", "Use --yes to confirm the operation in non-interactive environments"),
+    ("不再确认，直接执行删除、清理等操作", "Remove, clean up and so on without asking for confirmation"),
+
+    // 出站代理
+    ("已清除全局代理，将沿用 HTTPS_PROXY 等环境变量", "Cleared the global proxy; HTTPS_PROXY and similar environment variables will be used"),
+    ("全局代理已设置为 {}", "Global proxy set to {}"),
+    ("全局代理: {}", "Global proxy: {}"),
+    ("全局代理: {}（不经代理: {}）", "Global proxy: {} (bypassed for: {})"),
+    ("未设置全局代理，沿用 HTTPS_PROXY 等环境变量", "No global proxy set; HTTPS_PROXY and similar environment variables are used"),
+    ("没有需要检查的连接", "No tunnels to check"),
+    ("{} 个连接无法访问，请检查代理设置", "{} tunnel(s) are unreachable; check the proxy settings"),
+    ("代理地址 {} 无效：应为 http://、https://、socks5:// 或 socks5h:// 开头的地址，或 {}", "Invalid proxy address {}: expected an http://, https://, socks5:// or socks5h:// URL, or {}"),
+    ("后端使用的出站代理（http://、socks5:// 等），direct 表示不使用全局代理；传入空字符串删除", "Outbound proxy for the backend (http://, socks5:// etc.); direct bypasses the global proxy; pass an empty string to remove"),
+    ("后端使用的出站代理（http://、socks5:// 等），direct 表示不使用全局代理", "Outbound proxy for the backend (http://, socks5:// etc.); direct bypasses the global proxy"),
+    ("只检查该连接，默认检查全部 cloudflared 连接", "Only check this tunnel; all cloudflared tunnels are checked by default"),
+    ("查看或设置全局出站代理，未单独设置代理的连接经由它访问外网", "Show or set the global outbound proxy used by tunnels without their own proxy"),
+    ("代理地址，如 http://proxy.corp:3128 或 socks5://127.0.0.1:1080；省略时显示当前设置", "Proxy address, e.g. http://proxy.corp:3128 or socks5://127.0.0.1:1080; omit to show the current setting"),
+    ("不经过代理的主机，多个用逗号分隔", "Hosts that bypass the proxy, comma-separated"),
+    ("清除全局代理，改为沿用 HTTPS_PROXY 等环境变量", "Clear the global proxy and fall back to HTTPS_PROXY and similar environment variables"),
+
+    // 监听地址
+    ("监听地址 {} 无效，应为 IPv4 或 IPv6 地址", "Invalid listen address {}: expected an IPv4 or IPv6 address"),
+    ("连接 {} 将监听 {}，其他机器也可以通过它访问目标服务", "Tunnel {} will listen on {}; other machines can reach the target service through it"),
+    ("本地端口绑定的地址，如 0.0.0.0 或 ::1；传入空字符串恢复为只监听 127.0.0.1", "Address to bind the local port to, e.g. 0.0.0.0 or ::1; pass an empty string to listen on 127.0.0.1 only"),
+    ("本地端口绑定的地址，如 0.0.0.0、::1 或某个网卡的 IP，默认只监听 127.0.0.1", "Address to bind the local port to, e.g. 0.0.0.0, ::1 or an interface IP; defaults to 127.0.0.1 only"),
+    ("监听地址", "Listen address"),
+
+    // 等待健康检查
+    ("连接 {} 已通过健康检查", "Tunnel {} passed its health check"),
+    ("无效的时长 {}，应为 30、30s、2m 这样的格式", "Invalid duration {}: expected a value such as 30, 30s or 2m"),
+    ("连接 {} 在 {} 秒内未通过健康检查（状态: {}）", "Tunnel {} did not pass its health check within {} seconds (status: {})"),
+    ("可使用 'tfa log' 查看连接 {} 的日志，或用 --timeout 延长等待时间", "Use 'tfa log' to inspect the log of tunnel {}, or raise the wait time with --timeout"),
+    ("要启动的连接别名，支持 `prod-*` 这样的通配符（不再交互选择）", "Aliases of the tunnels to start, wildcards such as `prod-*` allowed (skips interactive selection)"),
+    ("等到所选连接都通过健康检查才返回，适合在 CI 中下一步使用连接之前调用", "Return only after all selected tunnels pass their health check; useful in CI before the next step connects through them"),
+    ("--wait 的最长等待时间，如 30、30s、2m", "Maximum time to wait with --wait, e.g. 30, 30s or 2m"),
+    ("等待正在启动的连接通过健康检查，超时以退出码 6 失败", "Wait for starting tunnels to pass their health check; fails with exit code 6 on timeout"),
+    ("最长等待时间，如 30、30s、2m", "Maximum time to wait, e.g. 30, 30s or 2m"),
+
+    // 有效期
+    ("所选连接将在 {} 后自动停止", "The selected tunnels will stop automatically in {}"),
+    ("{}（{} 后停止）", "{} (stops in {})"),
+    ("连接 {} 不是由本工具启动的，无法设置有效期", "Tunnel {} was not started by this tool; cannot set a time to live"),
+    ("有效期，如 30m、2h；到期后自动停止所选连接", "Time to live, e.g. 30m or 2h; the selected tunnels are stopped when it expires"),
+    ("在有效期到达后停止连接（内部使用）", "Stop a tunnel when its time to live expires (internal)"),
+
+    // 访问记录
+    ("连接 {} 暂无访问记录", "No access records for tunnel {}"),
+    ("最近一次访问: {} (UTC)", "Last access: {} (UTC)"),
+    ("连接 {} 未启用统计代理，不会记录访问；可使用 'tfa set {} --proxy true' 启用", "Tunnel {} has no stats proxy, so access is not recorded; enable it with 'tfa set {} --proxy true'"),
+    ("{}（进行中）", "{} (ongoing)"),
+    ("查看经由连接的访问记录：客户端、建立时间、时长与流量（需启用代理）", "Show access records through a tunnel: client, open time, duration and traffic (requires the proxy)"),
+
+    // 日志脱敏
+    ("脱敏规则 {} 无效: {}", "Invalid redaction rule {}: {}"),
+    ("无法启动日志脱敏进程", "Failed to start the log redaction process"),
+    ("cloudflared 的日志级别", "cloudflared log level"),
+    ("写入日志前替换为 ****** 的正则表达式，可重复指定；传入空字符串删除全部规则", "Regular expression replaced with ****** before writing logs, repeatable; pass an empty string to remove all rules"),
+    ("写入日志前替换为 ****** 的正则表达式，可重复指定", "Regular expression replaced with ****** before writing logs, repeatable"),
+    ("日志文件路径", "Log file path"),
+
+    // 日志转发
+    ("已清除全局日志转发目标", "Cleared the global log sinks"),
+    ("全局日志转发目标已设置为 {}，对之后启动的连接生效", "Global log sinks set to {}; takes effect for tunnels started from now on"),
+    ("未设置全局日志转发目标", "No global log sinks configured"),
+    ("全局日志转发目标: {}", "Global log sinks: {}"),
+    ("日志转发: {}\n", "Log sinks: {}\n"),
+    ("当前平台不支持日志转发目标 {}", "Log sink {} is not supported on this platform"),
+    ("日志转发目标 {} 无效：应为 syslog、journald、eventlog、tcp://主机:端口、http(s):// 或 loki+http(s):// 地址", "Invalid log sink {}: expected syslog, journald, eventlog, tcp://host:port, an http(s):// or a loki+http(s):// URL"),
+    ("无法解析地址 {}", "Cannot resolve address {}"),
+    ("未找到 syslog 套接字", "syslog socket not found"),
+    ("eventcreate 执行失败: {}", "eventcreate failed: {}"),
+    ("日志转发目标（syslog、journald、eventlog、tcp://、http(s)://、loki+http(s)://），可重复指定；传入空字符串改为使用全局设置", "Log sink (syslog, journald, eventlog, tcp://, http(s)://, loki+http(s)://), repeatable; pass an empty string to use the global setting"),
+    ("日志转发目标（syslog、journald、eventlog、tcp://、http(s)://、loki+http(s)://），可重复指定，默认使用全局设置", "Log sink (syslog, journald, eventlog, tcp://, http(s)://, loki+http(s)://), repeatable; defaults to the global setting"),
+    ("脱敏并转发后端输出，写入日志文件（内部使用）", "Redact and forward backend output and write it to the log file (internal)"),
+    ("查看或设置全局日志转发目标，未单独设置转发目标的连接使用它们", "Show or set the global log sinks, used by tunnels without their own sinks"),
+    ("转发目标：syslog、journald、eventlog、tcp://主机:端口、http(s)://（按行 JSON）或 loki+http(s)://（Loki 推送接口）；省略时显示当前设置", "Sinks: syslog, journald, eventlog, tcp://host:port, http(s):// (newline-delimited JSON) or loki+http(s):// (Loki push API); omit to show the current setting"),
+    ("清除全局转发目标", "Clear the global log sinks"),
+
+    // 崩溃循环熔断
+    ("已清除连接 {} 的失败记录", "Cleared the failure record of tunnel {}"),
+    ("连接 {} 没有失败记录", "Tunnel {} has no failure record"),
+    ("{}: {} 分钟内失败 {} 次，已停止重试（tfa reset {} 解除）: {}", "{0}: failed {2} times within {1} minutes, retries stopped (run tfa reset {3} to clear): {4}"),
+    ("连接 {} 在 {} 分钟内失败了 {} 次，已停止重试；最后的错误: {}", "Tunnel {0} failed {2} times within {1} minutes, retries stopped; last error: {3}"),
+    ("排除问题后使用 'tfa reset {}' 清除失败记录，或等待 {} 分钟后自动恢复；完整日志位于配置目录下的 run/{}.log", "After fixing the problem, run 'tfa reset {}' to clear the failure record, or wait {} minutes for automatic recovery; the full log is at run/{}.log in the config directory"),
+    ("连接进程意外退出: {}", "Tunnel process exited unexpectedly: {}"),
+    ("清除连接的失败记录，解除因反复失败而停止重试（failed）的状态", "Clear the failure record of tunnels, lifting the failed state entered after repeated failures"),
+    ("在 --crash-window 分钟内失败达到该次数时停止重试，0 表示不限制", "Stop retrying after this many failures within --crash-window minutes; 0 means no limit"),
+    ("统计失败次数的时间窗口（分钟）", "Time window for counting failures (minutes)"),
+    ("连接反复失败而停止重试时执行的命令，可通过 TUNNEL_ERROR 获取最后的错误；传入空字符串删除", "Command run when the tunnel stops retrying after repeated failures, with the last error in TUNNEL_ERROR; pass an empty string to remove"),
+
+    // 事件
+    ("处理不及，跳过了 {} 个事件", "Falling behind, skipped {} event(s)"),
+    ("{} 失败: {}", "{} failed: {}"),
+    ("{} 状态 {} → {}", "{} status {} → {}"),
+    ("持续输出连接事件：启动、停止、失败、状态变化，以及可选的日志行，按 Ctrl-C 退出", "Stream tunnel events: starts, stops, failures, status changes and optionally log lines; press Ctrl-C to exit"),
+    ("只看这些连接的事件，默认全部", "Only show events of these tunnels; all by default"),
+    ("同时输出连接日志中新写入的行", "Also print lines newly written to tunnel logs"),
+    ("每行输出一个 JSON 对象，便于脚本处理", "Print one JSON object per line for scripts"),
+    ("检测状态变化的间隔秒数", "Interval in seconds between status checks"),
+
+    // top
+    ("实时显示各连接进程的 CPU 与内存占用及合计，便于发现失控的进程，按 Ctrl-C 退出", "Show live CPU and memory usage of each tunnel process plus the total, to spot runaway processes; press Ctrl-C to exit"),
+    ("刷新间隔秒数", "Refresh interval in seconds"),
+    ("采样一次后输出并退出，不清屏", "Sample once, print and exit without clearing the screen"),
+    ("{} 个进程", "{} processes"),
+
+    // rate limits
+    ("上行限速（字节/秒，可带 K、M、G 后缀），需要统计代理；运行中的连接立即生效，0 表示不限速", "Upload rate limit (bytes/s, K, M or G suffixes allowed); requires the stats proxy; applies to a running tunnel immediately, 0 removes the limit"),
+    ("下行限速（字节/秒，可带 K、M、G 后缀），需要统计代理；运行中的连接立即生效，0 表示不限速", "Download rate limit (bytes/s, K, M or G suffixes allowed); requires the stats proxy; applies to a running tunnel immediately, 0 removes the limit"),
+    ("上行限速（字节/秒，可带 K、M、G 后缀），需要 --proxy", "Upload rate limit (bytes/s, K, M or G suffixes allowed); requires --proxy"),
+    ("下行限速（字节/秒，可带 K、M、G 后缀），需要 --proxy", "Download rate limit (bytes/s, K, M or G suffixes allowed); requires --proxy"),
+    ("新的限速已应用到运行中的连接", "The new rate limits were applied to the running tunnel"),
+    ("限速: 上行 {}，下行 {}\n", "Rate limit: up {}, down {}\n"),
+    ("无效的速率 {}，应为 500000、512K、2M 这样的格式", "Invalid rate {}, expected a value like 500000, 512K or 2M"),
+    ("不限", "unlimited"),
+    ("连接 {} 需要启用统计代理（--proxy true）才能限速", "Tunnel {} needs the stats proxy enabled (--proxy true) to apply rate limits"),
+    ("设置了限速但未启用统计代理，限速不会生效", "Rate limits are set but the stats proxy is disabled, so they have no effect"),
+
+    // start lock
+    ("{} 正在由另一个进程启动，等待其完成", "{} is being started by another process, waiting for it to finish"),
+
+    // pause
+    ("暂停连接：停止运行中的进程，并在 run --all、--tag、--project 等批量启动时跳过，直到恢复", "Pause tunnels: stop their running processes and skip them in bulk starts such as run --all, --tag and --project until resumed"),
+    ("恢复暂停的连接并启动它们", "Resume paused tunnels and start them"),
+    ("已暂停 {}，批量启动时将跳过，使用 tfa resume 恢复", "Paused {}; bulk starts will skip them, use tfa resume to restore"),
+    ("连接 {} 没有暂停", "Tunnel {} is not paused"),
+    ("跳过已暂停的连接: {}（tfa resume 恢复）", "Skipping paused tunnels: {} (tfa resume to restore)"),
+    ("已暂停", "paused"),
+    ("已暂停 {}", "paused {}"),
+
+    // enable
+    ("启用连接，恢复参与批量启动与状态监视", "Enable tunnels so bulk starts and status monitoring include them again"),
+    ("停用连接：仍显示在列表中，但 run --all、--tag、--project 与状态监视都会跳过，运行中的连接不受影响", "Disable tunnels: they stay in the list but run --all, --tag, --project and status monitoring skip them; running tunnels are left alone"),
+    ("跳过已停用的连接: {}（tfa enable 启用）", "Skipping disabled tunnels: {} (tfa enable to re-enable)"),
+    ("连接 {} 已停用，请先使用 tfa enable {} 启用", "Tunnel {0} is disabled; enable it first with tfa enable {1}"),
+    ("已停用", "disabled"),
+    ("已启用 {}", "Enabled {}"),
+    ("已停用 {}，批量启动时将跳过，使用 tfa enable 重新启用", "Disabled {}; bulk starts will skip them, use tfa enable to re-enable"),
+
+    // glob
+    ("没有与 {} 匹配的连接", "No tunnel matches {}"),
+    ("{} 匹配到多个连接（{}），此处只能指定一个", "{0} matches several tunnels ({1}); only one is allowed here"),
+    ("无效的通配符 {}", "Invalid wildcard {}"),
+    ("要停止的连接别名，支持 `prod-*` 这样的通配符（不再交互选择）", "Aliases of the tunnels to stop, wildcards such as `prod-*` allowed (skips interactive selection)"),
+    ("要查看的连接别名，支持通配符（不再交互选择）", "Aliases of the tunnels to show, wildcards allowed (skips interactive selection)"),
+    ("{} 以下 {} 个连接: {}？", "{0} the following {1} tunnels: {2}?"),
+    ("停用", "Disable"),
+    ("暂停", "Pause"),
+    ("停止", "Stop"),
+
+    // tcp
+    ("临时连接到一个主机名而不写入配置：在前台运行并输出日志，按 Ctrl-C 结束", "Connect to a hostname ad hoc without saving it to the config: runs in the foreground with logs, Ctrl-C to end"),
+    ("Cloudflare Access 主机名", "Cloudflare Access hostname"),
+    ("本地监听端口，默认自动选取一个空闲端口", "Local port to listen on; a free port is picked by default"),
+    ("{} 已映射到 {}，按 Ctrl-C 结束", "{} is mapped to {}, press Ctrl-C to end"),
+
+    // status
+    ("快速输出连接状态，只读取运行时文件、不启动外部进程，适合状态栏每隔几秒轮询", "Quickly print tunnel status from runtime files only, without spawning external processes; suitable for status bars polling every few seconds"),
+    ("同时列出已停止的连接", "Also list stopped tunnels"),
+    ("输出紧凑的 JSON 数组：别名、状态、端口与运行时长", "Print a compact JSON array: alias, status, port and uptime"),
+
+    // hosts
+    ("已更新运行中连接的 hosts 记录", "Updated the hosts entry of the running tunnel"),
+    ("需要管理员权限来更新 {}", "Administrator privileges are needed to update {}"),
+    ("无法通过 sudo 更新 {}", "Failed to update {} via sudo"),
+    ("没有权限更新 {}，请以管理员身份运行", "No permission to update {}; run as administrator"),
+    ("无法把 {} 映射到本地: {}", "Failed to map {} locally: {}"),
+    ("无法删除 {} 的本地映射: {}", "Failed to remove the local mapping of {}: {}"),
+    ("运行期间在系统 hosts 文件中指向本地地址的主机名，如 prod-db.local；传入空字符串删除", "Hostname pointed at the local address in the system hosts file while running, e.g. prod-db.local; pass an empty string to remove"),
+    ("运行期间在系统 hosts 文件中指向本地地址的主机名，如 prod-db.local", "Hostname pointed at the local address in the system hosts file while running, e.g. prod-db.local"),
+
+    // probe
+    ("{} 的 {} 探测成功，往返 {} ms", "{} {} probe succeeded, round trip {} ms"),
+    ("{} 的 {} 探测失败: {}", "{} {} probe failed: {}"),
+    ("探测方式 {} 无效，可用 tcp、ssh、http[:/路径]、postgres", "Invalid probe {}; use tcp, ssh, http[:/path] or postgres"),
+    ("无法连接 {}: {}", "Cannot connect to {}: {}"),
+    ("没有收到 SSH 版本标识: {}", "No SSH version banner received: {}"),
+    ("HTTP 状态码 {}", "HTTP status {}"),
+    ("没有收到 HTTP 应答: {}", "No HTTP response received: {}"),
+    ("没有收到 PostgreSQL 应答", "No PostgreSQL response received"),
+    ("`tfa test` 使用的探测方式：tcp、ssh、http[:/路径]、postgres；传入空字符串恢复为 tcp", "Probe used by `tfa test`: tcp, ssh, http[:/path] or postgres; pass an empty string to reset to tcp"),
+    ("`tfa test` 使用的探测方式：tcp、ssh、http[:/路径]、postgres，默认 tcp", "Probe used by `tfa test`: tcp, ssh, http[:/path] or postgres; defaults to tcp"),
+    ("测试能否经连接访问目标服务：连接未运行时临时启动，按 probe 设置探测一次并报告往返耗时", "Test whether the target service is reachable through the tunnel: starts it temporarily if not running, probes once according to the probe setting and reports the round-trip time"),
+    ("本次使用的探测方式，覆盖连接的 probe 设置", "Probe to use this time, overriding the tunnel probe setting"),
+    ("等待连接就绪与探测应答的超时秒数", "Seconds to wait for the tunnel to be ready and for the probe reply"),
+
+    // probe
+    ("连接在收到应答之前被关闭", "The connection was closed before a reply was received"),
+
+    // latency
+    ("无法启动延迟记录进程: {}", "Failed to start the latency recorder: {}"),
+    ("运行期间每隔多少秒在后台探测一次并记录延迟，下次启动时生效；0 表示不记录", "Probe in the background every this many seconds while running and record the latency, effective from the next start; 0 disables recording"),
+    ("运行期间每隔多少秒在后台探测一次并记录延迟，显示在 list 中", "Probe in the background every this many seconds while running and record the latency, shown in list"),
+    ("按 probe_interval 定期探测，记录延迟并推送到 ping_url（内部使用）", "Periodically probe, record latency and push to ping_url according to probe_interval (internal)"),
+
+    // curl
+    ("经 `cloudflared access curl` 请求连接主机上受 Access 保护的 HTTP 接口，不需要先启动连接", "Request an Access-protected HTTP API on the tunnel host via `cloudflared access curl`, without starting the tunnel"),
+    ("请求的路径，如 /api/health", "Path to request, e.g. /api/health"),
+    ("传给 curl 的其他参数，写在 `--` 之后", "Extra arguments for curl, after `--`"),
+    ("连接 {} 不是 cloudflared 连接，无法使用 curl", "Tunnel {} is not a cloudflared tunnel; curl is not available"),
+
+    // http
+    ("无法执行 cloudflared: {}", "Failed to run cloudflared: {}"),
+    ("没有 {} 的 Access 令牌，请先运行 cloudflared access login {}", "No Access token for {}; run cloudflared access login {} first"),
+    ("运行 http 后端的本地反向代理（内部使用）", "Run the local reverse proxy of the http provider (internal)"),
+    ("Access 保护的 Web 应用主机名", "Hostname of the Access-protected web app"),
+
+    // template
+    ("模板中没有端口，请使用 --port 指定", "The template has no port; specify one with --port"),
+    ("连接 {} 没有设置客户端命令，请在 `--` 之后给出要执行的命令，或用 --client 设置", "Tunnel {} has no client command; give the command after `--` or set one with --client"),
+    ("模板 {} 不存在，可用的模板: {}", "Template {} does not exist; available templates: {}"),
+    ("`tfa exec <alias>` 不带命令时执行的客户端命令，可使用 TUNNEL_HOST、TUNNEL_PORT 环境变量；传入空字符串删除", "Client command run by `tfa exec <alias>` without a command; TUNNEL_HOST and TUNNEL_PORT are available; pass an empty string to remove"),
+    ("本地端口，使用模板时默认为模板中的端口", "Local port; defaults to the template port when a template is used"),
+    ("按模板预设本地端口、探测方式与客户端命令：内置 ssh、rdp、postgres、mysql、redis、mongo、k8s-api，也可以在配置中定义", "Preset the local port, probe and client command from a template: built-in ssh, rdp, postgres, mysql, redis, mongo, k8s-api, or ones defined in the config"),
+    ("`tfa exec <alias>` 不带命令时执行的客户端命令，可使用 TUNNEL_HOST、TUNNEL_PORT 环境变量", "Client command run by `tfa exec <alias>` without a command; TUNNEL_HOST and TUNNEL_PORT are available"),
+    ("要执行的命令，写在 `--` 之后；省略时执行连接的客户端命令", "Command to run, after `--`; runs the tunnel client command when omitted"),
+    ("列出可用于 `add --template` 的模板，包括内置模板与配置中定义的模板", "List templates usable with `add --template`, both built-in and defined in the config"),
+
+    // find
+    ("共 {} 个连接，输入关键字筛选（留空显示全部）", "{} tunnels, type a keyword to filter (leave empty to show all)"),
+    ("按别名、源地址与标签模糊查找连接，按匹配程度排序", "Fuzzy-find tunnels by alias, source and tags, best matches first"),
+    ("查找的关键字，字符按顺序出现即匹配，如 pdb 可以匹配 prod-db", "Keyword to search for; matches when its characters appear in order, e.g. pdb matches prod-db"),
+
+    // set
+    ("不能同时设置并删除 {}", "Cannot both set and unset {}"),
+    ("没有指定要修改的字段", "No fields to change were given"),
+    ("连接 {} 没有变化", "Tunnel {} is unchanged"),
+    ("改为使用的后端；改为 kubectl 时远端端口默认与本地端口相同", "Switch to another backend; when switching to kubectl the remote port defaults to the local port"),
+    ("kubectl 后端的远端端口", "Remote port for the kubectl backend"),
+    ("kubectl 后端使用的 kubeconfig context；传入空字符串删除", "kubeconfig context for the kubectl backend; pass an empty string to remove it"),
+    ("kubectl 后端的命名空间；传入空字符串删除", "Namespace for the kubectl backend; pass an empty string to remove it"),
+    ("是否启用连接，停用的连接不随批量操作启动", "Whether the tunnel is enabled; disabled tunnels are skipped by batch operations"),
+    ("删除该字段或恢复为默认值，可重复指定", "Remove the field or reset it to its default; can be repeated"),
+
+    // show
+    ("配置", "Configuration"),
+    ("运行状态", "Runtime"),
+    ("状态: {}", "Status: {}"),
+    ("启动时间: {}（已运行 {}）", "Started: {} (up {})"),
+    ("内部端口: {}", "Internal port: {}"),
+    ("到期时间: {}", "Expires: {}"),
+    ("延迟: {}", "Latency: {}"),
+    ("启动命令", "Start command"),
+    ("最近日志", "Recent logs"),
+    ("查看单个连接：合并默认值与项目配置后的完整配置、运行状态、启动命令与最近的日志", "Show one tunnel: its full configuration after defaults and project merging, runtime state, start command and recent logs"),
+    ("以 JSON 输出", "Output as JSON"),
+    ("显示的日志行数", "Number of log lines to show"),
+
+    // tty
+    ("非交互环境中无法选择连接，请指定别名（tfa run <alias>）或使用 --all、--tag、--project", "Cannot select tunnels without a terminal; give an alias (tfa run <alias>) or use --all, --tag or --project"),
+    ("非交互环境中无法选择连接，请指定别名（tfa stop <alias>，可以使用 '*' 停止全部）", "Cannot select tunnels without a terminal; give an alias (tfa stop <alias>, or '*' to stop all)"),
+    ("非交互环境中无法选择连接，请指定别名（tfa log <alias>）", "Cannot select tunnels without a terminal; give an alias (tfa log <alias>)"),
+    ("非交互环境中请通过 --client-id 与 --client-secret 传入服务令牌", "Without a terminal, pass the service token with --client-id and --client-secret"),
+
+    // cfgdir
+    ("配置目录，包含各档案的配置、运行时文件与操作历史，优先于 CF_MANAGER_CONFIG_DIR 环境变量", "Configuration directory holding every profile's config, runtime files and history; takes precedence over CF_MANAGER_CONFIG_DIR"),
+    ("配置文件，优先于 CF_MANAGER_CONFIG 环境变量；运行时文件仍在配置目录中", "Configuration file; takes precedence over CF_MANAGER_CONFIG. Runtime files stay in the configuration directory"),
+
+    // via
+    ("连接 {} 经过的 {} 不存在", "Tunnel {} goes through {}, which does not exist"),
+    ("bastion 方式只适用于 cloudflared 连接：{} 与 {} 都必须使用 cloudflared 后端", "The bastion mode only works with cloudflared tunnels: both {} and {} must use the cloudflared provider"),
+    ("连接 {} 本身经跳板访问，不能再作为 {} 的跳板", "Tunnel {} is itself reached through a bastion and cannot be the bastion for {}"),
+    ("连接 {} 没有经过其他连接，请同时指定 --via", "Tunnel {} does not go through another tunnel; specify --via as well"),
+    ("链路: {}（{}）\n", "Route: {} ({})\n"),
+    ("连接 {} 被 {} 经过，请先修改它们的 via", "Tunnel {} is used as an upstream by {}; change their via first"),
+    ("经过的 {} 不存在", "Upstream {} does not exist"),
+    ("后端经过的上游连接，如跳板机上的代理；传入空字符串改为直接访问", "Upstream tunnel the backend goes through, such as a proxy on a bastion host; pass an empty string to connect directly"),
+    ("经过上游连接的方式，默认 socks5", "How to go through the upstream tunnel; defaults to socks5"),
+    ("后端经过的上游连接，如跳板机上的代理；socks5、http 方式下启动前会先启动它", "Upstream tunnel the backend goes through, such as a proxy on a bastion host; with socks5 and http it is started first"),
+    ("经过上游连接的方式：socks5、http 把上游连接的本地端口作为代理，bastion 经 cloudflared 跳板以 --destination 访问 source", "How to go through the upstream tunnel: socks5 and http use its local port as a proxy, bastion reaches the source with --destination through a cloudflared bastion"),
+
+    // portrange
+    ("已清除保留端口范围", "Reserved port range cleared"),
+    ("保留端口范围已设置为 {}", "Reserved port range set to {}"),
+    ("提示: 以下连接的端口不在该范围内: {}", "Note: these tunnels use ports outside the range: {}"),
+    ("保留端口范围: {}", "Reserved port range: {}"),
+    ("不在范围内的连接: {}", "Tunnels outside the range: {}"),
+    ("未设置保留端口范围", "No reserved port range set"),
+    ("无效的端口 {}，应为 1-65535 或 auto", "Invalid port {}: expected 1-65535 or auto"),
+    ("无效的端口范围 {}，应为 42000-42999 这样的格式", "Invalid port range {}: expected a range like 42000-42999"),
+    ("没有为档案 {} 设置保留端口范围，请先运行 'tfa config port-range 42000-42999'", "Profile {} has no reserved port range; run 'tfa config port-range 42000-42999' first"),
+    ("保留端口范围 {} 中已没有可用的端口", "No free port left in the reserved range {}"),
+    ("本地端口 {} 不在保留端口范围 {} 内", "Local port {} is outside the reserved port range {}"),
+    ("本地端口，auto 表示从保留端口范围中分配", "Local port; auto allocates one from the reserved port range"),
+    ("本地端口，auto 表示从保留端口范围中分配；使用模板时默认为模板中的端口", "Local port; auto allocates one from the reserved port range. Defaults to the template's port when using a template"),
+    ("新连接的本地端口，auto 表示从保留端口范围中分配；默认与原连接相同", "Local port of the new tunnel; auto allocates one from the reserved port range. Defaults to the original tunnel's port"),
+    ("查看或设置本档案保留的本地端口范围，`--port auto` 从中分配端口", "Show or set the local port range reserved for this profile; `--port auto` allocates from it"),
+    ("端口范围，如 42000-42999；省略时显示当前设置", "Port range such as 42000-42999; omit to show the current setting"),
+    ("清除保留端口范围", "Clear the reserved port range"),
+
+    // cfpin
+    ("已取消固定 cloudflared 版本，将使用 PATH 中的 cloudflared", "cloudflared version unpinned; the cloudflared in PATH will be used"),
+    ("已将 cloudflared 版本固定为 {}，运行 'tfa upgrade-cloudflared' 安装", "cloudflared pinned to {}; run 'tfa upgrade-cloudflared' to install it"),
+    ("固定的 cloudflared 版本: {}", "Pinned cloudflared version: {}"),
+    ("未固定 cloudflared 版本，使用 PATH 中的 cloudflared", "cloudflared version not pinned; using the cloudflared in PATH"),
+    ("正在安装 cloudflared {}...", "Installing cloudflared {}..."),
+    ("正在升级 cloudflared...", "Upgrading cloudflared..."),
+    ("cloudflared {}（{}）", "cloudflared {} ({})"),
+    ("cloudflared {}（固定版本，{}）", "cloudflared {} (pinned, {})"),
+    ("未安装", "not installed"),
+    ("cloudflared 版本 {} 与固定的版本 {} 不一致", "cloudflared version {} differs from the pinned version {}"),
+    ("cloudflared 版本不一致，请运行 'tfa upgrade-cloudflared' 安装 {}", "cloudflared version drift; run 'tfa upgrade-cloudflared' to install {}"),
+    ("无效的 cloudflared 版本 {}，应为 2024.8.2 这样的格式", "Invalid cloudflared version {}: expected a version like 2024.8.2"),
+    ("无效的 SHA-256 {}，应为 64 位十六进制", "Invalid SHA-256 {}: expected 64 hex digits"),
+    ("cloudflared 没有提供 {}/{} 平台的发布文件", "cloudflared publishes no release file for {}/{}"),
+    ("{} 的 SHA-256 不匹配：期望 {}，实际 {}", "SHA-256 mismatch for {}: expected {}, got {}"),
+    ("解压 {} 失败", "Failed to extract {}"),
+    ("安装的 cloudflared 报告的版本为 {}，与固定的版本 {} 不一致", "The installed cloudflared reports version {}, not the pinned version {}"),
+    ("未固定版本时只能通过 winget 或 brew 升级 cloudflared，请先运行 'tfa config cloudflared-version <版本>'", "Without a pinned version cloudflared can only be upgraded with winget or brew; run 'tfa config cloudflared-version <version>' first"),
+    ("升级 cloudflared 失败，请手动运行: {}", "Failed to upgrade cloudflared; run manually: {}"),
+    ("检查 cloudflared 版本是否与档案固定的版本一致，以及能否经连接使用的出站代理访问 Cloudflare Access 主机", "Check that cloudflared matches the profile's pinned version and that Cloudflare Access hosts are reachable through each tunnel's outbound proxy"),
+    ("安装档案固定的 cloudflared 版本；未固定版本时通过 winget 或 brew 升级", "Install the profile's pinned cloudflared version; without a pin, upgrade with winget or brew"),
+    ("查看或设置本档案固定使用的 cloudflared 版本，由 `tfa upgrade-cloudflared` 从 GitHub Releases 安装", "Show or set the cloudflared version pinned for this profile; `tfa upgrade-cloudflared` installs it from GitHub Releases"),
+    ("版本号，如 2024.8.2；省略时显示当前设置", "Version such as 2024.8.2; omit to show the current setting"),
+    ("本平台发布文件的 SHA-256，设置后安装前校验", "SHA-256 of this platform's release file, verified before installing"),
+    ("取消固定，改为使用 PATH 中的 cloudflared", "Unpin and use the cloudflared in PATH"),
+
+    // replay
+    ("暂无交互选择记录", "No recorded interactive selections"),
+    ("暂无交互选择记录，请先运行 tfa run 或 tfa stop 并在菜单中选择连接", "No recorded interactive selections; run tfa run or tfa stop and pick tunnels in the menu first"),
+    ("只记录了 {} 次会话", "Only {} sessions recorded"),
+    ("跳过已不存在的连接: {}", "Skipping tunnels that no longer exist: {}"),
+    ("停止: {}", "Stop: {}"),
+    ("启动: {}", "Start: {}"),
+    ("按原顺序重新执行最近一次交互式 run / stop 会话中选择的连接，不再询问", "Replay the tunnels picked in the most recent interactive run / stop session, in order, without prompting"),
+    ("要重放的会话，1 为最近一次", "Session to replay, 1 is the most recent"),
+    ("列出记录的会话", "List recorded sessions"),
+    ("只显示将要执行的操作", "Only show what would be done"),
+
+    // token
+    ("令牌已过期，请运行 cloudflared access login {} 重新登录", "The token has expired, run cloudflared access login {} to log in again"),
+    ("令牌将在 {} 后过期，可以运行 cloudflared access login {} 重新登录", "The token expires in {}, run cloudflared access login {} to log in again"),
+    ("主机: {}\n", "Host: {}\n"),
+    ("身份: {}\n", "Identity: {}\n"),
+    ("受众: {}\n", "Audience: {}\n"),
+    ("签发者: {}\n", "Issuer: {}\n"),
+    ("签发时间: {} UTC\n", "Issued at: {} UTC\n"),
+    ("过期时间: {} UTC（剩余 {}）\n", "Expires at: {} UTC ({} left)\n"),
+    ("过期时间: {} UTC（已过期）\n", "Expires at: {} UTC (expired)\n"),
+    ("过期时间: 未知\n", "Expires at: unknown\n"),
+    ("令牌不是 JWT 格式", "The token is not a JWT"),
+    ("无法解析令牌中的声明: {}", "Failed to parse the token claims: {}"),
+    ("连接 {} 不是 cloudflared 连接，没有 Access 令牌", "Tunnel {} is not a cloudflared tunnel and has no Access token"),
+    ("取得连接主机当前的 Access 令牌并显示其中的身份、受众与过期时间，即将过期时给出提醒", "Fetch the current Access token for the tunnel host and show its identity, audience and expiry, warning when it is about to expire"),
+    ("输出令牌中的全部声明（JSON）", "Print all claims in the token (JSON)"),
+    ("只输出令牌本身，便于在脚本中使用", "Print only the token itself, for use in scripts"),
+
+    // config backup
+    ("没有 {} 的配置备份，运行 'tfa config backup --list' 查看全部备份", "No config backup at {}, run 'tfa config backup --list' to see all backups"),
+    ("备份 {} 无法解析: {}", "Failed to parse backup {}: {}"),
+    ("已关闭配置的自动备份", "Automatic config backups disabled"),
+    ("将保留最近 {} 份配置备份", "The last {} config backups will be kept"),
+    ("暂无配置备份", "No config backups"),
+    ("保留最近 {} 份备份", "Keeping the last {} backups"),
+    ("已备份配置到 {}", "Config backed up to {}"),
+    ("配置文件尚不存在，无需备份", "The config file does not exist yet, nothing to back up"),
+    ("非交互环境中无法选择备份，请使用 --at 指定备份时间", "Cannot pick a backup in a non-interactive environment, use --at to specify the backup time"),
+    ("{} UTC（{} 个连接）", "{} UTC ({} tunnels)"),
+    ("选择要恢复的配置备份", "Select the config backup to restore"),
+    ("用 {} UTC 的备份替换当前配置？", "Replace the current config with the backup from {} UTC?"),
+    ("已恢复 {} UTC 的配置备份", "Restored the config backup from {} UTC"),
+    ("备份配置失败", "Failed to back up the config"),
+    ("立即备份配置文件；修改配置的命令也会在写入前自动备份，只保留最近的若干份", "Back up the config file now; commands that modify the config also back it up automatically before writing, keeping only the most recent backups"),
+    ("列出全部备份", "List all backups"),
+    ("设置保留的备份份数，0 表示不再自动备份", "Set how many backups to keep, 0 disables automatic backups"),
+    ("用备份替换配置文件，替换前会先备份当前的配置", "Replace the config file with a backup, backing up the current config first"),
+    ("要恢复的备份时间，如 20241016-045511（见 `tfa config backup --list`）；省略时交互选择", "Time of the backup to restore, e.g. 20241016-045511 (see `tfa config backup --list`); pick interactively when omitted"),
+
+    // stop --others
+    ("非交互环境中无法选择连接，请指定要保留的别名（tfa stop --others <alias>）", "Cannot select tunnels in a non-interactive environment, specify the aliases to keep (tfa stop --others <alias>)"),
+    ("选择要保留的连接，其余的全部停止（空格选择，回车确认）", "Select the tunnels to keep, all others will be stopped (space to select, enter to confirm)"),
+    ("同时保留被依赖的连接: {}", "Also keeping the tunnels they depend on: {}"),
+    ("除保留的连接外没有正在运行的连接", "No running tunnels besides the kept ones"),
+    ("已停止 {} 个连接: {}", "Stopped {} tunnels: {}"),
+    ("现在没有正在运行的连接", "No tunnels are running now"),
+    ("仍在运行: {}", "Still running: {}"),
+    ("反过来停止给定连接以外的全部连接，给定连接依赖的连接同样保留；不带别名时交互选择要保留的连接", "Invert the selection: stop every tunnel except the given ones and the tunnels they depend on; without aliases, pick the tunnels to keep interactively"),
+
+    // set --apply
+    ("连接 {} 未运行，修改将在下次启动时生效", "Tunnel {} is not running, the changes take effect on the next start"),
+    ("连接 {} 仍在端口 {} 上运行，新端口在重启后生效；使用 --apply 立即重启", "Tunnel {} is still running on port {}, the new port takes effect after a restart; use --apply to restart now"),
+    ("正在重启 {}...", "Restarting {}..."),
+    ("{} 已重启，现在监听 {}", "{} restarted, now listening on {}"),
+    ("提示: {} 经过该连接访问目标，仍在使用原来的地址，需要重启后才会使用新地址", "Hint: {} reach their targets through this tunnel and still use the old address until restarted"),
+    ("连接正在运行时立即重启它，使修改（如新的本地端口）生效；只重启这一个连接", "Restart the tunnel right away if it is running so the changes (such as a new local port) take effect; only this tunnel is restarted"),
+
+    // ssh config
+    ("已删除连接 {} 的 known_hosts，下次 ssh {} 时需要重新确认主机密钥", "Deleted the known_hosts of tunnel {}, the host key must be confirmed again on the next ssh {}"),
+    ("连接 {} 还没有记录主机密钥", "Tunnel {} has no recorded host key yet"),
+    ("没有设置 ssh_host 的连接，已删除 ~/.ssh/config 中由本工具管理的条目", "No tunnels have ssh_host set, removed the managed entries from ~/.ssh/config"),
+    ("已更新 ~/.ssh/config 中的 {} 个主机: {}", "Updated {} hosts in ~/.ssh/config: {}"),
+    ("无法更新 ~/.ssh/config: {}", "Failed to update ~/.ssh/config: {}"),
+    ("无法获取用户主目录", "Cannot determine the home directory"),
+    ("是否在 ~/.ssh/config 中生成 `Host <别名>` 条目，使用该连接专用的 known_hosts", "Whether to generate a `Host <alias>` entry in ~/.ssh/config that uses a known_hosts file dedicated to the tunnel"),
+    ("在 ~/.ssh/config 中生成 `Host <别名>` 条目，之后可以直接 ssh <别名>，使用该连接专用的 known_hosts", "Generate a `Host <alias>` entry in ~/.ssh/config so that ssh <alias> works directly, using a known_hosts file dedicated to the tunnel"),
+    ("按设置了 ssh_host 的连接重新生成 ~/.ssh/config 中由本工具管理的条目", "Regenerate the managed entries in ~/.ssh/config from the tunnels with ssh_host set"),
+    ("只输出将要写入的条目，不修改文件", "Only print the entries that would be written, without modifying the file"),
+    ("删除该连接专用的 known_hosts，远端主机更换密钥后使用", "Delete the known_hosts dedicated to this tunnel, for when the remote host changes its key"),
+
+    // database clients
+    ("无法识别连接串 {}，应以 postgresql://、mysql://、redis:// 或 mongodb:// 开头", "Unrecognized connection string {}, it should start with postgresql://, mysql://, redis:// or mongodb://"),
+    ("按数据库类型启动 psql、mysql、redis-cli 或 mongosh 并连接到连接的本地地址，结束后自动停止由本次调用启动的连接", "Launch psql, mysql, redis-cli or mongosh for the tunnel's database type against its local address, stopping the tunnel afterwards if this command started it"),
+    ("数据库类型，默认按连接串、探测方式、客户端命令与端口判断", "Database type, detected from the connection string, probe, client command and port by default"),
+    ("输入数据库密码并保存到系统钥匙串，之后连接时自动使用", "Enter the database password and save it to the system keychain for later connections"),
+    ("删除系统钥匙串中保存的数据库密码", "Delete the database password saved in the system keychain"),
+    ("传给客户端的其他参数，写在 `--` 之后", "Extra arguments for the client, after `--`"),
+    ("`tfa connect` 使用的连接串模板，如 postgresql://app@{host}:{port}/orders；传入空字符串删除", "Connection string template used by `tfa connect`, such as postgresql://app@{host}:{port}/orders; pass an empty string to remove it"),
+    ("`tfa connect` 使用的连接串模板，可写用户名与库名，{host}、{port} 替换为本地地址，如 postgresql://app@{host}:{port}/orders", "Connection string template used by `tfa connect`, may include the user and database, with {host} and {port} replaced by the local address, such as postgresql://app@{host}:{port}/orders"),
+    ("已删除连接 {} 的数据库密码", "Deleted the database password of tunnel {}"),
+    ("已保存连接 {} 的数据库密码", "Saved the database password of tunnel {}"),
+    ("非交互环境中请通过 {} 环境变量提供数据库密码", "In non-interactive environments, provide the database password through the {} environment variable"),
+    ("数据库密码", "Database password"),
+    ("无法判断连接 {} 的数据库类型，请用 --kind 指定，或用 --connect-url 设置连接串", "Cannot tell the database type of tunnel {}, specify it with --kind or set a connection string with --connect-url"),
+
+    // apply
+    ("按清单声明式地调整连接：添加缺少的、更新有变化的，加上 --prune 时移除清单之外的；先输出计划，确认后执行", "Reconcile tunnels with a manifest: add missing ones, update changed ones and, with --prune, remove those not in the manifest; the plan is printed and confirmed first"),
+    ("清单文件：YAML 连接列表或 CSV，格式与 add --from-file 相同", "Manifest file: a YAML list of tunnels or CSV, in the same format as add --from-file"),
+    ("同时移除配置中有而清单中没有的连接", "Also remove tunnels that are configured but not in the manifest"),
+    ("只输出计划，不修改配置", "Only print the plan, without modifying the config"),
+    ("配置已与清单一致", "The config already matches the manifest"),
+    ("应用以上 {} 项修改？", "Apply the {} changes above?"),
+    ("已停止被移除的连接 {}", "Stopped removed tunnel {}"),
+    ("应用完成：新增 {}，更新 {}，移除 {}，跳过 {}", "Apply finished: {} added, {} updated, {} removed, {} skipped"),
+    ("已锁定", "Locked"),
+
+    // diff
+    ("显示应用清单时将添加、更新、移除的连接，以及需要重启或停止的运行中连接，不做任何修改", "Show which tunnels applying the manifest would add, update or remove and which running tunnels would be restarted or stopped, without changing anything"),
+    ("同时列出配置中有而清单中没有、apply --prune 会移除的连接", "Also list tunnels that are configured but not in the manifest, which apply --prune would remove"),
+    ("输出 JSON，便于在 CI 中处理", "Print JSON for use in CI"),
+    ("有待应用的修改时以退出码 9 结束", "Exit with code 9 when there are changes to apply"),
+    ("计划：新增 {}，更新 {}，移除 {}，跳过 {}；重启 {} 个、停止 {} 个运行中的连接", "Plan: {} to add, {} to update, {} to remove, {} skipped; {} running tunnels to restart, {} to stop"),
+    ("（运行中，将重启）", " (running, will be restarted)"),
+    ("（运行中，将停止）", " (running, will be stopped)"),
+
+    // system config
+    ("只显示系统配置中定义、由系统服务管理的连接", "Only show tunnels defined in the system config and run by the system service"),
+    ("无法读取系统配置 {}: {}", "Cannot read system config {}: {}"),
+    ("连接 {} 由系统服务管理，只有管理员可以启动或停止", "Tunnel {} is managed by the system service; only an administrator can start or stop it"),
+    ("请以管理员身份（如 sudo）运行，或在用户配置中添加同名连接覆盖它", "Run as an administrator (e.g. with sudo), or add a tunnel with the same alias to your user config to override it"),
+    ("连接 {} 定义在系统配置 {} 中，只能由管理员在该文件中删除", "Tunnel {} is defined in the system config {}; only an administrator can remove it from that file"),
+    ("跳过由系统服务管理的连接: {}（需要管理员权限）", "Skipping tunnels managed by the system service: {} (administrator required)"),
+
+    // privileged ports
+    ("设置本地端口低于 1024 时的处理方式：redirect 改为监听非特权端口并设置端口转发规则，elevate 经 sudo 启动后端进程，off 删除转发规则并恢复为直接监听", "Choose how to handle a local port below 1024: redirect listens on an unprivileged port and sets up a port-forwarding rule, elevate starts the backend through sudo, off removes the rule and listens directly again"),
+    ("端口转发时实际监听的非特权端口，默认为本地端口加 8000", "Unprivileged port to listen on when redirecting; defaults to the local port plus 8000"),
+    ("只输出端口转发规则的命令，由你以管理员身份自行执行", "Only print the port-forwarding commands for you to run as an administrator"),
+    ("监听非特权端口并设置端口转发", "Listen on an unprivileged port and set up port forwarding"),
+    ("经 sudo 启动后端进程", "Start the backend through sudo"),
+    ("删除端口转发规则，恢复为直接监听本地端口", "Remove the port-forwarding rule and listen on the local port directly"),
+    ("连接 {} 的本地端口 {} 低于 1024，当前用户无权绑定", "Tunnel {}: local port {} is below 1024 and the current user cannot bind it"),
+    ("使用 'tfa privileged-port {} redirect' 改为监听非特权端口并设置端口转发，或 'tfa privileged-port {} elevate' 经 sudo 启动；也可以用 'tfa set {} --port <端口>' 换成 1024 以上的端口", "Run 'tfa privileged-port {} redirect' to listen on an unprivileged port with port forwarding, or 'tfa privileged-port {} elevate' to start through sudo; or switch to a port above 1024 with 'tfa set {} --port <port>'"),
+    ("连接 {} 启用了统计代理，不能经 sudo 启动，请改用端口转发", "Tunnel {} uses the stats proxy and cannot be started through sudo; use port forwarding instead"),
+    ("设置端口转发需要管理员权限，请以管理员身份运行终端后重试", "Setting up port forwarding requires administrator rights; run the terminal as administrator and try again"),
+    ("命令执行失败（{}）: {}", "Command failed ({}): {}"),
+    ("sudo 验证失败，经 sudo 启动的连接将无法启动", "sudo authentication failed; tunnels started through sudo will not start"),
+    ("无法结束经 sudo 启动的进程 {}，请以管理员身份运行 kill {}", "Cannot stop process {} started through sudo; run kill {} as an administrator"),
+    ("连接 {} 将监听端口 {}，端口 {} 经转发规则到达该端口", "Tunnel {} will listen on port {}; port {} is forwarded to it"),
+    ("连接 {} 将经 sudo 启动后端进程", "Tunnel {} will start its backend through sudo"),
+    ("连接 {} 恢复为直接监听端口 {}", "Tunnel {} listens on port {} directly again"),
+    ("请以管理员身份执行以上命令", "Run the commands above as an administrator"),
+    ("连接 {} 正在运行，修改将在下次启动时生效", "Tunnel {} is running; the change takes effect on the next start"),
+    ("端口 {} 低于 1024，只有管理员可以直接绑定", "Port {} is below 1024; only an administrator can bind it directly"),
+    ("改为监听端口 {}，并以管理员身份设置从端口 {} 转发过去的规则", "Listen on port {} instead and set up a rule forwarding port {} to it as administrator"),
+    ("经 sudo 以管理员身份启动后端进程", "Start the backend as administrator through sudo"),
+    ("{}: 如何绑定端口 {}？", "{}: how should port {} be bound?"),
+    ("转发到的端口 {} 同样低于 1024，请用 --port 指定 1024 以上的端口", "The target port {} is also below 1024; choose a port above 1024 with --port"),
+    ("当前系统或用户绑定低于 1024 的端口不需要 sudo", "Binding ports below 1024 does not require sudo on this system or for this user"),
+    ("删除原有的端口转发规则失败: {}", "Failed to remove the previous port-forwarding rule: {}"),
+
+    // container runtime
+    ("在 Docker 或 Podman 容器中运行 cloudflared，主机上不需要安装", "Run cloudflared in a Docker or Podman container so it need not be installed on the host"),
+    ("在 Docker 或 Podman 容器中运行 cloudflared，主机上不需要安装；只适用于 cloudflared 后端", "Run cloudflared in a Docker or Podman container so it need not be installed on the host; cloudflared backend only"),
+    ("容器运行时只适用于 cloudflared 后端", "A container runtime only applies to the cloudflared backend"),
+    ("正在拉取镜像 {}...", "Pulling image {}..."),
+    ("拉取镜像 {} 失败: {}", "Failed to pull image {}: {}"),
+
+    // grpc
+    ("以 gRPC 提供控制接口（proto/tunnel_manager.proto），供其他程序列出、启动、停止连接并订阅日志和事件，按 Ctrl-C 退出", "Serve the control API over gRPC (proto/tunnel_manager.proto) so other programs can list, start and stop tunnels and subscribe to logs and events; press Ctrl-C to exit"),
+    ("没有配置 API 密钥，gRPC 控制接口不检查认证，监听 {} 时其他机器也可以启动或停止连接", "No API keys are configured, so the gRPC control API does not authenticate; listening on {} lets other machines start and stop tunnels"),
+    ("gRPC 控制接口正在监听 {}，按 Ctrl-C 停止", "gRPC control API listening on {}; press Ctrl-C to stop"),
+    ("依赖的连接 {} 已暂停", "Dependency {} is paused"),
+
+    // web
+    ("Web 服务正在监听 http://{}，按 Ctrl-C 停止", "Web service listening on http://{}; press Ctrl-C to stop"),
+    ("无效的正则表达式 {}: {}", "Invalid regular expression {}: {}"),
+    ("不接受来自其他网站的连接", "Connections from other websites are not accepted"),
+
+    // dashboard
+    ("运行本机 Web 服务：网页控制台，以及以 WebSocket 推送连接事件的 /events，按 Ctrl-C 退出", "Run the local web service: a web dashboard, and /events streaming tunnel events over a WebSocket; press Ctrl-C to exit"),
+    ("生成新的访问令牌，旧令牌随即失效", "Generate a new access token; the old one stops working immediately"),
+    ("Web 服务监听 {}，其他机器上持有访问令牌或 API 密钥的人也可以访问", "The web service listens on {}; anyone on another machine with the access token or an API key can use it"),
+    ("访问令牌保存在 {}", "The access token is stored in {}"),
+    ("缺少访问令牌或令牌无效", "Missing or invalid access token"),
+    ("控制台: http://{}/#token={}", "Dashboard: http://{}/#token={}"),
+    ("连接", "Tunnel"),
+    ("状态", "Status"),
+    ("本地地址", "Local address"),
+    ("后端", "Backend"),
+    ("运行时长", "Uptime"),
+    ("启动", "Start"),
+    ("日志", "Logs"),
+    ("请输入 tfa web 启动时输出的访问令牌或 API 密钥", "Enter the access token printed when tfa web started, or an API key"),
+    ("确定", "OK"),
+    ("访问令牌无效", "Invalid access token"),
+    ("实时更新中", "Live"),
+    ("已断开，正在重连...", "Disconnected, reconnecting..."),
+
+    // api keys
+    ("管理 tfa grpc 与 tfa web 的 API 密钥：按权限范围区分只读、操作和管理", "Manage API keys for tfa grpc and tfa web, scoped to read-only, operate or admin"),
+    ("创建 API 密钥；密钥只在创建时显示一次，配置中只保存摘要", "Create an API key; it is shown only once and only its digest is stored in the config"),
+    ("密钥名称，如使用它的程序名", "Key name, e.g. the program that uses it"),
+    ("权限范围：read 只能查看，operate 还可以启动、停止，admin 还可以强制停止", "Scope: read can only view, operate can also start and stop, admin can also force-stop"),
+    ("列出 API 密钥", "List API keys"),
+    ("撤销 API 密钥，使用它的请求立即被拒绝", "Revoke an API key; requests using it are rejected immediately"),
+    ("密钥名称", "Key name"),
+    ("查看连接、日志和事件", "View tunnels, logs and events"),
+    ("启动、停止连接", "Start and stop tunnels"),
+    ("强制停止不是由本工具启动的进程", "Force-stop processes not started by this tool"),
+    ("API 密钥 {} 已存在", "API key {} already exists"),
+    ("未找到 API 密钥 {}", "API key {} not found"),
+    ("已创建 API 密钥 {}（{}），请立即保存，之后无法再次查看:", "Created API key {} ({}); save it now, it cannot be shown again:"),
+    ("没有 API 密钥，使用 tfa apikey create <名称> 创建", "No API keys; create one with tfa apikey create <name>"),
+    ("撤销 API 密钥 {}？", "Revoke API key {}?"),
+    ("已撤销 API 密钥 {}", "Revoked API key {}"),
+    ("缺少 API 密钥或密钥无效", "Missing or invalid API key"),
+    ("API 密钥 {} 的权限为 {}，该操作需要 {}", "API key {} has scope {}, but this operation requires {}"),
+
+    // alerts
+    ("告警：连接持续不可用或反复重启时，按配置文件中 alerts 定义的规则发送到 notify 中的通知渠道", "Alerts: notify the channels in the config's notify section when a tunnel stays down or keeps restarting, per the rules in alerts"),
+    ("在前台监视连接并按规则发送通知，按 Ctrl-C 退出", "Watch tunnels in the foreground and send notifications per the rules; press Ctrl-C to exit"),
+    ("列出告警规则与通知渠道", "List alert rules and notification channels"),
+    ("向通知渠道发送一条测试通知，省略名称时发送到全部渠道", "Send a test notification to channels, or to all channels if none are named"),
+    ("通知渠道名称", "Notification channel name"),
+    ("不可用超过 {} 分钟", "down for over {} min"),
+    ("{} 分钟内重启 {} 次", "{1} restarts within {0} min"),
+    ("没有配置告警规则，请在配置文件的 alerts 中添加", "No alert rules configured; add them under alerts in the config file"),
+    ("没有配置通知渠道，请在配置文件的 notify 中添加", "No notification channels configured; add them under notify in the config file"),
+    ("没有配置告警规则和通知渠道，请在配置文件的 alerts 与 notify 中添加", "No alert rules or notification channels configured; add them under alerts and notify in the config file"),
+    ("告警规则 {} 重复", "Duplicate alert rule {}"),
+    ("告警规则 {} 使用的通知渠道 {} 不存在", "Alert rule {} uses notification channel {}, which does not exist"),
+    ("{} 已恢复", "{} recovered"),
+    ("{} 已暂停", "{} paused"),
+    ("{} 已不可用 {} 分钟", "{} has been down for {} min"),
+    ("{} 在 {} 分钟内重启了 {} 次", "{0} restarted {2} times within {1} min"),
+    ("（冷却期间另有 {} 次未通知）", "({} more occurrences during the cool-down were not notified)"),
+    ("正在按 {} 条告警规则监视连接，按 Ctrl-C 停止", "Watching tunnels with {} alert rules; press Ctrl-C to stop"),
+    ("通过 {} 发送通知失败: {}", "Failed to send notification via {}: {}"),
+    ("这是一条测试通知", "This is a test notification"),
+    ("通知渠道 {} 不存在", "Notification channel {} does not exist"),
+    ("已发送到 {}", "Sent to {}"),
+    ("{} 个通知渠道发送失败", "{} notification channels failed"),
+    ("告警", "ALERT"),
+    ("恢复", "RESOLVED"),
+    ("通知命令执行失败: {}", "Notification command failed: {}"),
+    ("通知渠道无效: {}", "Invalid notification channel: {}"),
+    ("告警规则无效: {}", "Invalid alert rule: {}"),
+
+    // email notifications
+    ("设置电子邮件渠道的 SMTP 密码，保存在系统钥匙串中", "Set the SMTP password of an email channel, stored in the system keychain"),
+    ("删除保存的密码", "Delete the saved password"),
+    ("通知渠道 {} 不是电子邮件渠道", "Notification channel {} is not an email channel"),
+    ("SMTP 密码", "SMTP password"),
+    ("已保存通知渠道 {} 的 SMTP 密码", "Saved the SMTP password for notification channel {}"),
+    ("已删除通知渠道 {} 的 SMTP 密码", "Deleted the SMTP password for notification channel {}"),
+    ("环境变量 {} 未设置", "Environment variable {} is not set"),
+    ("未设置通知渠道 {} 的 SMTP 密码，请使用 tfa alert password {} 设置", "No SMTP password set for notification channel {}; set it with tfa alert password {}"),
+    ("无效的邮件地址 {}", "Invalid email address {}"),
+    ("规则", "Rule"),
+    ("时间", "Time"),
+
+    // uptime pings
+    ("探测成功后请求的推送地址（healthchecks.io、Uptime Kuma），失败时发送失败信号，下次启动时生效；传入空字符串删除", "Push URL requested after each successful probe (healthchecks.io, Uptime Kuma); failures are signalled too. Takes effect on next start; pass an empty string to remove"),
+    ("探测成功后请求的推送地址（healthchecks.io、Uptime Kuma），失败时发送失败信号；未设置 --probe-interval 时每 60 秒探测一次", "Push URL requested after each successful probe (healthchecks.io, Uptime Kuma); failures are signalled too. Probes every 60 seconds unless --probe-interval is set"),
+    ("无效的推送地址 {}，应以 http:// 或 https:// 开头", "Invalid push URL {}; it must start with http:// or https://"),
+    ("连接进程意外退出", "Tunnel process exited unexpectedly"),
+
+    // opentelemetry
+    ("查看或设置 OpenTelemetry 导出：启动、停止、重启与健康探测以 OTLP/HTTP 发送 span 和耗时指标", "Show or set OpenTelemetry export: start, stop, restart and health probes are sent as spans and duration metrics over OTLP/HTTP"),
+    ("OTLP/HTTP 地址，如 http://otel-collector:4318；省略时显示当前设置", "OTLP/HTTP endpoint, e.g. http://otel-collector:4318; omit to show the current setting"),
+    ("附加的请求头，写作 名称=值，可重复使用", "Extra request header as NAME=VALUE; may be repeated"),
+    ("资源属性 service.name，默认为 tfa", "Resource attribute service.name; defaults to tfa"),
+    ("关闭导出", "Turn off export"),
+    ("已关闭 OpenTelemetry 导出", "OpenTelemetry export turned off"),
+    ("OpenTelemetry 导出已设置为 {}（service.name={}）", "OpenTelemetry export set to {} (service.name={})"),
+    ("OpenTelemetry 导出: {}（service.name={}）", "OpenTelemetry export: {} (service.name={})"),
+    ("请求头: {}", "Headers: {}"),
+    ("未设置 OpenTelemetry 导出", "OpenTelemetry export is not set"),
+    ("无效的 OTLP 地址 {}，应以 http:// 或 https:// 开头", "Invalid OTLP endpoint {}; it must start with http:// or https://"),
+    ("无效的请求头 {}，应为 名称=值", "Invalid header {}; expected NAME=VALUE"),
+
+    // recover
+    ("重新接管仍在运行的连接：核对记录的后端进程，补上意外退出的统计代理、有效期监视与延迟记录进程", "Take back tunnels that are still running: verify the recorded backend processes and respawn stats proxies, TTL watchers and latency recorders that exited unexpectedly"),
+    ("连接别名，支持通配符；省略时处理全部连接", "Tunnel aliases, wildcards allowed; all tunnels when omitted"),
+    ("{}: 记录的进程 (PID {}) 已退出，下次启动时计入失败次数", "{}: the recorded process (PID {}) has exited; it counts as a failure on the next start"),
+    ("{}: 进程 (PID {}) 仍在运行，后台进程正常", "{}: process (PID {}) is still running and its helpers are healthy"),
+    ("{}: 进程 (PID {}) 仍在运行，已重新启动 {}", "{}: process (PID {}) is still running; respawned {}"),
+    ("没有由本工具启动的连接", "No tunnels were started by this tool"),
+    ("后端进程仍在运行，但没有在端口 {} 上监听", "The backend process is still running but not listening on port {}"),
+    ("日志过滤进程已退出，之后的输出不再写入日志，重启连接后恢复", "The log filter has exited; further output will not reach the log until the tunnel is restarted"),
+    ("无法接管连接: {}", "Failed to take back the tunnel: {}"),
+
+    // service handoff
+    ("已接手 PID {} 的服务，它会在处理完已建立的请求后退出", "Took over the service from PID {}; it exits once its open requests finish"),
+    ("服务已由 PID {} 接手，不再接受新请求", "Service taken over by PID {}; no longer accepting new requests"),
+    ("等待 {} 秒后仍有未结束的请求，不再等待", "Requests still open after {} seconds; exiting without waiting further"),
+    ("无法接手后台进程: {}", "Failed to take over helper processes: {}"),
+
+    // config reload
+    ("`tfa grpc`、`tfa web` 运行期间，该连接加入配置或开启该项时自动启动；服务启动时也会启动它。只在本机生效", "Started automatically by `tfa grpc` / `tfa web` when added to the config or when this is turned on, and when the service starts. Local to this machine"),
+    ("运行期间配置被修改时，`tfa grpc`、`tfa web` 是否重启该连接以应用新配置；只在本机生效", "Whether `tfa grpc` / `tfa web` restart the tunnel to apply config changes made while it runs. Local to this machine"),
+    ("tfa grpc、tfa web 运行期间自动启动该连接", "Start the tunnel automatically while tfa grpc or tfa web is running"),
+    ("是否在 tfa grpc、tfa web 运行期间自动启动该连接", "Whether tfa grpc and tfa web start the tunnel automatically"),
+    ("连接运行期间配置被修改时，tfa grpc、tfa web 的处理方式", "What tfa grpc and tfa web do when the config of a running tunnel changes"),
+    ("立即重启以应用新配置", "Restart right away to apply the new config"),
+    ("保持运行，新配置在手动重启后生效", "Keep running; the new config takes effect after a manual restart"),
+    ("立即应用配置的修改：启动新加入的 autostart 连接，停止已删除的连接，按 on_change 重启有修改的运行中连接", "Apply config changes now: start newly added autostart tunnels, stop removed ones and restart changed running tunnels according to on_change"),
+    ("配置没有需要应用的变化", "No config changes to apply"),
+    ("{} 个连接未能应用新配置", "{} tunnel(s) failed to apply the new config"),
+    ("配置已重新加载", "Config reloaded"),
+    ("已启动: {}", "Started: {}"),
+    ("已停止: {}", "Stopped: {}"),
+    ("已重启: {}", "Restarted: {}"),
+    ("配置已修改，重启后生效: {}", "Config changed, takes effect after a restart: {}"),
+    ("无法加载修改后的配置，连接保持不变: {}", "Failed to load the changed config; tunnels left as they are: {}"),
+    ("无法监视配置文件，修改后需要执行 tfa reload: {}", "Failed to watch the config file; run tfa reload after changing it: {}"),
+
+    // extra ports
+    ("同时转发的其他端口，写作 本地端口:目标端口，可重复指定；只适用于 kubectl 连接和经跳板（bastion）访问的 cloudflared 连接", "Another port to forward alongside, as LOCAL:REMOTE, repeatable; only for kubectl tunnels and cloudflared tunnels reached through a bastion"),
+    ("同时转发的其他端口，写作 本地端口:目标端口，可重复指定；传入空字符串删除全部额外端口", "Another port to forward alongside, as LOCAL:REMOTE, repeatable; pass an empty string to remove all extra ports"),
+    ("无效的端口映射 {}，应为 本地端口:目标端口", "Invalid port mapping {}, expected LOCAL:REMOTE"),
+    ("连接 {} 不能转发额外端口：只有 kubectl 连接和经跳板（--via-mode bastion）访问的 cloudflared 连接可以指定目标端口", "Tunnel {} cannot forward extra ports: only kubectl tunnels and cloudflared tunnels reached through a bastion (--via-mode bastion) can choose the target port"),
+    ("连接 {} 的本地端口 {} 重复", "Tunnel {} uses local port {} more than once"),
+    ("连接 {} 的额外端口 {} 已被占用", "Extra port {1} of tunnel {0} is already in use"),
+
+    // remote service
+    ("Access 应用背后的服务，如 tcp://localhost:5432，用于显示目标端口和判断客户端类型；传入空字符串删除", "Service behind the Access application, e.g. tcp://localhost:5432, used to show the target port and detect the client type; pass an empty string to remove it"),
+    ("Access 应用背后的服务，如 tcp://localhost:5432，用于显示目标端口和判断客户端类型", "Service behind the Access application, e.g. tcp://localhost:5432, used to show the target port and detect the client type"),
+
+    // usage notes
+    ("连接的使用说明，由 tfa show 显示，启动后也会显示；传入空字符串删除", "Notes on using the tunnel, shown by tfa show and after it starts; pass an empty string to remove them"),
+    ("连接的使用说明，由 tfa show 显示，启动后也会显示", "Notes on using the tunnel, shown by tfa show and after it starts"),
+    ("启动后显示的使用方法模板，如 ssh -p {port} user@{host}；传入空字符串删除", "Usage template shown after the tunnel starts, e.g. ssh -p {port} user@{host}; pass an empty string to remove it"),
+    ("启动后显示的使用方法模板，{alias}、{host}、{port} 替换为连接的别名与本地地址，如 ssh -p {port} user@{host}", "Usage template shown after the tunnel starts; {alias}, {host} and {port} are replaced with the alias and local address, e.g. ssh -p {port} user@{host}"),
+    ("使用方法", "Usage"),
+    ("{} 的使用方法:", "How to use {}:"),
+
+    // clipboard
+    ("启动后把连接的使用方法（未设置时为本地地址）复制到剪贴板；默认值见 tfa config copy", "Copy the usage hint (or the local address if unset) to the clipboard after starting; see tfa config copy for the default"),
+    ("不复制到剪贴板，即使配置中默认复制", "Do not copy to the clipboard even if the config copies by default"),
+    ("持有标准输入中的内容作为剪贴板内容，直到其他程序写入（内部使用）", "Hold stdin as the clipboard content until another program replaces it (internal)"),
+    ("查看或设置 tfa run 启动后是否默认把连接的使用方法复制到剪贴板", "Show or set whether tfa run copies the usage hint to the clipboard by default"),
+    ("是否默认复制（true 或 false），省略时显示当前设置", "Whether to copy by default (true or false); shows the current setting when omitted"),
+    ("tfa run 启动后将默认复制使用方法，--no-copy 可跳过", "tfa run now copies the usage hint by default; skip with --no-copy"),
+    ("tfa run 启动后不再默认复制，--copy 可复制", "tfa run no longer copies by default; use --copy to copy"),
+    ("启动后默认复制到剪贴板: {}", "Copy to clipboard after start by default: {}"),
+    ("已复制到剪贴板: {}", "Copied to clipboard: {}"),
+    ("无法访问剪贴板: {}", "Failed to access the clipboard: {}"),
+    ("无法写入剪贴板: {}", "Failed to write to the clipboard: {}"),
+
+    // quick tunnel sharing
+    ("以 quick tunnel 把本机服务临时公开到 trycloudflare.com，不需要 Cloudflare 账号：在前台运行，显示地址与二维码，按 Ctrl-C 结束", "Expose a local service on a temporary trycloudflare.com address with a quick tunnel, no Cloudflare account needed: runs in the foreground, shows the address and a QR code, Ctrl-C to stop"),
+    ("要公开的本机端口，或完整的地址，如 http://localhost:3000", "Local port to expose, or a full address such as http://localhost:3000"),
+    ("不显示二维码", "Do not show the QR code"),
+    ("{} 已公开到 {}，按 Ctrl-C 结束", "{} is public at {}; press Ctrl-C to stop"),
+
+    // rate limit validation
+    ("限速不能为 0，不限速时删除该字段", "Rate limit cannot be 0; remove the field for no limit"),
+
+    // local control protocol
+    ("显示已有的日志后继续显示新写入的行，按 Ctrl-C 退出", "Show existing logs, then keep showing new lines; Ctrl-C to exit"),
+    ("无法打开控制套接字，tfa log --follow 等命令改为自行读取日志文件: {}", "Failed to open the control socket; tfa log --follow and similar commands will read log files themselves: {}"),
+    ("控制套接字接受连接失败: {}", "Control socket failed to accept a connection: {}"),
+    ("控制协议的帧过长（{} 字节）", "Control protocol frame too long ({} bytes)"),
+    ("不支持客户端的控制协议版本 {}，本服务支持 {}", "Client control protocol versions {} are not supported; this server supports {}"),
+    ("控制协议不支持方法 {}", "Control protocol does not support method {}"),
+    ("控制协议的参数有误: {}", "Invalid control protocol parameters: {}"),
+    ("无法与正在运行的服务（tfa {}）通信: {}", "Cannot talk to the running server (tfa {}): {}"),
+    ("服务已关闭控制连接", "The server closed the control connection"),
+
+    // system tray
+    ("在系统托盘显示连接菜单：勾选启动连接，取消勾选停止连接；需要 tfa web 或 tfa grpc 在运行，按 Ctrl-C 退出", "Show a tunnel menu in the system tray: check to start a tunnel, uncheck to stop it; needs tfa web or tfa grpc running, Ctrl-C to exit"),
+    ("当前平台不支持托盘图标，请使用 tfa web 的网页控制台", "The tray icon is not supported on this platform; use the tfa web dashboard instead"),
+    ("无法显示托盘图标: {}", "Failed to show the tray icon: {}"),
+    ("托盘图标已显示，按 Ctrl-C 退出", "Tray icon shown; press Ctrl-C to exit"),
+    ("{}/{} 个连接在运行", "{}/{} tunnels running"),
+    ("没有正在运行的 tfa grpc 或 tfa web", "No tfa grpc or tfa web is running"),
+    ("请先启动 tfa web 或 tfa grpc", "Start tfa web or tfa grpc first"),
+    ("没有配置任何连接", "No tunnels configured"),
+    ("{}（已停用）", "{} (disabled)"),
+    ("{}（处理中）", "{} (working...)"),
+    ("退出", "Quit"),
+];
@@ -0,0 +1,134 @@
+//! 从 HTTP(S) 地址导入团队维护的连接目录。
+//!
+//! 目录格式与全局配置文件相同（`{"tunnels": {...}}`）。导入的连接记录来源地址，
+//! 成为只读的受管连接：本地不能修改或删除，再次导入同一地址时整体更新，
+//! 目录中已删除的连接随之移除。与本地自建连接同名的条目会被跳过，不覆盖本地连接。
+
+use anyhow::Result;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::process::Command;
+
+use crate::config::Config;
+use crate::error::TunnelError;
+use crate::i18n::t;
+use crate::tunnel::Tunnel;
+use tracing::debug;
+
+/// 提供 Bearer 令牌的环境变量，`--token` 未指定时使用
+pub const TOKEN_ENV: &str = "CF_MANAGER_IMPORT_TOKEN";
+
+/// 下载超时秒数
+const FETCH_TIMEOUT: u64 = 30;
+
+#[derive(Deserialize)]
+struct Catalogue {
+    tunnels: BTreeMap<String, serde_json::Value>,
+}
+
+/// 单个连接的导入结果
+#[derive(Debug, PartialEq, Eq)]
+pub enum Change {
+    Added,
+    Updated,
+    Unchanged,
+    Removed,
+    /// 与本地自建或其他目录的连接同名，附带原因
+    Skipped(String),
+}
+
+/// 下载并校验目录，返回其中的连接
+pub fn fetch(url: &str, token: Option<&str>, sha256: Option<&str>) -> Result<Vec<Tunnel>> {
+    debug!(url, "下载连接目录");
+    let mut command = Command::new("curl");
+    command.args(["-fsSL", "--max-time", &FETCH_TIMEOUT.to_string()]);
+    if let Some(token) = token {
+        command.arg("-H").arg(format!("Authorization: Bearer {}", token));
+    }
+    let output = command.arg(url).output()
+        .map_err(|e| anyhow::anyhow!(t!("无法执行 curl: {}", e)))?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(t!("下载 {} 失败: {}", url, String::from_utf8_lossy(&output.stderr).trim())));
+    }
+
+    if let Some(expected) = sha256 {
+        let actual: String = Sha256::digest(&output.stdout).iter().map(|byte| format!("{:02x}", byte)).collect();
+        if !actual.eq_ignore_ascii_case(expected.trim()) {
+            return Err(TunnelError::config(t!("目录的 SHA-256 不匹配：期望 {}，实际 {}", expected, actual)));
+        }
+    }
+
+    let catalogue: Catalogue = serde_json::from_slice(&output.stdout)
+        .map_err(|e| TunnelError::config(t!("解析 {} 失败: {}", url, e)))?;
+    catalogue.tunnels.into_iter()
+        .map(|(alias, value)| {
+            let mut tunnel: Tunnel = serde_json::from_value(value)
+                .map_err(|e| TunnelError::config(t!("目录中的连接 {} 无效: {}", alias, e)))?;
+            tunnel.alias = alias;
+            tunnel.managed_by = Some(url.to_string());
+            Ok(tunnel)
+        })
+        .collect()
+}
+
+/// 计算导入结果并得到导入后的全局连接；`dry_run` 之外由调用方保存
+pub fn merge(config: &Config, url: &str, imported: Vec<Tunnel>) -> (Vec<(String, Change)>, Vec<Tunnel>) {
+    let mut current = config.global_tunnels();
+    let mut changes = Vec::new();
+    let mut incoming: HashMap<String, Tunnel> = HashMap::new();
+    for mut tunnel in imported {
+        let alias = tunnel.alias.clone();
+        let change = match current.get(&alias) {
+            None => Change::Added,
+            Some(existing) if existing.managed_by.as_deref() != Some(url) => Change::Skipped(match &existing.managed_by {
+                Some(other) => t!("已由目录 {} 管理", other),
+                None => t!("与本地连接同名"),
+            }),
+            Some(existing) => {
+                // 钩子、服务令牌、数据库密码、特权端口处理方式、出站代理、日志转发和启用状态是本机设置，更新时保留
+                tunnel.hooks = existing.hooks.clone();
+                tunnel.service_token = existing.service_token;
+                tunnel.client_password = existing.client_password;
+                tunnel.privileged = existing.privileged;
+                tunnel.outbound_proxy = existing.outbound_proxy.clone();
+                tunnel.log_sinks = existing.log_sinks.clone();
+                tunnel.ping_url = existing.ping_url.clone();
+                tunnel.enabled = existing.enabled;
+                tunnel.autostart = existing.autostart;
+                tunnel.on_change = existing.on_change;
+                if serde_json::to_value(existing).ok() == serde_json::to_value(&tunnel).ok() {
+                    Change::Unchanged
+                } else {
+                    Change::Updated
+                }
+            }
+        };
+        if !matches!(change, Change::Skipped(_)) {
+            incoming.insert(alias.clone(), tunnel);
+        }
+        changes.push((alias, change));
+    }
+
+    let removed: Vec<String> = current.values()
+        .filter(|t| t.managed_by.as_deref() == Some(url) && !incoming.contains_key(&t.alias))
+        .map(|t| t.alias.clone())
+        .collect();
+    for alias in removed {
+        current.remove(&alias);
+        changes.push((alias, Change::Removed));
+    }
+    current.extend(incoming);
+    changes.sort_by(|a, b| a.0.cmp(&b.0));
+    (changes, current.into_values().collect())
+}
+
+/// 移除由该目录管理的全部连接，返回被移除的别名
+pub fn forget(config: &Config, url: &str) -> (Vec<String>, Vec<Tunnel>) {
+    let (managed, kept): (Vec<Tunnel>, Vec<Tunnel>) = config.global_tunnels()
+        .into_values()
+        .partition(|t| t.managed_by.as_deref() == Some(url));
+    let mut removed: Vec<String> = managed.into_iter().map(|t| t.alias).collect();
+    removed.sort();
+    (removed, kept)
+}
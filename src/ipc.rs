@@ -0,0 +1,607 @@
+//! 本机控制协议：`tfa grpc` 与 `tfa web` 运行期间同时在状态目录的 Unix 套接字 `control.sock`
+//! （Windows 上为按状态目录命名的命名管道）上提供控制接口，命令行和托盘据此与常驻的服务协作，
+//! 例如 `tfa log --follow` 直接订阅服务的日志流，而不必自己读取日志文件。
+//!
+//! 每帧是 4 字节大端长度加一个 JSON 对象。连接建立后客户端先发送 `{"versions": [1]}` 列出支持的协议版本，
+//! 服务选择双方都支持的最高版本回复 `{"version": 1, "server": "<tfa 版本>"}`，没有共同版本时回复
+//! `{"error": "..."}` 并断开。之后客户端发送 `{"id": 1, "method": "list", "params": {...}}`，服务以相同的 id
+//! 回复 `{"id": 1, "result": ...}` 或 `{"id": 1, "error": {"code": <退出码>, "message": "..."}}`；
+//! 流式的方法先回复任意个 `{"id": 1, "item": ...}`，结束时再回复 `result`。同一连接上可以同时进行多个请求，
+//! `cancel` 结束指定 id 的流式请求，断开连接结束所有请求。
+//!
+//! 与状态目录中的文件一样，这是不同版本的 tfa 之间的接口：新增字段一律可以缺省，双方忽略不认识的字段，
+//! 不认识的方法以错误回复而不断开连接；不兼容的修改必须增加协议版本。
+//! 只有当前用户可以连接，因此不检查 API 密钥，权限与直接执行命令相同。
+
+use anyhow::{bail, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadHalf, WriteHalf};
+use tokio::sync::{broadcast, mpsc, oneshot};
+
+use crate::config::Config;
+use crate::control;
+use crate::error::{exit_code, TunnelError};
+use crate::events::{self, TunnelEvent};
+use crate::i18n::t;
+use crate::tunnel::{Tunnel, TunnelStatus};
+use tracing::{debug, warn};
+
+/// 本版本支持的协议版本
+const PROTOCOL_VERSIONS: &[u32] = &[1];
+/// 单帧的最大长度，超过时视为对方出错而断开
+const MAX_FRAME_BYTES: u32 = 16 * 1024 * 1024;
+/// 每个连接中等待写出的回复数
+const REPLY_CAPACITY: usize = 256;
+
+/// 客户端的第一帧：支持的协议版本
+#[derive(Debug, Serialize, Deserialize)]
+struct Hello {
+    versions: Vec<u32>,
+}
+
+/// 服务对 [`Hello`] 的回复：选定的版本，或没有共同版本时的错误
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Welcome {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    server: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Request {
+    id: u64,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Response {
+    id: u64,
+    #[serde(flatten)]
+    reply: Reply,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Reply {
+    /// 流式请求的一项
+    Item(Value),
+    /// 请求结束
+    Result(Value),
+    Error(RemoteError),
+}
+
+/// 请求失败的原因：`code` 与同样的操作在命令行中失败时的退出码相同
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RemoteError {
+    pub code: i32,
+    pub message: String,
+}
+
+impl From<anyhow::Error> for RemoteError {
+    fn from(error: anyhow::Error) -> Self {
+        let Some(tunnel_error) = error.downcast_ref::<TunnelError>() else {
+            return Self { code: exit_code::FAILURE, message: error.to_string() };
+        };
+        let message = match tunnel_error.hint() {
+            Some(hint) => format!("{}\n{}", tunnel_error, hint),
+            None => tunnel_error.to_string(),
+        };
+        Self { code: tunnel_error.exit_code(), message }
+    }
+}
+
+/// `list`、`start`、`stop` 返回的连接
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TunnelInfo {
+    pub alias: String,
+    #[serde(default)]
+    pub source: String,
+    #[serde(default)]
+    pub local_address: String,
+    /// 与 `tfa status --json` 相同的状态名，如 running、stopped
+    #[serde(default)]
+    pub status: String,
+    /// 后端进程在运行（running 或 degraded）
+    #[serde(default)]
+    pub running: bool,
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl TunnelInfo {
+    fn of(tunnel: &Tunnel) -> Self {
+        let status = tunnel.status();
+        Self {
+            alias: tunnel.alias.clone(),
+            source: tunnel.source.clone(),
+            local_address: SocketAddr::new(tunnel.listen_ip(), tunnel.port).to_string(),
+            status: status.to_string(),
+            running: matches!(status, TunnelStatus::Running | TunnelStatus::Degraded),
+            enabled: tunnel.enabled,
+            tags: tunnel.tags.clone(),
+        }
+    }
+}
+
+/// `logs` 流中的一行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogLine {
+    pub alias: String,
+    pub line: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ListParams {
+    /// 要列出的连接，支持通配符；为空时列出全部
+    #[serde(default)]
+    aliases: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StartParams {
+    alias: String,
+    #[serde(default)]
+    ttl_seconds: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StopParams {
+    alias: String,
+    /// 同 `tfa stop --force`
+    #[serde(default)]
+    force: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LogsParams {
+    /// 要读取日志的连接，支持通配符
+    aliases: Vec<String>,
+    /// 发送已有的日志后继续发送新写入的行，直到取消
+    #[serde(default)]
+    follow: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CancelParams {
+    id: u64,
+}
+
+#[cfg(unix)]
+type Stream = tokio::net::UnixStream;
+#[cfg(windows)]
+type Stream = tokio::net::windows::named_pipe::NamedPipeClient;
+
+#[cfg(unix)]
+fn socket_path() -> Result<std::path::PathBuf> {
+    Ok(crate::state::StateStore::dir()?.join("control.sock"))
+}
+
+/// 命名管道不在文件系统中，按状态目录命名，使不同的档案和配置目录互不干扰
+#[cfg(windows)]
+fn pipe_name() -> Result<String> {
+    use sha2::{Digest, Sha256};
+
+    let dir = crate::state::StateStore::dir()?;
+    let digest: String = Sha256::digest(dir.to_string_lossy().as_bytes()).iter().take(8).map(|byte| format!("{:02x}", byte)).collect();
+    Ok(format!(r"\\.\pipe\tfa-control-{}", digest))
+}
+
+async fn read_frame<R: AsyncRead + Unpin, T: DeserializeOwned>(reader: &mut R) -> Result<Option<T>> {
+    let length = match reader.read_u32().await {
+        Ok(length) => length,
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    if length > MAX_FRAME_BYTES {
+        bail!(t!("控制协议的帧过长（{} 字节）", length));
+    }
+    let mut bytes = vec![0; length as usize];
+    reader.read_exact(&mut bytes).await?;
+    Ok(Some(serde_json::from_slice(&bytes)?))
+}
+
+async fn write_frame<W: AsyncWrite + Unpin, T: Serialize>(writer: &mut W, value: &T) -> Result<()> {
+    let bytes = serde_json::to_vec(value)?;
+    let Ok(length) = u32::try_from(bytes.len()) else {
+        bail!(t!("控制协议的帧过长（{} 字节）", bytes.len()));
+    };
+    writer.write_u32(length).await?;
+    writer.write_all(&bytes).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// 本进程的控制套接字，释放时删除；已被新启动的服务替换时保留
+pub struct Listening {
+    #[cfg(unix)]
+    inode: u64,
+}
+
+impl Drop for Listening {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+
+            if let Ok(path) = socket_path()
+                && std::fs::metadata(&path).is_ok_and(|metadata| metadata.ino() == self.inode)
+            {
+                let _ = std::fs::remove_file(path);
+            }
+        }
+    }
+}
+
+/// 开始在控制套接字上提供服务，直到进程退出；已有其他服务在监听时由本进程接手，之后的连接都由本进程处理
+#[cfg(unix)]
+pub fn listen() -> Result<Listening> {
+    use std::os::unix::fs::MetadataExt;
+
+    let path = socket_path()?;
+    // 旧进程留下的或正在使用的套接字：删除后重新绑定，旧进程已建立的连接不受影响
+    let _ = std::fs::remove_file(&path);
+    let listener = tokio::net::UnixListener::bind(&path)?;
+    // 没有 XDG_RUNTIME_DIR 时状态目录位于配置目录下，不一定只属于当前用户
+    std::fs::set_permissions(&path, std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+    let inode = std::fs::metadata(&path)?.ino();
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tokio::spawn(connection(stream));
+                }
+                Err(e) => {
+                    warn!("{}", t!("控制套接字接受连接失败: {}", e));
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+            }
+        }
+    });
+    Ok(Listening { inode })
+}
+
+/// 开始在控制管道上提供服务，直到进程退出；同名管道允许多个实例，新旧进程可以同时提供服务
+#[cfg(windows)]
+pub fn listen() -> Result<Listening> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let name = pipe_name()?;
+    let mut server = ServerOptions::new().reject_remote_clients(true).create(&name)?;
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = server.connect().await {
+                warn!("{}", t!("控制套接字接受连接失败: {}", e));
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                continue;
+            }
+            // 先创建下一个实例再交出已连接的实例，使管道始终可以连接；
+            // 创建失败时保留已连接的实例，稍后重试，不能就此停止服务
+            let next = loop {
+                match ServerOptions::new().reject_remote_clients(true).create(&name) {
+                    Ok(next) => break next,
+                    Err(e) => {
+                        warn!("{}", t!("控制套接字接受连接失败: {}", e));
+                        tokio::time::sleep(Duration::from_secs(1)).await;
+                    }
+                }
+            };
+            tokio::spawn(connection(std::mem::replace(&mut server, next)));
+        }
+    });
+    Ok(Listening {})
+}
+
+async fn connection<S: AsyncRead + AsyncWrite + Send + 'static>(stream: S) {
+    if let Err(e) = converse(stream).await {
+        debug!(error = %e, "控制连接中断");
+    }
+}
+
+type Pending = Arc<Mutex<HashMap<u64, oneshot::Sender<()>>>>;
+
+async fn converse<S: AsyncRead + AsyncWrite + Send + 'static>(stream: S) -> Result<()> {
+    let (mut reader, mut writer) = tokio::io::split(stream);
+    let Some(hello) = read_frame::<_, Hello>(&mut reader).await? else {
+        return Ok(());
+    };
+    let server = Some(env!("CARGO_PKG_VERSION").to_string());
+    let Some(version) = hello.versions.iter().copied().filter(|version| PROTOCOL_VERSIONS.contains(version)).max() else {
+        let versions = |versions: &[u32]| versions.iter().map(u32::to_string).collect::<Vec<_>>().join(", ");
+        let error = t!("不支持客户端的控制协议版本 {}，本服务支持 {}", versions(&hello.versions), versions(PROTOCOL_VERSIONS));
+        return write_frame(&mut writer, &Welcome { server, error: Some(error), ..Welcome::default() }).await;
+    };
+    write_frame(&mut writer, &Welcome { version: Some(version), server, error: None }).await?;
+
+    // 回复经由通道交给单独的任务写出，各个请求的回复按帧交错，不会混在一起
+    let (replies, mut outgoing) = mpsc::channel::<Response>(REPLY_CAPACITY);
+    let writing = tokio::spawn(async move {
+        while let Some(response) = outgoing.recv().await {
+            if let Err(e) = write_frame(&mut writer, &response).await {
+                debug!(error = %e, "写出控制协议的回复失败");
+                break;
+            }
+        }
+    });
+    let pending = Pending::default();
+    let result = receive(&mut reader, &replies, &pending).await;
+    // 客户端已断开：结束仍在进行的流式请求
+    for (_, cancel) in pending.lock().map(|mut pending| pending.drain().collect::<Vec<_>>()).unwrap_or_default() {
+        let _ = cancel.send(());
+    }
+    drop(replies);
+    let _ = writing.await;
+    result
+}
+
+async fn receive<R: AsyncRead + Unpin>(reader: &mut R, replies: &mpsc::Sender<Response>, pending: &Pending) -> Result<()> {
+    while let Some(request) = read_frame::<_, Request>(reader).await? {
+        let id = request.id;
+        if request.method == "cancel" {
+            let reply = match parse::<CancelParams>(request.params) {
+                Ok(params) => {
+                    if let Some(cancel) = pending.lock().ok().and_then(|mut pending| pending.remove(&params.id)) {
+                        let _ = cancel.send(());
+                    }
+                    Reply::Result(Value::Null)
+                }
+                Err(e) => Reply::Error(e),
+            };
+            replies.send(Response { id, reply }).await?;
+            continue;
+        }
+        let (cancel, cancelled) = oneshot::channel();
+        if let Ok(mut pending) = pending.lock() {
+            pending.insert(id, cancel);
+        }
+        let replies = replies.clone();
+        let pending = pending.clone();
+        tokio::spawn(async move {
+            let reply = match dispatch(request, &replies, cancelled).await {
+                Ok(result) => Reply::Result(result),
+                Err(e) => Reply::Error(e),
+            };
+            if let Ok(mut pending) = pending.lock() {
+                pending.remove(&id);
+            }
+            let _ = replies.send(Response { id, reply }).await;
+        });
+    }
+    Ok(())
+}
+
+async fn dispatch(request: Request, replies: &mpsc::Sender<Response>, cancelled: oneshot::Receiver<()>) -> Result<Value, RemoteError> {
+    match request.method.as_str() {
+        "list" => {
+            let params: ListParams = parse(request.params)?;
+            blocking(move || {
+                let config = Config::load()?;
+                let tunnels: Vec<TunnelInfo> = if params.aliases.is_empty() {
+                    config.list_tunnels()?.into_iter().map(TunnelInfo::of).collect()
+                } else {
+                    config.expand_aliases(&params.aliases)?
+                        .iter()
+                        .map(|alias| config.get_tunnel(alias).map(TunnelInfo::of))
+                        .collect::<Result<_, _>>()?
+                };
+                Ok(serde_json::to_value(tunnels)?)
+            })
+            .await
+        }
+        "start" => {
+            let params: StartParams = parse(request.params)?;
+            blocking(move || {
+                let config = Config::load()?;
+                let tunnel = config.get_tunnel(&params.alias)?;
+                control::start(&config, &params.alias)?;
+                if let Some(ttl) = params.ttl_seconds {
+                    tunnel.set_ttl(Duration::from_secs(ttl))?;
+                }
+                Ok(serde_json::to_value(TunnelInfo::of(tunnel))?)
+            })
+            .await
+        }
+        "stop" => {
+            let params: StopParams = parse(request.params)?;
+            blocking(move || {
+                let config = Config::load()?;
+                let tunnel = config.get_tunnel(&params.alias)?;
+                control::stop(&config, &params.alias, params.force)?;
+                Ok(serde_json::to_value(TunnelInfo::of(tunnel))?)
+            })
+            .await
+        }
+        "logs" => logs(request.id, parse(request.params)?, replies, cancelled).await,
+        method => Err(RemoteError { code: exit_code::USAGE, message: t!("控制协议不支持方法 {}", method) }),
+    }
+}
+
+/// 发送已有的日志，`follow` 时继续发送事件总线上的新行
+async fn logs(id: u64, params: LogsParams, replies: &mpsc::Sender<Response>, mut cancelled: oneshot::Receiver<()>) -> Result<Value, RemoteError> {
+    // 先订阅再读取已有的日志，以免两者之间写入的行丢失；代价是这些行可能重复出现一次
+    let mut events = events::subscribe();
+    let (aliases, history) = blocking(move || {
+        let config = Config::load()?;
+        let aliases = config.expand_aliases(&params.aliases)?;
+        let mut history = Vec::new();
+        for alias in &aliases {
+            // 已有的行带有 [alias] 前缀，与实时推送的行保持一致而去掉；钩子日志的行保留前缀以便区分
+            let prefix = format!("[{}] ", alias);
+            for line in config.get_tunnel(alias)?.get_logs() {
+                let line = line.strip_prefix(&prefix).map_or(line.clone(), str::to_string);
+                history.push(LogLine { alias: alias.clone(), line });
+            }
+        }
+        Ok((aliases, history))
+    })
+    .await?;
+    for line in history {
+        if !send_item(id, replies, &line).await {
+            return Ok(Value::Null);
+        }
+    }
+    if !params.follow {
+        return Ok(Value::Null);
+    }
+    loop {
+        let event = tokio::select! {
+            _ = &mut cancelled => return Ok(Value::Null),
+            event = events.recv() => event,
+        };
+        match event {
+            Ok(TunnelEvent::LogLine { alias, line }) if aliases.contains(&alias) => {
+                if !send_item(id, replies, &LogLine { alias, line }).await {
+                    return Ok(Value::Null);
+                }
+            }
+            Ok(_) => {}
+            Err(broadcast::error::RecvError::Lagged(skipped)) => warn!("{}", t!("处理不及，跳过了 {} 个事件", skipped)),
+            Err(broadcast::error::RecvError::Closed) => return Ok(Value::Null),
+        }
+    }
+}
+
+/// 发送流式请求的一项；连接已关闭时返回 false
+async fn send_item<T: Serialize>(id: u64, replies: &mpsc::Sender<Response>, item: &T) -> bool {
+    match serde_json::to_value(item) {
+        Ok(item) => replies.send(Response { id, reply: Reply::Item(item) }).await.is_ok(),
+        Err(_) => true,
+    }
+}
+
+fn parse<T: DeserializeOwned>(params: Value) -> Result<T, RemoteError> {
+    // 省略 params 与传入空对象相同
+    let params = if params.is_null() { Value::Object(Default::default()) } else { params };
+    serde_json::from_value(params).map_err(|e| RemoteError { code: exit_code::USAGE, message: t!("控制协议的参数有误: {}", e) })
+}
+
+/// 在阻塞线程中处理请求：读取配置、启动和停止连接都会阻塞
+async fn blocking<T: Send + 'static>(f: impl FnOnce() -> Result<T> + Send + 'static) -> Result<T, RemoteError> {
+    match tokio::task::spawn_blocking(f).await {
+        Ok(result) => result.map_err(RemoteError::from),
+        Err(e) => Err(RemoteError { code: exit_code::FAILURE, message: e.to_string() }),
+    }
+}
+
+/// 与正在运行的 `tfa grpc` 或 `tfa web` 的连接；请求依次进行
+pub struct Client {
+    reader: ReadHalf<Stream>,
+    writer: WriteHalf<Stream>,
+    next_id: u64,
+}
+
+impl Client {
+    /// 连接控制套接字并协商协议版本；没有正在运行的服务时返回 None
+    pub async fn connect() -> Result<Option<Self>> {
+        let Some(stream) = open().await? else {
+            return Ok(None);
+        };
+        let (mut reader, mut writer) = tokio::io::split(stream);
+        write_frame(&mut writer, &Hello { versions: PROTOCOL_VERSIONS.to_vec() }).await?;
+        let Some(welcome) = read_frame::<_, Welcome>(&mut reader).await? else {
+            return Ok(None);
+        };
+        if welcome.version.is_none() {
+            let server = welcome.server.unwrap_or_default();
+            bail!(t!("无法与正在运行的服务（tfa {}）通信: {}", server, welcome.error.unwrap_or_default()));
+        }
+        Ok(Some(Self { reader, writer, next_id: 1 }))
+    }
+
+    /// 列出连接，`aliases` 为空时列出全部；连接的列表、启动和停止目前只有托盘使用
+    #[cfg(feature = "tray")]
+    pub async fn list(&mut self, aliases: &[String]) -> Result<Vec<TunnelInfo>> {
+        self.call("list", ListParams { aliases: aliases.to_vec() }).await
+    }
+
+    #[cfg(feature = "tray")]
+    pub async fn start(&mut self, alias: &str) -> Result<TunnelInfo> {
+        self.call("start", StartParams { alias: alias.to_string(), ttl_seconds: None }).await
+    }
+
+    #[cfg(feature = "tray")]
+    pub async fn stop(&mut self, alias: &str) -> Result<TunnelInfo> {
+        self.call("stop", StopParams { alias: alias.to_string(), force: false }).await
+    }
+
+    /// 读取日志，每行交给 `on_line`；`follow` 时持续读取，直到连接断开
+    pub async fn logs(&mut self, aliases: &[String], follow: bool, mut on_line: impl FnMut(LogLine)) -> Result<()> {
+        let params = LogsParams { aliases: aliases.to_vec(), follow };
+        self.request("logs", params, |item| {
+            if let Ok(line) = serde_json::from_value(item) {
+                on_line(line);
+            }
+        })
+        .await?;
+        Ok(())
+    }
+
+    #[cfg(feature = "tray")]
+    async fn call<P: Serialize, T: DeserializeOwned>(&mut self, method: &str, params: P) -> Result<T> {
+        let result = self.request(method, params, |_| {}).await?;
+        Ok(serde_json::from_value(result)?)
+    }
+
+    /// 发送请求并等待结束，流式请求的每一项交给 `on_item`
+    async fn request<P: Serialize>(&mut self, method: &str, params: P, mut on_item: impl FnMut(Value)) -> Result<Value> {
+        let id = self.next_id;
+        self.next_id += 1;
+        let request = Request { id, method: method.to_string(), params: serde_json::to_value(params)? };
+        write_frame(&mut self.writer, &request).await?;
+        loop {
+            let Some(response) = read_frame::<_, Response>(&mut self.reader).await? else {
+                bail!(t!("服务已关闭控制连接"));
+            };
+            if response.id != id {
+                continue;
+            }
+            match response.reply {
+                Reply::Item(item) => on_item(item),
+                Reply::Result(result) => return Ok(result),
+                Reply::Error(error) => bail!(error.message),
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+async fn open() -> Result<Option<Stream>> {
+    let path = socket_path()?;
+    match Stream::connect(&path).await {
+        Ok(stream) => Ok(Some(stream)),
+        // 没有服务，或服务已退出而留下了套接字文件
+        Err(e) if matches!(e.kind(), std::io::ErrorKind::NotFound | std::io::ErrorKind::ConnectionRefused) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(windows)]
+async fn open() -> Result<Option<Stream>> {
+    use tokio::net::windows::named_pipe::ClientOptions;
+
+    /// 所有实例都在使用中
+    const ERROR_PIPE_BUSY: i32 = 231;
+
+    let name = pipe_name()?;
+    loop {
+        match ClientOptions::new().open(&name) {
+            Ok(stream) => return Ok(Some(stream)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) if e.raw_os_error() == Some(ERROR_PIPE_BUSY) => tokio::time::sleep(Duration::from_millis(50)).await,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
@@ -0,0 +1,68 @@
+//! 解析 Cloudflare Access 令牌（JWT）中的声明，只用于查看，不校验签名。
+
+use anyhow::Result;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::Deserialize;
+
+use crate::i18n::t;
+
+/// 令牌剩余有效期少于该秒数时给出提醒
+pub const EXPIRY_WARNING: u64 = 10 * 60;
+
+/// Access 令牌中常用的声明
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Claims {
+    /// 过期时间（Unix 时间）
+    pub exp: Option<u64>,
+    /// 签发时间（Unix 时间）
+    pub iat: Option<u64>,
+    pub iss: Option<String>,
+    /// Access 应用的 AUD 标签，可能是单个字符串或数组
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub aud: Vec<String>,
+    /// 用户登录时为邮箱
+    pub email: Option<String>,
+    /// 使用服务令牌时为服务令牌的 Client ID
+    pub common_name: Option<String>,
+    pub sub: Option<String>,
+}
+
+impl Claims {
+    /// 令牌代表的身份：用户邮箱、服务令牌或 sub
+    pub fn identity(&self) -> Option<&str> {
+        self.email.as_deref().or(self.common_name.as_deref()).or(self.sub.as_deref()).filter(|s| !s.is_empty())
+    }
+
+    /// 距离过期的秒数，已过期时为负数
+    pub fn remaining(&self, now: u64) -> Option<i64> {
+        self.exp.map(|exp| exp as i64 - now as i64)
+    }
+}
+
+/// 解码令牌的载荷部分，返回声明与原始 JSON
+pub fn decode(token: &str) -> Result<(Claims, serde_json::Value)> {
+    let payload = token.trim().split('.').nth(1)
+        .ok_or_else(|| anyhow::anyhow!(t!("令牌不是 JWT 格式")))?;
+    // 个别实现会保留 base64 的填充字符
+    let bytes = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('='))
+        .map_err(|_| anyhow::anyhow!(t!("令牌不是 JWT 格式")))?;
+    let raw: serde_json::Value = serde_json::from_slice(&bytes)
+        .map_err(|e| anyhow::anyhow!(t!("无法解析令牌中的声明: {}", e)))?;
+    let claims = serde_json::from_value(raw.clone())
+        .map_err(|e| anyhow::anyhow!(t!("无法解析令牌中的声明: {}", e)))?;
+    Ok((claims, raw))
+}
+
+fn one_or_many<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match Option::<OneOrMany>::deserialize(deserializer)? {
+        Some(OneOrMany::One(aud)) => vec![aud],
+        Some(OneOrMany::Many(aud)) => aud,
+        None => Vec::new(),
+    })
+}
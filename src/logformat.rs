@@ -0,0 +1,99 @@
+//! 内部日志的输出格式。`--log-format json` 时每条日志输出为一行 JSON，
+//! 以服务方式运行 `tfa grpc`、`tfa web`、`tfa alert run` 时可直接交给 journald、Fluent Bit 等采集。
+//!
+//! 每行包含 timestamp、level、message，事件带有连接别名（`alias` 字段）时输出为 tunnel，
+//! 其余字段放在 fields 中，保持原有的类型。
+
+use clap::ValueEnum;
+use serde_json::{Map, Value};
+use std::fmt;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::time::{FormatTime, SystemTime};
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields};
+use tracing_subscriber::registry::LookupSpan;
+
+/// 内部日志的格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    /// 便于阅读的文本
+    #[default]
+    Text,
+    /// 每行一个 JSON 对象
+    Json,
+}
+
+/// 每行一个 JSON 对象的日志格式
+pub struct Json;
+
+impl<S, N> FormatEvent<S, N> for Json
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, _ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> fmt::Result {
+        let mut timestamp = String::new();
+        SystemTime.format_time(&mut Writer::new(&mut timestamp))?;
+        let mut visitor = Fields::default();
+        event.record(&mut visitor);
+
+        let mut line = Map::new();
+        line.insert("timestamp".to_string(), timestamp.into());
+        line.insert("level".to_string(), event.metadata().level().as_str().into());
+        if let Some(tunnel) = visitor.tunnel {
+            line.insert("tunnel".to_string(), tunnel.into());
+        }
+        line.insert("message".to_string(), visitor.message.unwrap_or_default().into());
+        if !visitor.fields.is_empty() {
+            line.insert("fields".to_string(), Value::Object(visitor.fields));
+        }
+        writeln!(writer, "{}", Value::Object(line))
+    }
+}
+
+/// 收集事件的字段，message 与 alias 单独取出
+#[derive(Default)]
+struct Fields {
+    message: Option<String>,
+    tunnel: Option<String>,
+    fields: Map<String, Value>,
+}
+
+impl Fields {
+    fn insert(&mut self, field: &Field, value: Value) {
+        match (field.name(), value) {
+            ("message", Value::String(message)) => self.message = Some(message),
+            ("alias", Value::String(alias)) => self.tunnel = Some(alias),
+            (name, value) => {
+                self.fields.insert(name.to_string(), value);
+            }
+        }
+    }
+}
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.insert(field, format!("{:?}", value).into());
+    }
+}
@@ -0,0 +1,451 @@
+//! 日志转发：把连接的日志同时发送到 syslog / journald、Windows 事件日志或远程收集端（Vector、Loki 等），
+//! 与已有的日志体系集成。
+//!
+//! 后端进程的输出直接写入日志文件，本工具启动后即退出，因此配置了转发目标或脱敏规则时，
+//! 输出先经过一个后台的 `log-filter` 进程：它逐行脱敏后追加到日志文件，再交给转发线程发送。
+//! 后端退出后管道关闭，`log-filter` 发送完剩余的日志后退出。
+//! 收集端不可用时只丢弃转发的日志行，不影响日志文件和后端进程。
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::{self, BufRead, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::RwLock;
+use std::sync::mpsc::{self, RecvTimeoutError, SyncSender, TrySendError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::error::TunnelError;
+use crate::i18n::t;
+use crate::outbound;
+use crate::redact::Redactor;
+use crate::state::{Watcher, WatcherMark};
+use crate::tunnel::Tunnel;
+use tracing::debug;
+
+/// 攒够这么多行或等待这么久后发送一批
+const BATCH_LINES: usize = 100;
+const BATCH_DELAY: Duration = Duration::from_secs(1);
+/// 等待发送的日志行上限，收集端跟不上时丢弃新的日志行
+const QUEUE_LINES: usize = 10_000;
+/// 连接收集端与发送一批日志的超时
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 写入 syslog / journald / 事件日志时使用的程序名
+const IDENTIFIER: &str = "tfa";
+
+/// 全局转发目标，加载配置时记录，供启动后端时使用
+static GLOBAL: RwLock<Vec<LogSink>> = RwLock::new(Vec::new());
+
+/// 日志转发目标，配置文件中以字符串保存：
+/// `syslog`、`journald`、`eventlog`、`tcp://主机:端口`、`http(s)://...`、`loki+http(s)://...`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum LogSink {
+    /// 本机 syslog（/dev/log），journald 同样会收到
+    Syslog,
+    /// systemd-journald 原生协议，附带 TFA_TUNNEL 字段，可用 journalctl TFA_TUNNEL=别名 筛选
+    Journald,
+    /// Windows 应用程序事件日志
+    EventLog,
+    /// 按行发送 JSON 的 TCP 端点，如 Vector 的 socket 源、Logstash 的 tcp 输入
+    Tcp(String),
+    /// 以 POST 发送按行分隔的 JSON，如 Vector 的 http_server 源
+    Http(String),
+    /// Loki 的推送接口 /loki/api/v1/push
+    Loki(String),
+}
+
+impl LogSink {
+    /// 解析并检查转发目标能否在本机使用
+    pub fn new(spec: &str) -> Result<Self> {
+        let sink = Self::parse(spec)?;
+        let supported = match sink {
+            LogSink::Syslog | LogSink::Journald => cfg!(unix),
+            LogSink::EventLog => cfg!(windows),
+            _ => true,
+        };
+        if !supported {
+            return Err(TunnelError::config(t!("当前平台不支持日志转发目标 {}", sink)));
+        }
+        Ok(sink)
+    }
+
+    fn parse(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+        let invalid = || TunnelError::config(t!(
+            "日志转发目标 {} 无效：应为 syslog、journald、eventlog、tcp://主机:端口、http(s):// 或 loki+http(s):// 地址",
+            outbound::redact(spec)
+        ));
+        let sink = match spec {
+            "syslog" => LogSink::Syslog,
+            "journald" => LogSink::Journald,
+            "eventlog" => LogSink::EventLog,
+            _ => match spec.split_once("://") {
+                Some(("tcp", address)) if address.rsplit_once(':').is_some_and(|(host, port)| {
+                    !host.is_empty() && port.parse::<u16>().is_ok_and(|port| port != 0)
+                }) => LogSink::Tcp(address.to_string()),
+                Some(("http" | "https", rest)) if !rest.is_empty() => LogSink::Http(spec.to_string()),
+                Some(("loki+http" | "loki+https", rest)) if !rest.is_empty() => {
+                    LogSink::Loki(spec.trim_start_matches("loki+").to_string())
+                }
+                _ => return Err(invalid()),
+            },
+        };
+        Ok(sink)
+    }
+
+    /// 写入配置文件的形式，与 `parse` 互逆
+    fn spec(&self) -> String {
+        match self {
+            LogSink::Syslog => "syslog".to_string(),
+            LogSink::Journald => "journald".to_string(),
+            LogSink::EventLog => "eventlog".to_string(),
+            LogSink::Tcp(address) => format!("tcp://{}", address),
+            LogSink::Http(url) => url.clone(),
+            LogSink::Loki(url) => format!("loki+{}", url),
+        }
+    }
+}
+
+impl TryFrom<String> for LogSink {
+    type Error = anyhow::Error;
+
+    fn try_from(spec: String) -> Result<Self> {
+        Self::parse(&spec)
+    }
+}
+
+impl From<LogSink> for String {
+    fn from(sink: LogSink) -> Self {
+        sink.spec()
+    }
+}
+
+impl fmt::Display for LogSink {
+    /// 隐藏地址中的密码，用于显示和日志
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&outbound::redact(&self.spec()))
+    }
+}
+
+/// 记录全局转发目标
+pub fn set_global(sinks: Vec<LogSink>) {
+    if let Ok(mut global) = GLOBAL.write() {
+        *global = sinks;
+    }
+}
+
+/// 连接实际使用的转发目标：连接自身设置了目标时使用自身的设置，否则使用全局设置
+pub fn effective(own: &[LogSink]) -> Vec<LogSink> {
+    if !own.is_empty() {
+        return own.to_vec();
+    }
+    GLOBAL.read().map(|global| global.clone()).unwrap_or_default()
+}
+
+/// log-filter 进程：从标准输入逐行读取后端输出，脱敏后追加到日志文件并转发，直到输入关闭
+pub fn pipe(tunnel: &Tunnel, log_path: &Path) -> Result<()> {
+    let redactor = Redactor::new(&tunnel.redact)?;
+    // 登记失败不影响日志过滤，只是 tfa recover 无法判断它是否仍在运行
+    let _claim = WatcherMark::claim(&tunnel.alias, Watcher::LogFilter).ok();
+    let forwarder = Forwarder::start(&tunnel.alias, effective(&tunnel.log_sinks));
+    let mut file = OpenOptions::new().create(true).append(true).open(log_path)?;
+    let stdin = io::stdin();
+    let mut line = Vec::new();
+    let mut reader = stdin.lock();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        let text = String::from_utf8_lossy(&line);
+        let text = redactor.apply(&text);
+        file.write_all(text.as_bytes())?;
+        file.flush()?;
+        if let Some(forwarder) = &forwarder {
+            forwarder.send(text.trim_end());
+        }
+    }
+    if let Some(forwarder) = forwarder {
+        forwarder.finish();
+    }
+    Ok(())
+}
+
+/// 日志行的级别，取值与 syslog 的 severity 相同
+#[derive(Debug, Clone, Copy)]
+enum Severity {
+    Error = 3,
+    Warning = 4,
+    Info = 6,
+    Debug = 7,
+}
+
+impl Severity {
+    /// 按 cloudflared（`2024-01-01T00:00:00Z ERR ...`）或 kubectl（`E1016 10:00:00.000000 ...`）的日志格式判断级别
+    fn of(line: &str) -> Self {
+        for word in line.split_whitespace().take(2) {
+            match word {
+                "DBG" => return Severity::Debug,
+                "INF" => return Severity::Info,
+                "WRN" => return Severity::Warning,
+                "ERR" | "FTL" | "PNC" => return Severity::Error,
+                _ => {}
+            }
+            if word.len() == 5 && word.is_ascii() && word[1..].bytes().all(|b| b.is_ascii_digit()) {
+                match word.as_bytes()[0] {
+                    b'E' | b'F' => return Severity::Error,
+                    b'W' => return Severity::Warning,
+                    b'I' => return Severity::Info,
+                    _ => {}
+                }
+            }
+        }
+        Severity::Info
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+            Severity::Debug => "debug",
+        }
+    }
+}
+
+/// 等待转发的一行日志
+struct Entry {
+    time: SystemTime,
+    severity: Severity,
+    message: String,
+}
+
+impl Entry {
+    fn json(&self, alias: &str) -> String {
+        serde_json::json!({
+            "timestamp": self.time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs_f64(),
+            "tunnel": alias,
+            "level": self.severity.name(),
+            "message": self.message,
+        }).to_string()
+    }
+}
+
+/// 后台转发线程，按批发送日志行，避免收集端变慢时阻塞日志文件的写入
+struct Forwarder {
+    sender: SyncSender<Entry>,
+    handle: JoinHandle<()>,
+}
+
+impl Forwarder {
+    /// 没有转发目标时返回 None
+    fn start(alias: &str, sinks: Vec<LogSink>) -> Option<Self> {
+        if sinks.is_empty() {
+            return None;
+        }
+        let (sender, receiver) = mpsc::sync_channel::<Entry>(QUEUE_LINES);
+        let alias = alias.to_string();
+        let handle = thread::spawn(move || {
+            let mut targets: Vec<Target> = sinks.into_iter().map(Target::new).collect();
+            loop {
+                let Ok(first) = receiver.recv() else {
+                    return;
+                };
+                let mut batch = vec![first];
+                let deadline = Instant::now() + BATCH_DELAY;
+                let mut closed = false;
+                while batch.len() < BATCH_LINES {
+                    match receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                        Ok(entry) => batch.push(entry),
+                        Err(RecvTimeoutError::Timeout) => break,
+                        Err(RecvTimeoutError::Disconnected) => {
+                            closed = true;
+                            break;
+                        }
+                    }
+                }
+                for target in &mut targets {
+                    if let Err(e) = target.send(&alias, &batch) {
+                        debug!(alias = %alias, sink = %target.sink, error = %e, "转发日志失败");
+                    }
+                }
+                if closed {
+                    return;
+                }
+            }
+        });
+        Some(Self { sender, handle })
+    }
+
+    fn send(&self, line: &str) {
+        let entry = Entry {
+            time: SystemTime::now(),
+            severity: Severity::of(line),
+            message: line.to_string(),
+        };
+        if let Err(TrySendError::Full(_)) = self.sender.try_send(entry) {
+            debug!("转发队列已满，丢弃日志行");
+        }
+    }
+
+    /// 发送剩余的日志行后结束
+    fn finish(self) {
+        drop(self.sender);
+        let _ = self.handle.join();
+    }
+}
+
+/// 转发目标及其保持的连接
+struct Target {
+    sink: LogSink,
+    stream: Option<TcpStream>,
+}
+
+impl Target {
+    fn new(sink: LogSink) -> Self {
+        Self { sink, stream: None }
+    }
+
+    fn send(&mut self, alias: &str, batch: &[Entry]) -> Result<()> {
+        match &self.sink {
+            LogSink::Syslog => send_syslog(alias, batch),
+            LogSink::Journald => send_journald(alias, batch),
+            LogSink::EventLog => send_event_log(alias, batch),
+            LogSink::Tcp(address) => {
+                let address = address.clone();
+                self.send_tcp(&address, alias, batch)
+            }
+            LogSink::Http(url) => {
+                let body: Vec<String> = batch.iter().map(|entry| entry.json(alias)).collect();
+                post(url, &body.join("\n"))
+            }
+            LogSink::Loki(url) => {
+                let values: Vec<[String; 2]> = batch.iter()
+                    .map(|entry| [
+                        entry.time.duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos().to_string(),
+                        entry.message.clone(),
+                    ])
+                    .collect();
+                let body = serde_json::json!({
+                    "streams": [{
+                        "stream": { "job": IDENTIFIER, "tunnel": alias },
+                        "values": values,
+                    }]
+                });
+                post(url, &body.to_string())
+            }
+        }
+    }
+
+    /// 连接断开时丢弃这一批，下一批重新连接
+    fn send_tcp(&mut self, address: &str, alias: &str, batch: &[Entry]) -> Result<()> {
+        if self.stream.is_none() {
+            let addr = address.to_socket_addrs()?.next()
+                .ok_or_else(|| anyhow::anyhow!(t!("无法解析地址 {}", address)))?;
+            let stream = TcpStream::connect_timeout(&addr, SEND_TIMEOUT)?;
+            stream.set_write_timeout(Some(SEND_TIMEOUT))?;
+            self.stream = Some(stream);
+        }
+        let mut body = String::new();
+        for entry in batch {
+            body.push_str(&entry.json(alias));
+            body.push('\n');
+        }
+        let result = self.stream.as_mut().map(|stream| stream.write_all(body.as_bytes()));
+        if let Some(Err(e)) = result {
+            self.stream = None;
+            return Err(e.into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+fn send_syslog(alias: &str, batch: &[Entry]) -> Result<()> {
+    use std::os::unix::net::UnixDatagram;
+    // Linux 为 /dev/log，macOS 为 /var/run/syslog
+    let path = ["/dev/log", "/var/run/syslog"].into_iter()
+        .find(|path| Path::new(path).exists())
+        .ok_or_else(|| anyhow::anyhow!(t!("未找到 syslog 套接字")))?;
+    let socket = UnixDatagram::unbound()?;
+    for entry in batch {
+        // facility 为 user（1）
+        let message = format!("<{}>{}[{}]: {}: {}", 8 + entry.severity as u8, IDENTIFIER, std::process::id(), alias, entry.message);
+        socket.send_to(message.as_bytes(), path)?;
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn send_journald(alias: &str, batch: &[Entry]) -> Result<()> {
+    use std::os::unix::net::UnixDatagram;
+    let socket = UnixDatagram::unbound()?;
+    for entry in batch {
+        let message = format!(
+            "MESSAGE={}\nPRIORITY={}\nSYSLOG_IDENTIFIER={}\nTFA_TUNNEL={}\n",
+            entry.message, entry.severity as u8, IDENTIFIER, alias
+        );
+        socket.send_to(message.as_bytes(), "/run/systemd/journal/socket")?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn send_syslog(_alias: &str, _batch: &[Entry]) -> Result<()> {
+    Err(anyhow::anyhow!(t!("当前平台不支持日志转发目标 {}", LogSink::Syslog)))
+}
+
+#[cfg(not(unix))]
+fn send_journald(_alias: &str, _batch: &[Entry]) -> Result<()> {
+    Err(anyhow::anyhow!(t!("当前平台不支持日志转发目标 {}", LogSink::Journald)))
+}
+
+/// 通过 eventcreate 写入应用程序事件日志，首次写入时会注册事件源 tfa
+fn send_event_log(alias: &str, batch: &[Entry]) -> Result<()> {
+    if !cfg!(windows) {
+        return Err(anyhow::anyhow!(t!("当前平台不支持日志转发目标 {}", LogSink::EventLog)));
+    }
+    for entry in batch {
+        let kind = match entry.severity {
+            Severity::Error => "ERROR",
+            Severity::Warning => "WARNING",
+            Severity::Info | Severity::Debug => "INFORMATION",
+        };
+        let status = Command::new("eventcreate")
+            .args(["/L", "APPLICATION", "/SO", IDENTIFIER, "/T", kind, "/ID", "1", "/D"])
+            .arg(format!("{}: {}", alias, entry.message))
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()?;
+        if !status.success() {
+            return Err(anyhow::anyhow!(t!("eventcreate 执行失败: {}", status)));
+        }
+    }
+    Ok(())
+}
+
+/// 通过 curl 以 POST 发送 JSON
+pub fn post(url: &str, body: &str) -> Result<()> {
+    let mut child = Command::new("curl")
+        .args(["-sS", "-f", "-o", "/dev/null", "--max-time", &SEND_TIMEOUT.as_secs().to_string()])
+        .args(["-H", "Content-Type: application/json", "--data-binary", "@-", url])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow::anyhow!(t!("无法执行 curl: {}", e)))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(body.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(String::from_utf8_lossy(&output.stderr).trim().trim_start_matches("curl: ").to_string()));
+    }
+    Ok(())
+}
@@ -1,37 +1,227 @@
+mod adopt;
+mod alert;
+mod apikey;
+mod apply;
+mod audit;
+mod backup;
+mod breaker;
+mod chain;
+mod client;
+mod clipboard;
+mod cloudflared;
 mod cli;
+mod compose;
 mod config;
+mod container;
+mod control;
+mod error;
+mod events;
+mod grpc;
+mod handoff;
+mod history;
+mod hooks;
+mod hosts;
+mod httpproxy;
+mod i18n;
+mod import;
+mod ipc;
+mod jwt;
+mod logformat;
+mod logsink;
+mod manifest;
+mod notify;
+mod otel;
+mod outbound;
+mod ping;
+mod platform;
+mod privileged;
+mod probe;
+mod profile;
+mod provider;
+mod proxy;
+mod prune;
+mod redact;
+mod reload;
+mod replay;
+mod search;
+mod secrets;
+mod share;
+mod sshconfig;
+mod state;
+mod sync;
+mod system;
+mod template;
+#[cfg(feature = "tray")]
+mod tray;
 mod tunnel;
+mod validate;
+mod web;
 
 use anyhow::Result;
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches, Parser, ValueEnum};
 use dialoguer::{theme::ColorfulTheme, Confirm};
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use tracing_subscriber::EnvFilter;
+
+use crate::error::{exit_code, TunnelError};
+use crate::i18n::{t, Lang};
+use crate::logformat::LogFormat;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
+    /// 输出更多内部日志，可重复使用（-v、-vv、-vvv）；设置 RUST_LOG 时以其为准
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
+    /// 只输出错误
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    /// 内部日志的格式；json 时每条日志输出一行 JSON，便于 journald、Fluent Bit 采集
+    #[arg(long, value_enum, global = true, default_value_t = LogFormat::Text, value_name = "FORMAT")]
+    log_format: LogFormat,
+    /// 界面语言，优先于配置文件和 LANG 环境变量
+    #[arg(long, value_enum, global = true)]
+    lang: Option<Lang>,
+    /// 使用的配置档案，优先于 CF_MANAGER_PROFILE 环境变量和 profile switch 的选择
+    #[arg(long, global = true, value_name = "NAME")]
+    profile: Option<String>,
+    /// 配置目录，包含各档案的配置、运行时文件与操作历史，优先于 CF_MANAGER_CONFIG_DIR 环境变量
+    #[arg(long, global = true, value_name = "DIR")]
+    config_dir: Option<PathBuf>,
+    /// 配置文件，优先于 CF_MANAGER_CONFIG 环境变量；运行时文件仍在配置目录中
+    #[arg(long, global = true, value_name = "FILE")]
+    config: Option<PathBuf>,
+    /// 不再确认，直接执行删除、清理等操作
+    #[arg(short, long, global = true)]
+    yes: bool,
     #[command(subcommand)]
     command: cli::Commands,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // 检查 cloudflared 是否已安装
-    if !tunnel::Tunnel::check_cloudflared()? {
-        println!("未检测到 cloudflared，这是运行本程序必需的。");
-        if Confirm::with_theme(&ColorfulTheme::default())
-            .with_prompt("是否要自动安装 cloudflared？")
-            .default(true)
-            .interact()?
-        {
-            println!("正在安装 cloudflared...");
-            tunnel::Tunnel::install_cloudflared()?;
-            println!("cloudflared 安装完成！");
-        } else {
-            return Err(anyhow::anyhow!("请先安装 cloudflared 后再运行本程序"));
+    // 语言设置保存在档案的配置中，因此先确定配置目录与档案；无效时在解析命令行之后报告
+    let profile = profile::select_root(global_arg("--config-dir").as_deref())
+        .and_then(|()| config::Config::select_file(global_arg("--config").as_deref()))
+        .and_then(|()| profile::select(global_arg("--profile").as_deref()));
+    // 帮助信息也需要本地化，因此在解析命令行之前确定语言
+    let lang = global_arg("--lang")
+        .and_then(|value| Lang::from_str(&value, true).ok())
+        .or_else(config::Config::stored_lang)
+        .or_else(Lang::from_env)
+        .unwrap_or_default();
+    i18n::set(lang);
+    let cli = match i18n::localize_command(Cli::command())
+        .try_get_matches()
+        .and_then(|matches| Cli::from_arg_matches(&matches))
+    {
+        Ok(cli) => cli,
+        Err(e) => {
+            // --help、--version 也以错误形式返回，它们应正常退出
+            let _ = e.print();
+            std::process::exit(if e.use_stderr() { exit_code::USAGE } else { 0 });
         }
-    }
+    };
+    init_logging(cli.verbose, cli.quiet, cli.log_format);
 
-    let cli = Cli::parse();
-    cli.command.execute().await?;
+    if let Err(e) = profile {
+        report(&e);
+        std::process::exit(error_exit_code(&e));
+    }
+    let result = match ensure_cloudflared(&cli.command) {
+        Ok(()) => cli.command.execute(cli.yes).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        report(&e);
+        std::process::exit(error_exit_code(&e));
+    }
     Ok(())
 }
+
+/// 检查 cloudflared 是否已安装，交互终端中可选择自动安装
+fn ensure_cloudflared(command: &cli::Commands) -> Result<()> {
+    if !command.requires_cloudflared() {
+        return Ok(());
+    }
+    // 读取档案固定的 cloudflared 版本；配置有误时留给命令本身报告
+    let config = config::Config::load();
+    // 所有连接都在容器中运行 cloudflared 时，主机上不需要安装
+    if let Ok(config) = &config
+        && config.list_tunnels().is_ok_and(|tunnels| !tunnels.is_empty() && tunnels.iter().all(|t| t.container_runtime().is_some()))
+    {
+        return Ok(());
+    }
+    if tunnel::Tunnel::check_cloudflared()? {
+        return Ok(());
+    }
+    println!("{}", t!("未检测到 cloudflared，这是运行本程序必需的。"));
+    if !platform::is_interactive() {
+        return Err(TunnelError::CloudflaredNotFound.into());
+    }
+    if Confirm::with_theme(&ColorfulTheme::default())
+        .with_prompt(t!("是否要自动安装 cloudflared？"))
+        .default(true)
+        .interact()?
+    {
+        println!("{}", t!("正在安装 cloudflared..."));
+        tunnel::Tunnel::install_cloudflared()?;
+        println!("{}", t!("cloudflared 安装完成！"));
+        Ok(())
+    } else {
+        Err(TunnelError::CloudflaredNotFound.into())
+    }
+}
+
+/// 预先从命令行中取出 --lang、--profile 这类全局参数的值，无效的值留给 clap 报错
+fn global_arg(name: &str) -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    args.iter().enumerate().find_map(|(index, arg)| match arg.strip_prefix(name) {
+        Some("") => args.get(index + 1).cloned(),
+        Some(value) => value.strip_prefix('=').map(str::to_string),
+        None => None,
+    })
+}
+
+/// 初始化内部日志，输出到 stderr。默认只显示警告，RUST_LOG 可覆盖命令行参数
+fn init_logging(verbose: u8, quiet: bool, format: LogFormat) {
+    let level = match (quiet, verbose) {
+        (true, _) => "error",
+        (false, 0) => "warn",
+        (false, 1) => "info",
+        (false, 2) => "debug",
+        _ => "trace",
+    };
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new(format!("warn,{}={}", env!("CARGO_CRATE_NAME"), level)));
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr);
+    if format == LogFormat::Json {
+        builder.event_format(logformat::Json).init();
+        return;
+    }
+    let builder = builder
+        // 输出被重定向到文件或管道时不带颜色控制符；NO_COLOR 同样关闭颜色
+        .with_ansi(std::io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty()))
+        .with_target(false);
+    if verbose > 0 {
+        builder.init();
+    } else {
+        builder.without_time().init();
+    }
+}
+
+/// 输出错误信息，对已知的失败类型附带修复建议
+fn report(error: &anyhow::Error) {
+    eprintln!("{}", t!("错误: {}", format!("{:#}", error)));
+    if let Some(hint) = error.downcast_ref::<TunnelError>().and_then(TunnelError::hint) {
+        eprintln!("{}", t!("提示: {}", hint));
+    }
+}
+
+/// 按错误类型选择退出码，见 [`exit_code`]
+fn error_exit_code(error: &anyhow::Error) -> i32 {
+    error.downcast_ref::<TunnelError>().map_or(exit_code::FAILURE, TunnelError::exit_code)
+}
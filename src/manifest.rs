@@ -0,0 +1,70 @@
+use anyhow::Result;
+use std::fs;
+use std::path::Path;
+
+use crate::error::TunnelError;
+use crate::i18n::t;
+use crate::tunnel::Tunnel;
+
+/// 从清单文件读取要批量添加的连接。
+/// `.yaml`/`.yml` 为连接列表，字段与配置文件相同；其他文件按 CSV 解析，
+/// 每行 `alias,source,port[,tags]`，多个标签用分号分隔，可有表头行，`#` 开头的行为注释。
+/// 所有行都会被检查，有错误时一次性报告全部问题。
+pub fn load(path: &Path) -> Result<Vec<Tunnel>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| TunnelError::config(t!("无法读取 {}: {}", path.display(), e)))?;
+    let is_yaml = path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("yaml") || ext.eq_ignore_ascii_case("yml"));
+    if is_yaml {
+        serde_yaml::from_str(&content)
+            .map_err(|e| TunnelError::config(t!("解析 {} 失败: {}", path.display(), e)))
+    } else {
+        parse_csv(&content)
+    }
+}
+
+fn parse_csv(content: &str) -> Result<Vec<Tunnel>> {
+    let mut tunnels = Vec::new();
+    let mut errors = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if tunnels.is_empty() && errors.is_empty() && fields.first() == Some(&"alias") {
+            continue;
+        }
+        match parse_row(&fields) {
+            Ok(tunnel) => tunnels.push(tunnel),
+            Err(e) => errors.push(t!("第 {} 行: {}", index + 1, e)),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(TunnelError::config(t!("清单中有无效的行:\n{}", errors.join("\n"))));
+    }
+    Ok(tunnels)
+}
+
+fn parse_row(fields: &[&str]) -> Result<Tunnel> {
+    if !(3..=4).contains(&fields.len()) {
+        return Err(TunnelError::config(t!("应为 alias,source,port[,tags]，实际有 {} 列", fields.len())));
+    }
+    let (alias, source) = (fields[0], fields[1]);
+    if alias.is_empty() || source.is_empty() {
+        return Err(TunnelError::config(t!("alias 和 source 不能为空")));
+    }
+    let port: u16 = fields[2].parse()
+        .map_err(|_| TunnelError::config(t!("端口 {} 无效", fields[2])))?;
+
+    let mut tunnel = Tunnel::new(alias, source, port);
+    if let Some(tags) = fields.get(3) {
+        tunnel.tags = tags.split(';')
+            .map(str::trim)
+            .filter(|tag| !tag.is_empty())
+            .map(str::to_string)
+            .collect();
+    }
+    Ok(tunnel)
+}
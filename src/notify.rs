@@ -0,0 +1,226 @@
+//! 通知渠道：告警规则触发时发送到 Slack、任意 Webhook、电子邮件或本机命令。
+//! 渠道在配置文件的 `notify` 中按名称定义，告警规则按名称引用；每个档案有自己的配置，因此渠道也按档案区分。
+
+use anyhow::Result;
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use crate::history;
+use crate::i18n::t;
+use crate::logsink;
+use crate::secrets::SmtpPassword;
+
+/// 连接 SMTP 服务器与发送邮件的超时
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 一个通知渠道
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Channel {
+    /// Slack（或 Mattermost 等兼容的）incoming webhook，发送 `{"text": ...}`
+    Slack { url: String },
+    /// 以 POST 发送通知的 JSON 对象
+    Webhook { url: String },
+    /// 经 SMTP 发送的电子邮件，适合只能发邮件、无法访问外部 webhook 的服务器
+    Email(Email),
+    /// 经系统 shell 执行的命令，通过环境变量获取通知内容
+    Command { command: String },
+}
+
+impl Channel {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Channel::Slack { .. } => "slack",
+            Channel::Webhook { .. } => "webhook",
+            Channel::Email(_) => "email",
+            Channel::Command { .. } => "command",
+        }
+    }
+
+    /// 列表中显示的目标；webhook 地址的路径中通常含有密钥，只显示主机
+    pub fn target(&self) -> String {
+        match self {
+            Channel::Slack { url } | Channel::Webhook { url } => {
+                let (scheme, rest) = url.split_once("://").unwrap_or(("", url));
+                let host = rest.split('/').next().unwrap_or_default();
+                let host = host.rsplit_once('@').map_or(host, |(_, host)| host);
+                format!("{}://{}/…", scheme, host)
+            }
+            Channel::Email(email) => format!("smtp://{}:{} → {}", email.host, email.port(), email.to.join(", ")),
+            Channel::Command { command } => command.clone(),
+        }
+    }
+
+    /// `name` 为渠道名称，电子邮件渠道据此查找钥匙串中的密码
+    pub fn send(&self, name: &str, notification: &Notification) -> Result<()> {
+        match self {
+            Channel::Slack { url } => logsink::post(url, &serde_json::json!({ "text": notification.text() }).to_string()),
+            Channel::Webhook { url } => logsink::post(url, &serde_json::to_string(notification)?),
+            Channel::Email(email) => email.send(name, notification),
+            Channel::Command { command } => run_command(command, notification),
+        }
+    }
+}
+
+/// SMTP 的加密方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpTls {
+    /// 先以明文连接，再用 STARTTLS 升级，默认端口 587
+    #[default]
+    Starttls,
+    /// 直接以 TLS 连接（SMTPS），默认端口 465
+    Tls,
+    /// 不加密，默认端口 25，只应用于本机或内网的中继
+    None,
+}
+
+impl SmtpTls {
+    fn default_port(self) -> u16 {
+        match self {
+            SmtpTls::Starttls => 587,
+            SmtpTls::Tls => 465,
+            SmtpTls::None => 25,
+        }
+    }
+}
+
+/// 电子邮件渠道。密码不写入配置文件：保存在系统钥匙串中（`tfa alert password`），
+/// 或在没有钥匙串的服务器上从 `password_env` 指定的环境变量读取
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Email {
+    /// SMTP 服务器主机名
+    pub host: String,
+    /// 省略时按加密方式选择
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub tls: SmtpTls,
+    /// 省略时不认证
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_env: Option<String>,
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+impl Email {
+    fn port(&self) -> u16 {
+        self.port.unwrap_or_else(|| self.tls.default_port())
+    }
+
+    /// 优先读取环境变量，其次是系统钥匙串
+    fn password(&self, channel: &str) -> Result<String> {
+        if let Some(var) = &self.password_env {
+            return std::env::var(var).map_err(|_| anyhow::anyhow!(t!("环境变量 {} 未设置", var)));
+        }
+        SmtpPassword::load(channel)?.ok_or_else(|| anyhow::anyhow!(t!("未设置通知渠道 {} 的 SMTP 密码，请使用 tfa alert password {} 设置", channel, channel)))
+    }
+
+    fn send(&self, channel: &str, notification: &Notification) -> Result<()> {
+        let invalid = |address: &str| anyhow::anyhow!(t!("无效的邮件地址 {}", address));
+        let mut builder = Message::builder()
+            .from(self.from.parse().map_err(|_| invalid(&self.from))?)
+            .subject(notification.text())
+            .header(ContentType::TEXT_PLAIN);
+        for to in &self.to {
+            builder = builder.to(to.parse().map_err(|_| invalid(to))?);
+        }
+        let message = builder.body(notification.body())?;
+
+        let transport = match self.tls {
+            SmtpTls::Starttls => SmtpTransport::starttls_relay(&self.host)?,
+            SmtpTls::Tls => SmtpTransport::relay(&self.host)?,
+            SmtpTls::None => SmtpTransport::builder_dangerous(&self.host),
+        };
+        let mut transport = transport.port(self.port()).timeout(Some(SEND_TIMEOUT));
+        if let Some(username) = &self.username {
+            transport = transport.credentials(Credentials::new(username.clone(), self.password(channel)?));
+        }
+        transport.build().send(&message)?;
+        Ok(())
+    }
+}
+
+/// 告警的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertState {
+    Firing,
+    Resolved,
+}
+
+impl fmt::Display for AlertState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AlertState::Firing => "firing",
+            AlertState::Resolved => "resolved",
+        })
+    }
+}
+
+/// 一条通知，Webhook 渠道直接发送它的 JSON 形式
+#[derive(Debug, Clone, Serialize)]
+pub struct Notification {
+    /// 触发的告警规则
+    pub rule: String,
+    pub alias: String,
+    pub state: AlertState,
+    pub message: String,
+    pub time: u64,
+}
+
+impl Notification {
+    /// 发送给聊天工具的一行文字
+    pub fn text(&self) -> String {
+        let state = match self.state {
+            AlertState::Firing => t!("告警"),
+            AlertState::Resolved => t!("恢复"),
+        };
+        format!("[tfa] [{}] {}: {}", state, self.rule, self.message)
+    }
+
+    /// 邮件正文：通知内容及其规则、连接、状态与时间
+    fn body(&self) -> String {
+        format!(
+            "{}\n\n{}: {}\n{}: {}\n{}: {}\n{}: {}\n",
+            self.message,
+            t!("规则"), self.rule,
+            t!("连接"), self.alias,
+            t!("状态"), self.state,
+            t!("时间"), history::format_time(self.time),
+        )
+    }
+}
+
+/// 通过环境变量 ALERT_RULE、ALERT_STATE、ALERT_MESSAGE、TUNNEL_ALIAS 传递通知内容
+fn run_command(script: &str, notification: &Notification) -> Result<()> {
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.arg("/C").arg(script);
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(script);
+        command
+    };
+    let output = command
+        .env("ALERT_RULE", &notification.rule)
+        .env("ALERT_STATE", notification.state.to_string())
+        .env("ALERT_MESSAGE", &notification.message)
+        .env("TUNNEL_ALIAS", &notification.alias)
+        .stdin(Stdio::null())
+        .output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let detail = stderr.lines().rev().find(|line| !line.trim().is_empty()).map_or_else(|| output.status.to_string(), str::to_string);
+        return Err(anyhow::anyhow!(t!("通知命令执行失败: {}", detail)));
+    }
+    Ok(())
+}
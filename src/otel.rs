@@ -0,0 +1,307 @@
+//! OpenTelemetry 导出：连接的启动、停止、重启与健康探测记录为 span 和耗时指标，
+//! 按 OTLP/HTTP（JSON 编码）发送到设置的收集端（OpenTelemetry Collector、Grafana Alloy、Tempo 等），
+//! 连接的可靠性即可出现在已有的 Grafana 面板中。
+//!
+//! 每次命令都是独立的短进程，因此不做批量缓冲：每个操作结束时把 span 和一个 delta 直方图数据点
+//! 交给后台的 curl 发送，本进程退出后 curl 仍会完成发送。重启中的停止与启动记录为重启的子 span。
+//! 收集端不可用时只丢弃数据，不影响连接本身。
+
+use anyhow::Result;
+use chacha20poly1305::aead::OsRng;
+use chacha20poly1305::aead::rand_core::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::RwLock;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::error::TunnelError;
+use crate::i18n::t;
+use crate::tunnel::Tunnel;
+use tracing::debug;
+
+/// 未设置服务名时使用的 service.name
+const DEFAULT_SERVICE_NAME: &str = "tfa";
+
+/// 发送一次数据的超时
+const SEND_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 耗时直方图的桶边界（毫秒），覆盖从本地探测到带重试的启动
+const BUCKETS_MS: &[f64] = &[5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0, 10000.0, 30000.0];
+
+/// OTLP 的 span 状态码与 span 类型
+const STATUS_OK: u8 = 1;
+const STATUS_ERROR: u8 = 2;
+const SPAN_KIND_INTERNAL: u8 = 1;
+/// 每个数据点只包含本次操作，收集端负责累加
+const TEMPORALITY_DELTA: u8 = 1;
+
+/// 全局导出设置，加载配置时记录
+static GLOBAL: RwLock<Option<OtelSettings>> = RwLock::new(None);
+
+thread_local! {
+    /// 当前线程中尚未结束的 span（trace id、span id），之后开始的 span 作为它的子 span
+    static CURRENT: RefCell<Option<(String, String)>> = const { RefCell::new(None) };
+}
+
+/// OTLP 导出设置，保存在配置文件中
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OtelSettings {
+    /// OTLP/HTTP 地址，如 http://otel-collector:4318；span 与指标分别发送到其下的 /v1/traces 和 /v1/metrics
+    pub endpoint: String,
+    /// 附加的请求头，如收集端要求的 Authorization
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// 资源属性 service.name，未设置时为 tfa
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service_name: Option<String>,
+}
+
+impl OtelSettings {
+    /// 检查地址与请求头，请求头写作 `名称=值`
+    pub fn new(endpoint: &str, headers: &[String], service_name: Option<&str>) -> Result<Self> {
+        if !(endpoint.starts_with("http://") || endpoint.starts_with("https://")) {
+            return Err(TunnelError::config(t!("无效的 OTLP 地址 {}，应以 http:// 或 https:// 开头", endpoint)));
+        }
+        let headers = headers
+            .iter()
+            .map(|header| match header.split_once('=') {
+                Some((name, value)) if !name.trim().is_empty() => Ok((name.trim().to_string(), value.trim().to_string())),
+                _ => Err(TunnelError::config(t!("无效的请求头 {}，应为 名称=值", header))),
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            headers,
+            service_name: service_name.map(str::to_string),
+        })
+    }
+
+    pub fn service_name(&self) -> &str {
+        self.service_name.as_deref().unwrap_or(DEFAULT_SERVICE_NAME)
+    }
+
+    /// 请求头的名称，值中通常含有凭据，不显示
+    pub fn header_names(&self) -> Vec<&str> {
+        self.headers.keys().map(String::as_str).collect()
+    }
+}
+
+/// 记录的连接操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Start,
+    Stop,
+    Restart,
+    Probe,
+}
+
+impl Operation {
+    fn name(self) -> &'static str {
+        match self {
+            Operation::Start => "start",
+            Operation::Stop => "stop",
+            Operation::Restart => "restart",
+            Operation::Probe => "probe",
+        }
+    }
+}
+
+/// 记录全局导出设置
+pub fn set_global(settings: Option<OtelSettings>) {
+    if let Ok(mut global) = GLOBAL.write() {
+        *global = settings;
+    }
+}
+
+fn settings() -> Option<OtelSettings> {
+    GLOBAL.read().ok().and_then(|global| global.clone())
+}
+
+/// 执行一次连接操作并记录为 span；未设置导出时直接执行
+pub fn traced<T, E: fmt::Display>(operation: Operation, tunnel: &Tunnel, f: impl FnOnce() -> Result<T, E>) -> Result<T, E> {
+    let Some(settings) = settings() else {
+        return f();
+    };
+    let parent = CURRENT.with(|current| current.borrow().clone());
+    let trace_id = parent.as_ref().map_or_else(|| random_hex(16), |(trace_id, _)| trace_id.clone());
+    let span_id = random_hex(8);
+    CURRENT.with(|current| *current.borrow_mut() = Some((trace_id.clone(), span_id.clone())));
+    let start = unix_nanos();
+    let result = f();
+    let end = unix_nanos();
+    CURRENT.with(|current| *current.borrow_mut() = parent.clone());
+
+    let span = Span {
+        operation,
+        alias: &tunnel.alias,
+        provider: tunnel.provider.name(),
+        trace_id,
+        span_id,
+        parent_span_id: parent.map(|(_, span_id)| span_id),
+        start,
+        end,
+        error: result.as_ref().err().map(ToString::to_string),
+    };
+    send(&settings, "v1/traces", span.traces(&settings));
+    send(&settings, "v1/metrics", span.metrics(&settings));
+    result
+}
+
+/// 一个已结束的操作
+struct Span<'a> {
+    operation: Operation,
+    alias: &'a str,
+    provider: &'a str,
+    trace_id: String,
+    span_id: String,
+    parent_span_id: Option<String>,
+    start: u64,
+    end: u64,
+    error: Option<String>,
+}
+
+impl Span<'_> {
+    fn outcome(&self) -> &'static str {
+        if self.error.is_some() { "error" } else { "ok" }
+    }
+
+    fn traces(&self, settings: &OtelSettings) -> serde_json::Value {
+        let status = match &self.error {
+            Some(message) => json!({ "code": STATUS_ERROR, "message": message }),
+            None => json!({ "code": STATUS_OK }),
+        };
+        json!({
+            "resourceSpans": [{
+                "resource": resource(settings),
+                "scopeSpans": [{
+                    "scope": scope(),
+                    "spans": [{
+                        "traceId": self.trace_id,
+                        "spanId": self.span_id,
+                        "parentSpanId": self.parent_span_id.clone().unwrap_or_default(),
+                        "name": format!("tunnel.{}", self.operation.name()),
+                        "kind": SPAN_KIND_INTERNAL,
+                        "startTimeUnixNano": self.start.to_string(),
+                        "endTimeUnixNano": self.end.to_string(),
+                        "attributes": attributes(&[
+                            ("tunnel.alias", self.alias),
+                            ("tunnel.provider", self.provider),
+                            ("tunnel.operation", self.operation.name()),
+                        ]),
+                        "status": status,
+                    }],
+                }],
+            }],
+        })
+    }
+
+    /// 操作耗时的直方图 tfa.tunnel.operation.duration，按连接、操作与结果区分
+    fn metrics(&self, settings: &OtelSettings) -> serde_json::Value {
+        let ms = self.end.saturating_sub(self.start) as f64 / 1_000_000.0;
+        let mut buckets = vec![0u64; BUCKETS_MS.len() + 1];
+        buckets[BUCKETS_MS.iter().take_while(|bound| ms > **bound).count()] = 1;
+        json!({
+            "resourceMetrics": [{
+                "resource": resource(settings),
+                "scopeMetrics": [{
+                    "scope": scope(),
+                    "metrics": [{
+                        "name": "tfa.tunnel.operation.duration",
+                        "unit": "ms",
+                        "histogram": {
+                            "aggregationTemporality": TEMPORALITY_DELTA,
+                            "dataPoints": [{
+                                "attributes": attributes(&[
+                                    ("tunnel.alias", self.alias),
+                                    ("tunnel.operation", self.operation.name()),
+                                    ("outcome", self.outcome()),
+                                ]),
+                                "startTimeUnixNano": self.start.to_string(),
+                                "timeUnixNano": self.end.to_string(),
+                                "count": "1",
+                                "sum": ms,
+                                "min": ms,
+                                "max": ms,
+                                "bucketCounts": buckets.iter().map(ToString::to_string).collect::<Vec<_>>(),
+                                "explicitBounds": BUCKETS_MS,
+                            }],
+                        },
+                    }],
+                }],
+            }],
+        })
+    }
+}
+
+fn resource(settings: &OtelSettings) -> serde_json::Value {
+    json!({
+        "attributes": attributes(&[
+            ("service.name", settings.service_name()),
+            ("service.version", env!("CARGO_PKG_VERSION")),
+        ]),
+    })
+}
+
+fn scope() -> serde_json::Value {
+    json!({ "name": env!("CARGO_PKG_NAME"), "version": env!("CARGO_PKG_VERSION") })
+}
+
+fn attributes(pairs: &[(&str, &str)]) -> serde_json::Value {
+    pairs
+        .iter()
+        .map(|(key, value)| json!({ "key": key, "value": { "stringValue": value } }))
+        .collect()
+}
+
+fn random_hex(bytes: usize) -> String {
+    let mut id = vec![0u8; bytes];
+    OsRng.fill_bytes(&mut id);
+    id.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn unix_nanos() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_nanos() as u64)
+}
+
+/// 交给后台的 curl 以 POST 发送，不等待结果；失败只记录到调试日志
+fn send(settings: &OtelSettings, path: &str, body: serde_json::Value) {
+    let url = format!("{}/{}", settings.endpoint, path);
+    let mut command = Command::new("curl");
+    command.args(["-sS", "-f", "-o", "/dev/null", "--max-time", &SEND_TIMEOUT.as_secs().to_string()]);
+    command.args(["-H", "Content-Type: application/json"]);
+    for (name, value) in &settings.headers {
+        command.arg("-H").arg(format!("{}: {}", name, value));
+    }
+    let child = command
+        .args(["--data-binary", "@-", &url])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(e) => {
+            debug!(url, error = %e, "无法执行 curl 导出遥测数据");
+            return;
+        }
+    };
+    if let Some(mut stdin) = child.stdin.take()
+        && let Err(e) = stdin.write_all(body.to_string().as_bytes())
+    {
+        debug!(url, error = %e, "写入遥测数据失败");
+    }
+    // 在后台回收 curl，避免常驻的探测进程中留下僵尸进程
+    thread::spawn(move || match child.wait_with_output() {
+        Ok(output) if !output.status.success() => {
+            debug!(url, error = %String::from_utf8_lossy(&output.stderr).trim(), "导出遥测数据失败");
+        }
+        Err(e) => debug!(url, error = %e, "导出遥测数据失败"),
+        Ok(_) => {}
+    });
+}
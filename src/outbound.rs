@@ -0,0 +1,140 @@
+//! 出站代理：网络要求经代理访问外网时，后端进程通过代理连接 Cloudflare 或集群。
+//!
+//! cloudflared 与 kubectl 都是 Go 程序，`access tcp` / `port-forward` 没有单独的代理参数，
+//! 而是读取 HTTPS_PROXY、NO_PROXY 等环境变量；HTTPS_PROXY 为 socks5:// 地址时同样生效。
+//! 因此启动后端时按连接或全局的代理设置改写这些环境变量。
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::process::Command;
+use std::sync::RwLock;
+
+use crate::error::TunnelError;
+use crate::i18n::t;
+use crate::provider::REDACTED;
+use tracing::debug;
+
+/// 连接上设置为该值时不使用全局代理，直接连接
+pub const DIRECT: &str = "direct";
+
+/// 后端读取的代理环境变量，大小写两种写法都会被 Go 程序识别
+const PROXY_VARS: &[&str] = &["HTTPS_PROXY", "https_proxy", "HTTP_PROXY", "http_proxy", "ALL_PROXY", "all_proxy"];
+const NO_PROXY_VARS: &[&str] = &["NO_PROXY", "no_proxy"];
+
+const SCHEMES: &[&str] = &["http", "https", "socks5", "socks5h"];
+
+/// 连通性检查的超时秒数
+const CHECK_TIMEOUT: u64 = 10;
+
+/// 全局代理设置，加载配置时记录，供启动后端时使用
+static GLOBAL: RwLock<Option<OutboundProxy>> = RwLock::new(None);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutboundProxy {
+    /// 代理地址，如 http://proxy.corp:3128、socks5://127.0.0.1:1080；`direct` 表示不使用代理
+    pub url: String,
+    /// 不经过代理的主机，写入 NO_PROXY
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub no_proxy: Vec<String>,
+}
+
+impl OutboundProxy {
+    /// 校验代理地址：只支持 http、https、socks5、socks5h，且必须包含主机名
+    pub fn new(url: &str, no_proxy: Vec<String>) -> Result<Self> {
+        let url = url.trim();
+        if url != DIRECT {
+            let valid = url.split_once("://").is_some_and(|(scheme, rest)| {
+                SCHEMES.contains(&scheme.to_ascii_lowercase().as_str())
+                    && !rest.rsplit('@').next().unwrap_or_default().trim_end_matches('/').is_empty()
+            });
+            if !valid {
+                return Err(TunnelError::config(t!(
+                    "代理地址 {} 无效：应为 http://、https://、socks5:// 或 socks5h:// 开头的地址，或 {}",
+                    url,
+                    DIRECT
+                )));
+            }
+        }
+        Ok(Self { url: url.to_string(), no_proxy })
+    }
+
+    pub fn is_direct(&self) -> bool {
+        self.url == DIRECT
+    }
+
+    /// 设置后端进程的代理环境变量；不使用代理时清除从本进程继承的代理变量
+    pub fn apply(&self, command: &mut Command) {
+        if self.is_direct() {
+            for var in PROXY_VARS.iter().chain(NO_PROXY_VARS) {
+                command.env_remove(var);
+            }
+            return;
+        }
+        for var in PROXY_VARS {
+            command.env(var, &self.url);
+        }
+        if !self.no_proxy.is_empty() {
+            let no_proxy = self.no_proxy.join(",");
+            for var in NO_PROXY_VARS {
+                command.env(var, &no_proxy);
+            }
+        }
+    }
+}
+
+impl fmt::Display for OutboundProxy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&redact(&self.url))
+    }
+}
+
+/// 隐藏代理地址中的密码，用于显示和日志
+pub fn redact(url: &str) -> String {
+    match url.split_once("://").zip(url.rsplit_once('@')) {
+        Some(((scheme, rest), (_, host))) => {
+            let user = rest.split(['@', ':']).next().unwrap_or_default();
+            format!("{}://{}:{}@{}", scheme, user, REDACTED, host)
+        }
+        None => url.to_string(),
+    }
+}
+
+/// 记录全局代理设置
+pub fn set_global(proxy: Option<OutboundProxy>) {
+    if let Ok(mut global) = GLOBAL.write() {
+        *global = proxy;
+    }
+}
+
+/// 连接实际使用的代理：连接自身的设置优先，其次是全局设置；None 表示沿用本进程的环境变量
+pub fn effective(own: Option<&OutboundProxy>) -> Option<OutboundProxy> {
+    own.cloned().or_else(|| GLOBAL.read().ok().and_then(|global| global.clone()))
+}
+
+/// 检查能否访问 `https://<host>`，返回 HTTP 状态码，任何 HTTP 响应都说明网络可达；
+/// `proxy` 为 None 时按本进程的环境变量访问
+pub fn check(proxy: Option<&OutboundProxy>, host: &str) -> Result<u16> {
+    let mut command = Command::new("curl");
+    command.args(["-sS", "-o", "/dev/null", "-w", "%{http_code}", "--max-time", &CHECK_TIMEOUT.to_string()]);
+    match proxy {
+        Some(proxy) if proxy.is_direct() => {
+            command.args(["--noproxy", "*"]);
+        }
+        Some(proxy) => {
+            command.args(["--proxy", &proxy.url]);
+            if !proxy.no_proxy.is_empty() {
+                command.args(["--noproxy", &proxy.no_proxy.join(",")]);
+            }
+        }
+        None => {}
+    }
+    debug!(host, proxy = %proxy.map(ToString::to_string).unwrap_or_default(), "检查连通性");
+    let output = command.arg(format!("https://{}", host)).output()
+        .map_err(|e| anyhow::anyhow!(t!("无法执行 curl: {}", e)))?;
+    let code = String::from_utf8_lossy(&output.stdout).trim().parse::<u16>().unwrap_or(0);
+    if !output.status.success() || code == 0 {
+        return Err(anyhow::anyhow!(String::from_utf8_lossy(&output.stderr).trim().trim_start_matches("curl: ").to_string()));
+    }
+    Ok(code)
+}
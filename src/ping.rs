@@ -0,0 +1,121 @@
+//! 可用性推送：连接设置了 `ping_url` 时，后台定期探测成功后请求该地址，失败时发送失败信号，
+//! 已有的 healthchecks.io、Uptime Kuma 等面板即可跟踪连接是否可用，不需要另外部署监控。
+//!
+//! 路径中含有 `/api/push/` 的地址按 Uptime Kuma 的推送接口发送状态、消息和延迟；
+//! 其他地址按 healthchecks.io 的约定：成功时请求地址本身，失败时以 POST 把错误信息发送到 `<地址>/fail`。
+
+use anyhow::Result;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use crate::error::TunnelError;
+use crate::i18n::t;
+use crate::tunnel::Tunnel;
+use tracing::debug;
+
+/// 请求的超时，推送失败不应拖住探测或重试
+const SEND_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Uptime Kuma 推送地址的路径特征
+const KUMA_PATH: &str = "/api/push/";
+
+/// 检查推送地址，只接受 http 与 https
+pub fn check(url: &str) -> Result<()> {
+    if !(url.starts_with("http://") || url.starts_with("https://")) {
+        return Err(TunnelError::config(t!("无效的推送地址 {}，应以 http:// 或 https:// 开头", url)));
+    }
+    Ok(())
+}
+
+/// 推送一次探测成功，`ms` 为探测的往返耗时
+pub fn up(tunnel: &Tunnel, ms: u64) {
+    if let Some(url) = &tunnel.ping_url {
+        report(tunnel, url, send_up(url, ms));
+    }
+}
+
+/// 推送一次失败，`reason` 为错误信息
+pub fn down(tunnel: &Tunnel, reason: &str) {
+    if let Some(url) = &tunnel.ping_url {
+        report(tunnel, url, send_down(url, reason));
+    }
+}
+
+/// 推送失败只记录到调试日志，不影响连接本身
+fn report(tunnel: &Tunnel, url: &str, result: Result<()>) {
+    if let Err(e) = result {
+        debug!(alias = %tunnel.alias, url = %redact(url), error = %e, "推送可用性失败");
+    }
+}
+
+fn send_up(url: &str, ms: u64) -> Result<()> {
+    if is_kuma(url) {
+        get(base(url), &[("status", "up"), ("msg", "OK"), ("ping", &ms.to_string())])
+    } else {
+        get(url, &[])
+    }
+}
+
+fn send_down(url: &str, reason: &str) -> Result<()> {
+    if is_kuma(url) {
+        get(base(url), &[("status", "down"), ("msg", reason)])
+    } else {
+        post(&format!("{}/fail", url.trim_end_matches('/')), reason)
+    }
+}
+
+fn is_kuma(url: &str) -> bool {
+    base(url).contains(KUMA_PATH)
+}
+
+/// 去掉查询参数；Uptime Kuma 显示的推送地址自带默认的 status、msg、ping 参数
+fn base(url: &str) -> &str {
+    url.split_once('?').map_or(url, |(base, _)| base)
+}
+
+/// 日志中只显示主机，推送地址的路径就是密钥
+fn redact(url: &str) -> String {
+    let (scheme, rest) = url.split_once("://").unwrap_or(("", url));
+    format!("{}://{}/…", scheme, rest.split('/').next().unwrap_or_default())
+}
+
+/// 通过 curl 以 GET 发送，查询参数由 curl 编码
+fn get(url: &str, query: &[(&str, &str)]) -> Result<()> {
+    let mut command = curl();
+    command.arg("-G");
+    for (key, value) in query {
+        command.arg("--data-urlencode").arg(format!("{}={}", key, value));
+    }
+    run(command.arg(url), None)
+}
+
+/// 通过 curl 以 POST 发送纯文本
+fn post(url: &str, body: &str) -> Result<()> {
+    let mut command = curl();
+    command.args(["-H", "Content-Type: text/plain; charset=utf-8", "--data-binary", "@-", url]);
+    run(&mut command, Some(body))
+}
+
+fn curl() -> Command {
+    let mut command = Command::new("curl");
+    command.args(["-sS", "-f", "-o", "/dev/null", "--max-time", &SEND_TIMEOUT.as_secs().to_string()]);
+    command
+}
+
+fn run(command: &mut Command, body: Option<&str>) -> Result<()> {
+    let mut child = command
+        .stdin(if body.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow::anyhow!(t!("无法执行 curl: {}", e)))?;
+    if let (Some(mut stdin), Some(body)) = (child.stdin.take(), body) {
+        stdin.write_all(body.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(String::from_utf8_lossy(&output.stderr).trim().trim_start_matches("curl: ").to_string()));
+    }
+    Ok(())
+}
@@ -0,0 +1,534 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::IsTerminal;
+use std::net::{Ipv4Addr, TcpListener};
+use std::process::{ChildStdin, Command, Stdio};
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 标准输入与标准输出都连接到终端时才可以交互询问；在 cron、CI 或管道中运行时跳过所有询问
+pub fn is_interactive() -> bool {
+    std::io::stdin().is_terminal() && std::io::stdout().is_terminal()
+}
+
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// 正在监听某个端口的进程
+#[derive(Debug, Clone)]
+pub struct PortOccupant {
+    pub pid: u32,
+    pub process: String,
+}
+
+impl PortOccupant {
+    /// 进程名是否为指定程序（如 cloudflared、kubectl）
+    pub fn is_program(&self, program: &str) -> bool {
+        self.process.to_lowercase().contains(program)
+    }
+}
+
+/// 查询监听指定 TCP 端口的进程（精确匹配端口号）
+pub fn port_listeners(port: u16) -> Vec<PortOccupant> {
+    #[cfg(windows)]
+    return win32::port_listeners(port);
+    #[cfg(not(windows))]
+    unix_port_listeners(port)
+}
+
+#[cfg(not(windows))]
+fn unix_port_listeners(port: u16) -> Vec<PortOccupant> {
+    if cfg!(target_os = "linux")
+        && let Some(occupants) = linux_port_listeners(port)
+    {
+        return occupants;
+    }
+    lsof_port_listeners(port)
+}
+
+/// 是否有套接字在监听该端口，包括无权查看其进程的其他用户（如 root）持有的套接字
+pub fn port_listened(port: u16) -> bool {
+    #[cfg(not(windows))]
+    if cfg!(target_os = "linux")
+        && let Some(inodes) = linux_listen_inodes(port)
+    {
+        return !inodes.is_empty();
+    }
+    !port_listeners(port).is_empty()
+}
+
+/// /proc/net/tcp{,6} 中监听该端口的套接字（`socket:[inode]` 形式）；/proc/net 不可读时返回 None
+#[cfg(not(windows))]
+fn linux_listen_inodes(port: u16) -> Option<Vec<String>> {
+    // TCP_LISTEN 状态
+    const LISTEN: &str = "0A";
+    let tables: Vec<String> = ["/proc/net/tcp", "/proc/net/tcp6"].iter()
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .collect();
+    if tables.is_empty() {
+        return None;
+    }
+    let port = format!(":{:04X}", port);
+    // 每行为 `sl local_address rem_address st tx:rx tr:when retrnsmt uid timeout inode ...`
+    Some(tables.iter()
+        .flat_map(|table| table.lines().skip(1))
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let inode = fields.get(9)?;
+            (fields.get(1)?.ends_with(&port) && *fields.get(3)? == LISTEN).then(|| format!("socket:[{}]", inode))
+        })
+        .collect())
+}
+
+/// 由 /proc/net/tcp{,6} 找到监听该端口的套接字，再在 /proc/*/fd 中找到持有它的进程，
+/// 不依赖 lsof（精简的 Linux 系统上通常没有安装）。/proc/net 不可读时返回 None
+#[cfg(not(windows))]
+fn linux_port_listeners(port: u16) -> Option<Vec<PortOccupant>> {
+    let inodes = linux_listen_inodes(port)?;
+    if inodes.is_empty() {
+        return Some(Vec::new());
+    }
+
+    let mut occupants = Vec::new();
+    for entry in fs::read_dir("/proc").ok()?.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|name| name.parse::<u32>().ok()) else {
+            continue;
+        };
+        // 其他用户的进程没有权限读取，跳过
+        let Ok(fds) = fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+        let holds = fds.flatten()
+            .filter_map(|fd| fs::read_link(fd.path()).ok())
+            .any(|target| target.to_str().is_some_and(|target| inodes.iter().any(|inode| inode == target)));
+        if holds {
+            let process = fs::read_to_string(entry.path().join("comm")).unwrap_or_default().trim().to_string();
+            occupants.push(PortOccupant { pid, process });
+        }
+    }
+    Some(occupants)
+}
+
+/// 其他 Unix 系统，以及 Linux 上 /proc 不可用时的后备方式
+#[cfg(not(windows))]
+fn lsof_port_listeners(port: u16) -> Vec<PortOccupant> {
+    let Ok(output) = Command::new("lsof")
+        .args(["-nP", &format!("-iTCP:{}", port), "-sTCP:LISTEN", "-Fpc"])
+        .output()
+    else {
+        return Vec::new();
+    };
+
+    // 每个进程输出 `p<pid>` 与 `c<命令名>` 两行
+    let mut occupants: Vec<PortOccupant> = Vec::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        if let Some(pid) = line.strip_prefix('p')
+            && let Ok(pid) = pid.parse::<u32>()
+        {
+            occupants.push(PortOccupant { pid, process: String::new() });
+        } else if let Some(name) = line.strip_prefix('c')
+            && let Some(last) = occupants.last_mut()
+        {
+            last.process = name.to_string();
+        }
+    }
+    occupants
+}
+
+/// 正在运行的进程及其命令行
+#[derive(Debug, Clone)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    /// 命令行参数，按空白拆分（不保留原有的引号）
+    pub args: Vec<String>,
+}
+
+/// 列出进程名包含 `program` 的所有进程
+pub fn processes(program: &str) -> Vec<ProcessInfo> {
+    if cfg!(windows) {
+        windows_processes(program)
+    } else {
+        unix_processes(program)
+    }
+}
+
+fn windows_processes(program: &str) -> Vec<ProcessInfo> {
+    let script = format!(
+        "Get-CimInstance Win32_Process -Filter \"Name like '%{}%'\" | ForEach-Object {{ \"$($_.ProcessId) $($_.CommandLine)\" }}",
+        program
+    );
+    let Ok(output) = Command::new("powershell")
+        .args(["-NoProfile", "-Command", &script])
+        .output()
+    else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pid = fields.next()?.parse().ok()?;
+            Some(ProcessInfo { pid, args: fields.map(|f| f.trim_matches('"').to_string()).collect() })
+        })
+        .collect()
+}
+
+fn unix_processes(program: &str) -> Vec<ProcessInfo> {
+    let Ok(output) = Command::new("ps")
+        .args(["-axo", "pid=,comm=,args="])
+        .output()
+    else {
+        return Vec::new();
+    };
+    // 每行为 `<pid> <进程名> <命令行>`
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pid = fields.next()?.parse().ok()?;
+            let name = fields.next()?;
+            name.to_lowercase().contains(program)
+                .then(|| ProcessInfo { pid, args: fields.map(str::to_string).collect() })
+        })
+        .collect()
+}
+
+/// 进程的启动时间（Unix 秒）；进程不存在时返回 None
+pub fn process_start_time(pid: u32) -> Option<u64> {
+    #[cfg(windows)]
+    return win32::process_start_time(pid);
+    #[cfg(not(windows))]
+    if cfg!(target_os = "linux") {
+        linux_process_start_time(pid)
+    } else {
+        let output = Command::new("ps")
+            .args(["-o", "etime=", "-p", &pid.to_string()])
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        let elapsed = parse_etime(String::from_utf8_lossy(&output.stdout).trim())?;
+        Some(unix_now().saturating_sub(elapsed))
+    }
+}
+
+#[cfg(not(windows))]
+/// 由 /proc 中的进程启动时刻（开机后的时钟周期数）与开机时间求得，不启动外部进程
+fn linux_process_start_time(pid: u32) -> Option<u64> {
+    const CLOCK_TICKS: u64 = 100;
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+    let start_ticks: u64 = fields.get(19)?.parse().ok()?;
+    let boot_time: u64 = fs::read_to_string("/proc/stat").ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("btime "))?
+        .trim()
+        .parse()
+        .ok()?;
+    Some(boot_time + start_ticks / CLOCK_TICKS)
+}
+
+#[cfg(not(windows))]
+/// 解析 ps 的 etime 格式：`[[dd-]hh:]mm:ss`
+fn parse_etime(etime: &str) -> Option<u64> {
+    let (days, clock) = match etime.split_once('-') {
+        Some((days, clock)) => (days.parse::<u64>().ok()?, clock),
+        None => (0, etime),
+    };
+    let mut seconds = 0;
+    for part in clock.split(':') {
+        seconds = seconds * 60 + part.parse::<u64>().ok()?;
+    }
+    Some(days * 86400 + seconds)
+}
+
+/// 进程累计占用的 CPU 时间与当前内存
+#[derive(Debug, Clone, Copy)]
+pub struct ProcessUsage {
+    /// 累计 CPU 时间（秒），两次采样之差除以间隔即为 CPU 占用率
+    pub cpu_seconds: f64,
+    /// 常驻内存（字节）
+    pub memory: u64,
+}
+
+/// 查询一组进程的资源占用；已退出的进程不在结果中
+pub fn process_usage(pids: &[u32]) -> HashMap<u32, ProcessUsage> {
+    if pids.is_empty() {
+        HashMap::new()
+    } else if cfg!(windows) {
+        windows_process_usage(pids)
+    } else if cfg!(target_os = "linux") {
+        pids.iter().filter_map(|pid| Some((*pid, linux_process_usage(*pid)?))).collect()
+    } else {
+        unix_process_usage(pids)
+    }
+}
+
+fn windows_process_usage(pids: &[u32]) -> HashMap<u32, ProcessUsage> {
+    let ids: Vec<String> = pids.iter().map(u32::to_string).collect();
+    // 以整数毫秒输出，避免小数点随区域设置变化
+    let script = format!(
+        "Get-Process -Id {} -ErrorAction SilentlyContinue | ForEach-Object {{ \"$($_.Id) $([long]$_.TotalProcessorTime.TotalMilliseconds) $($_.WorkingSet64)\" }}",
+        ids.join(",")
+    );
+    let Ok(output) = Command::new("powershell").args(["-NoProfile", "-Command", &script]).output() else {
+        return HashMap::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pid = fields.next()?.parse().ok()?;
+            let millis: u64 = fields.next()?.parse().ok()?;
+            let memory = fields.next()?.parse().ok()?;
+            Some((pid, ProcessUsage { cpu_seconds: millis as f64 / 1000.0, memory }))
+        })
+        .collect()
+}
+
+/// 读取 /proc，精度高于 ps 输出的整秒 CPU 时间
+fn linux_process_usage(pid: u32) -> Option<ProcessUsage> {
+    // 内核导出的时钟频率在常见架构上固定为 100
+    const CLOCK_TICKS: f64 = 100.0;
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // 进程名可能包含空格，从最后一个右括号之后开始按字段拆分，第一个字段为进程状态
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    let status = fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+    let rss_kb: u64 = status.lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))
+        .and_then(|value| value.split_whitespace().next()?.parse().ok())
+        .unwrap_or(0);
+    Some(ProcessUsage { cpu_seconds: (utime + stime) as f64 / CLOCK_TICKS, memory: rss_kb * 1024 })
+}
+
+fn unix_process_usage(pids: &[u32]) -> HashMap<u32, ProcessUsage> {
+    let ids: Vec<String> = pids.iter().map(u32::to_string).collect();
+    let Ok(output) = Command::new("ps")
+        .args(["-o", "pid=,time=,rss=", "-p", &ids.join(",")])
+        .output()
+    else {
+        return HashMap::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let pid = fields.next()?.parse().ok()?;
+            let cpu_seconds = parse_cpu_time(fields.next()?)?;
+            let rss_kb: u64 = fields.next()?.parse().ok()?;
+            Some((pid, ProcessUsage { cpu_seconds, memory: rss_kb * 1024 }))
+        })
+        .collect()
+}
+
+/// 解析 ps 的 time 格式：`[dd-][hh:]mm:ss[.ss]`
+fn parse_cpu_time(time: &str) -> Option<f64> {
+    let (days, clock) = match time.split_once('-') {
+        Some((days, clock)) => (days.parse::<f64>().ok()?, clock),
+        None => (0.0, time),
+    };
+    let mut seconds = 0.0;
+    for part in clock.split(':') {
+        seconds = seconds * 60.0 + part.parse::<f64>().ok()?;
+    }
+    Some(days * 86400.0 + seconds)
+}
+
+/// 让子进程脱离当前终端的进程组，终端的 Ctrl-C 或关闭不会连带结束它
+pub fn detach(command: &mut Command) -> &mut Command {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NEW_PROCESS_GROUP | CREATE_NO_WINDOW);
+    }
+    command
+}
+
+/// 把子进程的标准输入复制为两个输出端，供另一个进程的 stdout 和 stderr 共同写入
+pub fn split_stdin(stdin: ChildStdin) -> std::io::Result<(Stdio, Stdio)> {
+    #[cfg(unix)]
+    let handle = std::os::fd::OwnedFd::from(stdin);
+    #[cfg(windows)]
+    let handle = std::os::windows::io::OwnedHandle::from(stdin);
+    Ok((Stdio::from(handle.try_clone()?), Stdio::from(handle)))
+}
+
+/// 由系统分配一个当前空闲的本地端口
+pub fn free_port() -> std::io::Result<u16> {
+    Ok(TcpListener::bind("127.0.0.1:0")?.local_addr()?.port())
+}
+
+/// 本地端口当前是否可以绑定，即没有其他程序在监听
+pub fn port_available(port: u16) -> bool {
+    TcpListener::bind((Ipv4Addr::LOCALHOST, port)).is_ok()
+}
+
+/// 当前用户是否为管理员：Unix 上为 root，Windows 上为以管理员身份运行
+pub fn is_admin() -> bool {
+    static ADMIN: OnceLock<bool> = OnceLock::new();
+    *ADMIN.get_or_init(|| {
+        if cfg!(windows) {
+            // 只有管理员可以列出服务器会话
+            Command::new("net")
+                .arg("session")
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .is_ok_and(|status| status.success())
+        } else {
+            Command::new("id")
+                .arg("-u")
+                .output()
+                .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).trim() == "0")
+        }
+    })
+}
+
+/// 强制结束进程
+pub fn kill_process(pid: u32) {
+    #[cfg(windows)]
+    win32::terminate_process(pid);
+    #[cfg(not(windows))]
+    let _ = Command::new("kill")
+        .args(["-9", &pid.to_string()])
+        .output();
+}
+
+/// Windows 上直接调用 Win32 API，不解析 netstat / tasklist 的输出：
+/// 这些命令的输出随系统语言变化，逐个启动也明显更慢
+#[cfg(windows)]
+mod win32 {
+    use windows_sys::Win32::Foundation::{CloseHandle, ERROR_INSUFFICIENT_BUFFER, FILETIME, HANDLE, STILL_ACTIVE};
+    use windows_sys::Win32::NetworkManagement::IpHelper::{
+        GetExtendedTcpTable, MIB_TCP6ROW_OWNER_PID, MIB_TCPROW_OWNER_PID, TCP_TABLE_OWNER_PID_LISTENER,
+    };
+    use windows_sys::Win32::Networking::WinSock::{AF_INET, AF_INET6};
+    use windows_sys::Win32::System::Threading::{
+        GetExitCodeProcess, GetProcessTimes, OpenProcess, PROCESS_ACCESS_RIGHTS, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_TERMINATE, QueryFullProcessImageNameW, TerminateProcess,
+    };
+
+    use super::PortOccupant;
+
+    /// FILETIME 的起点（1601-01-01）与 Unix 纪元相差的秒数
+    const FILETIME_UNIX_OFFSET: u64 = 11_644_473_600;
+
+    /// 打开的进程句柄，随对象关闭
+    struct Process(HANDLE);
+
+    impl Process {
+        fn open(pid: u32, access: PROCESS_ACCESS_RIGHTS) -> Option<Self> {
+            // SAFETY: OpenProcess 失败时返回空句柄，不需要额外的前置条件
+            let handle = unsafe { OpenProcess(access, 0, pid) };
+            (!handle.is_null()).then_some(Self(handle))
+        }
+    }
+
+    impl Drop for Process {
+        fn drop(&mut self) {
+            // SAFETY: 句柄由 OpenProcess 返回且只在这里关闭一次
+            unsafe { CloseHandle(self.0) };
+        }
+    }
+
+    pub fn port_listeners(port: u16) -> Vec<PortOccupant> {
+        let mut pids: Vec<u32> = Vec::new();
+        for row in listener_rows::<MIB_TCPROW_OWNER_PID>(AF_INET) {
+            // 端口以网络字节序存放在低 16 位
+            if u16::from_be(row.dwLocalPort as u16) == port && !pids.contains(&row.dwOwningPid) {
+                pids.push(row.dwOwningPid);
+            }
+        }
+        for row in listener_rows::<MIB_TCP6ROW_OWNER_PID>(AF_INET6) {
+            if u16::from_be(row.dwLocalPort as u16) == port && !pids.contains(&row.dwOwningPid) {
+                pids.push(row.dwOwningPid);
+            }
+        }
+        pids.into_iter()
+            .map(|pid| PortOccupant { pid, process: process_name(pid).unwrap_or_default() })
+            .collect()
+    }
+
+    /// 读取一种地址族的监听表；表的开头是 u32 的行数，随后是按 `Row` 布局的各行
+    fn listener_rows<Row: Copy>(family: u16) -> Vec<Row> {
+        let mut size = 0u32;
+        let mut buffer: Vec<u64> = Vec::new();
+        // 两次调用之间表可能变大，缓冲区不够时按返回的大小重新分配
+        for _ in 0..3 {
+            // SAFETY: 缓冲区按 8 字节对齐，长度不小于传入的 size
+            let result = unsafe {
+                GetExtendedTcpTable(buffer.as_mut_ptr().cast(), &mut size, 0, family as u32, TCP_TABLE_OWNER_PID_LISTENER, 0)
+            };
+            if result == 0 {
+                if buffer.is_empty() {
+                    return Vec::new();
+                }
+                let base = buffer.as_ptr().cast::<u8>();
+                // SAFETY: 调用成功后缓冲区开头是行数，各行紧随其后
+                return unsafe {
+                    let count = base.cast::<u32>().read() as usize;
+                    let rows = base.add(std::mem::size_of::<u32>()).cast::<Row>();
+                    (0..count).map(|index| rows.add(index).read_unaligned()).collect()
+                };
+            }
+            if result != ERROR_INSUFFICIENT_BUFFER {
+                break;
+            }
+            buffer = vec![0u64; (size as usize).div_ceil(8)];
+        }
+        Vec::new()
+    }
+
+    /// 可执行文件名，如 `cloudflared.exe`
+    fn process_name(pid: u32) -> Option<String> {
+        let process = Process::open(pid, PROCESS_QUERY_LIMITED_INFORMATION)?;
+        let mut path = [0u16; 1024];
+        let mut len = path.len() as u32;
+        // SAFETY: len 为缓冲区的字符数，成功时改为写入的字符数
+        if unsafe { QueryFullProcessImageNameW(process.0, PROCESS_NAME_WIN32, path.as_mut_ptr(), &mut len) } == 0 {
+            return None;
+        }
+        let path = String::from_utf16_lossy(&path[..len as usize]);
+        path.rsplit('\\').next().map(str::to_string)
+    }
+
+    pub fn process_start_time(pid: u32) -> Option<u64> {
+        let process = Process::open(pid, PROCESS_QUERY_LIMITED_INFORMATION)?;
+        // 已退出但句柄尚未释放的进程仍能打开，需要排除
+        let mut exit_code = 0u32;
+        // SAFETY: 句柄有效，输出参数指向本地变量
+        if unsafe { GetExitCodeProcess(process.0, &mut exit_code) } == 0 || exit_code != STILL_ACTIVE as u32 {
+            return None;
+        }
+        let empty = FILETIME { dwLowDateTime: 0, dwHighDateTime: 0 };
+        let (mut created, mut exited, mut kernel, mut user) = (empty, empty, empty, empty);
+        // SAFETY: 句柄有效，输出参数指向本地变量
+        if unsafe { GetProcessTimes(process.0, &mut created, &mut exited, &mut kernel, &mut user) } == 0 {
+            return None;
+        }
+        // 以 100 纳秒为单位，自 1601-01-01 起
+        let ticks = (u64::from(created.dwHighDateTime) << 32) | u64::from(created.dwLowDateTime);
+        Some((ticks / 10_000_000).saturating_sub(FILETIME_UNIX_OFFSET))
+    }
+
+    pub fn terminate_process(pid: u32) {
+        if let Some(process) = Process::open(pid, PROCESS_TERMINATE) {
+            // SAFETY: 句柄以 PROCESS_TERMINATE 权限打开
+            unsafe { TerminateProcess(process.0, 1) };
+        }
+    }
+}
@@ -0,0 +1,207 @@
+//! 低于 1024 的本地端口：Linux 等系统上普通用户无权绑定。启动前检测出来并说明，
+//! 可以改为让后端监听非特权端口、由系统的端口转发规则（iptables、pf、netsh portproxy）把原端口转发过去，
+//! 或经 sudo 以管理员身份启动后端进程。
+
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::ErrorKind;
+use std::net::{SocketAddr, TcpListener};
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::error::TunnelError;
+use crate::i18n::t;
+use crate::platform;
+use crate::tunnel::Tunnel;
+use tracing::{debug, warn};
+
+/// 低于该值的端口需要管理员权限才能绑定
+pub const PRIVILEGED_PORTS: u16 = 1024;
+
+/// 端口转发时非特权端口与原端口的默认差值，如 443 → 8443、80 → 8080
+const REDIRECT_OFFSET: u16 = 8000;
+
+/// 等待经 sudo 启动的后端进程退出的时间
+const TERMINATE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 本地端口低于 1024 时绑定端口的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "lowercase")]
+pub enum Privileged {
+    /// 后端监听非特权端口 `port`，由系统的端口转发规则把本地端口转发过去
+    Redirect { port: u16 },
+    /// 经 sudo 以管理员身份启动后端进程
+    Elevate,
+}
+
+impl fmt::Display for Privileged {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Privileged::Redirect { port } => write!(f, "redirect → {}", port),
+            Privileged::Elevate => f.write_str("elevate"),
+        }
+    }
+}
+
+/// 命令行中选择的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Mode {
+    /// 监听非特权端口并设置端口转发
+    Redirect,
+    /// 经 sudo 启动后端进程
+    Elevate,
+    /// 删除端口转发规则，恢复为直接监听本地端口
+    Off,
+}
+
+/// 当前用户是否因权限不足而无法在该地址上绑定端口
+pub fn needs_privilege(addr: SocketAddr) -> bool {
+    if addr.port() == 0 || addr.port() >= PRIVILEGED_PORTS || platform::is_admin() {
+        return false;
+    }
+    let denied = TcpListener::bind(addr).is_err_and(|e| e.kind() == ErrorKind::PermissionDenied);
+    debug!(%addr, denied, "检查特权端口");
+    denied
+}
+
+/// 是否可以经 sudo 提权：Windows 上绑定低端口不需要管理员权限，已是管理员时也不需要
+pub fn can_elevate() -> bool {
+    cfg!(unix) && !platform::is_admin()
+}
+
+/// 端口转发默认使用的非特权端口
+pub fn default_redirect_port(port: u16) -> u16 {
+    port.checked_add(REDIRECT_OFFSET).unwrap_or(port)
+}
+
+/// 把连接的本地端口转发到 `to` 的命令，每项为一行 shell 命令
+pub fn redirect_commands(tunnel: &Tunnel, to: u16) -> Vec<String> {
+    rule_commands(tunnel, to, true)
+}
+
+/// 删除 `redirect_commands` 设置的规则的命令
+pub fn unredirect_commands(tunnel: &Tunnel, to: u16) -> Vec<String> {
+    rule_commands(tunnel, to, false)
+}
+
+fn rule_commands(tunnel: &Tunnel, to: u16, add: bool) -> Vec<String> {
+    let (from, listen, target) = (tunnel.port, tunnel.listen_ip(), tunnel.local_ip());
+    if cfg!(windows) {
+        let family = if listen.is_ipv6() { "v6tov6" } else { "v4tov4" };
+        return vec![if add {
+            format!(
+                "netsh interface portproxy add {} listenaddress={} listenport={} connectaddress={} connectport={}",
+                family, listen, from, target, to
+            )
+        } else {
+            format!("netsh interface portproxy delete {} listenaddress={} listenport={}", family, listen, from)
+        }];
+    }
+    if cfg!(target_os = "macos") {
+        let anchor = format!("com.apple/cf-manager.{}", tunnel.alias);
+        return vec![if add {
+            let family = if listen.is_ipv6() { "inet6" } else { "inet" };
+            let destination = if listen.is_unspecified() { "any".to_string() } else { listen.to_string() };
+            format!(
+                "echo 'rdr pass {} proto tcp from any to {} port {} -> {} port {}' | pfctl -a {} -f - && pfctl -E",
+                family, destination, from, target, to, anchor
+            )
+        } else {
+            format!("pfctl -a {} -F all", anchor)
+        }];
+    }
+    // 本机发往该端口的连接经过 OUTPUT 链，其他主机发来的连接经过 PREROUTING 链
+    let program = if listen.is_ipv6() { "ip6tables" } else { "iptables" };
+    let action = if add { "-A" } else { "-D" };
+    let destination = if listen.is_unspecified() { String::new() } else { format!(" -d {}", listen) };
+    let rule = |chain: &str, interface: &str| format!(
+        "{} -t nat {} {}{}{} -p tcp --dport {} -m comment --comment cf-manager:{} -j REDIRECT --to-ports {}",
+        program, action, chain, interface, destination, from, tunnel.alias, to
+    );
+    let mut commands = vec![rule("OUTPUT", " -o lo")];
+    if !listen.is_loopback() {
+        commands.push(rule("PREROUTING", ""));
+    }
+    commands
+}
+
+/// 以管理员身份逐行执行命令：Unix 上不是 root 时经 sudo（可能询问密码），Windows 上要求已以管理员身份运行
+pub fn run_as_admin(commands: &[String]) -> Result<()> {
+    if cfg!(windows) && !platform::is_admin() {
+        return Err(anyhow::anyhow!(t!("设置端口转发需要管理员权限，请以管理员身份运行终端后重试")));
+    }
+    for line in commands {
+        let mut command = if cfg!(windows) {
+            let mut command = Command::new("cmd");
+            command.arg("/C").arg(line);
+            command
+        } else if platform::is_admin() {
+            let mut command = Command::new("sh");
+            command.arg("-c").arg(line);
+            command
+        } else {
+            let mut command = Command::new("sudo");
+            command.args(["sh", "-c"]).arg(line);
+            command
+        };
+        debug!(command = %line, "以管理员身份执行");
+        let status = command.status()?;
+        if !status.success() {
+            return Err(anyhow::anyhow!(t!("命令执行失败（{}）: {}", status, line)));
+        }
+    }
+    Ok(())
+}
+
+/// 经 `sudo -n` 执行后端命令，需要先由 `authorize` 缓存 sudo 凭据。
+/// 命令设置的环境变量（如出站代理）以及 HOME 同样传给后端，使其沿用当前用户的 Access 登录状态
+pub fn elevate(command: &Command) -> Command {
+    let mut preserved = vec!["HOME".to_string()];
+    preserved.extend(command.get_envs().map(|(name, _)| name.to_string_lossy().into_owned()));
+    let mut elevated = Command::new("sudo");
+    elevated
+        .arg("-n")
+        .arg(format!("--preserve-env={}", preserved.join(",")))
+        .arg("--")
+        .arg(command.get_program())
+        .args(command.get_args());
+    for (name, value) in command.get_envs() {
+        match value {
+            Some(value) => elevated.env(name, value),
+            None => elevated.env_remove(name),
+        };
+    }
+    elevated
+}
+
+/// 启动经 sudo 运行的后端前验证 sudo 凭据，必要时询问密码；非交互环境中只能依赖已缓存的凭据或免密配置
+pub fn authorize() -> bool {
+    if !platform::is_interactive() {
+        return true;
+    }
+    let authorized = Command::new("sudo").arg("-v").status().is_ok_and(|status| status.success());
+    if !authorized {
+        warn!("{}", t!("sudo 验证失败，经 sudo 启动的连接将无法启动"));
+    }
+    authorized
+}
+
+/// 结束经 sudo 启动的后端进程：向 sudo 发送 SIGTERM，由它转发给后端，并等待其退出
+pub fn terminate(pid: u32) -> Result<(), TunnelError> {
+    let mut command = Command::new("sudo");
+    if !platform::is_interactive() {
+        command.arg("-n");
+    }
+    let status = command.args(["kill", "-TERM", &pid.to_string()]).status()?;
+    if !status.success() {
+        return Err(anyhow::anyhow!(t!("无法结束经 sudo 启动的进程 {}，请以管理员身份运行 kill {}", pid, pid)).into());
+    }
+    let deadline = Instant::now() + TERMINATE_TIMEOUT;
+    while platform::process_start_time(pid).is_some() && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(100));
+    }
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crate::i18n::t;
+
+/// 经本地端口对目标服务做的应用层探测。配置中写作 `tcp`、`ssh`、`http:/healthz`、`postgres`
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Probe {
+    /// 只建立 TCP 连接
+    #[default]
+    Tcp,
+    /// 读取 SSH 服务端的版本标识行
+    Ssh,
+    /// 发送 GET 请求，2xx、3xx 视为成功
+    Http { path: String },
+    /// 发送 PostgreSQL 的 SSLRequest，服务端应答 S 或 N
+    Postgres,
+}
+
+impl FromStr for Probe {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim() {
+            "tcp" => Ok(Probe::Tcp),
+            "ssh" => Ok(Probe::Ssh),
+            "http" => Ok(Probe::Http { path: "/".to_string() }),
+            "postgres" => Ok(Probe::Postgres),
+            other => match other.strip_prefix("http:") {
+                Some(path) if path.starts_with('/') => Ok(Probe::Http { path: path.to_string() }),
+                _ => Err(t!("探测方式 {} 无效，可用 tcp、ssh、http[:/路径]、postgres", value)),
+            },
+        }
+    }
+}
+
+impl TryFrom<String> for Probe {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Probe> for String {
+    fn from(probe: Probe) -> Self {
+        probe.to_string()
+    }
+}
+
+impl fmt::Display for Probe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Probe::Tcp => f.write_str("tcp"),
+            Probe::Ssh => f.write_str("ssh"),
+            Probe::Http { path } => write!(f, "http:{}", path),
+            Probe::Postgres => f.write_str("postgres"),
+        }
+    }
+}
+
+impl Probe {
+    /// 执行一次探测，成功时返回从建立连接到收到应答的耗时
+    pub fn run(&self, addr: SocketAddr, host: &str, timeout: Duration) -> Result<Duration> {
+        let started = Instant::now();
+        let mut stream = TcpStream::connect_timeout(&addr, timeout)
+            .map_err(|e| anyhow::anyhow!(t!("无法连接 {}: {}", addr, e)))?;
+        stream.set_read_timeout(Some(timeout))?;
+        stream.set_write_timeout(Some(timeout))?;
+        match self {
+            Probe::Tcp => {}
+            Probe::Ssh => {
+                let line = read_reply_line(&stream)?;
+                if !line.starts_with("SSH-") {
+                    return Err(anyhow::anyhow!(t!("没有收到 SSH 版本标识: {}", line.trim())));
+                }
+            }
+            Probe::Http { path } => {
+                write!(stream, "GET {} HTTP/1.0\r\nHost: {}\r\nConnection: close\r\n\r\n", path, host)?;
+                let line = read_reply_line(&stream)?;
+                let status = line.split_whitespace().nth(1).and_then(|code| code.parse::<u16>().ok());
+                match status {
+                    Some(200..=399) => {}
+                    Some(code) => return Err(anyhow::anyhow!(t!("HTTP 状态码 {}", code))),
+                    None => return Err(anyhow::anyhow!(t!("没有收到 HTTP 应答: {}", line.trim()))),
+                }
+            }
+            Probe::Postgres => {
+                // SSLRequest：长度 8 与请求码 80877103
+                stream.write_all(&[0, 0, 0, 8, 0x04, 0xd2, 0x16, 0x2f])?;
+                let mut reply = [0u8; 1];
+                stream.read_exact(&mut reply)?;
+                if !matches!(reply[0], b'S' | b'N') {
+                    return Err(anyhow::anyhow!(t!("没有收到 PostgreSQL 应答")));
+                }
+            }
+        }
+        Ok(started.elapsed())
+    }
+}
+
+/// 读取应答的第一行；对端未应答就关闭连接时报错
+fn read_reply_line(stream: &TcpStream) -> Result<String> {
+    let mut line = String::new();
+    if BufReader::new(stream).read_line(&mut line)? == 0 {
+        return Err(anyhow::anyhow!(t!("连接在收到应答之前被关闭")));
+    }
+    Ok(line)
+}
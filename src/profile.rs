@@ -0,0 +1,162 @@
+//! 配置档案：每个档案有独立的配置、运行时文件与操作历史，互不混用。
+//! 默认档案沿用配置目录本身，其他档案位于 `profiles/<名称>/` 下。
+
+use anyhow::Result;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::error::TunnelError;
+use crate::i18n::t;
+
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// 选择档案的环境变量，优先级低于 --profile
+pub const PROFILE_ENV: &str = "CF_MANAGER_PROFILE";
+
+/// 指定配置目录根部的环境变量，优先级低于 --config-dir
+pub const CONFIG_DIR_ENV: &str = "CF_MANAGER_CONFIG_DIR";
+
+/// 记录 `profile switch` 所选档案的文件，位于配置目录根部
+const CURRENT_FILE: &str = "profile";
+
+static CURRENT: OnceLock<String> = OnceLock::new();
+
+static ROOT: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+/// 确定配置目录根部：--config-dir、CF_MANAGER_CONFIG_DIR，都没有时使用系统的配置目录。
+/// 需要在选择档案之前调用
+pub fn select_root(arg: Option<&str>) -> Result<()> {
+    let dir = match arg {
+        Some(dir) => Some(dir.to_string()),
+        None => std::env::var(CONFIG_DIR_ENV).ok(),
+    };
+    // 转为绝对路径，由本工具启动的后台进程工作目录不同时也指向同一目录
+    let dir = dir.filter(|dir| !dir.is_empty()).map(std::path::absolute).transpose()?;
+    let _ = ROOT.set(dir);
+    Ok(())
+}
+
+/// 通过 --config-dir 或环境变量指定的配置目录根部
+pub fn custom_root() -> Option<&'static PathBuf> {
+    ROOT.get().and_then(Option::as_ref)
+}
+
+/// 确定本进程使用的档案：--profile、CF_MANAGER_PROFILE、`profile switch` 的选择，最后是默认档案
+pub fn select(arg: Option<&str>) -> Result<()> {
+    let name = match arg {
+        Some(name) => name.to_string(),
+        None => match std::env::var(PROFILE_ENV).ok().filter(|value| !value.is_empty()) {
+            Some(name) => name,
+            None => switched().unwrap_or_else(|| DEFAULT_PROFILE.to_string()),
+        },
+    };
+    check_name(&name)?;
+    let _ = CURRENT.set(name);
+    Ok(())
+}
+
+pub fn current() -> &'static str {
+    CURRENT.get().map_or(DEFAULT_PROFILE, String::as_str)
+}
+
+pub fn is_default() -> bool {
+    current() == DEFAULT_PROFILE
+}
+
+/// 所有档案共用的配置目录根部
+pub fn root_dir() -> Result<PathBuf> {
+    if let Some(root) = custom_root() {
+        return Ok(root.clone());
+    }
+    let mut path = dirs::config_dir()
+        .ok_or_else(|| anyhow::anyhow!(t!("无法获取配置目录")))?;
+    path.push("cf-manager");
+    Ok(path)
+}
+
+/// 档案的目录；默认档案即根目录
+pub fn dir(name: &str) -> Result<PathBuf> {
+    let root = root_dir()?;
+    Ok(if name == DEFAULT_PROFILE { root } else { root.join("profiles").join(name) })
+}
+
+pub fn exists(name: &str) -> Result<bool> {
+    Ok(name == DEFAULT_PROFILE || dir(name)?.is_dir())
+}
+
+/// 全部档案，默认档案在前，其余按名称排序
+pub fn list() -> Result<Vec<String>> {
+    let mut names = Vec::new();
+    if let Ok(entries) = fs::read_dir(root_dir()?.join("profiles")) {
+        for entry in entries.flatten() {
+            if entry.path().is_dir()
+                && let Some(name) = entry.file_name().to_str()
+                && check_name(name).is_ok()
+            {
+                names.push(name.to_string());
+            }
+        }
+    }
+    names.sort();
+    names.insert(0, DEFAULT_PROFILE.to_string());
+    Ok(names)
+}
+
+/// 创建空档案
+pub fn create(name: &str) -> Result<()> {
+    check_name(name)?;
+    if exists(name)? {
+        return Err(TunnelError::config(t!("档案 {} 已存在", name)));
+    }
+    fs::create_dir_all(dir(name)?)?;
+    Ok(())
+}
+
+/// 把档案的连接配置复制为新档案；运行时文件、操作历史与钥匙串中的服务令牌不复制
+pub fn copy(from: &str, to: &str) -> Result<()> {
+    ensure_exists(from)?;
+    create(to)?;
+    let source = dir(from)?.join("config.json");
+    if source.exists() {
+        fs::copy(source, dir(to)?.join("config.json"))?;
+    }
+    Ok(())
+}
+
+/// 设置之后默认使用的档案
+pub fn switch(name: &str) -> Result<()> {
+    ensure_exists(name)?;
+    let path = root_dir()?.join(CURRENT_FILE);
+    if name == DEFAULT_PROFILE {
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+    } else {
+        fs::create_dir_all(root_dir()?)?;
+        fs::write(path, name)?;
+    }
+    Ok(())
+}
+
+/// `profile switch` 选择的档案
+pub fn switched() -> Option<String> {
+    let name = fs::read_to_string(root_dir().ok()?.join(CURRENT_FILE)).ok()?;
+    Some(name.trim().to_string()).filter(|name| !name.is_empty())
+}
+
+pub fn ensure_exists(name: &str) -> Result<()> {
+    check_name(name)?;
+    if !exists(name)? {
+        return Err(TunnelError::config(t!("档案 {} 不存在，可使用 'tfa profile create {}' 创建", name, name)));
+    }
+    Ok(())
+}
+
+/// 档案名用作目录名，只允许字母、数字、`-` 和 `_`
+fn check_name(name: &str) -> Result<()> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(TunnelError::config(t!("档案名 {} 无效：只能包含字母、数字、- 和 _", name)));
+    }
+    Ok(())
+}
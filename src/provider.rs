@@ -0,0 +1,257 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::process::Command;
+
+use crate::chain::Route;
+use crate::cloudflared;
+use crate::container;
+use crate::error::TunnelError;
+use crate::i18n::t;
+use crate::outbound;
+use crate::secrets::ServiceToken;
+use crate::tunnel::Tunnel;
+
+/// 显示或记录命令时替代敏感参数的文本
+pub const REDACTED: &str = "******";
+
+/// 命令的程序名与参数，用于显示和日志，服务令牌密钥会被隐藏
+pub fn redacted_args(command: &Command) -> Vec<String> {
+    let mut args = vec![command.get_program().to_string_lossy().into_owned()];
+    let mut hide_next = false;
+    for arg in command.get_args() {
+        let arg = arg.to_string_lossy();
+        args.push(if hide_next { REDACTED.to_string() } else { arg.to_string() });
+        hide_next = arg == "--service-token-secret";
+    }
+    args
+}
+
+/// 提供连接的后端及其参数
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Provider {
+    /// cloudflared access tcp，连接的 source 为 Access 应用的主机名
+    #[default]
+    Cloudflared,
+    /// kubectl port-forward，连接的 source 为目标资源，如 svc/foo
+    Kubectl {
+        remote_port: u16,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        context: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        namespace: Option<String>,
+    },
+    /// 本地 HTTP 反向代理，转发到 Access 保护的 Web 应用并附带 Access 令牌，source 为应用的主机名
+    Http,
+}
+
+/// cloudflared 的 --loglevel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Debug,
+    Info,
+    Warn,
+    Error,
+    Fatal,
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+            LogLevel::Fatal => "fatal",
+        })
+    }
+}
+
+/// 命令行中选择后端使用的名称
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ProviderKind {
+    Cloudflared,
+    Kubectl,
+    Http,
+}
+
+impl Provider {
+    pub fn is_default(&self) -> bool {
+        *self == Provider::Cloudflared
+    }
+
+    /// 后端的可执行文件名，也用于识别监听端口的进程
+    pub fn name(&self) -> &'static str {
+        match self {
+            Provider::Cloudflared => "cloudflared",
+            Provider::Kubectl { .. } => "kubectl",
+            // 由本程序自身提供
+            Provider::Http => "tfa",
+        }
+    }
+
+    /// 列表中显示的后端类型
+    pub fn label(&self) -> &'static str {
+        match self {
+            Provider::Http => "http",
+            _ => self.name(),
+        }
+    }
+
+    /// 日志中出现即表示已开始监听的内容（小写）
+    pub fn ready_markers(&self) -> &'static [&'static str] {
+        match self {
+            Provider::Cloudflared => &["websocket listener", "connected"],
+            Provider::Kubectl { .. } => &["forwarding from"],
+            Provider::Http => &["http proxy listening"],
+        }
+    }
+
+    /// 日志中出现即表示需要先完成认证的内容（小写）
+    pub fn auth_markers(&self) -> &'static [&'static str] {
+        match self {
+            Provider::Cloudflared => &["access login", "unable to find token", "please open the following url"],
+            Provider::Kubectl { .. } | Provider::Http => &[],
+        }
+    }
+
+    /// 找不到可执行文件时的错误
+    pub fn not_found(&self) -> TunnelError {
+        match self {
+            // HTTP 代理通过 cloudflared 取得令牌
+            Provider::Cloudflared | Provider::Http => TunnelError::CloudflaredNotFound,
+            Provider::Kubectl { .. } => TunnelError::ProgramNotFound {
+                program: self.name().to_string(),
+            },
+        }
+    }
+
+    /// 构造在 `addr` 上监听并按 `route` 转发到连接目标的命令
+    pub fn command(&self, tunnel: &Tunnel, addr: SocketAddr, route: &Route) -> Result<Command, TunnelError> {
+        let mut command = match self {
+            Provider::Http => Command::new(std::env::current_exe()?),
+            _ => Command::new(Self::resolve_program(self.name())?),
+        };
+        match self {
+            Provider::Cloudflared => {
+                let listen = if tunnel.runtime.is_some() { container::inner_addr(addr) } else { addr };
+                let url = if listen.ip() == Ipv4Addr::LOCALHOST {
+                    format!("tcp://localhost:{}", listen.port())
+                } else {
+                    format!("tcp://{}", listen)
+                };
+                command.args(["access", "tcp", "--hostname", &route.hostname, "--url", &url]);
+                if let Some(destination) = &route.destination {
+                    command.args(["--destination", destination]);
+                }
+                if let Some(level) = tunnel.log_level {
+                    command.args(["--loglevel", &level.to_string()]);
+                }
+                if tunnel.service_token {
+                    let token = ServiceToken::load(&tunnel.alias)?.ok_or_else(|| anyhow::anyhow!(t!(
+                        "连接 {} 已启用服务令牌，但系统钥匙串中没有找到，请重新运行 'tfa service-token {}'",
+                        tunnel.alias,
+                        tunnel.alias
+                    )))?;
+                    command.args([
+                        "--service-token-id", &token.client_id,
+                        "--service-token-secret", &token.client_secret,
+                    ]);
+                }
+            }
+            Provider::Kubectl { remote_port, context, namespace } => {
+                command.args([
+                    "port-forward", &tunnel.source, &format!("{}:{}", addr.port(), remote_port),
+                    "--address", &addr.ip().to_string(),
+                ]);
+                if let Some(context) = context {
+                    command.args(["--context", context]);
+                }
+                if let Some(namespace) = namespace {
+                    command.args(["--namespace", namespace]);
+                }
+            }
+            Provider::Http => {
+                command.args([
+                    "http-serve", &tunnel.source,
+                    "--listen", &addr.port().to_string(),
+                    "--bind", &addr.ip().to_string(),
+                ]);
+            }
+        }
+        if let Some(proxy) = &route.proxy {
+            proxy.apply(&mut command);
+        }
+        if let Some(runtime) = tunnel.container_runtime() {
+            return Ok(container::wrap(runtime, &tunnel.alias, addr, &command));
+        }
+        Ok(command)
+    }
+
+    /// 经 `cloudflared access curl` 请求连接主机上的路径，由 cloudflared 附带 Access 令牌；
+    /// 只适用于 cloudflared 与 http 后端
+    pub fn curl_command(tunnel: &Tunnel, path: &str, args: &[String]) -> Result<Command, TunnelError> {
+        if matches!(tunnel.provider, Provider::Kubectl { .. }) {
+            return Err(TunnelError::Config(t!("连接 {} 不是 cloudflared 连接，无法使用 curl", tunnel.alias)));
+        }
+        let host = tunnel.source.trim_start_matches("https://").trim_start_matches("http://").trim_end_matches('/');
+        let path = if path.starts_with('/') { path.to_string() } else { format!("/{}", path) };
+        let mut command = Command::new(Self::resolve_program(Provider::Cloudflared.name())?);
+        command.args(["access", "curl", &format!("https://{}{}", host, path)]).args(args);
+        if let Some(proxy) = outbound::effective(tunnel.outbound_proxy.as_ref()) {
+            proxy.apply(&mut command);
+        }
+        Ok(command)
+    }
+
+    /// 经 `cloudflared access token` 取得连接主机当前的 Access 令牌；经跳板访问时为跳板的主机名
+    pub fn token_command(tunnel: &Tunnel, route: &Route) -> Result<Command, TunnelError> {
+        if matches!(tunnel.provider, Provider::Kubectl { .. }) {
+            return Err(TunnelError::Config(t!("连接 {} 不是 cloudflared 连接，没有 Access 令牌", tunnel.alias)));
+        }
+        let host = route.hostname.trim_start_matches("https://").trim_start_matches("http://").trim_end_matches('/');
+        let mut command = Command::new(Self::resolve_program(Provider::Cloudflared.name())?);
+        command.args(["access", "token", &format!("-app=https://{}", host)]);
+        if let Some(proxy) = &route.proxy {
+            proxy.apply(&mut command);
+        }
+        Ok(command)
+    }
+
+    /// 以 `cloudflared tunnel --url` 建立 quick tunnel，把本机的 `origin` 公开到 trycloudflare.com 的临时地址
+    pub fn share_command(origin: &str) -> Result<Command, TunnelError> {
+        let mut command = Command::new(Self::resolve_program(Provider::Cloudflared.name())?);
+        command.args(["tunnel", "--no-autoupdate", "--url", origin]);
+        if let Some(proxy) = outbound::effective(None) {
+            proxy.apply(&mut command);
+        }
+        Ok(command)
+    }
+
+    /// 已安装档案固定的 cloudflared 版本时使用它；否则 Windows 下通过 where 查找完整路径，
+    /// 其他系统直接交给 PATH 解析
+    fn resolve_program(program: &str) -> Result<String, TunnelError> {
+        if program == Provider::Cloudflared.name()
+            && let Some(path) = cloudflared::managed_program()
+        {
+            return Ok(path.to_string_lossy().into_owned());
+        }
+        if !cfg!(windows) {
+            return Ok(program.to_string());
+        }
+        let output = Command::new("where").arg(program).output()?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .next()
+                .unwrap_or(program)
+                .trim()
+                .to_string())
+        } else {
+            Ok(program.to_string())
+        }
+    }
+}
@@ -0,0 +1,310 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+
+use crate::audit;
+use crate::config::Config;
+use crate::platform::unix_now;
+use crate::state::StateStore;
+
+const COPY_BUFFER_SIZE: usize = 16 * 1024;
+const STATS_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 单个客户端连接的统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionInfo {
+    pub peer: String,
+    pub opened_at: u64,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+}
+
+/// 代理进程定期写入运行目录的统计快照
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProxyStats {
+    pub pid: u32,
+    pub listen_port: u16,
+    pub upstream_port: u16,
+    pub started_at: u64,
+    pub last_activity: u64,
+    pub total_connections: u64,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    pub active: BTreeMap<u64, ConnectionInfo>,
+}
+
+impl ProxyStats {
+    pub fn path(alias: &str) -> Result<PathBuf> {
+        let mut path = Config::runtime_dir()?;
+        path.push(format!("{}.proxy.json", alias));
+        Ok(path)
+    }
+
+    pub fn load(alias: &str) -> Option<Self> {
+        let content = fs::read_to_string(Self::path(alias).ok()?).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    pub fn remove(alias: &str) {
+        if let Ok(path) = Self::path(alias) {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    fn save(&self, alias: &str) -> Result<()> {
+        StateStore::write(&Self::path(alias)?, serde_json::to_string_pretty(self)?.as_bytes())
+    }
+}
+
+/// 连接的限速设置（字节/秒），由启动和 `tfa set` 写入运行目录，代理进程每秒重新读取
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RateLimits {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_up: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_down: Option<u64>,
+}
+
+impl RateLimits {
+    fn path(alias: &str) -> Result<PathBuf> {
+        let mut path = Config::runtime_dir()?;
+        path.push(format!("{}.limits.json", alias));
+        Ok(path)
+    }
+
+    pub fn load(alias: &str) -> Self {
+        Self::path(alias).ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, alias: &str) -> Result<()> {
+        StateStore::write(&Self::path(alias)?, serde_json::to_string_pretty(self)?.as_bytes())
+    }
+
+    pub fn remove(alias: &str) {
+        if let Ok(path) = Self::path(alias) {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// 令牌桶限速，同一方向的所有客户端连接共用一个桶；最多积攒一秒的额度
+struct Throttle {
+    rate: Option<u64>,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Throttle {
+    /// 限速为 0 视为不限速，与命令行中的 0 一致
+    fn new(rate: Option<u64>) -> Self {
+        Self { rate: rate.filter(|rate| *rate > 0), tokens: 0.0, updated: Instant::now() }
+    }
+
+    fn set_rate(&mut self, rate: Option<u64>) {
+        if self.rate != rate.filter(|rate| *rate > 0) {
+            *self = Self::new(rate);
+        }
+    }
+
+    /// 每次读取的最大字节数，限速较低时缩小，使流量更平滑
+    fn chunk(&self) -> usize {
+        self.rate.map_or(COPY_BUFFER_SIZE, |rate| (rate as usize).clamp(1, COPY_BUFFER_SIZE))
+    }
+
+    /// 消耗 `n` 字节的额度，返回转发前需要等待的时间
+    fn take(&mut self, n: usize) -> Duration {
+        let Some(rate) = self.rate else {
+            return Duration::ZERO;
+        };
+        let rate = rate as f64;
+        let now = Instant::now();
+        self.tokens = (self.tokens + now.duration_since(self.updated).as_secs_f64() * rate).min(rate);
+        self.updated = now;
+        self.tokens -= n as f64;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / rate)
+        }
+    }
+}
+
+/// 上行（客户端 -> cloudflared）与下行两个方向的限速
+struct Throttles {
+    up: Throttle,
+    down: Throttle,
+}
+
+impl Throttles {
+    fn new(limits: RateLimits) -> Self {
+        Self { up: Throttle::new(limits.max_up), down: Throttle::new(limits.max_down) }
+    }
+
+    fn apply(&mut self, limits: RateLimits) {
+        self.up.set_rate(limits.max_up);
+        self.down.set_rate(limits.max_down);
+    }
+
+    fn get(&mut self, direction: Direction) -> &mut Throttle {
+        match direction {
+            Direction::In => &mut self.up,
+            Direction::Out => &mut self.down,
+        }
+    }
+}
+
+type SharedThrottles = Arc<Mutex<Throttles>>;
+
+#[derive(Clone, Copy)]
+enum Direction {
+    /// 客户端 -> cloudflared
+    In,
+    /// cloudflared -> 客户端
+    Out,
+}
+
+type SharedStats = Arc<Mutex<ProxyStats>>;
+
+/// 在 `listen` 地址接受连接并转发到 `upstream` 端口上的 cloudflared，
+/// 同时记录活动连接和收发字节数，直到进程被终止。
+pub async fn serve(alias: &str, listen: SocketAddr, upstream: u16) -> Result<()> {
+    let listener = TcpListener::bind(listen).await?;
+
+    let stats: SharedStats = Arc::new(Mutex::new(ProxyStats {
+        pid: std::process::id(),
+        listen_port: listen.port(),
+        upstream_port: upstream,
+        started_at: unix_now(),
+        last_activity: unix_now(),
+        ..Default::default()
+    }));
+    stats.lock().unwrap().save(alias)?;
+    let throttles: SharedThrottles = Arc::new(Mutex::new(Throttles::new(RateLimits::load(alias))));
+
+    // 定期把统计写入磁盘，供 `connections` 命令读取，并读取 `tfa set` 调整后的限速
+    let flush_stats = stats.clone();
+    let flush_throttles = throttles.clone();
+    let flush_alias = alias.to_string();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(STATS_FLUSH_INTERVAL);
+        loop {
+            interval.tick().await;
+            let snapshot = flush_stats.lock().unwrap().clone();
+            let _ = snapshot.save(&flush_alias);
+            flush_throttles.lock().unwrap().apply(RateLimits::load(&flush_alias));
+        }
+    });
+
+    let mut next_id = 0u64;
+    loop {
+        let (client, peer) = listener.accept().await?;
+        next_id += 1;
+        let id = next_id;
+
+        {
+            let mut stats = stats.lock().unwrap();
+            stats.total_connections += 1;
+            stats.last_activity = unix_now();
+            stats.active.insert(id, ConnectionInfo {
+                peer: peer.to_string(),
+                opened_at: unix_now(),
+                bytes_in: 0,
+                bytes_out: 0,
+            });
+        }
+
+        let stats = stats.clone();
+        let throttles = throttles.clone();
+        let alias = alias.to_string();
+        tokio::spawn(async move {
+            if let Ok(server) = TcpStream::connect(("127.0.0.1", upstream)).await {
+                let (client_read, client_write) = client.into_split();
+                let (server_read, server_write) = server.into_split();
+                let _ = tokio::join!(
+                    pipe(client_read, server_write, id, Direction::In, stats.clone(), throttles.clone()),
+                    pipe(server_read, client_write, id, Direction::Out, stats.clone(), throttles.clone()),
+                );
+            }
+            let closed = stats.lock().unwrap().active.remove(&id);
+            if let Some(conn) = closed {
+                audit::record(&alias, &conn, unix_now());
+            }
+        });
+    }
+}
+
+async fn pipe(
+    mut reader: OwnedReadHalf,
+    mut writer: OwnedWriteHalf,
+    id: u64,
+    direction: Direction,
+    stats: SharedStats,
+    throttles: SharedThrottles,
+) -> std::io::Result<()> {
+    let mut buf = vec![0u8; COPY_BUFFER_SIZE];
+    loop {
+        let chunk = throttles.lock().unwrap().get(direction).chunk();
+        let n = reader.read(&mut buf[..chunk]).await?;
+        if n == 0 {
+            break;
+        }
+        let wait = throttles.lock().unwrap().get(direction).take(n);
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+        writer.write_all(&buf[..n]).await?;
+
+        let mut stats = stats.lock().unwrap();
+        stats.last_activity = unix_now();
+        match direction {
+            Direction::In => stats.bytes_in += n as u64,
+            Direction::Out => stats.bytes_out += n as u64,
+        }
+        if let Some(conn) = stats.active.get_mut(&id) {
+            match direction {
+                Direction::In => conn.bytes_in += n as u64,
+                Direction::Out => conn.bytes_out += n as u64,
+            }
+        }
+    }
+    writer.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_rate_is_unlimited() {
+        let mut throttle = Throttle::new(Some(0));
+        assert_eq!(throttle.chunk(), COPY_BUFFER_SIZE);
+        assert_eq!(throttle.take(COPY_BUFFER_SIZE), Duration::ZERO);
+
+        let mut throttle = Throttle::new(Some(1));
+        throttle.set_rate(Some(0));
+        assert_eq!(throttle.chunk(), COPY_BUFFER_SIZE);
+        assert_eq!(throttle.take(COPY_BUFFER_SIZE), Duration::ZERO);
+    }
+
+    #[test]
+    fn one_byte_per_second() {
+        let mut throttle = Throttle::new(Some(1));
+        assert_eq!(throttle.chunk(), 1);
+        let wait = throttle.take(1);
+        assert!(wait > Duration::from_millis(900) && wait <= Duration::from_secs(1), "{:?}", wait);
+        // 额度已经透支，下一个字节还要再等一秒
+        let wait = throttle.take(1);
+        assert!(wait > Duration::from_millis(1900) && wait <= Duration::from_secs(2), "{:?}", wait);
+    }
+}
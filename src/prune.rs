@@ -0,0 +1,127 @@
+use anyhow::Result;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::i18n::t;
+use crate::platform;
+use crate::proxy::ProxyStats;
+use crate::state::{RuntimeState, StateStore};
+use tracing::debug;
+
+/// 运行目录中按连接别名命名的文件后缀，较长的后缀在前
+const RUNTIME_SUFFIXES: &[&str] = &[".state.json", ".ttl-watch.json", ".latency-watch.json", ".log-filter.json", ".proxy.json", ".limits.json", ".paused.json", ".latency.json", ".lock", ".failures.json", ".hooks.log", ".log"];
+
+/// 需要清理的残留项
+#[derive(Debug)]
+pub enum Orphan {
+    /// 已不在配置中的连接仍在运行的后端进程或统计代理
+    Process { alias: String, pid: u32, proxy: bool },
+    /// 已不在配置中的连接留下的文件，或记录的进程已退出的状态文件
+    File { alias: String, path: PathBuf, stale: bool },
+}
+
+impl Orphan {
+    pub fn alias(&self) -> &str {
+        match self {
+            Orphan::Process { alias, .. } | Orphan::File { alias, .. } => alias,
+        }
+    }
+
+    pub fn is_process(&self) -> bool {
+        matches!(self, Orphan::Process { .. })
+    }
+
+    /// 类型、详情与原因，用于列表展示
+    pub fn describe(&self) -> (&'static str, String, String) {
+        match self {
+            Orphan::Process { pid, proxy, .. } => (
+                if *proxy { "proxy" } else { "process" },
+                format!("PID {}", pid),
+                t!("连接已不在配置中"),
+            ),
+            Orphan::File { path, stale, .. } => (
+                "file",
+                path.display().to_string(),
+                if *stale { t!("记录的进程已退出") } else { t!("连接已不在配置中") },
+            ),
+        }
+    }
+
+    /// 结束进程或删除文件
+    pub fn clean(&self) {
+        match self {
+            Orphan::Process { alias, pid, .. } => {
+                debug!(alias = %alias, pid, "结束残留进程");
+                platform::kill_process(*pid);
+            }
+            Orphan::File { path, .. } => {
+                debug!(path = %path.display(), "删除残留文件");
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+}
+
+/// 扫描运行目录与进程状态目录，找出已不在配置中的连接留下的进程与文件，以及过期的状态记录。
+/// 由其他项目配置启动、且该项目仍定义了它的连接不算残留
+pub fn scan(config: &Config) -> Result<Vec<Orphan>> {
+    // 额外端口的后端进程按 `<alias>@<端口>` 保存状态
+    let configured: HashSet<String> = config.list_tunnels()?
+        .iter()
+        .flat_map(|t| std::iter::once(t.alias.clone()).chain(t.extra_ports.iter().map(|mapping| t.member_alias(mapping))))
+        .collect();
+
+    let mut files: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+    for dir in StateStore::dirs()? {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            if let Some(alias) = RUNTIME_SUFFIXES.iter().find_map(|suffix| name.strip_suffix(suffix)) {
+                files.entry(alias.to_string()).or_default().push(path.clone());
+            }
+        }
+    }
+
+    let mut orphans = Vec::new();
+    for (alias, paths) in files {
+        let state = RuntimeState::load(&alias);
+        let proxy = ProxyStats::load(&alias);
+        let known = configured.contains(alias.as_str())
+            || state.as_ref()
+                .and_then(|state| state.project.as_deref())
+                .is_some_and(|project| Config::project_defines(project, &alias));
+
+        if known {
+            if let Some(state) = &state
+                && !state.is_alive()
+            {
+                orphans.push(Orphan::File { alias: alias.clone(), path: RuntimeState::path(&alias)?, stale: true });
+            }
+            if let Some(stats) = &proxy
+                && platform::process_start_time(stats.pid).is_none()
+            {
+                orphans.push(Orphan::File { alias: alias.clone(), path: ProxyStats::path(&alias)?, stale: true });
+            }
+            continue;
+        }
+
+        if let Some(state) = &state
+            && state.is_alive()
+        {
+            orphans.push(Orphan::Process { alias: alias.clone(), pid: state.pid, proxy: false });
+        }
+        if let Some(stats) = &proxy
+            && platform::process_start_time(stats.pid).is_some()
+        {
+            orphans.push(Orphan::Process { alias: alias.clone(), pid: stats.pid, proxy: true });
+        }
+        for path in paths {
+            orphans.push(Orphan::File { alias: alias.clone(), path, stale: false });
+        }
+    }
+    Ok(orphans)
+}
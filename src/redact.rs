@@ -0,0 +1,37 @@
+//! 日志脱敏：连接可以配置一组正则表达式，后端输出在写入日志文件之前把匹配的内容替换为 `******`。
+//!
+//! 脱敏由后台的 `log-filter` 进程逐行完成，见 `logsink` 模块。
+
+use anyhow::Result;
+use regex::Regex;
+use std::borrow::Cow;
+
+use crate::error::TunnelError;
+use crate::i18n::t;
+use crate::provider::REDACTED;
+
+#[derive(Default)]
+pub struct Redactor {
+    patterns: Vec<Regex>,
+}
+
+impl Redactor {
+    pub fn new(patterns: &[String]) -> Result<Self> {
+        let patterns = patterns.iter()
+            .map(|pattern| Regex::new(pattern)
+                .map_err(|e| TunnelError::config(t!("脱敏规则 {} 无效: {}", pattern, e))))
+            .collect::<Result<_>>()?;
+        Ok(Self { patterns })
+    }
+
+    /// 把一行中所有匹配任一规则的内容替换为 `******`
+    pub fn apply<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        let mut line = Cow::Borrowed(line);
+        for pattern in &self.patterns {
+            if let Cow::Owned(replaced) = pattern.replace_all(&line, REDACTED) {
+                line = Cow::Owned(replaced);
+            }
+        }
+        line
+    }
+}
@@ -0,0 +1,246 @@
+//! 配置热加载：`tfa grpc`、`tfa web` 运行期间监视配置文件，修改后立即应用到连接上。
+//! 新加入的 autostart 连接被启动，从配置中删除的连接被停止，参数有变化的运行中连接按 on_change 重启；
+//! `tfa reload` 不等文件变化，立即执行一次同样的检查。
+//!
+//! 上次应用的配置保存在状态目录的 `reload.json` 中，与当前配置比较得出变化。多个服务与 `tfa reload`
+//! 持有该文件的锁依次执行，同一次修改只会应用一次。状态目录在重启后清空，服务再次启动时
+//! 没有上次的记录，所有 autostart 连接都会被启动。只监视用户配置文件，项目配置与系统配置的修改需要 `tfa reload`。
+
+use ::notify::{EventKind, RecursiveMode, Watcher};
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::control;
+use crate::history::{self, HistoryAction};
+use crate::i18n::t;
+use crate::state::{PauseMark, StateStore};
+use crate::tunnel::Tunnel;
+use tracing::{debug, info, warn};
+
+/// 保存上次应用的配置的文件，位于状态目录
+const APPLIED_FILE: &str = "reload.json";
+
+/// 编辑器保存文件时会连续产生多个事件，等待这段时间后合并为一次加载
+const SETTLE: Duration = Duration::from_millis(500);
+
+/// 比较配置时忽略的字段：修改它们不需要重启连接
+const IGNORED_FIELDS: &[&str] = &["autostart", "on_change", "enabled", "locked", "tags", "managed_by"];
+
+/// 运行中的连接配置被修改时的处理方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum OnChange {
+    /// 立即重启以应用新配置
+    #[default]
+    Restart,
+    /// 保持运行，新配置在手动重启后生效
+    Manual,
+}
+
+impl OnChange {
+    pub fn is_default(&self) -> bool {
+        *self == OnChange::Restart
+    }
+}
+
+impl fmt::Display for OnChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            OnChange::Restart => "restart",
+            OnChange::Manual => "manual",
+        })
+    }
+}
+
+/// 一次加载的结果
+#[derive(Debug, Default)]
+pub struct Outcome {
+    pub started: Vec<String>,
+    pub stopped: Vec<String>,
+    pub restarted: Vec<String>,
+    /// 配置已修改、on_change 为 manual 而没有重启的连接
+    pub pending: Vec<String>,
+    /// 操作失败的连接与错误
+    pub failed: Vec<(String, String)>,
+}
+
+impl Outcome {
+    pub fn is_empty(&self) -> bool {
+        self.started.is_empty()
+            && self.stopped.is_empty()
+            && self.restarted.is_empty()
+            && self.pending.is_empty()
+            && self.failed.is_empty()
+    }
+
+    /// 每项变化一行，用于显示
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if !self.started.is_empty() {
+            lines.push(t!("已启动: {}", self.started.join(", ")));
+        }
+        if !self.stopped.is_empty() {
+            lines.push(t!("已停止: {}", self.stopped.join(", ")));
+        }
+        if !self.restarted.is_empty() {
+            lines.push(t!("已重启: {}", self.restarted.join(", ")));
+        }
+        if !self.pending.is_empty() {
+            lines.push(t!("配置已修改，重启后生效: {}", self.pending.join(", ")));
+        }
+        for (alias, error) in &self.failed {
+            lines.push(t!("{} 失败: {}", alias, error));
+        }
+        lines
+    }
+}
+
+/// 比较上次应用的配置与当前配置，启动、停止或重启有变化的连接
+pub fn reload() -> Result<Outcome> {
+    let mut file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(StateStore::dir()?.join(APPLIED_FILE))?;
+    file.lock()?;
+    let mut content = String::new();
+    file.read_to_string(&mut content)?;
+    // 没有记录或记录已损坏时视为首次加载
+    let before: BTreeMap<String, Tunnel> = serde_json::from_str(&content).unwrap_or_default();
+
+    let config = Config::load()?;
+    let after: BTreeMap<String, Tunnel> = config.list_tunnels()?
+        .into_iter()
+        .map(|tunnel| (tunnel.alias.clone(), tunnel.clone()))
+        .collect();
+    let outcome = apply(&config, &before, &after);
+    info!(
+        started = ?outcome.started,
+        stopped = ?outcome.stopped,
+        restarted = ?outcome.restarted,
+        pending = ?outcome.pending,
+        failed = outcome.failed.len(),
+        "配置已重新加载"
+    );
+    save(&mut file, &after)?;
+    Ok(outcome)
+}
+
+fn save(file: &mut File, applied: &BTreeMap<String, Tunnel>) -> Result<()> {
+    file.set_len(0)?;
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(serde_json::to_string(applied)?.as_bytes())?;
+    Ok(())
+}
+
+fn apply(config: &Config, before: &BTreeMap<String, Tunnel>, after: &BTreeMap<String, Tunnel>) -> Outcome {
+    let mut outcome = Outcome::default();
+
+    // 先停止删除的连接，空出的端口可能正被新配置使用
+    for (alias, tunnel) in before {
+        if after.contains_key(alias) || !tunnel.is_running() {
+            continue;
+        }
+        let result = tunnel.stop(false);
+        history::record(HistoryAction::Stop, alias, Some("reload".to_string()), &result);
+        match result {
+            Ok(()) => outcome.stopped.push(alias.clone()),
+            Err(e) => outcome.failed.push((alias.clone(), e.to_string())),
+        }
+    }
+
+    for (alias, tunnel) in after {
+        match before.get(alias) {
+            Some(previous) if changed(previous, tunnel) && tunnel.is_running() => match tunnel.on_change {
+                OnChange::Restart => match control::restart(previous, tunnel) {
+                    Ok(()) => outcome.restarted.push(alias.clone()),
+                    Err(e) => outcome.failed.push((alias.clone(), e.to_string())),
+                },
+                OnChange::Manual => outcome.pending.push(alias.clone()),
+            },
+            Some(previous) if previous.autostart || !tunnel.autostart => {}
+            // 新加入的连接、刚开启 autostart 的连接
+            _ => {
+                if !tunnel.autostart || !tunnel.enabled || PauseMark::is_paused(alias) || tunnel.is_running() {
+                    continue;
+                }
+                match control::start(config, alias) {
+                    Ok(()) => outcome.started.push(alias.clone()),
+                    Err(e) => outcome.failed.push((alias.clone(), e.to_string())),
+                }
+            }
+        }
+    }
+    outcome
+}
+
+/// 是否修改了需要重启才能生效的字段
+fn changed(before: &Tunnel, after: &Tunnel) -> bool {
+    let fields = |tunnel: &Tunnel| {
+        let mut value = serde_json::to_value(tunnel).unwrap_or_default();
+        if let Some(map) = value.as_object_mut() {
+            for field in IGNORED_FIELDS {
+                map.remove(*field);
+            }
+        }
+        value
+    };
+    fields(before) != fields(after)
+}
+
+/// 先加载一次，再在后台线程中监视配置文件，文件变化后重新加载
+pub fn watch() -> Result<()> {
+    let path = Config::config_path()?;
+    let dir = path.parent().map(ToOwned::to_owned).unwrap_or_default();
+    let (sender, receiver) = mpsc::channel();
+    // 保存配置时先写临时文件再重命名，监视所在目录才能在替换后继续收到事件
+    let mut watcher = ::notify::recommended_watcher(sender)?;
+    watcher.watch(&dir, RecursiveMode::NonRecursive)?;
+    thread::spawn(move || {
+        let _watcher = watcher;
+        report(reload());
+        while let Ok(event) = receiver.recv() {
+            let event = match event {
+                Ok(event) => event,
+                Err(e) => {
+                    debug!(error = %e, "监视配置文件出错");
+                    continue;
+                }
+            };
+            let touched = matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_))
+                && event.paths.iter().any(|changed| changed.file_name() == path.file_name());
+            if !touched {
+                continue;
+            }
+            thread::sleep(SETTLE);
+            while receiver.try_recv().is_ok() {}
+            debug!(path = %path.display(), "配置文件已修改");
+            report(reload());
+        }
+    });
+    Ok(())
+}
+
+/// 在服务的输出中显示加载结果；配置有误时保持当前状态，等待下一次修改
+fn report(result: Result<Outcome>) {
+    match result {
+        Ok(outcome) if outcome.is_empty() => {}
+        Ok(outcome) => {
+            eprintln!("{}", t!("配置已重新加载"));
+            for line in outcome.lines() {
+                eprintln!("  {}", line);
+            }
+        }
+        Err(e) => warn!("{}", t!("无法加载修改后的配置，连接保持不变: {}", e)),
+    }
+}
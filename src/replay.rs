@@ -0,0 +1,84 @@
+//! 记录交互式 run / stop 中选择的连接，供 `tfa replay` 按原顺序非交互地重新执行。
+//!
+//! 相隔不超过 [`SESSION_GAP`] 秒的选择视为同一次会话，例如每天早上先停掉几个连接再启动另外几个。
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::history::HistoryAction;
+use crate::platform;
+use tracing::debug;
+
+/// 同一会话中相邻两次选择的最大间隔（秒）
+const SESSION_GAP: u64 = 10 * 60;
+
+/// 保留的选择条数，超过时删除最早的记录
+const MAX_STEPS: usize = 200;
+
+/// 一次交互选择：启动或停止所选的连接
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Step {
+    pub time: u64,
+    /// 只会是 start 或 stop
+    pub action: HistoryAction,
+    pub aliases: Vec<String>,
+}
+
+/// 记录一次交互选择。记录只用于重放，写入失败不影响操作本身
+pub fn record(action: HistoryAction, aliases: &[String]) {
+    let step = Step { time: platform::unix_now(), action, aliases: aliases.to_vec() };
+    if let Err(e) = append(&step) {
+        debug!(error = %e, "写入交互选择记录失败");
+    }
+}
+
+fn append(step: &Step) -> Result<()> {
+    let path = path()?;
+    let mut steps = load()?;
+    if steps.len() >= MAX_STEPS {
+        steps.drain(..steps.len() + 1 - MAX_STEPS);
+        let mut content = String::new();
+        for step in &steps {
+            content.push_str(&serde_json::to_string(step)?);
+            content.push('\n');
+        }
+        fs::write(&path, content)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(step)?)?;
+    Ok(())
+}
+
+fn load() -> Result<Vec<Step>> {
+    let path = path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// 记录的会话，最近的在前；每个会话内的选择按时间先后排列
+pub fn sessions() -> Result<Vec<Vec<Step>>> {
+    let mut sessions: Vec<Vec<Step>> = Vec::new();
+    for step in load()? {
+        match sessions.last_mut() {
+            Some(session) if session.last().is_some_and(|last| step.time.saturating_sub(last.time) <= SESSION_GAP) => {
+                session.push(step);
+            }
+            _ => sessions.push(vec![step]),
+        }
+    }
+    sessions.reverse();
+    Ok(sessions)
+}
+
+fn path() -> Result<PathBuf> {
+    Ok(Config::config_dir()?.join("sessions.jsonl"))
+}
@@ -0,0 +1,39 @@
+use crate::tunnel::Tunnel;
+
+/// 模糊匹配的得分：`pattern` 的字符按顺序出现在 `text` 中即为匹配（不区分大小写），
+/// 连续出现、出现在开头或分隔符之后的字符得分更高；不匹配时返回 None
+pub fn score(pattern: &str, text: &str) -> Option<u32> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+    for c in pattern.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let index = position + text[position..].iter().position(|t| *t == c)?;
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == index) {
+            score += 4;
+        }
+        if index == 0 || matches!(text[index - 1], '-' | '_' | '.' | '/' | ' ') {
+            score += 2;
+        }
+        previous = Some(index);
+        position = index + 1;
+    }
+    Some(score)
+}
+
+/// 按别名、源地址与标签模糊匹配连接，按得分从高到低排序，得分相同时按别名排序
+pub fn tunnels<'a>(tunnels: &[&'a Tunnel], pattern: &str) -> Vec<&'a Tunnel> {
+    let mut matches: Vec<(u32, &Tunnel)> = tunnels.iter()
+        .filter_map(|tunnel| {
+            let best = std::iter::once(tunnel.alias.as_str())
+                .chain(std::iter::once(tunnel.source.as_str()))
+                .chain(tunnel.tags.iter().map(String::as_str))
+                .filter_map(|text| score(pattern, text))
+                .max()?;
+            Some((best, *tunnel))
+        })
+        .collect();
+    matches.sort_by(|(a, x), (b, y)| b.cmp(a).then_with(|| x.alias.cmp(&y.alias)));
+    matches.into_iter().map(|(_, tunnel)| tunnel).collect()
+}
@@ -0,0 +1,195 @@
+use anyhow::Result;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::t;
+use crate::profile;
+
+const KEYRING_SERVICE: &str = "cf-manager";
+const CONFIG_KEY_USER: &str = "config-key";
+/// 无法使用系统钥匙串时（如无图形界面的服务器），可通过该环境变量提供 base64 编码的密钥
+pub const CONFIG_KEY_ENV: &str = "CF_MANAGER_KEY";
+const ENCRYPTED_PREFIX: &str = "enc:v1:";
+const NONCE_LEN: usize = 24;
+
+/// 读取系统钥匙串中的条目；不存在时返回 None
+pub fn keyring_get(user: &str) -> Result<Option<String>> {
+    outside_runtime(|| match keyring::Entry::new(KEYRING_SERVICE, user)?.get_password() {
+        Ok(value) => Ok(Some(value)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(anyhow::anyhow!(t!("读取系统钥匙串失败: {}", e))),
+    })
+}
+
+pub fn keyring_set(user: &str, value: &str) -> Result<()> {
+    outside_runtime(|| {
+        keyring::Entry::new(KEYRING_SERVICE, user)?
+            .set_password(value)
+            .map_err(|e| anyhow::anyhow!(t!("写入系统钥匙串失败: {}", e)))
+    })
+}
+
+pub fn keyring_delete(user: &str) -> Result<()> {
+    outside_runtime(|| match keyring::Entry::new(KEYRING_SERVICE, user)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(anyhow::anyhow!(t!("删除系统钥匙串条目失败: {}", e))),
+    })
+}
+
+/// Linux 的 Secret Service 后端内部会阻塞等待自己的运行时，
+/// 不能直接在 tokio 运行时线程上调用，因此放到独立线程执行
+fn outside_runtime<T: Send>(f: impl FnOnce() -> Result<T> + Send) -> Result<T> {
+    std::thread::scope(|scope| {
+        scope.spawn(f)
+            .join()
+            .unwrap_or_else(|_| Err(anyhow::anyhow!(t!("访问系统钥匙串时发生异常"))))
+    })
+}
+
+/// Cloudflare Access 服务令牌（CF-Access-Client-Id / CF-Access-Client-Secret），
+/// 只保存在系统钥匙串中，不写入配置文件
+#[derive(Serialize, Deserialize)]
+pub struct ServiceToken {
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+impl ServiceToken {
+    /// 钥匙串中按档案和别名区分；默认档案保持原有的键名
+    fn keyring_user(alias: &str) -> String {
+        if profile::is_default() {
+            format!("service-token:{}", alias)
+        } else {
+            format!("service-token:{}:{}", profile::current(), alias)
+        }
+    }
+
+    pub fn load(alias: &str) -> Result<Option<Self>> {
+        keyring_get(&Self::keyring_user(alias))?
+            .map(|value| serde_json::from_str(&value).map_err(Into::into))
+            .transpose()
+    }
+
+    pub fn save(&self, alias: &str) -> Result<()> {
+        keyring_set(&Self::keyring_user(alias), &serde_json::to_string(self)?)
+    }
+
+    pub fn delete(alias: &str) -> Result<()> {
+        keyring_delete(&Self::keyring_user(alias))
+    }
+}
+
+/// `tfa connect` 使用的数据库密码，与服务令牌一样只保存在系统钥匙串中
+pub struct ClientPassword;
+
+impl ClientPassword {
+    fn keyring_user(alias: &str) -> String {
+        if profile::is_default() {
+            format!("client-password:{}", alias)
+        } else {
+            format!("client-password:{}:{}", profile::current(), alias)
+        }
+    }
+
+    pub fn load(alias: &str) -> Result<Option<String>> {
+        keyring_get(&Self::keyring_user(alias))
+    }
+
+    pub fn save(alias: &str, password: &str) -> Result<()> {
+        keyring_set(&Self::keyring_user(alias), password)
+    }
+
+    pub fn delete(alias: &str) -> Result<()> {
+        keyring_delete(&Self::keyring_user(alias))
+    }
+}
+
+/// 电子邮件通知渠道的 SMTP 密码，按档案和渠道名称区分
+pub struct SmtpPassword;
+
+impl SmtpPassword {
+    fn keyring_user(channel: &str) -> String {
+        if profile::is_default() {
+            format!("smtp-password:{}", channel)
+        } else {
+            format!("smtp-password:{}:{}", profile::current(), channel)
+        }
+    }
+
+    pub fn load(channel: &str) -> Result<Option<String>> {
+        keyring_get(&Self::keyring_user(channel))
+    }
+
+    pub fn save(channel: &str, password: &str) -> Result<()> {
+        keyring_set(&Self::keyring_user(channel), password)
+    }
+
+    pub fn delete(channel: &str) -> Result<()> {
+        keyring_delete(&Self::keyring_user(channel))
+    }
+}
+
+/// 配置加密密钥：优先使用环境变量，其次是系统钥匙串
+pub fn config_key() -> Result<Option<Key>> {
+    let encoded = match std::env::var(CONFIG_KEY_ENV) {
+        Ok(value) => Some(value),
+        Err(_) => keyring_get(CONFIG_KEY_USER).map_err(with_env_hint)?,
+    };
+    encoded.map(|value| decode_key(&value)).transpose()
+}
+
+/// 获取配置加密密钥，不存在时生成一个并保存到系统钥匙串
+pub fn ensure_config_key() -> Result<Key> {
+    if let Some(key) = config_key()? {
+        return Ok(key);
+    }
+    let key = XChaCha20Poly1305::generate_key(&mut OsRng);
+    keyring_set(CONFIG_KEY_USER, &STANDARD.encode(key)).map_err(with_env_hint)?;
+    Ok(key)
+}
+
+fn with_env_hint(error: anyhow::Error) -> anyhow::Error {
+    anyhow::anyhow!(t!(
+        "{}；也可以通过 {} 环境变量提供 base64 编码的 32 字节密钥（例如 `openssl rand -base64 32` 的输出）",
+        error,
+        CONFIG_KEY_ENV
+    ))
+}
+
+fn decode_key(encoded: &str) -> Result<Key> {
+    let bytes = STANDARD.decode(encoded.trim())
+        .map_err(|_| anyhow::anyhow!(t!("配置密钥不是有效的 base64")))?;
+    if bytes.len() != 32 {
+        return Err(anyhow::anyhow!(t!("配置密钥长度应为 32 字节")));
+    }
+    Ok(*Key::from_slice(&bytes))
+}
+
+pub fn encrypt(key: &Key, plaintext: &str) -> Result<String> {
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = XChaCha20Poly1305::new(key)
+        .encrypt(&nonce, plaintext.as_bytes())
+        .map_err(|_| anyhow::anyhow!(t!("加密失败")))?;
+    let mut payload = nonce.to_vec();
+    payload.extend_from_slice(&ciphertext);
+    Ok(format!("{}{}", ENCRYPTED_PREFIX, STANDARD.encode(payload)))
+}
+
+/// 解密由 [`encrypt`] 生成的值；未加密的值原样返回
+pub fn decrypt(key: &Key, value: &str) -> Result<String> {
+    let Some(encoded) = value.strip_prefix(ENCRYPTED_PREFIX) else {
+        return Ok(value.to_string());
+    };
+    let payload = STANDARD.decode(encoded)
+        .map_err(|_| anyhow::anyhow!(t!("加密字段格式错误")))?;
+    if payload.len() < NONCE_LEN {
+        return Err(anyhow::anyhow!(t!("加密字段格式错误")));
+    }
+    let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+    let plaintext = XChaCha20Poly1305::new(key)
+        .decrypt(XNonce::from_slice(nonce), ciphertext)
+        .map_err(|_| anyhow::anyhow!(t!("解密失败，密钥可能不匹配")))?;
+    Ok(String::from_utf8(plaintext)?)
+}
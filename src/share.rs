@@ -0,0 +1,73 @@
+//! 快速分享：`tfa share` 以 cloudflared 的 quick tunnel 把本机的服务公开到 trycloudflare.com 的临时地址，
+//! 不需要 Cloudflare 账号，也不写入配置。地址出现在 cloudflared 的输出中，取出后同时显示为终端二维码，
+//! 演示时用手机扫码即可打开。
+
+use anyhow::Result;
+use qrcode::QrCode;
+use qrcode::render::unicode::Dense1x2;
+use std::process::{ExitStatus, Stdio};
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+use crate::i18n::t;
+use crate::provider::Provider;
+
+/// quick tunnel 地址的域名
+const QUICK_TUNNEL_DOMAIN: &str = ".trycloudflare.com";
+
+/// 要分享的本机服务：单独的端口视为 http://localhost:端口，其余原样交给 cloudflared
+fn origin(target: &str) -> String {
+    match target.parse::<u16>() {
+        Ok(port) => format!("http://localhost:{}", port),
+        Err(_) => target.to_string(),
+    }
+}
+
+/// 从 cloudflared 的一行输出中取出 quick tunnel 的地址
+fn public_url(line: &str) -> Option<&str> {
+    let start = line.find("https://")?;
+    let url = line[start..].split(|c: char| c.is_whitespace() || c == '|').next()?;
+    url.ends_with(QUICK_TUNNEL_DOMAIN).then_some(url)
+}
+
+/// 把地址渲染为终端中的二维码，两行像素合为一行字符；深色背景的终端上以浅色为码点
+fn qr(url: &str) -> Result<String> {
+    let code = QrCode::new(url)?;
+    Ok(code.render::<Dense1x2>().dark_color(Dense1x2::Light).light_color(Dense1x2::Dark).build())
+}
+
+/// 在前台运行 quick tunnel 并转发 cloudflared 的输出，取得地址后显示地址与二维码；按 Ctrl-C 结束
+pub async fn run(target: &str, show_qr: bool) -> Result<ExitStatus> {
+    let origin = origin(target);
+    let mut command: tokio::process::Command = Provider::share_command(&origin)?.into();
+    let mut child = command
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow::anyhow!(t!("无法执行 {}: {}", "cloudflared", e)))?;
+    let Some(stderr) = child.stderr.take() else {
+        return Ok(child.wait().await?);
+    };
+    let mut lines = BufReader::new(stderr).lines();
+    let mut announced = false;
+    loop {
+        tokio::select! {
+            line = lines.next_line() => match line? {
+                Some(line) => {
+                    eprintln!("{}", line);
+                    if announced {
+                        continue;
+                    }
+                    if let Some(url) = public_url(&line) {
+                        announced = true;
+                        println!("\n{}", t!("{} 已公开到 {}，按 Ctrl-C 结束", origin, url));
+                        if show_qr {
+                            println!("{}", qr(url)?);
+                        }
+                    }
+                }
+                // cloudflared 关闭了输出，等待它退出
+                None => return Ok(child.wait().await?),
+            },
+            _ = tokio::signal::ctrl_c() => {}
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! 为经连接访问的 SSH 主机生成 `~/.ssh/config` 条目。
+//!
+//! 经连接访问的 SSH 服务在本机看来都是 localhost:端口，不同主机的密钥会在 known_hosts 中互相冲突。
+//! 设置了 `ssh_host` 的连接各自生成一个 `Host <别名>` 条目，以 HostKeyAlias 区分主机密钥，并使用
+//! 该连接专用的 known_hosts 文件（`<档案目录>/ssh/<别名>.known_hosts`），之后直接 `ssh <别名>` 即可。
+//!
+//! 条目写在 `# BEGIN tfa:<档案>` 与 `# END tfa:<档案>` 之间，每个档案一个区块，区块之外的内容保持不变。
+//! 新区块放在文件开头：ssh 对同一选项采用最先出现的值，放在用户的 `Host *` 之前才能生效。
+
+use anyhow::Result;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::i18n::t;
+use crate::profile;
+use crate::tunnel::Tunnel;
+use tracing::debug;
+
+fn begin_marker() -> String {
+    format!("# BEGIN tfa:{}", profile::current())
+}
+
+fn end_marker() -> String {
+    format!("# END tfa:{}", profile::current())
+}
+
+fn ssh_config_path() -> Result<PathBuf> {
+    let home = dirs::home_dir().ok_or_else(|| anyhow::anyhow!(t!("无法获取用户主目录")))?;
+    Ok(home.join(".ssh").join("config"))
+}
+
+/// 连接专用的 known_hosts 文件
+pub fn known_hosts_path(alias: &str) -> Result<PathBuf> {
+    Ok(Config::config_dir()?.join("ssh").join(format!("{}.known_hosts", alias)))
+}
+
+/// 连接的 `Host` 条目；主机密钥以远端主机名区分，source 不是主机名（如 kubectl 的 svc/db）时以别名区分
+fn host_block(tunnel: &Tunnel) -> Result<String> {
+    let host = tunnel.source.trim_start_matches("https://").trim_start_matches("http://").trim_end_matches('/');
+    let key_alias = if host.contains('/') { tunnel.alias.as_str() } else { host };
+    let known_hosts = known_hosts_path(&tunnel.alias)?;
+    Ok(format!(
+        "Host {}\n    HostName {}\n    Port {}\n    HostKeyAlias {}\n    UserKnownHostsFile \"{}\"\n",
+        tunnel.alias,
+        tunnel.local_host(),
+        tunnel.port,
+        key_alias,
+        known_hosts.display()
+    ))
+}
+
+/// 当前档案中设置了 ssh_host 的连接（按别名排序）及其条目；项目配置中的连接随目录变化，不生成条目
+pub fn render(config: &Config) -> Result<(Vec<String>, String)> {
+    let mut tunnels: Vec<&Tunnel> = config.list_tunnels()?
+        .into_iter()
+        .filter(|t| t.ssh_host && t.project.is_none())
+        .collect();
+    tunnels.sort_by(|a, b| a.alias.cmp(&b.alias));
+    let mut block = String::new();
+    for tunnel in &tunnels {
+        block.push_str(&host_block(tunnel)?);
+    }
+    Ok((tunnels.iter().map(|t| t.alias.clone()).collect(), block))
+}
+
+/// 按配置重写当前档案的区块，返回写入的主机别名；内容没有变化时不改动文件
+pub fn sync(config: &Config) -> Result<Vec<String>> {
+    let (hosts, block) = render(config)?;
+    let path = ssh_config_path()?;
+    let content = fs::read_to_string(&path).unwrap_or_default();
+    let updated = replace_block(&content, &block);
+    if updated != content {
+        debug!(path = %path.display(), hosts = hosts.len(), "更新 ssh 配置");
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, updated)?;
+    }
+    Ok(hosts)
+}
+
+/// 删除连接专用的 known_hosts 文件，远端主机更换密钥后需要重新确认；文件不存在时返回 false
+pub fn forget_key(alias: &str) -> Result<bool> {
+    let path = known_hosts_path(alias)?;
+    if !path.exists() {
+        return Ok(false);
+    }
+    fs::remove_file(path)?;
+    Ok(true)
+}
+
+/// 替换当前档案的区块；`block` 为空时删除区块。原来没有区块时把新区块放在文件开头
+fn replace_block(content: &str, block: &str) -> String {
+    let (begin, end) = (begin_marker(), end_marker());
+    let mut before = String::new();
+    let mut after = String::new();
+    // 0：区块之前，1：区块之中，2：区块之后
+    let mut section = 0;
+    for line in content.lines() {
+        match section {
+            0 if line.trim() == begin => section = 1,
+            1 if line.trim() == end => section = 2,
+            1 => {}
+            0 => {
+                before.push_str(line);
+                before.push('\n');
+            }
+            _ => {
+                after.push_str(line);
+                after.push('\n');
+            }
+        }
+    }
+    if section == 0 && block.is_empty() {
+        return content.to_string();
+    }
+    if section == 0 {
+        // 没有区块：新区块放在开头
+        (before, after) = (String::new(), before);
+    }
+    if block.is_empty() && before.is_empty() {
+        // 删除开头的区块时一并去掉它与原有内容之间的空行
+        after = after.strip_prefix('\n').map(str::to_string).unwrap_or(after);
+    }
+    let mut updated = before;
+    if !block.is_empty() {
+        updated.push_str(&begin);
+        updated.push('\n');
+        updated.push_str(block);
+        updated.push_str(&end);
+        updated.push('\n');
+        if section == 0 && !after.is_empty() {
+            updated.push('\n');
+        }
+    }
+    updated.push_str(&after);
+    updated
+}
@@ -0,0 +1,391 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+use std::sync::Once;
+
+use crate::config::Config;
+use crate::container::Container;
+use crate::platform;
+use crate::profile;
+use tracing::{debug, trace};
+
+/// 允许记录的启动时间与系统报告的进程启动时间之间的误差（秒）
+const START_TIME_TOLERANCE: u64 = 10;
+
+/// 原子写入时临时文件名中的标记，残留的临时文件在打开状态目录时清理
+const TEMP_MARKER: &str = ".tmp-";
+
+/// 进程状态目录：保存后端进程的 PID、启动时间与内部端口（`<alias>.state.json`，写入即表示已就绪）
+/// 以及启动锁（`<alias>.lock`）。设置了 `XDG_RUNTIME_DIR` 时位于 `$XDG_RUNTIME_DIR/cf-manager`，
+/// 该目录只属于当前用户且在注销或重启后清空，不会留下指向已不存在进程的记录；
+/// 其他平台沿用配置目录下的 `run/`。日志、统计等需要跨重启保留的文件仍在 `run/` 中
+pub struct StateStore;
+
+impl StateStore {
+    /// 当前档案的进程状态目录，不存在时以仅当前用户可访问的权限创建
+    pub fn dir() -> Result<PathBuf> {
+        let path = match Self::volatile_root() {
+            Some(root) => {
+                let path = if profile::is_default() { root } else { root.join("profiles").join(profile::current()) };
+                if !path.is_dir() {
+                    let mut builder = fs::DirBuilder::new();
+                    builder.recursive(true);
+                    #[cfg(unix)]
+                    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+                    builder.create(&path)?;
+                }
+                path
+            }
+            None => Config::runtime_dir()?,
+        };
+        static SWEEP: Once = Once::new();
+        SWEEP.call_once(|| {
+            Self::sweep(&path);
+            if let Ok(legacy) = Config::runtime_dir()
+                && legacy != path
+            {
+                Self::sweep(&legacy);
+            }
+        });
+        Ok(path)
+    }
+
+    /// `$XDG_RUNTIME_DIR/cf-manager`；未设置或目录不存在时为 None。
+    /// 指定了配置目录时状态也随之隔离，同样返回 None
+    fn volatile_root() -> Option<PathBuf> {
+        if profile::custom_root().is_some() {
+            return None;
+        }
+        let dir = PathBuf::from(std::env::var_os("XDG_RUNTIME_DIR").filter(|value| !value.is_empty())?);
+        dir.is_dir().then(|| dir.join("cf-manager"))
+    }
+
+    /// 所有可能存放进程状态的目录：状态目录在前，其后是旧版本使用的 `run/`（两者相同时只有一个）
+    pub fn dirs() -> Result<Vec<PathBuf>> {
+        let dir = Self::dir()?;
+        let legacy = Config::runtime_dir()?;
+        Ok(if dir == legacy { vec![dir] } else { vec![dir, legacy] })
+    }
+
+    pub fn path(alias: &str, suffix: &str) -> Result<PathBuf> {
+        Ok(Self::dir()?.join(format!("{}{}", alias, suffix)))
+    }
+
+    /// 先写入同目录下的临时文件再重命名，读取方不会读到写了一半的文件，
+    /// 写入过程中进程崩溃也只会留下临时文件
+    pub fn write(path: &Path, content: &[u8]) -> Result<()> {
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(format!("{}{}", TEMP_MARKER, std::process::id()));
+        let temp = PathBuf::from(temp);
+        fs::write(&temp, content)?;
+        if let Err(e) = fs::rename(&temp, path) {
+            let _ = fs::remove_file(&temp);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    /// 清理崩溃留下的临时文件；写入它的进程仍在运行时保留
+    fn sweep(dir: &Path) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            let Some(pid) = path.file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.rsplit_once(TEMP_MARKER))
+                .and_then(|(_, pid)| pid.parse::<u32>().ok())
+            else {
+                continue;
+            };
+            if pid != std::process::id() && platform::process_start_time(pid).is_none() {
+                debug!(path = %path.display(), "删除残留的临时文件");
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+}
+
+/// 由本工具启动的后端进程信息，用于判断进程归属
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuntimeState {
+    pub pid: u32,
+    pub started_at: u64,
+    pub port: u16,
+    /// 启动时连接所属的项目配置文件，用于在项目目录之外识别该连接
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<PathBuf>,
+    /// 设置了有效期时自动停止的时间（Unix 秒）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+    /// 是否经 sudo 启动，此时 PID 为 sudo 进程，结束它同样需要 sudo
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub elevated: bool,
+    /// 在容器中运行时的容器，此时 PID 为 docker / podman 进程
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<Container>,
+}
+
+impl RuntimeState {
+    /// 状态文件所在位置；旧版本写在 `run/` 中的记录仍然有效，找不到时返回状态目录中的位置
+    pub fn path(alias: &str) -> Result<PathBuf> {
+        let paths = Self::paths(alias)?;
+        Ok(paths.iter().find(|path| path.exists()).unwrap_or(&paths[0]).clone())
+    }
+
+    fn paths(alias: &str) -> Result<Vec<PathBuf>> {
+        Ok(StateStore::dirs()?.into_iter().map(|dir| dir.join(format!("{}.state.json", alias))).collect())
+    }
+
+    pub fn load(alias: &str) -> Option<Self> {
+        let content = fs::read_to_string(Self::path(alias).ok()?).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    pub fn save(&self, alias: &str) -> Result<()> {
+        StateStore::write(&StateStore::path(alias, ".state.json")?, serde_json::to_string_pretty(self)?.as_bytes())?;
+        // 旧版本留下的记录已被取代
+        for path in Self::paths(alias)?.iter().skip(1) {
+            let _ = fs::remove_file(path);
+        }
+        Ok(())
+    }
+
+    pub fn remove(alias: &str) {
+        for path in Self::paths(alias).unwrap_or_default() {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    /// 有状态记录、别名以 `prefix` 开头的连接
+    pub fn aliases_with_prefix(prefix: &str) -> Vec<String> {
+        let mut aliases: Vec<String> = StateStore::dirs()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|dir| fs::read_dir(dir).ok())
+            .flat_map(|entries| entries.flatten())
+            .filter_map(|entry| entry.file_name().to_str()?.strip_suffix(".state.json").map(str::to_string))
+            .filter(|alias| alias.starts_with(prefix))
+            .collect();
+        aliases.sort();
+        aliases.dedup();
+        aliases
+    }
+
+    /// 记录的进程是否仍然存在，且启动时间一致（排除 PID 被复用的情况）
+    pub fn is_alive(&self) -> bool {
+        process_matches(self.pid, self.started_at)
+    }
+}
+
+/// 进程是否仍然存在，且启动时间与记录一致（排除 PID 被复用的情况）
+pub fn process_matches(pid: u32, started_at: u64) -> bool {
+    let started = platform::process_start_time(pid);
+    let alive = started.is_some_and(|started| started.abs_diff(started_at) <= START_TIME_TOLERANCE);
+    trace!(pid, recorded = started_at, actual = ?started, alive, "核对进程启动时间");
+    alive
+}
+
+/// 为连接工作的后台进程
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Watcher {
+    /// 有效期监视 `ttl-watch`
+    Ttl,
+    /// 延迟记录 `latency-watch`
+    Latency,
+    /// 日志过滤 `log-filter`
+    LogFilter,
+}
+
+impl Watcher {
+    pub const ALL: [Watcher; 3] = [Watcher::Ttl, Watcher::Latency, Watcher::LogFilter];
+
+    /// 即后台进程的子命令名
+    pub fn name(self) -> &'static str {
+        match self {
+            Watcher::Ttl => "ttl-watch",
+            Watcher::Latency => "latency-watch",
+            Watcher::LogFilter => "log-filter",
+        }
+    }
+}
+
+/// 后台进程的登记：状态目录中的 `<alias>.<子命令>.json`，记录当前为该连接工作的进程。
+/// 新进程启动时取代旧的登记，旧进程发现登记已不是自己时退出，同一连接同时只有一个在工作；
+/// `tfa recover` 据此判断后台进程是否仍在运行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatcherMark {
+    pub pid: u32,
+    pub started_at: u64,
+}
+
+impl WatcherMark {
+    fn path(alias: &str, watcher: Watcher) -> Result<PathBuf> {
+        StateStore::path(alias, &format!(".{}.json", watcher.name()))
+    }
+
+    pub fn load(alias: &str, watcher: Watcher) -> Option<Self> {
+        let content = fs::read_to_string(Self::path(alias, watcher).ok()?).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// 把本进程登记为该连接的后台进程，返回的对象释放时删除登记
+    pub fn claim(alias: &str, watcher: Watcher) -> Result<WatcherClaim> {
+        let pid = std::process::id();
+        let mark = Self { pid, started_at: platform::process_start_time(pid).unwrap_or_else(platform::unix_now) };
+        StateStore::write(&Self::path(alias, watcher)?, serde_json::to_string(&mark)?.as_bytes())?;
+        Ok(WatcherClaim { alias: alias.to_string(), watcher })
+    }
+
+    /// 登记的仍是本进程；登记被删除时同样返回 false
+    pub fn is_mine(alias: &str, watcher: Watcher) -> bool {
+        Self::load(alias, watcher).is_some_and(|mark| mark.pid == std::process::id())
+    }
+
+    /// 登记的进程仍在运行
+    pub fn is_alive(&self) -> bool {
+        process_matches(self.pid, self.started_at)
+    }
+
+    /// 删除连接的全部登记，正在运行的后台进程随后退出
+    pub fn remove_all(alias: &str) {
+        for watcher in Watcher::ALL {
+            if let Ok(path) = Self::path(alias, watcher) {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+
+}
+
+/// 本进程的登记，释放时删除；登记已被新进程取代时保留新进程的登记
+pub struct WatcherClaim {
+    alias: String,
+    watcher: Watcher,
+}
+
+impl Drop for WatcherClaim {
+    fn drop(&mut self) {
+        if WatcherMark::is_mine(&self.alias, self.watcher)
+            && let Ok(path) = WatcherMark::path(&self.alias, self.watcher)
+        {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// 暂停标记：运行目录中的 `<alias>.paused.json`。暂停的连接不随 `run --all` 等批量操作启动，
+/// 直到 `tfa resume` 或按别名显式启动
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PauseMark {
+    pub paused_at: u64,
+}
+
+impl PauseMark {
+    fn path(alias: &str) -> Result<PathBuf> {
+        let mut path = Config::runtime_dir()?;
+        path.push(format!("{}.paused.json", alias));
+        Ok(path)
+    }
+
+    pub fn load(alias: &str) -> Option<Self> {
+        let content = fs::read_to_string(Self::path(alias).ok()?).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    pub fn is_paused(alias: &str) -> bool {
+        Self::path(alias).is_ok_and(|path| path.exists())
+    }
+
+    pub fn set(alias: &str) -> Result<()> {
+        let mark = Self { paused_at: platform::unix_now() };
+        fs::write(Self::path(alias)?, serde_json::to_string_pretty(&mark)?)?;
+        Ok(())
+    }
+
+    /// 取消暂停；原本没有暂停时返回 false
+    pub fn clear(alias: &str) -> Result<bool> {
+        let path = Self::path(alias)?;
+        if !path.exists() {
+            return Ok(false);
+        }
+        fs::remove_file(path)?;
+        Ok(true)
+    }
+}
+
+/// 同一连接的启动互斥锁，持有状态目录中 `<alias>.lock` 的文件锁。
+/// 避免多个终端同时启动同一连接时都通过端口检查；锁随对象释放，进程意外退出时由系统释放
+pub struct StartLock {
+    _file: File,
+}
+
+impl StartLock {
+    fn open(alias: &str) -> Result<File> {
+        Ok(OpenOptions::new().create(true).truncate(false).write(true).open(StateStore::path(alias, ".lock")?)?)
+    }
+
+    /// 立即获取锁；其他进程正在启动该连接时返回 None
+    pub fn try_acquire(alias: &str) -> Result<Option<Self>> {
+        let file = Self::open(alias)?;
+        match file.try_lock() {
+            Ok(()) => Ok(Some(Self { _file: file })),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(e)) => Err(e.into()),
+        }
+    }
+
+    /// 等待其他进程完成启动后获取锁
+    pub fn acquire(alias: &str) -> Result<Self> {
+        let file = Self::open(alias)?;
+        file.lock()?;
+        Ok(Self { _file: file })
+    }
+}
+
+/// 探测延迟的历史记录：运行目录中的 `<alias>.latency.json`，只保留最近 `LATENCY_SAMPLES` 次
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LatencyHistory {
+    pub samples: Vec<LatencySample>,
+}
+
+/// 一次探测的结果，失败时 `ms` 为 None
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatencySample {
+    pub at: u64,
+    pub ms: Option<f64>,
+}
+
+/// 历史记录保留的探测次数
+const LATENCY_SAMPLES: usize = 60;
+
+impl LatencyHistory {
+    fn path(alias: &str) -> Result<PathBuf> {
+        let mut path = Config::runtime_dir()?;
+        path.push(format!("{}.latency.json", alias));
+        Ok(path)
+    }
+
+    pub fn load(alias: &str) -> Self {
+        Self::path(alias)
+            .ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// 追加一次探测结果，超出保留次数时丢弃最早的记录
+    pub fn record(alias: &str, ms: Option<f64>) -> Result<()> {
+        let mut history = Self::load(alias);
+        history.samples.push(LatencySample { at: platform::unix_now(), ms });
+        let excess = history.samples.len().saturating_sub(LATENCY_SAMPLES);
+        history.samples.drain(..excess);
+        StateStore::write(&Self::path(alias)?, serde_json::to_string(&history)?.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn last(&self) -> Option<&LatencySample> {
+        self.samples.last()
+    }
+}
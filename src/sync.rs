@@ -0,0 +1,283 @@
+//! 通过 git 仓库在多台机器之间同步连接目录。
+//!
+//! 仓库中保存去除了本机专属字段（钩子脚本、服务令牌标记、出站代理、日志转发）的明文连接目录，
+//! 与全局配置文件的格式相同。同步时以上次同步的目录为基准按连接做三方合并：
+//! 只有一方修改的连接采用修改方的版本，双方都修改且结果不同的连接视为冲突。
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::config::Config;
+use crate::error::TunnelError;
+use crate::history;
+use crate::i18n::t;
+use crate::reload::OnChange;
+use crate::tunnel::Tunnel;
+use tracing::{debug, warn};
+
+/// 同步设置，保存在配置文件中
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncSettings {
+    pub remote: String,
+    #[serde(default = "default_branch")]
+    pub branch: String,
+    /// 连接目录在仓库中的路径
+    #[serde(default = "default_file")]
+    pub file: String,
+}
+
+fn default_branch() -> String {
+    "main".to_string()
+}
+
+fn default_file() -> String {
+    "tunnels.json".to_string()
+}
+
+impl SyncSettings {
+    pub fn new(remote: &str, branch: Option<&str>, file: Option<&str>) -> Self {
+        Self {
+            remote: remote.to_string(),
+            branch: branch.map_or_else(default_branch, str::to_string),
+            file: file.map_or_else(default_file, str::to_string),
+        }
+    }
+}
+
+/// 仓库中的连接目录
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Catalogue {
+    tunnels: BTreeMap<String, serde_json::Value>,
+}
+
+/// 双方都修改了同一连接时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolve {
+    /// 报告冲突，不做任何修改
+    Abort,
+    /// 采用本地版本
+    Ours,
+    /// 采用远端版本
+    Theirs,
+}
+
+/// 单个连接的同步结果
+#[derive(Debug, PartialEq, Eq)]
+pub enum Change {
+    /// 远端的新增、修改或删除应用到本地
+    Pulled,
+    /// 本地的新增、修改或删除推送到远端
+    Pushed,
+    /// 双方修改不同
+    Conflict,
+}
+
+/// 同步计划：每个有变化的连接及其结果，以及合并后的目录
+pub struct Plan {
+    pub changes: Vec<(String, Change)>,
+    merged: BTreeMap<String, serde_json::Value>,
+}
+
+impl Plan {
+    pub fn conflicts(&self) -> Vec<&str> {
+        self.changes.iter()
+            .filter(|(_, change)| *change == Change::Conflict)
+            .map(|(alias, _)| alias.as_str())
+            .collect()
+    }
+
+    fn has(&self, wanted: Change) -> bool {
+        self.changes.iter().any(|(_, change)| *change == wanted)
+    }
+}
+
+/// 拉取远端目录并与本地合并；`dry_run` 时只返回计划，不修改配置也不推送
+pub fn sync(config: &mut Config, resolve: Resolve, dry_run: bool) -> Result<Plan> {
+    let settings = config.sync_settings()
+        .cloned()
+        .ok_or_else(|| TunnelError::config(t!("尚未设置同步仓库，请先运行 'tfa config sync <仓库地址>'")))?;
+    let repo = Config::runtime_dir()?.join("sync-repo");
+    let result = sync_with(config, &settings, &repo, resolve, dry_run);
+    let _ = fs::remove_dir_all(&repo);
+    result
+}
+
+fn sync_with(config: &mut Config, settings: &SyncSettings, repo: &Path, resolve: Resolve, dry_run: bool) -> Result<Plan> {
+    checkout(settings, repo)?;
+    let file = repo.join(&settings.file);
+    let remote = match fs::read_to_string(&file) {
+        Ok(content) => serde_json::from_str::<Catalogue>(&content)
+            .map_err(|e| TunnelError::config(t!("解析远端 {} 失败: {}", settings.file, e)))?
+            .tunnels,
+        Err(_) => BTreeMap::new(),
+    };
+    let local = catalogue(config.global_tunnels().values())?;
+    let base = load_base()?;
+    let plan = merge(&base, &local, &remote, resolve);
+    if dry_run || !plan.conflicts().is_empty() {
+        return Ok(plan);
+    }
+
+    if plan.has(Change::Pulled) {
+        apply(config, &plan.merged)?;
+    }
+    if plan.has(Change::Pushed) {
+        let content = serde_json::to_string_pretty(&Catalogue { tunnels: plan.merged.clone() })?;
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&file, content + "\n")?;
+        git(repo, &["add", "--", &settings.file])?;
+        git(repo, &["commit", "-q", "-m", &format!("tfa sync by {}", history::current_user())])?;
+        git(repo, &["push", "-q", "origin", &format!("HEAD:refs/heads/{}", settings.branch)])
+            .map_err(|e| anyhow::anyhow!(t!("推送失败，远端可能刚被其他人更新，请重新运行 sync: {}", e)))?;
+    }
+    save_base(&plan.merged)?;
+    Ok(plan)
+}
+
+/// 克隆远端仓库并检出同步分支；分支还不存在时从空目录开始
+fn checkout(settings: &SyncSettings, repo: &Path) -> Result<()> {
+    if repo.exists() {
+        fs::remove_dir_all(repo)?;
+    }
+    debug!(remote = %settings.remote, branch = %settings.branch, "克隆同步仓库");
+    let output = Command::new("git")
+        .args(["clone", "-q", "--no-checkout", &settings.remote])
+        .arg(repo)
+        .output()
+        .map_err(|e| anyhow::anyhow!(t!("无法执行 git: {}", e)))?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(t!("克隆 {} 失败: {}", settings.remote, String::from_utf8_lossy(&output.stderr).trim())));
+    }
+    let remote_branch = format!("refs/remotes/origin/{}", settings.branch);
+    if git(repo, &["rev-parse", "-q", "--verify", &remote_branch]).is_ok() {
+        git(repo, &["checkout", "-q", "-B", &settings.branch, &remote_branch])?;
+    } else {
+        git(repo, &["checkout", "-q", "--orphan", &settings.branch])?;
+    }
+    Ok(())
+}
+
+fn git(repo: &Path, args: &[&str]) -> Result<String> {
+    debug!(?args, "执行 git");
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .output()
+        .map_err(|e| anyhow::anyhow!(t!("无法执行 git: {}", e)))?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(t!("git {} 失败: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim())));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// 去除本机专属字段后的连接目录；从团队目录导入的受管连接不参与同步
+fn catalogue<'a>(tunnels: impl Iterator<Item = &'a Tunnel>) -> Result<BTreeMap<String, serde_json::Value>> {
+    tunnels
+        .filter(|tunnel| tunnel.managed_by.is_none())
+        .map(|tunnel| {
+            let mut shared = tunnel.clone();
+            shared.hooks = Default::default();
+            shared.service_token = false;
+            shared.client_password = false;
+            shared.privileged = None;
+            shared.outbound_proxy = None;
+            shared.log_sinks = Vec::new();
+            shared.ping_url = None;
+            shared.enabled = true;
+            shared.autostart = false;
+            shared.on_change = OnChange::default();
+            Ok((tunnel.alias.clone(), serde_json::to_value(shared)?))
+        })
+        .collect()
+}
+
+/// 按连接三方合并
+fn merge(
+    base: &BTreeMap<String, serde_json::Value>,
+    local: &BTreeMap<String, serde_json::Value>,
+    remote: &BTreeMap<String, serde_json::Value>,
+    resolve: Resolve,
+) -> Plan {
+    let aliases: BTreeSet<&String> = base.keys().chain(local.keys()).chain(remote.keys()).collect();
+    let mut changes = Vec::new();
+    let mut merged = BTreeMap::new();
+    for alias in aliases {
+        let (b, l, r) = (base.get(alias), local.get(alias), remote.get(alias));
+        let (value, change) = if l == r {
+            (l, None)
+        } else if l == b {
+            (r, Some(Change::Pulled))
+        } else if r == b {
+            (l, Some(Change::Pushed))
+        } else {
+            match resolve {
+                Resolve::Ours => (l, Some(Change::Pushed)),
+                Resolve::Theirs => (r, Some(Change::Pulled)),
+                Resolve::Abort => (l, Some(Change::Conflict)),
+            }
+        };
+        if let Some(value) = value {
+            merged.insert(alias.clone(), value.clone());
+        }
+        if let Some(change) = change {
+            changes.push((alias.clone(), change));
+        }
+    }
+    Plan { changes, merged }
+}
+
+/// 用合并后的目录替换本地全局连接，保留本机的钩子、服务令牌标记、出站代理、日志转发以及受管连接
+fn apply(config: &mut Config, merged: &BTreeMap<String, serde_json::Value>) -> Result<()> {
+    let current = config.global_tunnels();
+    let mut tunnels: Vec<Tunnel> = current.values().filter(|t| t.managed_by.is_some()).cloned().collect();
+    for (alias, value) in merged {
+        if current.get(alias).is_some_and(|t| t.managed_by.is_some()) {
+            warn!("{}", t!("同步目录中的 {} 与受管连接同名，已跳过", alias));
+            continue;
+        }
+        let mut tunnel: Tunnel = serde_json::from_value(value.clone())
+            .map_err(|e| TunnelError::config(t!("远端的连接 {} 无效: {}", alias, e)))?;
+        tunnel.alias = alias.clone();
+        if let Some(existing) = current.get(alias) {
+            tunnel.hooks = existing.hooks.clone();
+            tunnel.service_token = existing.service_token;
+            tunnel.client_password = existing.client_password;
+            tunnel.privileged = existing.privileged;
+            tunnel.outbound_proxy = existing.outbound_proxy.clone();
+            tunnel.log_sinks = existing.log_sinks.clone();
+            tunnel.ping_url = existing.ping_url.clone();
+            tunnel.enabled = existing.enabled;
+            tunnel.autostart = existing.autostart;
+            tunnel.on_change = existing.on_change;
+        }
+        tunnels.push(tunnel);
+    }
+    config.set_global_tunnels(tunnels)
+}
+
+fn base_path() -> Result<PathBuf> {
+    Ok(Config::config_dir()?.join("sync-base.json"))
+}
+
+/// 上次同步完成时的目录，作为合并基准
+fn load_base() -> Result<BTreeMap<String, serde_json::Value>> {
+    let path = base_path()?;
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let catalogue: Catalogue = serde_json::from_str(&fs::read_to_string(path)?)?;
+    Ok(catalogue.tunnels)
+}
+
+fn save_base(tunnels: &BTreeMap<String, serde_json::Value>) -> Result<()> {
+    let content = serde_json::to_string_pretty(&Catalogue { tunnels: tunnels.clone() })?;
+    fs::write(base_path()?, content)?;
+    Ok(())
+}
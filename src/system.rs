@@ -0,0 +1,70 @@
+//! 系统配置：管理员在 /etc/cf-manager/config.json（Windows 上为 %ProgramData%\cf-manager\config.json）
+//! 中定义、由系统服务运行的共享连接。
+//!
+//! 系统连接合并在各用户的配置之下：用户配置中的同名连接覆盖它，用 `tfa set` 修改系统连接时
+//! 也会在用户配置中保存一份覆盖它的副本。系统连接本身不写入用户配置，只有管理员可以启动或停止。
+
+use anyhow::Result;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use crate::error::TunnelError;
+use crate::i18n::t;
+use crate::tunnel::Tunnel;
+use tracing::{debug, warn};
+
+/// 指定系统配置文件的环境变量
+pub const SYSTEM_CONFIG_ENV: &str = "CF_MANAGER_SYSTEM_CONFIG";
+
+/// 系统配置只使用其中的连接，格式与用户配置相同
+#[derive(Deserialize)]
+struct SystemFile {
+    #[serde(default)]
+    tunnels: BTreeMap<String, serde_json::Value>,
+}
+
+/// 系统配置文件的位置：CF_MANAGER_SYSTEM_CONFIG，否则为系统范围的配置目录下的 config.json
+pub fn config_path() -> PathBuf {
+    if let Some(path) = std::env::var_os(SYSTEM_CONFIG_ENV).filter(|path| !path.is_empty()) {
+        return PathBuf::from(path);
+    }
+    if cfg!(windows) {
+        std::env::var_os("ProgramData")
+            .map_or_else(|| PathBuf::from(r"C:\ProgramData"), PathBuf::from)
+            .join("cf-manager")
+            .join("config.json")
+    } else {
+        PathBuf::from("/etc/cf-manager/config.json")
+    }
+}
+
+/// 读取系统配置中的连接；文件不存在时为空，无权读取时给出警告后忽略
+pub fn load() -> Result<Vec<Tunnel>> {
+    let path = config_path();
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            warn!("{}", t!("无法读取系统配置 {}: {}", path.display(), e));
+            return Ok(Vec::new());
+        }
+    };
+    debug!(path = %path.display(), "加载系统配置");
+    let file: SystemFile = serde_json::from_str(&content)
+        .map_err(|e| TunnelError::config(t!("解析 {} 失败: {}", path.display(), e)))?;
+    file.tunnels.into_iter()
+        .map(|(alias, mut value)| {
+            // 与用户配置不同，系统配置中的连接可以省略与键相同的 alias 字段
+            if let Some(fields) = value.as_object_mut() {
+                fields.insert("alias".to_string(), alias.clone().into());
+            }
+            let mut tunnel: Tunnel = serde_json::from_value(value)
+                .map_err(|e| TunnelError::config(t!("{} 中的连接 {} 无效: {}", path.display(), alias, e)))?;
+            tunnel.system = true;
+            Ok(tunnel)
+        })
+        .collect()
+}
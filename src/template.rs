@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::probe::Probe;
+
+/// 添加连接时预设的本地端口、探测方式与客户端命令，命令行中给出的值优先
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Template {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub probe: Option<Probe>,
+    /// `tfa exec <alias>` 不带命令时执行的客户端命令，可使用 TUNNEL_HOST、TUNNEL_PORT
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client: Option<String>,
+}
+
+/// 内置模板；配置中同名的模板优先
+pub fn builtin() -> BTreeMap<String, Template> {
+    let template = |port: u16, probe: Probe, client: Option<&str>| Template {
+        port: Some(port),
+        probe: Some(probe),
+        client: client.map(str::to_string),
+    };
+    BTreeMap::from([
+        ("ssh".to_string(), template(2222, Probe::Ssh, Some("ssh -p \"$TUNNEL_PORT\" \"$TUNNEL_HOST\""))),
+        ("rdp".to_string(), template(13389, Probe::Tcp, None)),
+        ("postgres".to_string(), template(15432, Probe::Postgres, Some("psql -h \"$TUNNEL_HOST\" -p \"$TUNNEL_PORT\""))),
+        // mysql 客户端把 localhost 当作本地套接字，需要显式使用 TCP
+        ("mysql".to_string(), template(13306, Probe::Tcp, Some("mysql --protocol=TCP -h \"$TUNNEL_HOST\" -P \"$TUNNEL_PORT\""))),
+        ("redis".to_string(), template(16379, Probe::Tcp, Some("redis-cli -h \"$TUNNEL_HOST\" -p \"$TUNNEL_PORT\""))),
+        ("mongo".to_string(), template(27018, Probe::Tcp, Some("mongosh --host \"$TUNNEL_HOST\" --port \"$TUNNEL_PORT\""))),
+        ("k8s-api".to_string(), template(16443, Probe::Tcp, Some("kubectl --server \"https://$TUNNEL_HOST:$TUNNEL_PORT\" cluster-info"))),
+    ])
+}
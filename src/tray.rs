@@ -0,0 +1,246 @@
+//! `tfa tray`：在系统托盘显示连接菜单，不使用终端的用户也能查看状态、启动和停止连接。
+//!
+//! 托盘本身不管理连接，而是经[本机控制协议](crate::ipc)交给正在运行的 `tfa grpc` 或 `tfa web`，
+//! 每隔几秒重新读取一次连接列表；服务重启后自动重新连接。目前只支持以 D-Bus StatusNotifierItem
+//! 显示托盘图标的桌面（KDE、Xfce、安装了 AppIndicator 扩展的 GNOME 等）。
+
+use anyhow::Result;
+
+#[cfg(all(unix, not(target_os = "macos")))]
+pub use linux::run;
+
+/// 其他平台尚不支持托盘图标
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+pub async fn run() -> Result<()> {
+    Err(anyhow::anyhow!(crate::i18n::t!("当前平台不支持托盘图标，请使用 tfa web 的网页控制台")))
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod linux {
+    use super::Result;
+    use ksni::menu::{CheckmarkItem, StandardItem};
+    use ksni::{MenuItem, ToolTip, TrayMethods};
+    use std::collections::HashSet;
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+
+    use crate::i18n::t;
+    use crate::ipc::{self, TunnelInfo};
+    use tracing::warn;
+
+    /// 重新读取连接列表的间隔
+    const REFRESH_INTERVAL: Duration = Duration::from_secs(3);
+
+    /// 菜单中选择的操作，交给主循环经控制协议执行，菜单回调中不能阻塞
+    enum Action {
+        Start(String),
+        Stop(String),
+        Quit,
+    }
+
+    struct Tray {
+        /// None 表示没有正在运行的服务
+        tunnels: Option<Vec<TunnelInfo>>,
+        /// 上一次操作或连接服务失败的原因
+        error: Option<String>,
+        /// 正在启动或停止的连接，完成前不能再次选择
+        pending: HashSet<String>,
+        actions: mpsc::UnboundedSender<Action>,
+    }
+
+    /// 一轮刷新的结果
+    struct Refresh {
+        tunnels: Option<Vec<TunnelInfo>>,
+        error: Option<String>,
+    }
+
+    /// 显示托盘图标，直到选择“退出”或按下 Ctrl-C
+    pub async fn run() -> Result<()> {
+        let (actions, mut requested) = mpsc::unbounded_channel();
+        let (done, mut finished) = mpsc::unbounded_channel();
+        let tray = Tray { tunnels: None, error: None, pending: HashSet::new(), actions };
+        let handle = tray.spawn().await.map_err(|e| anyhow::anyhow!(t!("无法显示托盘图标: {}", e)))?;
+        eprintln!("{}", t!("托盘图标已显示，按 Ctrl-C 退出"));
+        let mut client = None;
+        let mut outcome: Option<(String, Option<String>)> = None;
+        loop {
+            let refresh = refresh(&mut client).await;
+            if let Some(error) = &refresh.error {
+                warn!("{}", error);
+            }
+            let updated = handle.update(move |tray| {
+                tray.tunnels = refresh.tunnels;
+                // 操作失败的提示保留到下一次操作完成
+                if let Some((alias, error)) = outcome {
+                    tray.pending.remove(&alias);
+                    tray.error = error;
+                }
+                if refresh.error.is_some() {
+                    tray.error = refresh.error;
+                }
+            });
+            // 托盘服务已退出，例如 D-Bus 连接断开
+            if updated.await.is_none() {
+                return Ok(());
+            }
+            outcome = tokio::select! {
+                action = requested.recv() => match action {
+                    Some(Action::Quit) | None => break,
+                    // 启动可能要等待较久，在单独的任务中执行，不影响刷新和退出
+                    Some(action) => {
+                        tokio::spawn(perform(action, done.clone()));
+                        None
+                    }
+                },
+                outcome = finished.recv() => outcome,
+                _ = tokio::time::sleep(REFRESH_INTERVAL) => None,
+                _ = tokio::signal::ctrl_c() => break,
+            };
+        }
+        handle.shutdown().await;
+        Ok(())
+    }
+
+    /// 重新读取连接列表；连接中断时在下一轮重新连接
+    async fn refresh(client: &mut Option<ipc::Client>) -> Refresh {
+        if client.is_none() {
+            match ipc::Client::connect().await {
+                Ok(connected) => *client = connected,
+                Err(e) => return Refresh { tunnels: None, error: Some(e.to_string()) },
+            }
+        }
+        let Some(connection) = client else {
+            return Refresh { tunnels: None, error: None };
+        };
+        match connection.list(&[]).await {
+            Ok(mut tunnels) => {
+                tunnels.sort_by(|a, b| a.alias.cmp(&b.alias));
+                Refresh { tunnels: Some(tunnels), error: None }
+            }
+            Err(e) => {
+                *client = None;
+                Refresh { tunnels: None, error: Some(e.to_string()) }
+            }
+        }
+    }
+
+    /// 经单独的连接执行启动或停止，完成后把别名和失败原因交回主循环
+    async fn perform(action: Action, done: mpsc::UnboundedSender<(String, Option<String>)>) {
+        let (alias, start) = match action {
+            Action::Start(alias) => (alias, true),
+            Action::Stop(alias) => (alias, false),
+            Action::Quit => return,
+        };
+        let result = match ipc::Client::connect().await {
+            Ok(Some(mut client)) if start => client.start(&alias).await.map(drop),
+            Ok(Some(mut client)) => client.stop(&alias).await.map(drop),
+            Ok(None) => Err(anyhow::anyhow!(t!("没有正在运行的 tfa grpc 或 tfa web"))),
+            Err(e) => Err(e),
+        };
+        let _ = done.send((alias, result.err().map(|e| e.to_string())));
+    }
+
+    impl Tray {
+        fn running(&self) -> usize {
+            self.tunnels.iter().flatten().filter(|tunnel| tunnel.running).count()
+        }
+
+        fn summary(&self) -> String {
+            match &self.tunnels {
+                Some(tunnels) => t!("{}/{} 个连接在运行", self.running(), tunnels.len()),
+                None => t!("没有正在运行的 tfa grpc 或 tfa web"),
+            }
+        }
+    }
+
+    /// 菜单中表示状态的圆点
+    fn dot(status: &str) -> &'static str {
+        match status {
+            "running" => "●",
+            "degraded" => "◐",
+            "failed" => "✕",
+            "paused" => "‖",
+            _ => "○",
+        }
+    }
+
+    impl ksni::Tray for Tray {
+        const MENU_ON_ACTIVATE: bool = true;
+
+        fn id(&self) -> String {
+            "tfa".into()
+        }
+
+        fn title(&self) -> String {
+            "tfa".into()
+        }
+
+        fn icon_name(&self) -> String {
+            if self.running() > 0 { "network-transmit-receive" } else { "network-offline" }.into()
+        }
+
+        fn tool_tip(&self) -> ToolTip {
+            ToolTip { title: "tfa".into(), description: self.summary(), ..Default::default() }
+        }
+
+        fn menu(&self) -> Vec<MenuItem<Self>> {
+            let mut menu: Vec<MenuItem<Self>> = Vec::new();
+            match &self.tunnels {
+                None => menu.push(StandardItem { label: t!("请先启动 tfa web 或 tfa grpc"), enabled: false, ..Default::default() }.into()),
+                Some(tunnels) if tunnels.is_empty() => {
+                    menu.push(StandardItem { label: t!("没有配置任何连接"), enabled: false, ..Default::default() }.into());
+                }
+                Some(tunnels) => {
+                    menu.push(StandardItem { label: self.summary(), enabled: false, ..Default::default() }.into());
+                    menu.push(MenuItem::Separator);
+                    for tunnel in tunnels {
+                        let alias = tunnel.alias.clone();
+                        let running = tunnel.running;
+                        // 菜单文字中的下划线表示快捷键，别名中的下划线需要写两次
+                        let mut label = format!("{} {}  {}", dot(&tunnel.status), tunnel.alias.replace('_', "__"), tunnel.local_address);
+                        if !tunnel.enabled {
+                            label = t!("{}（已停用）", label);
+                        }
+                        let pending = self.pending.contains(&tunnel.alias);
+                        if pending {
+                            label = t!("{}（处理中）", label);
+                        }
+                        menu.push(
+                            CheckmarkItem {
+                                label,
+                                checked: running,
+                                // 已停用的连接不能从托盘启动，但仍在运行时可以停止
+                                enabled: (tunnel.enabled || running) && !pending,
+                                activate: Box::new(move |tray: &mut Self| {
+                                    let action = if running { Action::Stop(alias.clone()) } else { Action::Start(alias.clone()) };
+                                    tray.pending.insert(alias.clone());
+                                    let _ = tray.actions.send(action);
+                                }),
+                                ..Default::default()
+                            }
+                            .into(),
+                        );
+                    }
+                }
+            }
+            if let Some(error) = &self.error {
+                menu.push(MenuItem::Separator);
+                let message = error.lines().next().unwrap_or_default().to_string();
+                menu.push(StandardItem { label: message.replace('_', "__"), enabled: false, icon_name: "dialog-error".into(), ..Default::default() }.into());
+            }
+            menu.push(MenuItem::Separator);
+            menu.push(
+                StandardItem {
+                    label: t!("退出"),
+                    icon_name: "application-exit".into(),
+                    activate: Box::new(|tray: &mut Self| {
+                        let _ = tray.actions.send(Action::Quit);
+                    }),
+                    ..Default::default()
+                }
+                .into(),
+            );
+            menu
+        }
+    }
+}
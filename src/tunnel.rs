@@ -1,12 +1,78 @@
 use serde::{Deserialize, Serialize};
 use std::process::{Child, Command, Stdio};
 use std::sync::{Mutex, Arc};
-use std::net::TcpListener;
-use std::io::{BufRead, BufReader, Read};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener};
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
 use std::thread;
-use std::collections::VecDeque;
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crate::breaker::{self, Failures};
+use crate::chain::{self, Route, Via, ViaMode};
+use crate::cloudflared;
+use crate::config::Config;
+use crate::container::{self, Container, Runtime};
+use crate::error::TunnelError;
+use crate::events::{self, TunnelEvent};
+use crate::hooks::{self, HookKind, Hooks};
+use crate::hosts;
+use crate::i18n::t;
+use crate::logsink::{self, LogSink};
+use crate::otel::{self, Operation};
+use crate::outbound::OutboundProxy;
+use crate::platform::{self, PortOccupant};
+use crate::privileged::{self, Privileged};
+use crate::probe::Probe;
+use crate::profile;
+use crate::provider::{self, LogLevel, Provider};
+use crate::redact::Redactor;
+use crate::reload::OnChange;
+use crate::proxy::{ProxyStats, RateLimits};
+use crate::state::{PauseMark, RuntimeState, StartLock, Watcher, WatcherMark};
+use tracing::{debug, info, trace, warn};
 
 const MAX_LOG_LINES: usize = 1000;
+const STARTUP_ERROR_LINES: usize = 10;
+const MIN_PROBE_DELAY: Duration = Duration::from_millis(100);
+const MAX_PROBE_DELAY: Duration = Duration::from_secs(1);
+const DEFAULT_START_TIMEOUT: u64 = 15;
+
+const DEFAULT_START_RETRIES: u32 = 2;
+const DEFAULT_RETRY_BACKOFF: u64 = 2;
+const DEFAULT_CRASH_LIMIT: u32 = 5;
+const DEFAULT_CRASH_WINDOW: u64 = 10;
+
+fn default_start_retries() -> u32 {
+    DEFAULT_START_RETRIES
+}
+
+fn default_retry_backoff() -> u64 {
+    DEFAULT_RETRY_BACKOFF
+}
+
+fn default_crash_limit() -> u32 {
+    DEFAULT_CRASH_LIMIT
+}
+
+fn default_crash_window() -> u64 {
+    DEFAULT_CRASH_WINDOW
+}
+
+fn default_start_timeout() -> u64 {
+    DEFAULT_START_TIMEOUT
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn is_true(value: &bool) -> bool {
+    *value
+}
 
 #[derive(Debug, Clone)]
 pub struct LogBuffer {
@@ -44,15 +110,219 @@ impl LogBuffer {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TunnelStatus {
+    Running,
+    Degraded,
+    /// 反复失败后熔断，不再重试
+    Failed,
+    /// 已暂停，批量启动时跳过
+    Paused,
+    Stopped,
+}
+
+impl fmt::Display for TunnelStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TunnelStatus::Running => "running",
+            TunnelStatus::Degraded => "degraded",
+            TunnelStatus::Failed => "failed",
+            TunnelStatus::Paused => "paused",
+            TunnelStatus::Stopped => "stopped",
+        })
+    }
+}
+
+/// 连接同时转发的一组额外端口，写作 `本地端口:目标端口`，两者相同时可以只写一个
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct PortMapping {
+    pub local: u16,
+    pub remote: u16,
+}
+
+impl FromStr for PortMapping {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |port: &str| port.trim().parse::<u16>().ok().filter(|port| *port != 0);
+        let mapping = match s.split_once(':') {
+            Some((local, remote)) => parse(local).zip(parse(remote)),
+            None => parse(s).map(|port| (port, port)),
+        };
+        mapping
+            .map(|(local, remote)| Self { local, remote })
+            .ok_or_else(|| t!("无效的端口映射 {}，应为 本地端口:目标端口", s))
+    }
+}
+
+impl TryFrom<String> for PortMapping {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<PortMapping> for String {
+    fn from(mapping: PortMapping) -> Self {
+        mapping.to_string()
+    }
+}
+
+impl fmt::Display for PortMapping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.local, self.remote)
+    }
+}
+
+/// `tfa recover` 对一个连接的处理结果
+#[derive(Debug)]
+pub enum Recovery {
+    /// 没有由本工具启动的进程记录
+    NotRunning,
+    /// 记录的进程已经退出，下次启动时计入失败次数
+    Exited { pid: u32 },
+    /// 进程仍在运行，已重新接管
+    Attached {
+        pid: u32,
+        /// 重新启动的后台进程
+        respawned: Vec<&'static str>,
+        /// 无法自动恢复的问题
+        warnings: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tunnel {
     pub alias: String,
     pub source: String,
+    /// 本地端口；配置文件中也可以写作 local_port
+    #[serde(alias = "local_port")]
     pub port: u16,
+    /// Access 应用背后的服务，如 tcp://localhost:5432。目标由服务端的 ingress 决定，这里只用于显示和判断客户端类型；
+    /// kubectl 连接的目标端口见 provider 的 remote_port
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service: Option<String>,
+    /// 是否在本地端口与后端进程之间插入统计代理
+    #[serde(default)]
+    pub proxy: bool,
+    /// 经统计代理上行（本地客户端发往远端）的限速，字节/秒
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_up: Option<u64>,
+    /// 经统计代理下行的限速，字节/秒
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_down: Option<u64>,
+    /// 等待后端进程就绪的秒数
+    #[serde(default = "default_start_timeout")]
+    pub start_timeout: u64,
+    /// 启动因崩溃或超时失败后的重试次数
+    #[serde(default = "default_start_retries")]
+    pub start_retries: u32,
+    /// 首次重试前等待的秒数，之后每次翻倍
+    #[serde(default = "default_retry_backoff")]
+    pub retry_backoff: u64,
+    /// `crash_window` 分钟内失败达到该次数时停止重试（熔断），0 表示不熔断
+    #[serde(default = "default_crash_limit")]
+    pub crash_limit: u32,
+    /// 统计失败次数的时间窗口（分钟）
+    #[serde(default = "default_crash_window")]
+    pub crash_window: u64,
+    /// 用于分类和筛选的标签
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// 是否使用保存在系统钥匙串中的 Access 服务令牌
+    #[serde(default)]
+    pub service_token: bool,
+    /// 启动/停止前后执行的脚本命令
+    #[serde(default, skip_serializing_if = "Hooks::is_empty")]
+    pub hooks: Hooks,
+    /// 启动前需要先启动的连接
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    /// 后端经过的上游连接，如跳板机上的代理；未设置时直接访问 source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub via: Option<Via>,
+    /// 同时转发的其他端口，每组由单独的后端进程提供，与连接一起启动和停止；
+    /// 只适用于 kubectl 连接和经跳板（bastion）访问的 cloudflared 连接
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_ports: Vec<PortMapping>,
+    /// 提供连接的后端，默认为 cloudflared
+    #[serde(default, skip_serializing_if = "Provider::is_default")]
+    pub provider: Provider,
+    /// 在 Docker 或 Podman 容器中运行 cloudflared，主机上不需要安装；只适用于 cloudflared 后端
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runtime: Option<Runtime>,
+    /// cloudflared 的日志级别，未设置时使用 cloudflared 的默认级别
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_level: Option<LogLevel>,
+    /// 写入日志前需要替换为 ****** 的内容（正则表达式）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redact: Vec<String>,
+    /// 本地端口绑定的地址，如 0.0.0.0、::1 或某个网卡的 IP；未设置时只监听 127.0.0.1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub listen_addr: Option<IpAddr>,
+    /// 本地端口低于 1024 且当前用户无权绑定时的处理方式：端口转发或经 sudo 启动；只在本机生效
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub privileged: Option<Privileged>,
+    /// 运行期间在系统 hosts 文件中指向本地监听地址的主机名，如 prod-db.local
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local_hostname: Option<String>,
+    /// 是否在 ~/.ssh/config 中生成 `Host <别名>` 条目，使用专用的 known_hosts；只在本机生效
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub ssh_host: bool,
+    /// `tfa test` 使用的探测方式，未设置时只建立 TCP 连接
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub probe: Option<Probe>,
+    /// 运行期间按该间隔（秒）在后台探测并记录延迟，未设置时不记录
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub probe_interval: Option<u64>,
+    /// 运行期间探测成功后请求的推送地址（healthchecks.io、Uptime Kuma 等），失败时发送失败信号；只在本机生效
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ping_url: Option<String>,
+    /// `tfa exec <alias>` 不带命令时执行的客户端命令，如 psql -h "$TUNNEL_HOST" -p "$TUNNEL_PORT"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client: Option<String>,
+    /// `tfa connect <alias>` 使用的连接串模板，如 postgresql://app@{host}:{port}/orders；未设置时按数据库类型生成
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connect_url: Option<String>,
+    /// 连接的使用说明，由 `tfa show` 显示，启动后也会显示
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    /// 启动后显示的使用方法模板，如 ssh -p {port} user@{host}；{alias}、{host}、{port} 替换为连接的别名与本地地址
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connect_hint: Option<String>,
+    /// 是否使用保存在系统钥匙串中的数据库密码；只在本机生效
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub client_password: bool,
+    /// 后端进程使用的出站代理，未设置时使用全局代理
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outbound_proxy: Option<OutboundProxy>,
+    /// 日志的转发目标，未设置时使用全局设置
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub log_sinks: Vec<LogSink>,
+    /// 停用的连接仍显示在列表中，但批量启动和状态监视都会跳过它；只在本机生效
+    #[serde(default = "default_enabled", skip_serializing_if = "is_true")]
+    pub enabled: bool,
+    /// `tfa grpc`、`tfa web` 运行期间，该连接加入配置或开启该项时自动启动；服务启动时也会启动它。只在本机生效
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub autostart: bool,
+    /// 运行期间配置被修改时，`tfa grpc`、`tfa web` 是否重启该连接以应用新配置；只在本机生效
+    #[serde(default, skip_serializing_if = "OnChange::is_default")]
+    pub on_change: OnChange,
+    /// 锁定的连接只有在命令中加上 --unlock 时才能修改或删除
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub locked: bool,
+    /// 导入该连接的团队目录地址；由目录管理的连接只能通过重新导入修改
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub managed_by: Option<String>,
+    /// 定义该连接的项目配置文件，全局配置中的连接为 None
     #[serde(skip)]
-    process: Mutex<Option<Child>>,
+    pub project: Option<PathBuf>,
+    /// 是否为系统配置中定义、没有被用户配置覆盖的连接
     #[serde(skip)]
-    log_buffer: LogBuffer,
+    pub system: bool,
 }
 
 impl Tunnel {
@@ -61,12 +331,53 @@ impl Tunnel {
             alias: alias.to_string(),
             source: source.to_string(),
             port,
-            process: Mutex::new(None),
-            log_buffer: LogBuffer::new(),
+            service: None,
+            proxy: false,
+            max_up: None,
+            max_down: None,
+            start_timeout: DEFAULT_START_TIMEOUT,
+            start_retries: DEFAULT_START_RETRIES,
+            retry_backoff: DEFAULT_RETRY_BACKOFF,
+            crash_limit: DEFAULT_CRASH_LIMIT,
+            crash_window: DEFAULT_CRASH_WINDOW,
+            tags: Vec::new(),
+            service_token: false,
+            hooks: Hooks::default(),
+            depends_on: Vec::new(),
+            via: None,
+            extra_ports: Vec::new(),
+            log_level: None,
+            redact: Vec::new(),
+            listen_addr: None,
+            privileged: None,
+            local_hostname: None,
+            ssh_host: false,
+            probe: None,
+            probe_interval: None,
+            ping_url: None,
+            client: None,
+            connect_url: None,
+            notes: None,
+            connect_hint: None,
+            client_password: false,
+            outbound_proxy: None,
+            log_sinks: Vec::new(),
+            provider: Provider::default(),
+            runtime: None,
+            enabled: true,
+            autostart: false,
+            on_change: OnChange::default(),
+            locked: false,
+            managed_by: None,
+            project: None,
+            system: false,
         }
     }
     
     pub fn check_cloudflared() -> anyhow::Result<bool> {
+        if cloudflared::managed_program().is_some() {
+            return Ok(true);
+        }
         let output = if cfg!(windows) {
             Command::new("where")
                 .arg("cloudflared")
@@ -80,268 +391,1036 @@ impl Tunnel {
         Ok(output.status.success())
     }
 
+    /// 档案固定了版本时从 GitHub Releases 安装该版本，否则通过系统的包管理器安装
     pub fn install_cloudflared() -> anyhow::Result<()> {
+        if let Some(pin) = cloudflared::pinned() {
+            cloudflared::install(&pin)?;
+            return Ok(());
+        }
         if cfg!(windows) {
             // Windows 使用 winget 安装
             let status = Command::new("winget")
-                .args(&["install", "--id", "Cloudflare.cloudflared", "--silent"])
+                .args(["install", "--id", "Cloudflare.cloudflared", "--silent"])
                 .status()?;
             
             if !status.success() {
-                return Err(anyhow::anyhow!("安装 cloudflared 失败，请手动运行: winget install --id Cloudflare.cloudflared"));
+                return Err(anyhow::anyhow!(t!("安装 cloudflared 失败，请手动运行: winget install --id Cloudflare.cloudflared")));
             }
         } else if cfg!(target_os = "macos") {
             // macOS 使用 brew 安装
             let status = Command::new("brew")
-                .args(&["install", "cloudflared"])
+                .args(["install", "cloudflared"])
                 .status()?;
             
             if !status.success() {
-                return Err(anyhow::anyhow!("安装 cloudflared 失败，请手动运行: brew install cloudflared"));
+                return Err(anyhow::anyhow!(t!("安装 cloudflared 失败，请手动运行: brew install cloudflared")));
             }
         } else {
-            return Err(anyhow::anyhow!("不支持的操作系统"));
+            return Err(anyhow::anyhow!(t!("不支持的操作系统")));
         }
         
         Ok(())
     }
+
+    /// 档案固定了版本时安装该版本，否则通过系统的包管理器升级到最新版本
+    pub fn upgrade_cloudflared() -> anyhow::Result<()> {
+        if let Some(pin) = cloudflared::pinned() {
+            cloudflared::install(&pin)?;
+            return Ok(());
+        }
+        let (program, args, manual): (&str, &[&str], &str) = if cfg!(windows) {
+            ("winget", &["upgrade", "--id", "Cloudflare.cloudflared", "--silent"], "winget upgrade --id Cloudflare.cloudflared")
+        } else if cfg!(target_os = "macos") {
+            ("brew", &["upgrade", "cloudflared"], "brew upgrade cloudflared")
+        } else {
+            return Err(anyhow::anyhow!(t!("未固定版本时只能通过 winget 或 brew 升级 cloudflared，请先运行 'tfa config cloudflared-version <版本>'")));
+        };
+        let status = Command::new(program).args(args).status()?;
+        if !status.success() {
+            return Err(anyhow::anyhow!(t!("升级 cloudflared 失败，请手动运行: {}", manual)));
+        }
+        Ok(())
+    }
     
+    /// 系统连接由系统服务管理，普通用户不能启动或停止
+    pub fn requires_admin(&self) -> bool {
+        self.system && !platform::is_admin()
+    }
+
     pub fn is_running(&self) -> bool {
-        // 检查是否有 cloudflared 进程在使用特定端口
-        #[cfg(target_os = "windows")]
+        // 经 sudo 启动的后端进程属于 root，容器中的 cloudflared 由容器运行时发布端口，
+        // 都无法按端口上的进程名识别，只能按记录的进程判断
+        if let Some(state) = RuntimeState::load(&self.alias)
+            && ((state.elevated && !platform::is_admin()) || state.container.is_some())
         {
-            if let Ok(output) = Command::new("netstat")
-                .args(&["-aon"])
-                .output() 
-            {
-                let output = String::from_utf8_lossy(&output.stdout);
-                for line in output.lines() {
-                    if line.contains(&format!(":{}", self.port)) {
-                        if let Some(pid) = line.split_whitespace().last() {
-                            if let Ok(pid) = pid.parse::<u32>() {
-                                // 检查进程名称
-                                if let Ok(tasklist) = Command::new("tasklist")
-                                    .args(&["/FI", &format!("PID eq {}", pid)])
-                                    .output()
-                                {
-                                    let tasklist = String::from_utf8_lossy(&tasklist.stdout);
-                                    if tasklist.contains("cloudflared.exe") {
-                                        return true;
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
+            return state.is_alive();
+        }
+        self.provider_listening(self.provider_port())
+    }
+    
+    /// 连接是否还有需要停止的进程：运行中或异常（记录的进程仍在），
+    /// 停止和选择正在运行的连接时都按这个判断，否则异常的连接会留下无人管理的进程
+    pub fn is_live(&self) -> bool {
+        matches!(self.status(), TunnelStatus::Running | TunnelStatus::Degraded)
+    }
+
+    /// 连接状态：后端进程在监听端口即为运行中；
+    /// 记录的进程仍在但端口未监听，或统计代理已退出，则视为异常；未运行且已熔断时为失败，已暂停时为暂停
+    pub fn status(&self) -> TunnelStatus {
+        let status = if self.is_running() {
+            if self.proxy && !self.proxy_alive() {
+                debug!(alias = %self.alias, "后端进程在监听，但统计代理已退出");
+                TunnelStatus::Degraded
+            } else if !self.members_alive() {
+                debug!(alias = %self.alias, "后端进程在监听，但有额外端口的后端进程已退出");
+                TunnelStatus::Degraded
+            } else {
+                TunnelStatus::Running
             }
+        } else if RuntimeState::load(&self.alias).is_some_and(|state| state.is_alive()) {
+            debug!(alias = %self.alias, "记录的进程仍在运行，但端口没有被后端进程监听");
+            TunnelStatus::Degraded
+        } else if Failures::load(&self.alias).tripped(self) {
+            TunnelStatus::Failed
+        } else if PauseMark::is_paused(&self.alias) {
+            TunnelStatus::Paused
+        } else {
+            TunnelStatus::Stopped
+        };
+        debug!(alias = %self.alias, %status, "状态检测完成");
+        status
+    }
+
+    /// 只根据运行时文件推断的状态，不检查端口监听，因此不启动外部进程，适合频繁轮询。
+    /// 手动启动、未被接管的进程不会被识别为运行中
+    pub fn recorded_status(&self) -> TunnelStatus {
+        if RuntimeState::load(&self.alias).is_some_and(|state| state.is_alive()) {
+            if (self.proxy && !self.proxy_alive()) || !self.members_alive() {
+                TunnelStatus::Degraded
+            } else {
+                TunnelStatus::Running
+            }
+        } else if Failures::load(&self.alias).tripped(self) {
+            TunnelStatus::Failed
+        } else if PauseMark::is_paused(&self.alias) {
+            TunnelStatus::Paused
+        } else {
+            TunnelStatus::Stopped
         }
+    }
 
-        #[cfg(not(target_os = "windows"))]
-        {
-            if let Ok(output) = Command::new("lsof")
-                .args(&["-i", &format!(":{}", self.port)])
-                .output() 
+    /// 由本工具启动的进程已运行的秒数
+    pub fn uptime(&self) -> Option<u64> {
+        RuntimeState::load(&self.alias)
+            .filter(|state| state.is_alive())
+            .map(|state| platform::unix_now().saturating_sub(state.started_at))
+    }
+
+    /// 本地端口绑定的地址
+    pub fn listen_ip(&self) -> IpAddr {
+        self.listen_addr.unwrap_or(IpAddr::V4(Ipv4Addr::LOCALHOST))
+    }
+
+    /// 本地实际监听的端口：设置了端口转发时为转发到的非特权端口
+    pub fn listen_port(&self) -> u16 {
+        match self.privileged {
+            Some(Privileged::Redirect { port }) => port,
+            _ => self.port,
+        }
+    }
+
+    /// 运行 cloudflared 的容器运行时；只对 cloudflared 后端生效
+    pub fn container_runtime(&self) -> Option<Runtime> {
+        self.runtime.filter(|_| self.provider == Provider::Cloudflared)
+    }
+
+    /// 运行中时所在的容器
+    fn container(&self) -> Option<Container> {
+        self.container_runtime().map(|runtime| Container { runtime, name: container::name(&self.alias) })
+    }
+
+    /// 是否经 sudo 启动后端进程
+    pub fn elevated(&self) -> bool {
+        self.privileged == Some(Privileged::Elevate) && privileged::can_elevate()
+    }
+
+    /// 本机客户端访问连接使用的地址，也是 local_hostname 在 hosts 文件中指向的地址；监听所有地址时为回环地址
+    pub fn local_ip(&self) -> IpAddr {
+        match self.listen_ip() {
+            IpAddr::V4(ip) if ip.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+            IpAddr::V6(ip) if ip.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+            ip => ip,
+        }
+    }
+
+    /// 本机客户端访问连接使用的主机；监听所有地址时同样通过 localhost 访问
+    pub fn local_host(&self) -> String {
+        match self.listen_addr {
+            Some(ip) if !ip.is_unspecified() => ip.to_string(),
+            _ => "localhost".to_string(),
+        }
+    }
+
+    /// 按模板生成的使用方法，未设置模板时为 None
+    pub fn connect_hint(&self) -> Option<String> {
+        self.connect_hint.as_ref().map(|hint| {
+            hint.replace("{alias}", &self.alias)
+                .replace("{host}", &self.local_host())
+                .replace("{port}", &self.port.to_string())
+        })
+    }
+
+    /// 连接的本地地址，如 tcp://localhost:8080、tcp://[::1]:8080；http 后端为 http://localhost:8080
+    pub fn local_url(&self) -> String {
+        let scheme = match self.provider {
+            Provider::Http => "http",
+            _ => "tcp",
+        };
+        match self.listen_addr {
+            Some(ip) => format!("{}://{}", scheme, SocketAddr::new(ip, self.port)),
+            None => format!("{}://localhost:{}", scheme, self.port),
+        }
+    }
+
+    /// 连接目标的端口：kubectl 连接为远端端口，经跳板访问时取自 source，其余取自 service；无从得知时为 None
+    pub fn remote_port(&self) -> Option<u16> {
+        match &self.provider {
+            Provider::Kubectl { remote_port, .. } => Some(*remote_port),
+            _ if self.via.as_ref().is_some_and(|via| via.mode == ViaMode::Bastion) => port_of(&self.source),
+            _ => self.service.as_deref().and_then(port_of),
+        }
+    }
+
+    /// 连接的全部本地地址及其目标端口，如 `tcp://localhost:15432 → 5432`：主端口在前，其后是额外端口
+    pub fn port_mappings(&self) -> Vec<String> {
+        std::iter::once(self.clone())
+            .chain(self.extra_ports.iter().map(|mapping| self.member(mapping)))
+            .map(|tunnel| match tunnel.remote_port() {
+                Some(remote) => format!("{} → {}", tunnel.local_url(), remote),
+                None => tunnel.local_url(),
+            })
+            .collect()
+    }
+
+    /// 提供额外端口的后端进程使用的别名，运行状态与日志按它单独保存
+    pub fn member_alias(&self, mapping: &PortMapping) -> String {
+        format!("{}@{}", self.alias, mapping.local)
+    }
+
+    /// 额外端口的后端进程各自写入的日志文件及其别名
+    pub fn member_logs(&self) -> Vec<(String, PathBuf)> {
+        self.extra_ports.iter()
+            .map(|mapping| self.member(mapping))
+            .filter_map(|member| member.log_path().ok().map(|path| (member.alias, path)))
+            .collect()
+    }
+
+    /// 提供额外端口 `mapping` 的连接。钩子、统计代理、主机名映射和后台探测只属于主端口，这里只有后端进程
+    fn member(&self, mapping: &PortMapping) -> Tunnel {
+        let mut member = self.clone();
+        member.alias = self.member_alias(mapping);
+        member.port = mapping.local;
+        member.extra_ports = Vec::new();
+        member.proxy = false;
+        member.max_up = None;
+        member.max_down = None;
+        member.privileged = None;
+        member.hooks = Hooks::default();
+        member.local_hostname = None;
+        member.probe_interval = None;
+        member.ping_url = None;
+        match &mut member.provider {
+            Provider::Kubectl { remote_port, .. } => *remote_port = mapping.remote,
+            // 经跳板访问时 source 即 --destination，换成目标端口
+            _ => member.source = with_port(&self.source, mapping.remote),
+        }
+        member
+    }
+
+    /// 配置中的额外端口，以及修改配置之前启动、仍有运行状态记录的额外端口
+    fn recorded_members(&self) -> Vec<Tunnel> {
+        let prefix = format!("{}@", self.alias);
+        let mut mappings = self.extra_ports.clone();
+        for alias in RuntimeState::aliases_with_prefix(&prefix) {
+            if let Some(local) = alias.strip_prefix(&prefix).and_then(|port| port.parse().ok())
+                && !mappings.iter().any(|mapping| mapping.local == local)
             {
-                let output = String::from_utf8_lossy(&output.stdout);
-                if output.contains("cloudflared") {
-                    return true;
-                }
+                mappings.push(PortMapping { local, remote: local });
             }
         }
-
-        false
+        mappings.iter().map(|mapping| self.member(mapping)).collect()
     }
-    
-    pub fn status(&self) -> &'static str {
-        if self.is_running() {
-            "running"
-        } else {
-            "stopped"
+
+    /// 检查额外端口：cloudflared 主机名只对应一个服务，只有 kubectl 连接和经跳板访问的连接可以指定目标端口；
+    /// 本地端口不能重复
+    pub fn check_extra_ports(&self) -> Result<(), TunnelError> {
+        if self.extra_ports.is_empty() {
+            return Ok(());
+        }
+        let supported = match &self.provider {
+            Provider::Kubectl { .. } => true,
+            Provider::Cloudflared => self.via.as_ref().is_some_and(|via| via.mode == ViaMode::Bastion),
+            Provider::Http => false,
+        };
+        if !supported {
+            return Err(TunnelError::Config(t!(
+                "连接 {} 不能转发额外端口：只有 kubectl 连接和经跳板（--via-mode bastion）访问的 cloudflared 连接可以指定目标端口",
+                self.alias
+            )));
+        }
+        let mut seen = HashSet::from([self.port]);
+        for mapping in &self.extra_ports {
+            if !seen.insert(mapping.local) {
+                return Err(TunnelError::Config(t!("连接 {} 的本地端口 {} 重复", self.alias, mapping.local)));
+            }
         }
+        Ok(())
+    }
+
+    /// 额外端口的后端进程是否都在运行
+    fn members_alive(&self) -> bool {
+        self.extra_ports
+            .iter()
+            .all(|mapping| RuntimeState::load(&self.member_alias(mapping)).is_some_and(|state| state.is_alive()))
+    }
+
+    /// 开启配置加密时需要加密存储的字段
+    pub fn sensitive_fields_mut(&mut self) -> Vec<&mut String> {
+        vec![&mut self.source]
+    }
+
+    fn proxy_alive(&self) -> bool {
+        ProxyStats::load(&self.alias)
+            .is_some_and(|stats| platform::process_start_time(stats.pid).is_some())
+    }
+
+    /// 后端进程实际监听的端口；启用代理时为代理的上游端口
+    fn provider_port(&self) -> u16 {
+        RuntimeState::load(&self.alias)
+            .map(|state| state.port)
+            .unwrap_or(self.listen_port())
+    }
+
+    /// 检查是否有后端进程在使用指定端口
+    fn provider_listening(&self, port: u16) -> bool {
+        let listeners = platform::port_listeners(port);
+        let listening = listeners.iter().any(|o| o.is_program(self.provider.name()));
+        trace!(alias = %self.alias, port, ?listeners, listening, "检查端口上的 {}", self.provider.name());
+        listening
     }
 
     fn is_port_available(&self) -> bool {
         // 先检查端口是否被占用
-        if TcpListener::bind(format!("127.0.0.1:{}", self.port)).is_err() {
+        let port = self.listen_port();
+        if Self::port_listening(SocketAddr::new(self.listen_ip(), port)) {
+            debug!(alias = %self.alias, port, "本地端口无法绑定");
             return false;
         }
 
-        // 再检查是否有 cloudflared 进程在使用这个端口
-        if cfg!(windows) {
-            if let Ok(output) = Command::new("netstat")
-                .args(&["-aon"])
-                .output() 
-            {
-                let output = String::from_utf8_lossy(&output.stdout);
-                for line in output.lines() {
-                    if line.contains(&format!(":{}", self.port)) {
-                        if let Some(pid) = line.split_whitespace().last() {
-                            if let Ok(pid) = pid.parse::<u32>() {
-                                // 检查进程名称
-                                if let Ok(tasklist) = Command::new("tasklist")
-                                    .args(&["/FI", &format!("PID eq {}", pid)])
-                                    .output()
-                                {
-                                    let tasklist = String::from_utf8_lossy(&tasklist.stdout);
-                                    if tasklist.contains("cloudflared") {
-                                        return false;
-                                    }
-                                }
-                            }
-                        }
-                    }
+        // 再检查是否有后端进程在使用这个端口
+        !self.provider_listening(port)
+    }
+
+    /// 本地端口被占用时的占用进程；无法识别时返回 None
+    pub fn port_occupant(&self) -> Option<PortOccupant> {
+        platform::port_listeners(self.listen_port()).into_iter().next()
+    }
+
+    /// 以后台进程启动统计代理，并等待其写出统计文件
+    fn spawn_proxy(&self, upstream: u16) -> anyhow::Result<()> {
+        ProxyStats::remove(&self.alias);
+        self.rate_limits().save(&self.alias)?;
+        platform::detach(&mut Self::self_command()?)
+            .args([
+                "proxy-serve",
+                &self.alias,
+                "--listen", &self.listen_port().to_string(),
+                "--bind", &self.listen_ip().to_string(),
+                "--upstream", &upstream.to_string(),
+            ])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        for _ in 0..20 {
+            if ProxyStats::load(&self.alias).is_some() {
+                return Ok(());
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        Err(anyhow::anyhow!(t!("统计代理启动失败")))
+    }
+
+    /// 启动对后端输出逐行脱敏的 log-filter 进程，返回供后端写入的 stdout 与 stderr
+    fn spawn_log_filter(&self, log_path: &Path) -> anyhow::Result<(Stdio, Stdio)> {
+        // 规则无效时在启动前报告，而不是让 log-filter 进程直接退出
+        Redactor::new(&self.redact)?;
+        let mut command = Self::self_command()?;
+        command.arg("log-filter").arg(&self.alias).arg(log_path);
+        let mut filter = platform::detach(&mut command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let stdin = filter.stdin.take().ok_or_else(|| anyhow::anyhow!(t!("无法启动日志脱敏进程")))?;
+        Ok(platform::split_stdin(stdin)?)
+    }
+
+    /// 调用本程序自身的命令，子进程沿用当前的配置档案
+    fn self_command() -> anyhow::Result<Command> {
+        let mut command = Command::new(std::env::current_exe()?);
+        command.env(profile::PROFILE_ENV, profile::current());
+        if let Some(root) = profile::custom_root() {
+            command.env(profile::CONFIG_DIR_ENV, root);
+        }
+        if let Some(path) = Config::custom_file() {
+            command.env(crate::config::CONFIG_FILE_ENV, path);
+        }
+        Ok(command)
+    }
+
+    /// 为正在运行的连接设置有效期，由后台进程在到期后停止它；再次设置会覆盖之前的有效期
+    pub fn set_ttl(&self, ttl: Duration) -> anyhow::Result<()> {
+        let mut state = RuntimeState::load(&self.alias)
+            .filter(RuntimeState::is_alive)
+            .ok_or_else(|| anyhow::anyhow!(t!("连接 {} 不是由本工具启动的，无法设置有效期", self.alias)))?;
+        state.expires_at = Some(platform::unix_now().saturating_add(ttl.as_secs()));
+        state.save(&self.alias)?;
+        self.spawn_watcher(Watcher::Ttl)
+    }
+
+    /// 启动后台进程，按 probe_interval 定期探测并记录延迟、推送到 ping_url，连接停止或重新启动后退出
+    fn spawn_latency_watch(&self) -> anyhow::Result<()> {
+        self.spawn_watcher(Watcher::Latency)
+    }
+
+    fn spawn_watcher(&self, watcher: Watcher) -> anyhow::Result<()> {
+        platform::detach(&mut Self::self_command()?)
+            .args([watcher.name(), &self.alias])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        Ok(())
+    }
+
+    /// 为运行中的连接重新启动需要的后台进程（有效期监视、延迟记录），新进程登记后旧进程随之退出；
+    /// 返回重新启动的进程
+    pub fn respawn_watchers(&self) -> anyhow::Result<Vec<&'static str>> {
+        let Some(state) = RuntimeState::load(&self.alias).filter(RuntimeState::is_alive) else {
+            return Ok(Vec::new());
+        };
+        let mut respawned = Vec::new();
+        if state.expires_at.is_some() {
+            self.spawn_watcher(Watcher::Ttl)?;
+            respawned.push(Watcher::Ttl.name());
+        }
+        if self.probe_interval.is_some() || self.ping_url.is_some() {
+            self.spawn_watcher(Watcher::Latency)?;
+            respawned.push(Watcher::Latency.name());
+        }
+        Ok(respawned)
+    }
+
+    /// 重新接管仍在运行的后端进程：核对记录的进程（PID 与启动时间一致）后，
+    /// 补上意外退出的统计代理、有效期监视与延迟记录进程。
+    /// 日志过滤进程退出后后端的输出管道已经断开，无法重新接上，只在结果中说明
+    pub fn recover(&self) -> anyhow::Result<Recovery> {
+        let Some(state) = RuntimeState::load(&self.alias) else {
+            return Ok(Recovery::NotRunning);
+        };
+        if !state.is_alive() {
+            return Ok(Recovery::Exited { pid: state.pid });
+        }
+        let watcher_alive = |watcher| WatcherMark::load(&self.alias, watcher).is_some_and(|mark| mark.is_alive());
+        let mut respawned = Vec::new();
+        let mut warnings = Vec::new();
+        if !platform::port_listened(state.port) {
+            warnings.push(t!("后端进程仍在运行，但没有在端口 {} 上监听", state.port));
+        }
+        if self.proxy && !self.proxy_alive() {
+            self.spawn_proxy(state.port)?;
+            respawned.push("proxy-serve");
+        }
+        if state.expires_at.is_some() && !watcher_alive(Watcher::Ttl) {
+            self.spawn_watcher(Watcher::Ttl)?;
+            respawned.push(Watcher::Ttl.name());
+        }
+        if (self.probe_interval.is_some() || self.ping_url.is_some()) && !watcher_alive(Watcher::Latency) {
+            self.spawn_watcher(Watcher::Latency)?;
+            respawned.push(Watcher::Latency.name());
+        }
+        if WatcherMark::load(&self.alias, Watcher::LogFilter).is_some_and(|mark| !mark.is_alive()) {
+            warnings.push(t!("日志过滤进程已退出，之后的输出不再写入日志，重启连接后恢复"));
+        }
+        for name in &respawned {
+            info!(alias = %self.alias, pid = state.pid, "重新启动后台进程 {}", name);
+        }
+        Ok(Recovery::Attached { pid: state.pid, respawned, warnings })
+    }
+
+    /// 经本地端口探测一次目标服务，返回往返耗时
+    pub fn probe(&self, probe: &Probe, timeout: Duration) -> anyhow::Result<Duration> {
+        // HTTP 探测的 Host 头使用 Access 主机名，与直接访问时一致
+        let host = match self.provider {
+            Provider::Cloudflared => self.source.clone(),
+            _ => self.local_host(),
+        };
+        otel::traced(Operation::Probe, self, || probe.run(SocketAddr::new(self.local_ip(), self.port), &host, timeout))
+    }
+
+    /// 距离自动停止还剩的秒数；未设置有效期时为 None
+    pub fn remaining_ttl(&self) -> Option<u64> {
+        RuntimeState::load(&self.alias)
+            .filter(RuntimeState::is_alive)
+            .and_then(|state| state.expires_at)
+            .map(|expires_at| expires_at.saturating_sub(platform::unix_now()))
+    }
+
+    fn stop_proxy(&self) {
+        if let Some(stats) = ProxyStats::load(&self.alias) {
+            platform::kill_process(stats.pid);
+        }
+        ProxyStats::remove(&self.alias);
+        RateLimits::remove(&self.alias);
+    }
+
+    pub fn rate_limits(&self) -> RateLimits {
+        RateLimits { max_up: self.max_up, max_down: self.max_down }
+    }
+
+    /// 把限速设置交给正在运行的统计代理，代理在一秒内生效；代理未运行时返回 false
+    pub fn apply_rate_limits(&self) -> anyhow::Result<bool> {
+        if !self.proxy_alive() {
+            return Ok(false);
+        }
+        self.rate_limits().save(&self.alias)?;
+        Ok(true)
+    }
+
+    /// 修改 local_hostname 后更新运行中连接的 hosts 记录，删除 `previous` 的旧记录；连接未运行时返回 false
+    pub fn apply_local_hostname(&self, previous: Option<&str>) -> anyhow::Result<bool> {
+        if !self.is_running() {
+            return Ok(false);
+        }
+        if let Some(previous) = previous
+            && self.local_hostname.as_deref() != Some(previous)
+        {
+            hosts::unmap(previous)?;
+        }
+        if let Some(hostname) = &self.local_hostname {
+            hosts::map(hostname, self.local_ip())?;
+        }
+        Ok(true)
+    }
+
+    /// 启动连接，崩溃或超时等暂时性失败按 `start_retries` 与 `retry_backoff` 重试，
+    /// 每次重试通过 `progress` 报告并写入日志
+    pub fn start_with_progress(&self, progress: impl Fn(&str)) -> Result<(), TunnelError> {
+        otel::traced(Operation::Start, self, || self.start_inner(progress))
+    }
+
+    fn start_inner(&self, progress: impl Fn(&str)) -> Result<(), TunnelError> {
+        if self.requires_admin() {
+            return Err(TunnelError::SystemTunnel { alias: self.alias.clone() });
+        }
+        // 另一个终端正在启动同一连接时等它结束；它启动成功则直接使用，失败则由本次接着尝试
+        let _lock = match StartLock::try_acquire(&self.alias)? {
+            Some(lock) => lock,
+            None => {
+                progress(&t!("{} 正在由另一个进程启动，等待其完成", self.alias));
+                let lock = StartLock::acquire(&self.alias)?;
+                if self.is_running() {
+                    info!(alias = %self.alias, "连接已由另一个进程启动");
+                    return Ok(());
                 }
+                lock
             }
-        } else {
-            if let Ok(output) = Command::new("lsof")
-                .args(&["-i", &format!(":{}", self.port)])
-                .output() 
-            {
-                let output = String::from_utf8_lossy(&output.stdout);
-                if output.contains("cloudflared") {
-                    return false;
+        };
+
+        // 1. 先检查端口：无权绑定低于 1024 的端口时说明原因，而不是报告端口被占用
+        if self.privileged.is_none() && privileged::needs_privilege(SocketAddr::new(self.listen_ip(), self.port)) {
+            return Err(TunnelError::PrivilegedPort { alias: self.alias.clone(), port: self.port });
+        }
+        if self.proxy && self.elevated() {
+            return Err(TunnelError::Config(t!("连接 {} 启用了统计代理，不能经 sudo 启动，请改用端口转发", self.alias)));
+        }
+        if !self.is_port_available() {
+            let occupant = self.port_occupant();
+            return Err(TunnelError::PortInUse {
+                port: self.listen_port(),
+                pid: occupant.as_ref().map(|o| o.pid),
+                process: occupant.map(|o| o.process),
+            });
+        }
+        self.check_extra_ports()?;
+        // 主端口没有在运行，留下的额外端口进程属于上一次启动
+        for member in self.recorded_members() {
+            if RuntimeState::load(&member.alias).is_some() {
+                member.terminate(false)?;
+            }
+        }
+        for mapping in &self.extra_ports {
+            if !self.member(mapping).is_port_available() {
+                return Err(TunnelError::Config(t!("连接 {} 的额外端口 {} 已被占用", self.alias, mapping.local)));
+            }
+        }
+
+        // 上次启动的进程没有经过 stop 就退出了，说明它崩溃了，同样计入失败次数
+        if let Some(state) = RuntimeState::load(&self.alias)
+            && !state.is_alive()
+        {
+            RuntimeState::remove(&self.alias);
+            let log = self.log_path().ok().and_then(|path| fs::read_to_string(path).ok()).unwrap_or_default();
+            let message = t!("连接进程意外退出: {}", Self::tail(&log, 1));
+            info!(alias = %self.alias, "{}", message);
+            if let Some(e) = breaker::record(self, &message) {
+                return Err(e);
+            }
+        }
+        breaker::check(self)?;
+
+        // 2. pre_start 钩子失败时放弃启动
+        hooks::run(self, HookKind::PreStart)?;
+
+        // 在容器中运行时先准备好镜像，拉取镜像不计入启动超时
+        if let Some(runtime) = self.container_runtime() {
+            container::ensure_image(runtime, &progress)?;
+        }
+
+        // 新的启动过程清空上次的日志，重试的输出追加在后面
+        let log_path = self.log_path()?;
+        File::create(&log_path)?;
+
+        let mut attempt = 0;
+        loop {
+            info!(alias = %self.alias, attempt = attempt + 1, "启动 {}", self.provider.name());
+            match self.start_attempt(&log_path) {
+                Ok(()) => break,
+                Err(e) if e.is_transient() => {
+                    // 熔断后不再重试
+                    if let Some(tripped) = breaker::record(self, &e.to_string()) {
+                        self.append_log(&log_path, &tripped.to_string());
+                        return Err(tripped);
+                    }
+                    if attempt >= self.start_retries {
+                        return Err(e);
+                    }
+                    attempt += 1;
+                    let delay = self.retry_backoff.saturating_mul(1 << (attempt - 1).min(16));
+                    let message = t!(
+                        "第 {}/{} 次启动失败，{} 秒后重试: {}",
+                        attempt,
+                        self.start_retries + 1,
+                        delay,
+                        e
+                    );
+                    progress(&message);
+                    warn!(alias = %self.alias, "{}", message);
+                    self.append_log(&log_path, &message);
+                    thread::sleep(Duration::from_secs(delay));
                 }
+                Err(e) => return Err(e),
             }
         }
+        if let Err(e) = self.start_members() {
+            let _ = self.terminate(false);
+            return Err(e);
+        }
 
-        true
-    }
-    
-    pub fn start(&self) -> anyhow::Result<()> {
-        // 1. 先检查端口
-        if !self.is_port_available() {
-            return Err(anyhow::anyhow!(
-                "端口 {} 已被占用，请确保没有其他程序正在使用该端口",
-                self.port
-            ));
+        // 主机名映射失败不影响连接本身
+        if let Some(hostname) = &self.local_hostname
+            && let Err(e) = hosts::map(hostname, self.local_ip())
+        {
+            warn!(alias = %self.alias, "{}", t!("无法把 {} 映射到本地: {}", hostname, e));
         }
 
-        // 2. 启动 cloudflared
-        let cloudflared_path = if cfg!(windows) {
-            let output = Command::new("where")
-                .arg("cloudflared")
-                .output()?;
-            
-            if output.status.success() {
-                String::from_utf8_lossy(&output.stdout)
-                    .lines()
-                    .next()
-                    .unwrap_or("cloudflared")
-                    .trim()
-                    .to_string()
-            } else {
-                "cloudflared".to_string()
+        if (self.probe_interval.is_some() || self.ping_url.is_some())
+            && let Err(e) = self.spawn_latency_watch()
+        {
+            warn!(alias = %self.alias, "{}", t!("无法启动延迟记录进程: {}", e));
+        }
+
+        // 连接已经可用，post_start 钩子失败只记录到钩子日志
+        let _ = hooks::run(self, HookKind::PostStart);
+        events::publish(TunnelEvent::Started { alias: self.alias.clone() });
+        Ok(())
+    }
+
+    /// 为每个额外端口启动一个后端进程，日志写入各自的文件；任一失败时结束已启动的进程
+    fn start_members(&self) -> Result<(), TunnelError> {
+        let mut started: Vec<Tunnel> = Vec::new();
+        for mapping in &self.extra_ports {
+            let member = self.member(mapping);
+            info!(alias = %self.alias, port = mapping.local, remote = mapping.remote, "启动额外端口的 {}", self.provider.name());
+            let result = member.log_path().map_err(TunnelError::from).and_then(|log_path| {
+                File::create(&log_path)?;
+                member.start_attempt(&log_path)
+            });
+            if let Err(e) = result {
+                for member in started {
+                    let _ = member.terminate(false);
+                }
+                return Err(e);
             }
+            started.push(member);
+        }
+        Ok(())
+    }
+
+    /// 一次启动尝试：启动代理与后端进程并等待就绪，失败时清理已启动的进程
+    fn start_attempt(&self, log_path: &Path) -> Result<(), TunnelError> {
+        // 3. 启用代理时，代理占用本地端口，后端进程只在 127.0.0.1 上监听内部端口
+        let provider_addr = if self.proxy {
+            let upstream = platform::free_port()?;
+            self.spawn_proxy(upstream)?;
+            SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), upstream)
         } else {
-            "cloudflared".to_string()
+            SocketAddr::new(self.listen_ip(), self.listen_port())
+        };
+
+        // 4. 启动后端进程，输出写入日志文件，避免本进程退出后管道断开
+        let command = self.route().map_err(TunnelError::from)
+            .and_then(|route| self.provider.command(self, provider_addr, &route));
+        let mut command = match command {
+            Ok(command) if self.elevated() => privileged::elevate(&command),
+            Ok(command) => command,
+            Err(e) => {
+                self.stop_proxy();
+                return Err(e);
+            }
         };
-        
-        let mut process = Command::new(cloudflared_path)
-            .args(&["access", "tcp", "--hostname", &self.source, "--url", &format!("tcp://localhost:{}", self.port)])
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()?;
 
-        let stdout = process.stdout.take().unwrap();
-        let stderr = process.stderr.take().unwrap();
-        let log_buffer = self.log_buffer.clone();
-        let alias = self.alias.clone();
-
-        // 3. 日志线程
-        let log_buffer_clone = log_buffer.clone();
-        let alias_clone = alias.clone();
-        thread::spawn(move || {
-            let stdout_reader = BufReader::new(stdout);
-            for line in stdout_reader.lines() {
-                if let Ok(line) = line {
-                    let log_line = format!("[{}] {}", alias_clone, line);
-                    println!("{}", log_line);
-                    log_buffer_clone.add_line(log_line);
+        let log_file = OpenOptions::new().append(true).open(log_path)?;
+        let log_offset = log_file.metadata()?.len() as usize;
+        // 配置了脱敏规则或转发目标时，输出先经过 log-filter 进程再写入日志
+        let (stdout, stderr) = if self.redact.is_empty() && logsink::effective(&self.log_sinks).is_empty() {
+            (Stdio::from(log_file.try_clone()?), Stdio::from(log_file))
+        } else {
+            match self.spawn_log_filter(log_path) {
+                Ok(pipes) => pipes,
+                Err(e) => {
+                    self.stop_proxy();
+                    return Err(e.into());
                 }
             }
-        });
-
-        // 4. 启动后等待1秒，检查进程是否已退出
-        std::thread::sleep(std::time::Duration::from_secs(1));
-        if let Ok(Some(status)) = process.try_wait() {
-            // 进程已退出，采集 stderr
-            let mut err_reader = BufReader::new(stderr);
-            let mut err_msg = String::new();
-            let _ = err_reader.read_to_string(&mut err_msg);
-            let error_msg = if err_msg.trim().is_empty() {
-                format!("cloudflared 启动失败，退出码: {}", status)
-            } else {
-                format!("cloudflared 启动失败: {}", err_msg.trim())
-            };
-            log_buffer.add_line(format!("[{}][stderr] {}", alias, error_msg));
-            return Err(anyhow::anyhow!(error_msg));
+        };
+        // 上次崩溃时留下的同名容器会使新容器无法创建
+        if let Some(container) = self.container()
+            && let Err(e) = container::remove(&container)
+        {
+            self.stop_proxy();
+            return Err(match e.downcast_ref::<std::io::Error>() {
+                Some(io) if io.kind() == std::io::ErrorKind::NotFound => TunnelError::ProgramNotFound { program: container.runtime.program().to_string() },
+                _ => e.into(),
+            });
         }
+        let started_at = platform::unix_now();
+        debug!(alias = %self.alias, command = %provider::redacted_args(&command).join(" "), "启动后端进程");
+        let spawned = platform::detach(&mut command)
+            .stdin(Stdio::null())
+            .stdout(stdout)
+            .stderr(stderr)
+            .spawn();
+        let mut process = match spawned {
+            Ok(process) => process,
+            Err(e) => {
+                self.stop_proxy();
+                return Err(if e.kind() == std::io::ErrorKind::NotFound {
+                    match self.container_runtime() {
+                        Some(runtime) => TunnelError::ProgramNotFound { program: runtime.program().to_string() },
+                        None => self.provider.not_found(),
+                    }
+                } else {
+                    TunnelError::SpawnFailed(e)
+                });
+            }
+        };
 
-        // 5. 再次检查进程是否真的在运行
-        if !self.is_running() {
-            let error_msg = "cloudflared 进程启动后立即退出";
-            log_buffer.add_line(format!("[{}][error] {}", alias, error_msg));
-            return Err(anyhow::anyhow!(error_msg));
+        // 5. 等待就绪：日志出现监听/连接记录，或端口已被监听
+        if let Err(e) = self.wait_started(&mut process, provider_addr, log_path, log_offset) {
+            let _ = process.kill();
+            if let Some(container) = self.container() {
+                let _ = container::remove(&container);
+            }
+            self.stop_proxy();
+            return Err(e);
         }
 
-        if let Ok(mut guard) = self.process.lock() {
-            *guard = Some(process);
+        info!(alias = %self.alias, pid = process.id(), addr = %provider_addr, "后端进程已就绪");
+        RuntimeState {
+            pid: process.id(),
+            started_at,
+            port: provider_addr.port(),
+            project: self.project.clone(),
+            expires_at: None,
+            elevated: self.elevated(),
+            container: self.container(),
         }
-
+        .save(&self.alias)?;
         Ok(())
     }
+
+    /// 启动时将要执行的后端命令（不执行）。启用代理时内部端口在启动时才分配，这里临时选取一个空闲端口
+    pub fn preview_command(&self) -> Result<Command, TunnelError> {
+        let addr = if self.proxy {
+            SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), platform::free_port()?)
+        } else {
+            SocketAddr::new(self.listen_ip(), self.listen_port())
+        };
+        let command = self.provider.command(self, addr, &self.route()?)?;
+        Ok(if self.elevated() { privileged::elevate(&command) } else { command })
+    }
+
+    /// 后端访问目标的链路；经过其他连接时读取配置解析上游连接
+    pub fn route(&self) -> anyhow::Result<Route> {
+        match &self.via {
+            Some(_) => chain::resolve(&Config::load()?, self),
+            None => Ok(chain::direct(self)),
+        }
+    }
+
+    /// 在日志中记录本工具自身的消息，与后端进程的输出区分开
+    fn append_log(&self, log_path: &Path, message: &str) {
+        if let Ok(mut file) = OpenOptions::new().append(true).open(log_path) {
+            let _ = writeln!(file, "[tfa] {}", message);
+        }
+    }
+
+    /// 轮询后端进程的状态直到就绪、退出或超过 `start_timeout`，轮询间隔指数退避。
+    /// 只检查 `log_offset` 之后本次尝试写入的日志
+    fn wait_started(&self, process: &mut Child, addr: SocketAddr, log_path: &Path, log_offset: usize) -> Result<(), TunnelError> {
+        let deadline = Instant::now() + Duration::from_secs(self.start_timeout);
+        let mut delay = MIN_PROBE_DELAY;
+        loop {
+            let log = fs::read_to_string(log_path).unwrap_or_default();
+            let log = log.get(log_offset..).unwrap_or_default();
+
+            if let Some(status) = process.try_wait()? {
+                if Self::log_contains(log, self.provider.auth_markers()) {
+                    return Err(TunnelError::AuthRequired {
+                        hostname: self.source.clone(),
+                    });
+                }
+                let tail = Self::tail(log, STARTUP_ERROR_LINES);
+                return Err(TunnelError::CrashedOnStartup {
+                    stderr: if tail.is_empty() {
+                        t!("退出码: {}", status)
+                    } else {
+                        tail
+                    },
+                });
+            }
+
+            if Self::log_contains(log, self.provider.ready_markers()) {
+                debug!(alias = %self.alias, "日志中出现就绪标记");
+                return Ok(());
+            }
+            if Self::port_listening(addr) {
+                debug!(alias = %self.alias, %addr, "端口已被监听");
+                return Ok(());
+            }
+            trace!(alias = %self.alias, ?delay, "尚未就绪，继续等待");
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(TunnelError::Timeout {
+                    port: addr.port(),
+                    seconds: self.start_timeout,
+                });
+            }
+            thread::sleep(delay.min(deadline - now));
+            delay = (delay * 2).min(MAX_PROBE_DELAY);
+        }
+    }
+
+    fn log_contains(log: &str, markers: &[&str]) -> bool {
+        log.lines().any(|line| {
+            let line = line.to_lowercase();
+            markers.iter().any(|marker| line.contains(marker))
+        })
+    }
+
+    /// 端口是否已有进程监听。用绑定测试代替建立连接，
+    /// 避免探测连接触发 cloudflared 的 Access 认证流程；无权绑定低于 1024 的端口时改为查询系统的监听表
+    fn port_listening(addr: SocketAddr) -> bool {
+        match TcpListener::bind(addr) {
+            Ok(_) => false,
+            Err(e) if e.kind() == ErrorKind::PermissionDenied => platform::port_listened(addr.port()),
+            Err(_) => true,
+        }
+    }
+
+    fn tail(text: &str, lines: usize) -> String {
+        let all: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
+        all[all.len().saturating_sub(lines)..].join("\n")
+    }
     
-    pub fn stop(&self) -> anyhow::Result<()> {
-        // 首先尝试停止我们自己启动的进程
-        if let Ok(mut guard) = self.process.lock() {
-            if let Some(mut process) = guard.take() {
-                process.kill()?;
+    /// 等待连接通过健康检查：后端进程在监听端口（启用代理时代理也在运行），且本地端口可以访问。
+    /// 连接可能正由其他进程启动，未运行时同样继续等待直到超时
+    pub fn wait_healthy(&self, timeout: Duration) -> Result<(), TunnelError> {
+        let deadline = Instant::now() + timeout;
+        let mut delay = MIN_PROBE_DELAY;
+        loop {
+            let status = self.status();
+            if status == TunnelStatus::Running && Self::port_listening(SocketAddr::new(self.listen_ip(), self.listen_port())) {
+                return Ok(());
             }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(TunnelError::Unhealthy {
+                    alias: self.alias.clone(),
+                    seconds: timeout.as_secs(),
+                    status: status.to_string(),
+                });
+            }
+            trace!(alias = %self.alias, %status, ?delay, "尚未通过健康检查，继续等待");
+            thread::sleep(delay.min(deadline - now));
+            delay = (delay * 2).min(MAX_PROBE_DELAY);
         }
+    }
 
-        // 然后尝试停止系统中使用相同端口的 cloudflared 进程
-        if cfg!(windows) {
-            if let Ok(output) = Command::new("netstat")
-                .args(&["-aon"])
-                .output() 
-            {
-                let output = String::from_utf8_lossy(&output.stdout);
-                for line in output.lines() {
-                    if line.contains(&format!(":{}", self.port)) {
-                        if let Some(pid) = line.split_whitespace().last() {
-                            if let Ok(pid) = pid.parse::<u32>() {
-                                let _ = Command::new("taskkill")
-                                    .args(&["/F", "/PID", &pid.to_string()])
-                                    .output();
-                            }
-                        }
-                    }
-                }
+    /// 等待本地端口就绪
+    pub fn wait_ready(&self, timeout: Duration) -> anyhow::Result<()> {
+        let deadline = Instant::now() + timeout;
+        let mut delay = MIN_PROBE_DELAY;
+        while Instant::now() < deadline {
+            if Self::port_listening(SocketAddr::new(self.listen_ip(), self.listen_port())) {
+                return Ok(());
             }
-        } else {
-            if let Ok(output) = Command::new("lsof")
-                .args(&["-i", &format!(":{}", self.port)])
-                .output() 
-            {
-                let output = String::from_utf8_lossy(&output.stdout);
-                for line in output.lines() {
-                    if line.contains("cloudflared") {
-                        if let Some(pid) = line.split_whitespace().nth(1) {
-                            if let Ok(pid) = pid.parse::<u32>() {
-                                let _ = Command::new("kill")
-                                    .args(&["-9", &pid.to_string()])
-                                    .output();
-                            }
-                        }
-                    }
+            thread::sleep(delay);
+            delay = (delay * 2).min(MAX_PROBE_DELAY);
+        }
+        Err(anyhow::anyhow!(t!("等待端口 {} 就绪超时", self.port)))
+    }
+
+    pub fn log_path(&self) -> anyhow::Result<PathBuf> {
+        let mut path = Config::runtime_dir()?;
+        path.push(format!("{}.log", self.alias));
+        Ok(path)
+    }
+    
+    /// 停止连接。只结束由本工具启动（PID 与启动时间均匹配）的进程，
+    /// 端口上其他的同类后端进程需要 `force` 才会被结束
+    pub fn stop(&self, force: bool) -> Result<(), TunnelError> {
+        otel::traced(Operation::Stop, self, || self.stop_inner(force))
+    }
+
+    fn stop_inner(&self, force: bool) -> Result<(), TunnelError> {
+        if self.requires_admin() {
+            return Err(TunnelError::SystemTunnel { alias: self.alias.clone() });
+        }
+        // 停止钩子失败不影响停止连接，输出记录在钩子日志中
+        let _ = hooks::run(self, HookKind::PreStop);
+        self.terminate(force)?;
+        for member in self.recorded_members() {
+            member.terminate(force)?;
+        }
+
+        if let Some(hostname) = &self.local_hostname
+            && let Err(e) = hosts::unmap(hostname)
+        {
+            warn!(alias = %self.alias, "{}", t!("无法删除 {} 的本地映射: {}", hostname, e));
+        }
+
+        let _ = hooks::run(self, HookKind::PostStop);
+        events::publish(TunnelEvent::Stopped { alias: self.alias.clone() });
+        Ok(())
+    }
+
+    /// 结束后端进程与统计代理并删除运行状态；端口上其他的同类后端进程需要 `force` 才会被结束
+    fn terminate(&self, force: bool) -> Result<(), TunnelError> {
+        let mut port = self.listen_port();
+        let mut owned_pid = None;
+        if let Some(state) = RuntimeState::load(&self.alias) {
+            // docker 进程退出后容器可能仍在运行，总是删除它
+            if let Some(container) = &state.container {
+                container::remove(container)?;
+            }
+            if state.is_alive() {
+                info!(alias = %self.alias, pid = state.pid, "结束由本工具启动的进程");
+                if state.elevated && !platform::is_admin() {
+                    privileged::terminate(state.pid)?;
+                } else {
+                    platform::kill_process(state.pid);
                 }
+                owned_pid = Some(state.pid);
+            } else {
+                debug!(alias = %self.alias, pid = state.pid, "记录的进程已不存在或 PID 已被复用，只清理状态文件");
             }
+            port = state.port;
+            RuntimeState::remove(&self.alias);
+            WatcherMark::remove_all(&self.alias);
         }
 
+        if self.proxy {
+            self.stop_proxy();
+        }
+
+        let foreign = platform::port_listeners(port)
+            .into_iter()
+            .filter(|o| o.is_program(self.provider.name()) && Some(o.pid) != owned_pid);
+        for occupant in foreign {
+            debug!(alias = %self.alias, port, pid = occupant.pid, force, "端口上有不是由本工具启动的 {}", occupant.process);
+            if !force {
+                return Err(TunnelError::NotOwned {
+                    port,
+                    pid: occupant.pid,
+                    process: occupant.process,
+                });
+            }
+            warn!(alias = %self.alias, pid = occupant.pid, "{}", t!("强制结束不是由本工具启动的进程"));
+            platform::kill_process(occupant.pid);
+        }
         Ok(())
     }
 
+    /// 连接日志与钩子日志；配置了脱敏规则时同样应用于读取的内容，包括设置规则之前写入的行
     pub fn get_logs(&self) -> Vec<String> {
-        self.log_buffer.get_lines()
+        let buffer = LogBuffer::new();
+        let redactor = Redactor::new(&self.redact).unwrap_or_default();
+        if let Ok(path) = self.log_path()
+            && let Ok(content) = fs::read_to_string(path)
+        {
+            for line in content.lines() {
+                buffer.add_line(format!("[{}] {}", self.alias, redactor.apply(line)));
+            }
+        }
+        for member in self.extra_ports.iter().map(|mapping| self.member(mapping)) {
+            if let Ok(path) = member.log_path()
+                && let Ok(content) = fs::read_to_string(path)
+            {
+                for line in content.lines() {
+                    buffer.add_line(format!("[{}] {}", member.alias, redactor.apply(line)));
+                }
+            }
+        }
+        if let Ok(path) = hooks::log_path(&self.alias)
+            && let Ok(content) = fs::read_to_string(path)
+        {
+            for line in content.lines() {
+                buffer.add_line(format!("[{} hook] {}", self.alias, redactor.apply(line)));
+            }
+        }
+        buffer.get_lines()
+    }
+} 
+
+/// 把 `host:port` 形式的地址换成另一个端口；没有端口时加上
+fn with_port(address: &str, port: u16) -> String {
+    match address.rsplit_once(':') {
+        Some((host, existing)) if existing.parse::<u16>().is_ok() && (!host.contains(':') || host.ends_with(']')) => {
+            format!("{}:{}", host, port)
+        }
+        _ if address.contains(':') && !address.starts_with('[') => format!("[{}]:{}", address, port),
+        _ => format!("{}:{}", address, port),
     }
-} 
\ No newline at end of file
+}
+
+/// 地址中的端口，如 `tcp://db.internal:5432`、`db:5432` 或单独的 `5432`；没有端口时为 None
+fn port_of(address: &str) -> Option<u16> {
+    let address = address.split_once("://").map_or(address, |(_, rest)| rest);
+    let address = address.split('/').next().unwrap_or_default();
+    address.parse().ok().or_else(|| address.rsplit_once(':').and_then(|(_, port)| port.parse().ok()))
+}
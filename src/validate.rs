@@ -0,0 +1,488 @@
+//! 配置文件校验：不加载配置，逐项检查原始文件并一次性报告全部问题，便于在 CI 中使用。
+
+use anyhow::Result;
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::alert::{AlertRule, Engine};
+use crate::cloudflared::Pin;
+use crate::config::{Config, PortRange};
+use crate::error::TunnelError;
+use crate::hooks::Hooks;
+use crate::i18n::{t, Lang};
+use crate::notify::Channel;
+use crate::provider::Provider;
+use crate::redact::Redactor;
+use crate::secrets;
+use crate::tunnel::Tunnel;
+
+/// 全局配置文件的顶层字段
+const CONFIG_FIELDS: &[&str] = &[
+    "alerts", "api_keys", "backup_retention", "cloudflared", "encrypted", "lang", "log_sinks", "notify", "outbound_proxy", "port_range", "sync",
+    "templates", "tunnels",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// 校验发现的一个问题
+#[derive(Debug)]
+pub struct Issue {
+    pub severity: Severity,
+    pub file: PathBuf,
+    pub line: Option<usize>,
+    pub alias: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    /// 编译器风格的 `文件:行: 级别: [别名] 说明`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.file.display())?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+        }
+        match self.severity {
+            Severity::Error => write!(f, ": {}: ", t!("错误"))?,
+            Severity::Warning => write!(f, ": {}: ", t!("警告"))?,
+        }
+        if let Some(alias) = &self.alias {
+            write!(f, "[{}] ", alias)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+/// 一个配置文件的原始内容与从中解析出的连接
+struct Source {
+    file: PathBuf,
+    content: String,
+    /// 全局配置为 JSON，项目配置为 TOML
+    json: bool,
+    tunnels: Vec<Tunnel>,
+    /// 加密的配置在没有密钥时无法检查主机名
+    sealed: bool,
+    /// 全局配置中的保留端口范围
+    port_range: Option<PortRange>,
+}
+
+impl Source {
+    /// 依次查找连接与字段在文件中的位置，估计所在行；找不到字段时退回连接所在行
+    fn line_of(&self, alias: Option<&str>, field: Option<&str>) -> Option<usize> {
+        if alias.is_none() && field.is_none() {
+            return None;
+        }
+        let mut offset = 0;
+        if let Some(alias) = alias {
+            let key = if self.json { format!("\"{}\"", alias) } else { format!("[tunnels.{}]", alias) };
+            offset = self.content.find(&key)?;
+        }
+        if let Some(field) = field {
+            let needles = if self.json {
+                vec![format!("\"{}\"", field)]
+            } else {
+                vec![format!("\n{}", field), format!("\n[{}", field)]
+            };
+            match needles.iter().find_map(|needle| self.content[offset..].find(needle)) {
+                Some(found) => offset += found + usize::from(!self.json),
+                None if alias.is_none() => return None,
+                None => {}
+            }
+        }
+        Some(line_at(&self.content, offset))
+    }
+}
+
+fn line_at(content: &str, offset: usize) -> usize {
+    content[..offset.min(content.len())].matches('\n').count() + 1
+}
+
+#[derive(Default)]
+struct Report {
+    issues: Vec<Issue>,
+}
+
+impl Report {
+    fn push(&mut self, severity: Severity, source: &Source, alias: Option<&str>, field: Option<&str>, message: String) {
+        self.issues.push(Issue {
+            severity,
+            file: source.file.clone(),
+            line: source.line_of(alias, field),
+            alias: alias.map(str::to_string),
+            message,
+        });
+    }
+
+    fn error(&mut self, source: &Source, alias: Option<&str>, field: Option<&str>, message: String) {
+        self.push(Severity::Error, source, alias, field, message);
+    }
+
+    fn warning(&mut self, source: &Source, alias: Option<&str>, field: Option<&str>, message: String) {
+        self.push(Severity::Warning, source, alias, field, message);
+    }
+}
+
+/// 校验指定的文件；未指定时校验全局配置，以及当前目录所属项目的配置文件（若有）。
+/// `.toml` 文件按项目配置校验，其他文件按全局配置校验。返回校验过的文件与发现的问题
+pub fn run(file: Option<&Path>) -> Result<(Vec<PathBuf>, Vec<Issue>)> {
+    let files = match file {
+        Some(file) => vec![file.to_path_buf()],
+        None => [Some(Config::config_path()?), Config::find_project_file()]
+            .into_iter()
+            .flatten()
+            .filter(|path| path.exists())
+            .collect(),
+    };
+
+    let mut report = Report::default();
+    let mut sources = Vec::new();
+    for file in &files {
+        let content = fs::read_to_string(file)
+            .map_err(|e| TunnelError::config(t!("无法读取 {}: {}", file.display(), e)))?;
+        let json = file.extension().is_none_or(|ext| ext != "toml");
+        let mut source = Source { file: file.clone(), content, json, tunnels: Vec::new(), sealed: false, port_range: None };
+        if json {
+            check_config(&mut source, &mut report);
+        } else {
+            check_project(&mut source, &mut report);
+        }
+        sources.push(source);
+    }
+    check_tunnels(&sources, &mut report);
+    let mut issues = report.issues;
+    issues.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+    Ok((files, issues))
+}
+
+/// 全局配置：JSON 语法、顶层字段与每个连接
+fn check_config(source: &mut Source, report: &mut Report) {
+    let root: serde_json::Value = match serde_json::from_str(&source.content) {
+        Ok(root) => root,
+        Err(e) => {
+            report.issues.push(Issue {
+                severity: Severity::Error,
+                file: source.file.clone(),
+                line: Some(e.line()),
+                alias: None,
+                message: t!("JSON 语法错误: {}", e),
+            });
+            return;
+        }
+    };
+    let Some(root) = root.as_object() else {
+        report.error(source, None, None, t!("顶层应为 JSON 对象"));
+        return;
+    };
+    for key in root.keys().filter(|key| !CONFIG_FIELDS.contains(&key.as_str())) {
+        report.error(source, None, Some(key), t!("未知字段 {}", key));
+    }
+    if let Some(lang) = root.get("lang")
+        && serde_json::from_value::<Lang>(lang.clone()).is_err()
+    {
+        report.error(source, None, Some("lang"), t!("无效的语言 {}，可选 zh-CN、en-US", lang));
+    }
+    if let Some(pin) = root.get("cloudflared") {
+        match serde_json::from_value::<Pin>(pin.clone()) {
+            Ok(pin) => {
+                if let Err(e) = Pin::new(&pin.version, pin.sha256.clone()) {
+                    report.error(source, None, Some("cloudflared"), e.to_string());
+                }
+            }
+            Err(e) => report.error(source, None, Some("cloudflared"), e.to_string()),
+        }
+    }
+    if let Some(range) = root.get("port_range") {
+        match serde_json::from_value::<PortRange>(range.clone()) {
+            Ok(range) => source.port_range = Some(range),
+            Err(e) => report.error(source, None, Some("port_range"), e.to_string()),
+        }
+    }
+    check_alerts(source, report, root);
+    let encrypted = match root.get("encrypted") {
+        None => false,
+        Some(serde_json::Value::Bool(encrypted)) => *encrypted,
+        Some(_) => {
+            report.error(source, None, Some("encrypted"), t!("encrypted 应为 true 或 false"));
+            false
+        }
+    };
+    let Some(tunnels) = root.get("tunnels") else {
+        report.error(source, None, None, t!("缺少字段 tunnels"));
+        return;
+    };
+    let Some(tunnels) = tunnels.as_object() else {
+        report.error(source, None, Some("tunnels"), t!("tunnels 应为以别名为键的对象"));
+        return;
+    };
+
+    let key = if encrypted { secrets::config_key().ok().flatten() } else { None };
+    source.sealed = encrypted && key.is_none();
+    if source.sealed {
+        report.warning(source, None, Some("encrypted"), t!("配置已加密但未找到密钥，跳过主机名检查"));
+    }
+    for (alias, value) in tunnels {
+        if let Some(field) = value.get("alias").and_then(|v| v.as_str())
+            && field != alias
+        {
+            report.error(source, Some(alias), Some("alias"), t!("alias 字段 {} 与键名不一致", field));
+        }
+        if let Some(fields) = value.as_object() {
+            let hooks = fields.get("hooks").and_then(|hooks| hooks.as_object());
+            check_fields(source, report, alias, fields.keys(), hooks.into_iter().flat_map(|hooks| hooks.keys()));
+        }
+        match Tunnel::deserialize(value) {
+            Ok(mut tunnel) => {
+                // 加载配置时以键名为准
+                tunnel.alias = alias.clone();
+                if let Some(key) = &key {
+                    for field in tunnel.sensitive_fields_mut() {
+                        match secrets::decrypt(key, field) {
+                            Ok(plain) => *field = plain,
+                            Err(e) => report.error(source, Some(alias), Some("source"), e.to_string()),
+                        }
+                    }
+                }
+                source.tunnels.push(tunnel);
+            }
+            Err(e) => report.error(source, Some(alias), None, t!("连接无效: {}", e)),
+        }
+    }
+}
+
+/// 通知渠道与告警规则的格式，以及规则引用的渠道是否存在
+fn check_alerts(source: &Source, report: &mut Report, root: &serde_json::Map<String, serde_json::Value>) {
+    let channels = match root.get("notify").map(|notify| serde_json::from_value::<BTreeMap<String, Channel>>(notify.clone())) {
+        None => BTreeMap::new(),
+        Some(Ok(channels)) => channels,
+        Some(Err(e)) => {
+            report.error(source, None, Some("notify"), t!("通知渠道无效: {}", e));
+            return;
+        }
+    };
+    let Some(rules) = root.get("alerts") else {
+        return;
+    };
+    match serde_json::from_value::<Vec<AlertRule>>(rules.clone()) {
+        Ok(rules) if rules.is_empty() => {}
+        Ok(rules) => {
+            if let Err(e) = Engine::new(&rules, &channels) {
+                report.error(source, None, Some("alerts"), e.to_string());
+            }
+        }
+        Err(e) => report.error(source, None, Some("alerts"), t!("告警规则无效: {}", e)),
+    }
+}
+
+/// 项目配置：TOML 语法、顶层字段与每个连接
+fn check_project(source: &mut Source, report: &mut Report) {
+    let root: toml::Table = match toml::from_str(&source.content) {
+        Ok(root) => root,
+        Err(e) => {
+            report.issues.push(Issue {
+                severity: Severity::Error,
+                file: source.file.clone(),
+                line: e.span().map(|span| line_at(&source.content, span.start)),
+                alias: None,
+                message: t!("TOML 语法错误: {}", e.message().trim().replace('\n', "; ")),
+            });
+            return;
+        }
+    };
+    for key in root.keys().filter(|key| *key != "tunnels") {
+        report.error(source, None, Some(key), t!("未知字段 {}", key));
+    }
+    let Some(tunnels) = root.get("tunnels") else {
+        return;
+    };
+    let Some(tunnels) = tunnels.as_table() else {
+        report.error(source, None, Some("tunnels"), t!("tunnels 应为以别名为键的表"));
+        return;
+    };
+    for (alias, value) in tunnels {
+        let Some(table) = value.as_table() else {
+            report.error(source, Some(alias), None, t!("连接应为表"));
+            continue;
+        };
+        let hooks = table.get("hooks").and_then(|hooks| hooks.as_table());
+        check_fields(source, report, alias, table.keys(), hooks.into_iter().flat_map(|hooks| hooks.keys()));
+        let mut table = table.clone();
+        table.insert("alias".to_string(), toml::Value::String(alias.clone()));
+        match toml::Value::Table(table).try_into::<Tunnel>() {
+            Ok(mut tunnel) => {
+                tunnel.project = Some(source.file.clone());
+                source.tunnels.push(tunnel);
+            }
+            Err(e) => report.error(source, Some(alias), None, t!("连接无效: {}", e.message())),
+        }
+    }
+}
+
+/// 连接及其 hooks 中不认识的字段
+fn check_fields<'a>(
+    source: &Source,
+    report: &mut Report,
+    alias: &str,
+    keys: impl Iterator<Item = &'a String>,
+    hook_keys: impl Iterator<Item = &'a String>,
+) {
+    let tunnel_fields = field_names::<Tunnel>();
+    let hook_fields = field_names::<Hooks>();
+    for key in keys.filter(|key| !tunnel_fields.contains(&key.as_str())) {
+        report.error(source, Some(alias), Some(key), t!("未知字段 {}", key));
+    }
+    for key in hook_keys.filter(|key| !hook_fields.contains(&key.as_str())) {
+        report.error(source, Some(alias), Some(key), t!("未知字段 hooks.{}", key));
+    }
+}
+
+/// 合并全局与项目配置后检查端口、主机名与依赖
+fn check_tunnels(sources: &[Source], report: &mut Report) {
+    // 与加载配置时一致：项目中的连接覆盖全局配置中的同名连接
+    let mut effective: BTreeMap<&str, (&Source, &Tunnel)> = BTreeMap::new();
+    for source in sources {
+        for tunnel in &source.tunnels {
+            effective.insert(&tunnel.alias, (source, tunnel));
+        }
+    }
+
+    let port_range = sources.iter().find_map(|source| source.port_range);
+    let mut ports: BTreeMap<u16, Vec<&str>> = BTreeMap::new();
+    for (alias, (source, tunnel)) in &effective {
+        if tunnel.port == 0 {
+            report.error(source, Some(alias), Some("port"), t!("本地端口不能为 0"));
+        } else {
+            ports.entry(tunnel.port).or_default().push(alias);
+        }
+        for mapping in &tunnel.extra_ports {
+            let users = ports.entry(mapping.local).or_default();
+            if !users.contains(alias) {
+                users.push(alias);
+            }
+        }
+        if let Err(e) = tunnel.check_extra_ports() {
+            report.error(source, Some(alias), Some("extra_ports"), e.to_string());
+        }
+        if let Some(range) = port_range
+            && tunnel.port != 0
+            && !range.contains(tunnel.port)
+        {
+            report.warning(source, Some(alias), Some("port"), t!("本地端口 {} 不在保留端口范围 {} 内", tunnel.port, range));
+        }
+        match &tunnel.provider {
+            Provider::Cloudflared | Provider::Http if !source.sealed && !is_valid_hostname(&tunnel.source) => {
+                report.error(source, Some(alias), Some("source"), t!("主机名 {} 不符合 DNS 语法", tunnel.source));
+            }
+            Provider::Kubectl { remote_port: 0, .. } => {
+                report.error(source, Some(alias), Some("remote_port"), t!("远程端口不能为 0"));
+            }
+            _ => {}
+        }
+        if tunnel.runtime.is_some() && tunnel.provider != Provider::Cloudflared {
+            report.error(source, Some(alias), Some("runtime"), t!("容器运行时只适用于 cloudflared 后端"));
+        }
+        if let Some(hostname) = &tunnel.local_hostname
+            && (hostname.contains("://") || !is_valid_hostname(hostname))
+        {
+            report.error(source, Some(alias), Some("local_hostname"), t!("主机名 {} 不符合 DNS 语法", hostname));
+        }
+        if let Err(e) = Redactor::new(&tunnel.redact) {
+            report.error(source, Some(alias), Some("redact"), e.to_string());
+        }
+        for (field, rate) in [("max_up", tunnel.max_up), ("max_down", tunnel.max_down)] {
+            if rate == Some(0) {
+                report.error(source, Some(alias), Some(field), t!("限速不能为 0，不限速时删除该字段"));
+            }
+        }
+        // 为 0 的限速已报错，不再重复提示代理未启用
+        if (tunnel.max_up.is_some_and(|rate| rate > 0) || tunnel.max_down.is_some_and(|rate| rate > 0)) && !tunnel.proxy {
+            report.warning(source, Some(alias), Some("proxy"), t!("设置了限速但未启用统计代理，限速不会生效"));
+        }
+        for dependency in &tunnel.depends_on {
+            if !effective.contains_key(dependency.as_str()) {
+                report.error(source, Some(alias), Some("depends_on"), t!("依赖的 {} 不存在", dependency));
+            }
+        }
+        if let Some(via) = &tunnel.via
+            && !effective.contains_key(via.alias.as_str())
+        {
+            report.error(source, Some(alias), Some("via"), t!("经过的 {} 不存在", via.alias));
+        }
+    }
+    for (port, aliases) in ports.iter().filter(|(_, aliases)| aliases.len() > 1) {
+        let (source, _) = effective[aliases[0]];
+        report.warning(source, Some(aliases[0]), Some("port"), t!("本地端口 {} 同时被 {} 使用，它们不能同时运行", port, aliases.join(", ")));
+    }
+
+    // 依赖都存在时再检查循环，避免重复报告
+    let all_present = effective.values()
+        .all(|(_, tunnel)| {
+            tunnel.depends_on.iter().all(|d| effective.contains_key(d.as_str()))
+                && tunnel.via.as_ref().is_none_or(|via| effective.contains_key(via.alias.as_str()))
+        });
+    if all_present {
+        let tunnels: HashMap<String, Tunnel> = effective.values()
+            .map(|(_, tunnel)| (tunnel.alias.clone(), (*tunnel).clone()))
+            .collect();
+        if let Err(e) = Config::with_tunnels(tunnels).validate_dependencies()
+            && let Some(source) = sources.first()
+        {
+            report.error(source, None, None, e.to_string());
+        }
+    }
+}
+
+/// 基本的 DNS 主机名语法：允许带 http(s):// 前缀与结尾的斜杠，
+/// 每段 1 到 63 个字母、数字或连字符，且不以连字符开头或结尾，总长不超过 253
+pub fn is_valid_hostname(source: &str) -> bool {
+    let host = source.trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_end_matches('/');
+    !host.is_empty()
+        && host.len() <= 253
+        && host.split('.').all(|label| {
+            (1..=63).contains(&label.len())
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+        })
+}
+
+/// 结构体可以出现的字段名，取自它的 Deserialize 实现，随结构体定义自动更新
+fn field_names<'de, T: Deserialize<'de>>() -> &'static [&'static str] {
+    let mut fields: &'static [&'static str] = &[];
+    let _ = T::deserialize(FieldNames(&mut fields));
+    fields
+}
+
+/// 只记录 `deserialize_struct` 收到的字段列表，其余一律失败
+struct FieldNames<'a>(&'a mut &'static [&'static str]);
+
+impl<'de> Deserializer<'de> for FieldNames<'_> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(de::Error::custom("field names only"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        *self.0 = fields;
+        Err(de::Error::custom("field names only"))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
@@ -0,0 +1,258 @@
+//! `tfa web`：本机的 HTTP 服务。`/events` 是 WebSocket，把连接事件以 JSON 帧推送给浏览器等客户端，
+//! 页面可以实时更新而不必轮询 `tfa status --json`；启用 `dashboard` 功能时还提供网页控制台。
+//!
+//! 事件来自事件总线，由本进程的监视线程读取其他进程发布的事件、状态变化和新写入的日志行；
+//! 帧的格式与 `tfa events --json` 的每一行相同。除控制台页面本身外，所有地址都需要本机生成的访问令牌。
+
+use anyhow::Result;
+use axum::{Json, Router};
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Query, Request, State};
+use axum::http::{header, HeaderMap, Method, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chacha20poly1305::aead::OsRng;
+use chacha20poly1305::aead::rand_core::RngCore;
+use regex::Regex;
+use serde::Deserialize;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+use crate::apikey::{self, Denied, Scope};
+use crate::config::Config;
+use crate::events::{self, Monitor, TunnelEvent};
+use crate::handoff::{self, Service, Shutdown};
+use crate::i18n::t;
+use crate::ipc;
+use crate::platform;
+use crate::reload;
+use tracing::{debug, warn};
+
+#[cfg(feature = "dashboard")]
+mod dashboard;
+
+/// 默认监听地址
+pub const DEFAULT_LISTEN: &str = "127.0.0.1:7070";
+
+/// 检测状态变化的间隔
+const HEALTH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 在 `listen` 上提供 HTTP 服务，直到按下 Ctrl-C 或由新启动的服务接手；`new_token` 时先更换访问令牌，旧令牌随即失效
+pub async fn serve(listen: SocketAddr, new_token: bool) -> Result<()> {
+    if !listen.ip().is_loopback() {
+        warn!("{}", t!("Web 服务监听 {}，其他机器上持有访问令牌或 API 密钥的人也可以访问", listen));
+    }
+    let token = Arc::new(access_token(new_token)?);
+    Monitor::new().health(HEALTH_INTERVAL).logs(true).spawn()?;
+
+    let protected = Router::new().route("/events", get(events_socket));
+    #[cfg(feature = "dashboard")]
+    let protected = protected.merge(dashboard::api());
+    let app = Router::new().merge(protected.route_layer(middleware::from_fn_with_state(token.clone(), authorize)));
+    #[cfg(feature = "dashboard")]
+    let app = app.merge(dashboard::page());
+
+    let (listener, _registration) = handoff::bind(Service::Web, listen).await?;
+    let _control = ipc::listen()
+        .map_err(|e| warn!("{}", t!("无法打开控制套接字，tfa log --follow 等命令改为自行读取日志文件: {}", e)))
+        .ok();
+    if let Err(e) = reload::watch() {
+        warn!("{}", t!("无法监视配置文件，修改后需要执行 tfa reload: {}", e));
+    }
+    eprintln!("{}", t!("Web 服务正在监听 http://{}，按 Ctrl-C 停止", listen));
+    #[cfg(feature = "dashboard")]
+    eprintln!("{}", dashboard::url(listen, &token));
+    #[cfg(not(feature = "dashboard"))]
+    eprintln!("{}", t!("访问令牌保存在 {}", token_path()?.display()));
+    let shutdown = Shutdown::default();
+    let server = axum::serve(listener, app).with_graceful_shutdown(shutdown.clone().requested(Service::Web));
+    tokio::select! {
+        result = server => result?,
+        () = shutdown.drain_expired() => {}
+    }
+    Ok(())
+}
+
+/// 访问令牌保存在当前档案的配置目录中，只有当前用户可以读取
+fn token_path() -> Result<PathBuf> {
+    Ok(Config::config_dir()?.join("web-token"))
+}
+
+/// 读取访问令牌；不存在或 `renew` 时生成新的随机令牌并保存
+fn access_token(renew: bool) -> Result<String> {
+    let path = token_path()?;
+    if !renew
+        && let Ok(token) = fs::read_to_string(&path)
+        && !token.trim().is_empty()
+    {
+        return Ok(token.trim().to_string());
+    }
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    let token = URL_SAFE_NO_PAD.encode(bytes);
+    let _ = fs::remove_file(&path);
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(&path)?.write_all(token.as_bytes())?;
+    debug!(path = %path.display(), "已生成访问令牌");
+    Ok(token)
+}
+
+/// 检查访问令牌或 API 密钥：读取（GET）需要 read 权限，启动、停止等修改需要 operate 权限，
+/// 本机生成的访问令牌拥有全部权限。令牌放在 `Authorization: Bearer` 请求头中；
+/// 浏览器无法为 WebSocket 设置请求头，因此同样接受 `token` 查询参数
+async fn authorize(State(token): State<Arc<String>>, request: Request, next: Next) -> Response {
+    let from_header = request.headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    let from_query = request.uri()
+        .query()
+        .and_then(|query| query.split('&').find_map(|pair| pair.strip_prefix("token=")));
+    let Some(provided) = from_header.or(from_query).map(|value| value.trim().to_string()) else {
+        return (StatusCode::UNAUTHORIZED, t!("缺少访问令牌或令牌无效")).into_response();
+    };
+    if apikey::constant_time_eq(provided.as_bytes(), token.as_bytes()) {
+        return next.run(request).await;
+    }
+    let required = if request.method() == Method::GET { Scope::Read } else { Scope::Operate };
+    let checked = tokio::task::spawn_blocking(move || -> Result<Result<(), Denied>> {
+        Ok(apikey::authorize(Config::load()?.api_keys(), Some(&provided), required))
+    })
+    .await;
+    match checked {
+        Ok(Ok(Ok(()))) => next.run(request).await,
+        Ok(Ok(Err(Denied::Unknown))) => (StatusCode::UNAUTHORIZED, t!("缺少访问令牌或令牌无效")).into_response(),
+        // 与控制台接口的错误格式相同，页面可以直接显示
+        Ok(Ok(Err(denied))) => (StatusCode::FORBIDDEN, Json(serde_json::json!({ "error": denied.to_string() }))).into_response(),
+        Ok(Err(e)) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// `/events` 的查询参数
+#[derive(Debug, Default, Deserialize)]
+struct EventQuery {
+    /// 逗号分隔的别名或通配模式，只推送这些连接的事件
+    aliases: Option<String>,
+    /// 同时推送连接日志中新写入的行
+    #[serde(default)]
+    logs: bool,
+    /// 只推送匹配该正则表达式的日志行，隐含 logs
+    #[serde(rename = "match")]
+    pattern: Option<String>,
+}
+
+/// 按查询参数筛选推送的事件
+struct Filter {
+    aliases: Vec<String>,
+    logs: bool,
+    pattern: Option<Regex>,
+}
+
+impl Filter {
+    fn new(query: EventQuery) -> Result<Self> {
+        let patterns: Vec<String> = query.aliases
+            .iter()
+            .flat_map(|aliases| aliases.split(','))
+            .map(str::trim)
+            .filter(|alias| !alias.is_empty())
+            .map(str::to_string)
+            .collect();
+        let aliases = if patterns.is_empty() {
+            Vec::new()
+        } else {
+            let config = Config::load()?;
+            let aliases = config.expand_aliases(&patterns)?;
+            for alias in &aliases {
+                config.get_tunnel(alias)?;
+            }
+            aliases
+        };
+        let pattern = query.pattern
+            .map(|pattern| Regex::new(&pattern).map_err(|e| anyhow::anyhow!(t!("无效的正则表达式 {}: {}", pattern, e))))
+            .transpose()?;
+        Ok(Self { aliases, logs: query.logs || pattern.is_some(), pattern })
+    }
+
+    fn wants(&self, event: &TunnelEvent) -> bool {
+        if !self.aliases.is_empty() && !self.aliases.iter().any(|alias| alias == event.alias()) {
+            return false;
+        }
+        match event {
+            TunnelEvent::LogLine { line, .. } => self.logs && self.pattern.as_ref().is_none_or(|pattern| pattern.is_match(line)),
+            _ => true,
+        }
+    }
+}
+
+async fn events_socket(headers: HeaderMap, Query(query): Query<EventQuery>, upgrade: WebSocketUpgrade) -> Response {
+    // 浏览器允许任意网页连接本机的 WebSocket，只接受来自本服务自身页面或非浏览器客户端的连接
+    if !same_origin(&headers) {
+        return (StatusCode::FORBIDDEN, t!("不接受来自其他网站的连接")).into_response();
+    }
+    let filter = match tokio::task::spawn_blocking(move || Filter::new(query)).await {
+        Ok(Ok(filter)) => filter,
+        Ok(Err(e)) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    };
+    upgrade.on_upgrade(move |socket| push_events(socket, filter))
+}
+
+/// 没有 Origin 请求头（非浏览器客户端），或 Origin 与 Host 一致
+fn same_origin(headers: &HeaderMap) -> bool {
+    let Some(origin) = headers.get(header::ORIGIN).and_then(|value| value.to_str().ok()) else {
+        return true;
+    };
+    let host = headers.get(header::HOST).and_then(|value| value.to_str().ok());
+    let authority = origin.split_once("://").map_or(origin, |(_, authority)| authority);
+    host.is_some_and(|host| host.eq_ignore_ascii_case(authority))
+}
+
+/// 把事件逐个推送给客户端，直到客户端断开
+async fn push_events(mut socket: WebSocket, filter: Filter) {
+    let mut events = events::subscribe();
+    loop {
+        tokio::select! {
+            event = events.recv() => {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("{}", t!("处理不及，跳过了 {} 个事件", skipped));
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                if !filter.wants(&event) {
+                    continue;
+                }
+                let frame = match events::to_json(&event, platform::unix_now()) {
+                    Ok(frame) => frame.to_string(),
+                    Err(e) => {
+                        debug!(error = %e, "序列化事件失败");
+                        continue;
+                    }
+                };
+                if socket.send(Message::Text(frame.into())).await.is_err() {
+                    break;
+                }
+            }
+            // 客户端不需要发送内容，只关心它是否断开；ping 由底层自动应答
+            message = socket.recv() => {
+                if matches!(message, None | Some(Err(_)) | Some(Ok(Message::Close(_)))) {
+                    break;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! 内置的网页控制台（`dashboard` 功能）：列出连接，提供启动、停止按钮、实时状态和日志查看，
+//! 供不使用终端的同事使用。页面本身不含数据，数据都来自需要访问令牌的 `/api` 与 `/events`。
+
+use anyhow::Result;
+use axum::extract::Path;
+use axum::http::StatusCode;
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+
+use crate::config::Config;
+use crate::control;
+use crate::error::{exit_code, TunnelError};
+use crate::i18n::{self, t};
+use crate::tunnel::{Tunnel, TunnelStatus};
+
+const PAGE: &str = include_str!("dashboard.html");
+
+/// 页面中使用的文字，按当前界面语言翻译后嵌入页面
+const MESSAGES: &[&str] = &[
+    "连接", "状态", "本地地址", "后端", "运行时长", "启动", "停止", "日志", "暂无日志",
+    "请输入 tfa web 启动时输出的访问令牌或 API 密钥", "确定", "访问令牌无效", "实时更新中", "已断开，正在重连...",
+];
+
+/// 控制台页面，不需要访问令牌
+pub fn page() -> Router {
+    Router::new().route("/", get(index))
+}
+
+/// 控制台使用的接口
+pub fn api() -> Router {
+    Router::new()
+        .route("/api/tunnels", get(list))
+        .route("/api/tunnels/{alias}/start", post(start))
+        .route("/api/tunnels/{alias}/stop", post(stop))
+        .route("/api/tunnels/{alias}/logs", get(logs))
+}
+
+async fn index() -> Html<String> {
+    let messages: BTreeMap<&str, &str> = MESSAGES.iter().map(|id| (*id, i18n::translate(id))).collect();
+    let messages = serde_json::to_string(&messages).unwrap_or_else(|_| "{}".to_string());
+    Html(PAGE.replace("{{LANG}}", &i18n::current().to_string()).replace("{{MESSAGES}}", &messages))
+}
+
+/// 列表中的一个连接
+#[derive(Serialize)]
+struct TunnelView {
+    alias: String,
+    source: String,
+    local_address: String,
+    provider: &'static str,
+    status: TunnelStatus,
+    enabled: bool,
+    tags: Vec<String>,
+    /// 运行时长（秒），未运行时为 null
+    uptime: Option<u64>,
+    /// 系统连接只有管理员可以启动或停止
+    system: bool,
+}
+
+impl From<&Tunnel> for TunnelView {
+    fn from(tunnel: &Tunnel) -> Self {
+        Self {
+            alias: tunnel.alias.clone(),
+            source: tunnel.source.clone(),
+            local_address: SocketAddr::new(tunnel.listen_ip(), tunnel.port).to_string(),
+            provider: tunnel.provider.label(),
+            status: tunnel.status(),
+            enabled: tunnel.enabled,
+            tags: tunnel.tags.clone(),
+            uptime: tunnel.uptime(),
+            system: tunnel.requires_admin(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct LogsView {
+    lines: Vec<String>,
+}
+
+async fn list() -> Result<Json<Vec<TunnelView>>, ApiError> {
+    blocking(|| Ok(Config::load()?.list_tunnels()?.into_iter().map(TunnelView::from).collect())).await
+}
+
+async fn start(Path(alias): Path<String>) -> Result<Json<TunnelView>, ApiError> {
+    blocking(move || {
+        let config = Config::load()?;
+        let tunnel = find(&config, &alias)?;
+        control::start(&config, &alias)?;
+        Ok(TunnelView::from(tunnel))
+    })
+    .await
+}
+
+async fn stop(Path(alias): Path<String>) -> Result<Json<TunnelView>, ApiError> {
+    blocking(move || {
+        let config = Config::load()?;
+        let tunnel = find(&config, &alias)?;
+        control::stop(&config, &alias, false)?;
+        Ok(TunnelView::from(tunnel))
+    })
+    .await
+}
+
+async fn logs(Path(alias): Path<String>) -> Result<Json<LogsView>, ApiError> {
+    blocking(move || Ok(LogsView { lines: find(&Config::load()?, &alias)?.get_logs() })).await
+}
+
+/// 在阻塞线程中处理请求：读取配置、启动和停止连接都会阻塞
+async fn blocking<T: Send + 'static>(f: impl FnOnce() -> Result<T, ApiError> + Send + 'static) -> Result<Json<T>, ApiError> {
+    match tokio::task::spawn_blocking(f).await {
+        Ok(result) => result.map(Json),
+        Err(e) => Err(ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.into())),
+    }
+}
+
+fn find<'a>(config: &'a Config, alias: &str) -> Result<&'a Tunnel, ApiError> {
+    config.get_tunnel(alias).map_err(|e| ApiError(StatusCode::NOT_FOUND, e))
+}
+
+/// 接口返回的错误：`{"error": ..., "hint": ...}`，状态码按与退出码相同的分类选择
+struct ApiError(StatusCode, anyhow::Error);
+
+impl From<anyhow::Error> for ApiError {
+    fn from(error: anyhow::Error) -> Self {
+        let code = match error.downcast_ref::<TunnelError>() {
+            Some(TunnelError::SystemTunnel { .. }) => StatusCode::FORBIDDEN,
+            Some(e) => match e.exit_code() {
+                exit_code::CONFIG => StatusCode::BAD_REQUEST,
+                exit_code::PROGRAM_NOT_FOUND | exit_code::PORT_IN_USE | exit_code::AUTH_REQUIRED | exit_code::HOOK_FAILED => StatusCode::CONFLICT,
+                exit_code::TIMEOUT => StatusCode::GATEWAY_TIMEOUT,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            },
+            None => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        ApiError(code, error)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let hint = self.1.downcast_ref::<TunnelError>().and_then(TunnelError::hint);
+        let body = serde_json::json!({ "error": self.1.to_string(), "hint": hint });
+        (self.0, Json(body)).into_response()
+    }
+}
+
+/// 启动时输出的控制台地址，令牌放在 # 之后，不会随请求发送或出现在服务器日志中
+pub fn url(listen: SocketAddr, token: &str) -> String {
+    t!("控制台: http://{}/#token={}", listen, token)
+}